@@ -1,12 +1,16 @@
 use crate::{
-    db::entities::user::{list_users, reset_admin_password, reset_password_by_email, search_users},
+    crypto::is_sealed,
+    db::entities::{
+        code,
+        user::{list_users, reset_admin_password, reset_password_by_email, search_users},
+    },
     prelude::*,
 };
 
 use std::{num::NonZeroU16, path::PathBuf, process::ExitCode};
 
 use clap::{Parser, Subcommand};
-use sea_orm::DatabaseConnection;
+use sea_orm::{DatabaseConnection, prelude::Date};
 
 #[derive(Parser, Debug)]
 pub struct CliOpts {
@@ -33,6 +37,30 @@ pub struct CliOpts {
 
     #[clap(long, env = "HOOFPRINT_TLS_KEY")]
     pub tls_key: Option<PathBuf>,
+
+    /// Apple Wallet pass type identifier, needed to export codes as Wallet passes
+    #[clap(long, env = "HOOFPRINT_PASS_TYPE_ID")]
+    pub pass_type_id: Option<String>,
+
+    /// PEM certificate for signing Wallet passes
+    #[clap(long, env = "HOOFPRINT_PASS_CERTIFICATE")]
+    pub pass_certificate: Option<PathBuf>,
+
+    /// PEM private key for the pass signing certificate
+    #[clap(long, env = "HOOFPRINT_PASS_KEY")]
+    pub pass_key: Option<PathBuf>,
+
+    /// PEM Apple WWDR intermediate certificate
+    #[clap(long, env = "HOOFPRINT_PASS_WWDR_CERTIFICATE")]
+    pub pass_wwdr_certificate: Option<PathBuf>,
+
+    /// Encrypt code names as well as values, which hides them from anyone with the database
+    #[clap(long, env = "HOOFPRINT_ENCRYPT_CODE_NAMES")]
+    pub encrypt_code_names: bool,
+
+    /// Keep photos attached to codes in this directory, rather than in the database
+    #[clap(long, env = "HOOFPRINT_ATTACHMENT_DIR")]
+    pub attachment_dir: Option<PathBuf>,
 }
 
 #[derive(Subcommand, Debug)]
@@ -51,6 +79,12 @@ pub enum Command {
     },
     /// List all users sorted by email address
     ListAllUsers,
+    /// List every user's codes that expire within the next few days
+    ExpiringCodes {
+        /// How many days ahead to look
+        #[clap(long, default_value_t = 30)]
+        days: u32,
+    },
 }
 
 pub async fn handle_admin_reset(db: DatabaseConnection) -> Result<ExitCode, ExitCode> {
@@ -61,6 +95,9 @@ pub async fn handle_admin_reset(db: DatabaseConnection) -> Result<ExitCode, Exit
 
     eprintln!("Admin user has been reset.");
     eprintln!("New password: {}", new_password);
+    eprintln!(
+        "Codes saved before the reset stay locked until they're recovered with the old password from the account page."
+    );
 
     Ok(ExitCode::SUCCESS)
 }
@@ -78,6 +115,9 @@ pub async fn handle_user_reset(
 
     eprintln!("User {} has been reset.", username);
     eprintln!("New password: {}", new_password);
+    eprintln!(
+        "Codes saved before the reset stay locked until the user recovers them with their old password from their account page."
+    );
 
     Ok(ExitCode::SUCCESS)
 }
@@ -114,3 +154,36 @@ pub async fn handle_list_users(db: DatabaseConnection) -> Result<ExitCode, ExitC
     }
     Ok(ExitCode::SUCCESS)
 }
+
+pub async fn handle_expiring_codes(
+    db: DatabaseConnection,
+    days: u32,
+) -> Result<ExitCode, ExitCode> {
+    let today = code::today();
+    let until = today.iter_days().nth(days as usize).unwrap_or(Date::MAX);
+    let codes = code::Model::find_expiring(&db, today, until)
+        .await
+        .map_err(|err| {
+            error!("Failed to list expiring codes: {}", err);
+            ExitCode::FAILURE
+        })?;
+    if codes.is_empty() {
+        eprintln!("No codes expire in the next {} days.", days);
+        return Ok(ExitCode::SUCCESS);
+    }
+    for (code_model, user) in codes {
+        // names can't be read without the owner's password
+        let name = match code_model.name.as_deref() {
+            Some(name) if is_sealed(name) => "(encrypted name)",
+            Some(name) => name,
+            None => "(unnamed)",
+        };
+        let expires_at = code_model
+            .expires_at
+            .map(|date| date.to_string())
+            .unwrap_or_default();
+        let email = user.map(|user| user.email).unwrap_or_default();
+        eprintln!("{} - {} - {} ({})", expires_at, email, name, code_model.id);
+    }
+    Ok(ExitCode::SUCCESS)
+}
@@ -15,6 +15,21 @@ pub struct Configuration {
 
     pub tls_certificate: Option<PathBuf>,
     pub tls_key: Option<PathBuf>,
+
+    /// Apple Wallet pass type identifier, eg `pass.com.example.hoofprint`
+    pub pass_type_id: Option<String>,
+    /// PEM certificate for signing Wallet passes, its OU is used as the team identifier
+    pub pass_certificate: Option<PathBuf>,
+    /// PEM private key for `pass_certificate`
+    pub pass_key: Option<PathBuf>,
+    /// PEM Apple WWDR intermediate certificate, included in pass signatures
+    pub pass_wwdr_certificate: Option<PathBuf>,
+
+    /// Encrypt code names as well as values
+    pub encrypt_code_names: bool,
+
+    /// Where photos attached to codes are kept, in the database if this isn't set
+    pub attachment_dir: Option<PathBuf>,
 }
 
 impl Configuration {
@@ -27,6 +42,12 @@ impl Configuration {
             frontend_hostname: "localhost".to_string(),
             tls_certificate: None,
             tls_key: None,
+            pass_type_id: None,
+            pass_certificate: None,
+            pass_key: None,
+            pass_wwdr_certificate: None,
+            encrypt_code_names: false,
+            attachment_dir: None,
         }
     }
 }
@@ -40,6 +61,12 @@ impl From<&CliOpts> for Configuration {
             frontend_hostname: opts.frontend_hostname.clone(),
             tls_certificate: opts.tls_certificate.clone(),
             tls_key: opts.tls_key.clone(),
+            pass_type_id: opts.pass_type_id.clone(),
+            pass_certificate: opts.pass_certificate.clone(),
+            pass_key: opts.pass_key.clone(),
+            pass_wwdr_certificate: opts.pass_wwdr_certificate.clone(),
+            encrypt_code_names: opts.encrypt_code_names,
+            attachment_dir: opts.attachment_dir.clone(),
         }
     }
 }
@@ -55,6 +82,12 @@ fn test_configuration_from_cli_opts() {
         command: None,
         tls_certificate: None,
         tls_key: None,
+        pass_type_id: None,
+        pass_certificate: None,
+        pass_key: None,
+        pass_wwdr_certificate: None,
+        encrypt_code_names: false,
+        attachment_dir: None,
     };
     let config = Configuration::from(&cli_opts);
     assert_eq!(config.database_file, "test.db");
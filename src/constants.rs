@@ -5,14 +5,28 @@ pub(crate) enum Urls {
     Login,
     Logout,
     Scan,
+    ScanUpload,
     Create,
+    CreatePayload,
     Manifest,
     Static,
     CspReportOnly,
     Register,
     AdminDashboard,
     AdminPasswordReset,
+    AdminSites,
     HealthCheck,
+    WalletSheet,
+    WalletSheetPdf,
+    Account,
+    AccountPassword,
+    AccountRecover,
+    AccountPreferences,
+    Reorder,
+    Search,
+    SearchJson,
+    Households,
+    SiteStyles,
 }
 
 impl AsRef<str> for Urls {
@@ -22,14 +36,28 @@ impl AsRef<str> for Urls {
             Urls::Login => "/login",
             Urls::Logout => "/logout",
             Urls::Scan => "/scan",
+            Urls::ScanUpload => "/scan/upload",
             Urls::Create => "/create",
+            Urls::CreatePayload => "/create/payload",
             Urls::Manifest => "/manifest.webmanifest",
             Urls::Static => "/static/",
             Urls::CspReportOnly => "/csp/reportOnly",
             Urls::Register => "/register",
             Urls::AdminDashboard => "/admin",
             Urls::AdminPasswordReset => "/admin/password-reset",
+            Urls::AdminSites => "/admin/sites",
             Urls::HealthCheck => "/health",
+            Urls::WalletSheet => "/export/wallet",
+            Urls::WalletSheetPdf => "/export/wallet.pdf",
+            Urls::Account => "/account",
+            Urls::AccountPassword => "/account/password",
+            Urls::AccountRecover => "/account/recover",
+            Urls::AccountPreferences => "/account/preferences",
+            Urls::Reorder => "/reorder",
+            Urls::Search => "/search",
+            Urls::SearchJson => "/search.json",
+            Urls::Households => "/households",
+            Urls::SiteStyles => "/sites.css",
         }
     }
 }
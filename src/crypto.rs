@@ -0,0 +1,675 @@
+//! Encryption of code values at rest.
+//!
+//! Every user has a random data key that seals their code values, and optionally names, with
+//! AES-256-GCM. The data key is only ever stored wrapped by a key derived from the user's
+//! password, so a copy of the database alone can't be decrypted. While a user is logged in their
+//! data key sits in the session, sealed with a key that only lives in the server's memory.
+//!
+//! A code that's been shared gets a key of its own, so the people it's shared with can open it
+//! without being able to open the owner's other codes. Its owner keeps that key wrapped with
+//! their data key. Everyone else gets it wrapped with their public key, since their data key
+//! isn't around when the code's shared, and it's re-wrapped with their data key the next time
+//! they log in.
+//!
+//! Codes owned by a household have their keys wrapped with the household's key instead, which
+//! each member holds a copy of in the same way.
+//!
+//! A public link to a code carries a random token, which the code's key is wrapped with a key
+//! derived from, and links are looked up by a hash of the token. The token is only kept sealed
+//! with the code's key, so whoever has the link can open the code without logging in, but the
+//! database alone still can't.
+
+use std::{collections::HashMap, fmt::Debug};
+
+use base64::{
+    Engine,
+    engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD},
+};
+use openssl::{
+    pkey::{PKey, Private},
+    rsa::{Padding, Rsa},
+    sha::Sha256,
+    symm::{Cipher, decrypt_aead, encrypt_aead},
+};
+use rand::Rng;
+use uuid::Uuid;
+
+use crate::{error::HoofprintError, password::derive_key};
+
+pub(crate) const KEY_LENGTH: usize = 32;
+const NONCE_LENGTH: usize = 12;
+const TAG_LENGTH: usize = 16;
+const SALT_LENGTH: usize = 16;
+
+/// Marks a stored value as sealed, so values saved before encryption can still be read
+const SEALED_PREFIX: &str = "sealed:v1:";
+
+/// Associated data for wrapped keys, so a wrapped key can't pass for a sealed value
+const WRAPPED_KEY_CONTEXT: &[u8] = b"hoofprint data key";
+/// Associated data for sealed private keys
+const PRIVATE_KEY_CONTEXT: &[u8] = b"hoofprint private key";
+/// Marks a key wrapped with someone's public key, rather than a data key
+const PUBLIC_WRAPPED_PREFIX: &str = "rsa-oaep:";
+/// The size of users' RSA keys, in bits
+const RSA_KEY_BITS: u32 = 2048;
+/// Hashed with a link token to make the key that wraps the linked code's key
+const LINK_KEY_CONTEXT: &[u8] = b"hoofprint link key";
+/// Hashed with a link token to make what the link is looked up by
+const LINK_LOOKUP_CONTEXT: &[u8] = b"hoofprint link lookup";
+/// How many random bytes are in a link token
+const LINK_TOKEN_LENGTH: usize = 32;
+
+fn random_bytes<const N: usize>() -> [u8; N] {
+    let mut bytes = [0u8; N];
+    rand::rng().fill_bytes(&mut bytes);
+    bytes
+}
+
+/// A key for AES-256-GCM
+#[derive(Clone)]
+pub struct DataKey([u8; KEY_LENGTH]);
+
+impl Debug for DataKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // never log key material
+        write!(f, "DataKey(..)")
+    }
+}
+
+impl DataKey {
+    pub fn generate() -> Self {
+        Self(random_bytes())
+    }
+
+    /// Encrypt bytes, returning the nonce, ciphertext and tag together
+    fn seal_bytes(&self, plaintext: &[u8], context: &[u8]) -> Result<Vec<u8>, HoofprintError> {
+        let nonce: [u8; NONCE_LENGTH] = random_bytes();
+        let mut tag = [0u8; TAG_LENGTH];
+        let ciphertext = encrypt_aead(
+            Cipher::aes_256_gcm(),
+            &self.0,
+            Some(&nonce),
+            context,
+            plaintext,
+            &mut tag,
+        )?;
+        Ok([nonce.as_slice(), &ciphertext, &tag].concat())
+    }
+
+    /// Decrypt the output of [DataKey::seal_bytes], or None if the key or context is wrong or
+    /// the data has been tampered with
+    fn open_bytes(&self, sealed: &[u8], context: &[u8]) -> Option<Vec<u8>> {
+        if sealed.len() < NONCE_LENGTH + TAG_LENGTH {
+            return None;
+        }
+        let (nonce, rest) = sealed.split_at(NONCE_LENGTH);
+        let (ciphertext, tag) = rest.split_at(rest.len() - TAG_LENGTH);
+        decrypt_aead(
+            Cipher::aes_256_gcm(),
+            &self.0,
+            Some(nonce),
+            context,
+            ciphertext,
+            tag,
+        )
+        .ok()
+    }
+
+    /// Seal text for storage, bound to `context` so it can't be moved somewhere else
+    pub fn seal(&self, text: &str, context: &[u8]) -> Result<String, HoofprintError> {
+        Ok(format!(
+            "{}{}",
+            SEALED_PREFIX,
+            STANDARD.encode(self.seal_bytes(text.as_bytes(), context)?)
+        ))
+    }
+
+    /// Open text from [DataKey::seal], or None if it can't be decrypted with this key
+    pub fn open(&self, sealed: &str, context: &[u8]) -> Option<String> {
+        let bytes = STANDARD.decode(sealed.strip_prefix(SEALED_PREFIX)?).ok()?;
+        String::from_utf8(self.open_bytes(&bytes, context)?).ok()
+    }
+
+    /// Wrap another key with this one
+    pub fn wrap_key(&self, key: &DataKey) -> Result<String, HoofprintError> {
+        Ok(STANDARD.encode(self.seal_bytes(&key.0, WRAPPED_KEY_CONTEXT)?))
+    }
+
+    /// Unwrap a key from [DataKey::wrap_key]
+    pub fn unwrap_key(&self, wrapped: &str) -> Option<DataKey> {
+        let bytes = STANDARD.decode(wrapped).ok()?;
+        let key = self.open_bytes(&bytes, WRAPPED_KEY_CONTEXT)?;
+        key.try_into().ok().map(DataKey)
+    }
+
+    /// Seal a private key for storage
+    pub fn seal_private_key(&self, private_key: &PrivateKey) -> Result<String, HoofprintError> {
+        let der = private_key.0.private_key_to_der()?;
+        Ok(STANDARD.encode(self.seal_bytes(&der, PRIVATE_KEY_CONTEXT)?))
+    }
+
+    /// Open a private key from [DataKey::seal_private_key]
+    pub fn open_private_key(&self, sealed: &str) -> Option<PrivateKey> {
+        let bytes = STANDARD.decode(sealed).ok()?;
+        let der = self.open_bytes(&bytes, PRIVATE_KEY_CONTEXT)?;
+        PKey::private_key_from_der(&der).ok().map(PrivateKey)
+    }
+
+    /// Wrap this key so only the holder of the private key for `public_key_pem` can unwrap it
+    pub fn wrap_for_public_key(&self, public_key_pem: &str) -> Result<String, HoofprintError> {
+        let rsa = Rsa::public_key_from_pem(public_key_pem.as_bytes())?;
+        let mut wrapped = vec![0u8; usize::try_from(rsa.size()).unwrap_or_default()];
+        let len = rsa.public_encrypt(&self.0, &mut wrapped, Padding::PKCS1_OAEP)?;
+        wrapped.truncate(len);
+        Ok(format!(
+            "{}{}",
+            PUBLIC_WRAPPED_PREFIX,
+            STANDARD.encode(wrapped)
+        ))
+    }
+
+    /// Wrap this key for storage, with a key derived from the user's password
+    pub fn wrap_with_password(&self, password: &str) -> Result<String, HoofprintError> {
+        let salt: [u8; SALT_LENGTH] = random_bytes();
+        let wrapping_key = DataKey(derive_key(password, &salt)?);
+        Ok(format!(
+            "{}${}",
+            STANDARD.encode(salt),
+            wrapping_key.wrap_key(self)?
+        ))
+    }
+
+    /// Unwrap a key from [DataKey::wrap_with_password], or None if the password is wrong
+    pub fn unwrap_with_password(wrapped: &str, password: &str) -> Option<DataKey> {
+        let (salt, wrapped) = wrapped.split_once('$')?;
+        let salt = STANDARD.decode(salt).ok()?;
+        let wrapping_key = DataKey(derive_key(password, &salt).ok()?);
+        wrapping_key.unwrap_key(wrapped)
+    }
+}
+
+/// A user's private key, for unwrapping keys to codes that have been shared with them
+pub struct PrivateKey(PKey<Private>);
+
+impl Debug for PrivateKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // never log key material
+        write!(f, "PrivateKey(..)")
+    }
+}
+
+impl PrivateKey {
+    /// Make a new key pair, returning the private key and the public key as PEM
+    pub fn generate() -> Result<(Self, String), HoofprintError> {
+        let rsa = Rsa::generate(RSA_KEY_BITS)?;
+        let public_key_pem = String::from_utf8(rsa.public_key_to_pem()?).map_err(|err| {
+            HoofprintError::InternalError(format!("Public key wasn't valid PEM: {}", err))
+        })?;
+        Ok((Self(PKey::from_rsa(rsa)?), public_key_pem))
+    }
+
+    /// Unwrap a key from [DataKey::wrap_for_public_key]
+    pub fn unwrap_key(&self, wrapped: &str) -> Option<DataKey> {
+        let bytes = STANDARD
+            .decode(wrapped.strip_prefix(PUBLIC_WRAPPED_PREFIX)?)
+            .ok()?;
+        let rsa = self.0.rsa().ok()?;
+        let mut key = vec![0u8; usize::try_from(rsa.size()).ok()?];
+        let len = rsa
+            .private_decrypt(&bytes, &mut key, Padding::PKCS1_OAEP)
+            .ok()?;
+        key.truncate(len);
+        key.try_into().ok().map(DataKey)
+    }
+}
+
+/// The secret in a public link to a code
+#[derive(Clone)]
+pub struct LinkToken(String);
+
+impl Debug for LinkToken {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // never log a token, anyone with it can open the code
+        write!(f, "LinkToken(..)")
+    }
+}
+
+impl LinkToken {
+    pub fn generate() -> Self {
+        Self(URL_SAFE_NO_PAD.encode(random_bytes::<LINK_TOKEN_LENGTH>()))
+    }
+
+    /// A token from a link, as long as it looks like one
+    pub fn parse(token: &str) -> Option<Self> {
+        let bytes = URL_SAFE_NO_PAD.decode(token).ok()?;
+        (bytes.len() == LINK_TOKEN_LENGTH).then(|| Self(token.to_string()))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    fn hash(&self, context: &[u8]) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        hasher.update(context);
+        hasher.update(self.0.as_bytes());
+        hasher.finish()
+    }
+
+    /// What the link is stored under, which can't be turned back into the token
+    pub fn lookup_hash(&self) -> String {
+        URL_SAFE_NO_PAD.encode(self.hash(LINK_LOOKUP_CONTEXT))
+    }
+
+    /// The key that wraps the linked code's key
+    pub fn key(&self) -> DataKey {
+        DataKey(self.hash(LINK_KEY_CONTEXT))
+    }
+}
+
+/// Whether a key was wrapped with a public key by [DataKey::wrap_for_public_key], rather than
+/// with a data key
+pub fn is_public_wrapped(wrapped: &str) -> bool {
+    wrapped.starts_with(PUBLIC_WRAPPED_PREFIX)
+}
+
+/// Whether a stored value has been sealed, rather than saved before encryption was added
+pub fn is_sealed(value: &str) -> bool {
+    value.starts_with(SEALED_PREFIX)
+}
+
+/// Which field something of a code's is sealed as. It goes into the associated data along with
+/// the code's id, so a sealed field can't be copied onto another field, or another code's.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Field {
+    Value,
+    Name,
+    Notes,
+    /// The value of one of the code's secrets
+    Secret(Uuid),
+    /// The value saved in one of the code's revisions
+    RevisionValue(Uuid),
+    /// The name saved in one of the code's revisions
+    RevisionName(Uuid),
+    /// The notes saved in one of the code's revisions
+    RevisionNotes(Uuid),
+}
+
+impl Field {
+    fn context(self, code_id: Uuid) -> Vec<u8> {
+        let (label, id): (&[u8], _) = match self {
+            Field::Value => (b"value", None),
+            Field::Name => (b"name", None),
+            Field::Notes => (b"notes", None),
+            Field::Secret(secret_id) => (b"secret", Some(secret_id)),
+            Field::RevisionValue(revision_id) => (b"revision value", Some(revision_id)),
+            Field::RevisionName(revision_id) => (b"revision name", Some(revision_id)),
+            Field::RevisionNotes(revision_id) => (b"revision notes", Some(revision_id)),
+        };
+        let mut context = code_id.as_bytes().to_vec();
+        context.extend_from_slice(label);
+        if let Some(id) = id {
+            context.extend_from_slice(id.as_bytes());
+        }
+        context
+    }
+
+    fn is_name(self) -> bool {
+        matches!(self, Field::Name | Field::RevisionName(_))
+    }
+}
+
+/// A user's unlocked data key, used to seal and open the fields of their codes, along with the
+/// keys of any shared codes they can use
+#[derive(Clone, Debug)]
+pub struct CodeCipher {
+    key: DataKey,
+    /// Whether names are sealed as well as values
+    seal_names: bool,
+    /// The keys of shared codes, which seal those codes instead of the data key
+    code_keys: HashMap<Uuid, DataKey>,
+}
+
+impl CodeCipher {
+    pub fn new(key: DataKey, seal_names: bool) -> Self {
+        Self {
+            key,
+            seal_names,
+            code_keys: HashMap::new(),
+        }
+    }
+
+    pub fn key(&self) -> &DataKey {
+        &self.key
+    }
+
+    /// Use a shared code's own key for that code
+    pub fn add_code_key(&mut self, code_id: Uuid, key: DataKey) {
+        self.code_keys.insert(code_id, key);
+    }
+
+    /// A copy of this cipher that uses `key` for the code
+    pub fn with_code_key(&self, code_id: Uuid, key: DataKey) -> Self {
+        let mut cipher = self.clone();
+        cipher.add_code_key(code_id, key);
+        cipher
+    }
+
+    /// A shared code's own key, if the user has it
+    pub fn code_key(&self, code_id: Uuid) -> Option<&DataKey> {
+        self.code_keys.get(&code_id)
+    }
+
+    /// The key that seals a code
+    fn key_for(&self, code_id: Uuid) -> &DataKey {
+        self.code_keys.get(&code_id).unwrap_or(&self.key)
+    }
+
+    /// Seal one of a code's fields, tied to the code and the field. Names are left as they are
+    /// unless names are being sealed.
+    pub fn seal_field(
+        &self,
+        code_id: Uuid,
+        field: Field,
+        text: &str,
+    ) -> Result<String, HoofprintError> {
+        if field.is_name() && !self.seal_names {
+            return Ok(text.to_string());
+        }
+        self.key_for(code_id).seal(text, &field.context(code_id))
+    }
+
+    /// Seal a code's value
+    pub fn seal_value(&self, code_id: Uuid, value: &str) -> Result<String, HoofprintError> {
+        self.seal_field(code_id, Field::Value, value)
+    }
+
+    /// Seal a code's name, if names are being sealed
+    pub fn seal_name(
+        &self,
+        code_id: Uuid,
+        name: Option<String>,
+    ) -> Result<Option<String>, HoofprintError> {
+        name.map(|name| self.seal_field(code_id, Field::Name, &name))
+            .transpose()
+    }
+
+    /// Seal a code's notes
+    pub fn seal_notes(&self, code_id: Uuid, notes: &str) -> Result<String, HoofprintError> {
+        self.seal_field(code_id, Field::Notes, notes)
+    }
+
+    /// Seal the value of one of a code's secrets
+    pub fn seal_secret(
+        &self,
+        code_id: Uuid,
+        secret_id: Uuid,
+        value: &str,
+    ) -> Result<String, HoofprintError> {
+        self.seal_field(code_id, Field::Secret(secret_id), value)
+    }
+
+    /// Seal a photo attached to a code, tied to the attachment
+    pub fn seal_attachment(
+        &self,
+        code_id: Uuid,
+        attachment_id: Uuid,
+        bytes: &[u8],
+    ) -> Result<Vec<u8>, HoofprintError> {
+        self.key_for(code_id)
+            .seal_bytes(bytes, attachment_id.as_bytes())
+    }
+
+    /// Open a photo from [CodeCipher::seal_attachment], failing with
+    /// [HoofprintError::CodeLocked] if it was sealed with a key that's been retired
+    pub fn open_attachment(
+        &self,
+        code_id: Uuid,
+        attachment_id: Uuid,
+        sealed: &[u8],
+    ) -> Result<Vec<u8>, HoofprintError> {
+        self.key_for(code_id)
+            .open_bytes(sealed, attachment_id.as_bytes())
+            .ok_or_else(|| HoofprintError::CodeLocked(code_id.to_string()))
+    }
+
+    /// Open one of a code's fields, passing through anything saved before encryption
+    pub fn open(
+        &self,
+        code_id: Uuid,
+        field: Field,
+        stored: &str,
+    ) -> Result<String, HoofprintError> {
+        if !is_sealed(stored) {
+            return Ok(stored.to_string());
+        }
+        self.key_for(code_id)
+            .open(stored, &field.context(code_id))
+            .ok_or_else(|| HoofprintError::CodeLocked(code_id.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seal_and_open() {
+        let key = DataKey::generate();
+        let sealed = key.seal("member-1234", b"code").expect("Failed to seal");
+        assert!(is_sealed(&sealed));
+        assert!(!sealed.contains("member-1234"));
+        assert_eq!(key.open(&sealed, b"code").as_deref(), Some("member-1234"));
+
+        // the same text seals differently each time
+        assert_ne!(
+            sealed,
+            key.seal("member-1234", b"code").expect("Failed to seal")
+        );
+
+        assert_eq!(key.open(&sealed, b"another code"), None);
+        assert_eq!(DataKey::generate().open(&sealed, b"code"), None);
+        let tampered = format!("{}A", sealed.trim_end_matches('='));
+        assert_eq!(key.open(&tampered, b"code"), None);
+        assert_eq!(key.open("member-1234", b"code"), None);
+    }
+
+    #[test]
+    fn test_wrap_with_password() {
+        let key = DataKey::generate();
+        let wrapped = key
+            .wrap_with_password("correct horse")
+            .expect("Failed to wrap key");
+        let unwrapped =
+            DataKey::unwrap_with_password(&wrapped, "correct horse").expect("Failed to unwrap key");
+        assert_eq!(unwrapped.0, key.0);
+        assert!(DataKey::unwrap_with_password(&wrapped, "battery staple").is_none());
+        assert!(DataKey::unwrap_with_password("not-wrapped", "correct horse").is_none());
+        assert_eq!(format!("{:?}", key), "DataKey(..)");
+    }
+
+    #[test]
+    fn test_code_cipher() {
+        let code_id = Uuid::now_v7();
+        let cipher = CodeCipher::new(DataKey::generate(), false);
+        let value = cipher
+            .seal_value(code_id, "hello")
+            .expect("Failed to seal value");
+        assert_eq!(
+            cipher.open(code_id, Field::Value, &value).ok().as_deref(),
+            Some("hello")
+        );
+        assert!(matches!(
+            cipher.open(Uuid::now_v7(), Field::Value, &value),
+            Err(HoofprintError::CodeLocked(_))
+        ));
+        // values from before encryption are read as they are
+        assert_eq!(
+            cipher.open(code_id, Field::Value, "plain").ok().as_deref(),
+            Some("plain")
+        );
+
+        // a sealed field can't be passed off as another one
+        let notes = cipher
+            .seal_notes(code_id, "hello")
+            .expect("Failed to seal notes");
+        let secret_id = Uuid::now_v7();
+        let secret = cipher
+            .seal_secret(code_id, secret_id, "hello")
+            .expect("Failed to seal secret");
+        let revision_id = Uuid::now_v7();
+        let revision = cipher
+            .seal_field(code_id, Field::RevisionValue(revision_id), "hello")
+            .expect("Failed to seal revision");
+        for (sealed, field) in [
+            (&value, Field::Value),
+            (&notes, Field::Notes),
+            (&secret, Field::Secret(secret_id)),
+            (&revision, Field::RevisionValue(revision_id)),
+        ] {
+            assert_eq!(
+                cipher.open(code_id, field, sealed).ok().as_deref(),
+                Some("hello")
+            );
+            for other in [
+                Field::Value,
+                Field::Name,
+                Field::Notes,
+                Field::Secret(secret_id),
+                Field::Secret(Uuid::now_v7()),
+                Field::RevisionValue(revision_id),
+                Field::RevisionValue(Uuid::now_v7()),
+                Field::RevisionNotes(revision_id),
+            ]
+            .into_iter()
+            .filter(|other| *other != field)
+            {
+                assert!(matches!(
+                    cipher.open(code_id, other, sealed),
+                    Err(HoofprintError::CodeLocked(_))
+                ));
+            }
+        }
+
+        assert_eq!(
+            cipher
+                .seal_name(code_id, Some("Library".to_string()))
+                .expect("Failed to seal name")
+                .as_deref(),
+            Some("Library")
+        );
+        let cipher = CodeCipher::new(cipher.key().clone(), true);
+        let name = cipher
+            .seal_name(code_id, Some("Library".to_string()))
+            .expect("Failed to seal name")
+            .expect("Name missing");
+        assert!(is_sealed(&name));
+        assert_eq!(
+            cipher.open(code_id, Field::Name, &name).ok().as_deref(),
+            Some("Library")
+        );
+    }
+
+    #[test]
+    fn test_attachment_cipher() {
+        let code_id = Uuid::now_v7();
+        let attachment_id = Uuid::now_v7();
+        let cipher = CodeCipher::new(DataKey::generate(), false);
+        let sealed = cipher
+            .seal_attachment(code_id, attachment_id, b"\x89PNG")
+            .expect("Failed to seal attachment");
+        assert_eq!(
+            cipher
+                .open_attachment(code_id, attachment_id, &sealed)
+                .ok()
+                .as_deref(),
+            Some(b"\x89PNG".as_slice())
+        );
+        // attachments can't be swapped with each other
+        assert!(matches!(
+            cipher.open_attachment(code_id, Uuid::now_v7(), &sealed),
+            Err(HoofprintError::CodeLocked(_))
+        ));
+    }
+
+    #[test]
+    fn test_code_keys() {
+        let code_id = Uuid::now_v7();
+        let other_code_id = Uuid::now_v7();
+        let owner = CodeCipher::new(DataKey::generate(), false);
+        let code_key = DataKey::generate();
+
+        // a shared code is sealed with its own key, and other codes aren't
+        let owner = owner.with_code_key(code_id, code_key.clone());
+        let value = owner.seal_value(code_id, "costco").expect("Failed to seal");
+        let other_value = owner
+            .seal_value(other_code_id, "library")
+            .expect("Failed to seal");
+        assert!(
+            owner
+                .key()
+                .open(&value, &Field::Value.context(code_id))
+                .is_none()
+        );
+
+        let (private_key, public_key_pem) = PrivateKey::generate().expect("Failed to make keys");
+        let wrapped = code_key
+            .wrap_for_public_key(&public_key_pem)
+            .expect("Failed to wrap key");
+        assert!(is_public_wrapped(&wrapped));
+        assert!(
+            PrivateKey::generate()
+                .expect("Failed to make keys")
+                .0
+                .unwrap_key(&wrapped)
+                .is_none()
+        );
+        let unwrapped = private_key
+            .unwrap_key(&wrapped)
+            .expect("Failed to unwrap key");
+
+        let recipient =
+            CodeCipher::new(DataKey::generate(), false).with_code_key(code_id, unwrapped);
+        assert_eq!(
+            recipient
+                .open(code_id, Field::Value, &value)
+                .ok()
+                .as_deref(),
+            Some("costco")
+        );
+        assert!(matches!(
+            recipient.open(other_code_id, Field::Value, &other_value),
+            Err(HoofprintError::CodeLocked(_))
+        ));
+
+        let data_key = DataKey::generate();
+        let sealed = data_key
+            .seal_private_key(&private_key)
+            .expect("Failed to seal private key");
+        assert!(data_key.open_private_key(&sealed).is_some());
+        assert!(DataKey::generate().open_private_key(&sealed).is_none());
+        assert_eq!(format!("{:?}", private_key), "PrivateKey(..)");
+    }
+
+    #[test]
+    fn test_link_token() {
+        let token = LinkToken::generate();
+        assert_ne!(token.as_str(), LinkToken::generate().as_str());
+        assert_eq!(format!("{:?}", token), "LinkToken(..)");
+
+        let parsed = LinkToken::parse(token.as_str()).expect("Failed to parse token");
+        assert_eq!(parsed.lookup_hash(), token.lookup_hash());
+        assert_ne!(token.lookup_hash(), token.as_str());
+        assert!(LinkToken::parse("not a token").is_none());
+        assert!(LinkToken::parse("c2hvcnQ").is_none());
+
+        // the link's key opens what it wrapped, and another link's doesn't
+        let code_key = DataKey::generate();
+        let wrapped = token.key().wrap_key(&code_key).expect("Failed to wrap key");
+        assert_eq!(
+            parsed.key().unwrap_key(&wrapped).map(|key| key.0),
+            Some(code_key.0)
+        );
+        assert!(LinkToken::generate().key().unwrap_key(&wrapped).is_none());
+    }
+}
@@ -1,9 +1,18 @@
 //! A code is a single barcode/identifier that is associated with a user/site
 
-use sea_orm::{ActiveValue::Set, entity::prelude::*, sqlx::types::chrono};
+use std::path::Path;
+
+use sea_orm::{
+    ActiveValue::Set, QueryOrder, TransactionTrait, entity::prelude::*, sqlx::types::chrono,
+};
 use serde::{Deserialize, Serialize};
 
-use crate::{Code, error::HoofprintError};
+use crate::{
+    Code,
+    crypto::{CodeCipher, DataKey, Field, is_sealed},
+    error::HoofprintError,
+    render::RenderOptions,
+};
 
 #[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Serialize, Deserialize)]
 #[sea_orm(table_name = "code")]
@@ -18,32 +27,421 @@ pub struct Model {
     pub created_at: DateTimeUtc,
     pub last_updated: Option<DateTimeUtc>,
     pub name: Option<String>,
+    /// How the code is drawn
+    #[sea_orm(column_type = "Json")]
+    pub render_options: RenderOptions,
+    /// The first day the code can be used
+    pub valid_from: Option<Date>,
+    /// The last day the code can be used
+    pub expires_at: Option<Date>,
+    /// Favourites go before the rest of the user's codes
+    pub favourite: bool,
+    /// Where the code goes in the user's own ordering, lowest first
+    pub position: i32,
+    /// Markdown notes, sealed like the value
+    pub notes: Option<String>,
+    /// The code's own key, wrapped with its owner's data key, or its household's key, once it's
+    /// been shared
+    #[serde(skip_serializing)]
+    pub wrapped_key: Option<String>,
+    /// The household that owns the code, instead of `user_id`, who made it
+    pub household_id: Option<Uuid>,
+}
+
+/// How many days before a code expires it starts being flagged
+pub const EXPIRY_WARNING_DAYS: i64 = 30;
+
+/// Today's date, which codes' validity windows are checked against
+pub fn today() -> Date {
+    chrono::Utc::now().date_naive()
+}
+
+/// Where today falls in a code's validity window
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Validity {
+    Valid,
+    /// Can't be used until this day
+    NotYetValid(Date),
+    /// Expires in this many days, within [EXPIRY_WARNING_DAYS]
+    ExpiresSoon(i64),
+    Expired,
+}
+
+impl Validity {
+    /// The badge to show next to the code, if there's anything to say
+    pub fn badge(&self) -> Option<String> {
+        match self {
+            Validity::Valid => None,
+            Validity::NotYetValid(date) => Some(format!("valid from {}", date)),
+            Validity::ExpiresSoon(0) => Some("expires today".to_string()),
+            Validity::ExpiresSoon(1) => Some("expires in 1 day".to_string()),
+            Validity::ExpiresSoon(days) => Some(format!("expires in {} days", days)),
+            Validity::Expired => Some("expired".to_string()),
+        }
+    }
+
+    /// The CSS class for the badge
+    pub fn badge_class(&self) -> &'static str {
+        match self {
+            Validity::Valid | Validity::NotYetValid(_) => "badge badge_info",
+            Validity::ExpiresSoon(_) => "badge badge_warning",
+            Validity::Expired => "badge badge_expired",
+        }
+    }
+
+    pub fn is_expired(&self) -> bool {
+        matches!(self, Validity::Expired)
+    }
 }
 
 impl Model {
     /// Create a new code model
     pub async fn create_new(
         db: DatabaseConnection,
+        cipher: &CodeCipher,
         user_id: Uuid,
         type_: Code,
         value: &str,
         site_id: Uuid,
         name: Option<&str>,
     ) -> Result<Model, HoofprintError> {
+        let id = Uuid::now_v7();
+        let position = Self::next_position(&db, user_id).await?;
         ActiveModel {
-            id: Set(Uuid::now_v7()),
+            id: Set(id),
             user_id: Set(user_id),
             type_: Set(type_.to_string()),
-            value: Set(value.to_string()),
+            value: Set(cipher.seal_value(id, value)?),
             site_id: Set(site_id),
             created_at: Set(chrono::Utc::now()),
             last_updated: Set(None),
-            name: Set(name.map(|n| n.to_string())),
+            name: Set(cipher.seal_name(id, name.map(|n| n.to_string()))?),
+            render_options: Set(RenderOptions::default()),
+            valid_from: Set(None),
+            expires_at: Set(None),
+            favourite: Set(false),
+            position: Set(position),
+            notes: Set(None),
+            wrapped_key: Set(None),
+            household_id: Set(None),
         }
         .insert(&db)
         .await
         .map_err(HoofprintError::from)
     }
+
+    /// Open the sealed value, name and notes, failing with [HoofprintError::CodeLocked] if they
+    /// were sealed with a key that's been retired
+    pub fn decrypt(mut self, cipher: &CodeCipher) -> Result<Model, HoofprintError> {
+        self.value = cipher.open(self.id, Field::Value, &self.value)?;
+        self.name = self
+            .name
+            .map(|name| cipher.open(self.id, Field::Name, &name))
+            .transpose()?;
+        self.notes = self
+            .notes
+            .map(|notes| cipher.open(self.id, Field::Notes, &notes))
+            .transpose()?;
+        Ok(self)
+    }
+
+    /// Seal any of a user's values and names that were saved before encryption, returning how
+    /// many codes were updated
+    pub(crate) async fn seal_plaintext(
+        db: &DatabaseConnection,
+        user_id: Uuid,
+        cipher: &CodeCipher,
+    ) -> Result<usize, HoofprintError> {
+        let mut sealed = 0;
+        // codes with keys of their own aren't sealed with the user's key
+        for code in Entity::find()
+            .filter(Column::UserId.eq(user_id))
+            .filter(Column::WrappedKey.is_null())
+            .all(db)
+            .await?
+        {
+            let value = (!is_sealed(&code.value))
+                .then(|| cipher.seal_value(code.id, &code.value))
+                .transpose()?;
+            let name = match &code.name {
+                Some(name) if !is_sealed(name) => cipher
+                    .seal_name(code.id, Some(name.clone()))?
+                    .filter(|sealed_name| sealed_name != name),
+                _ => None,
+            };
+            if value.is_none() && name.is_none() {
+                continue;
+            }
+            let mut code: ActiveModel = code.into();
+            if let Some(value) = value {
+                code.value = Set(value);
+            }
+            if let Some(name) = name {
+                code.name = Set(Some(name));
+            }
+            code.update(db).await?;
+            sealed += 1;
+        }
+        Ok(sealed)
+    }
+
+    /// Re-seal a user's codes that were locked by a password reset, using retired data keys the
+    /// user has unwrapped with their old password. Returns how many codes were recovered.
+    pub(crate) async fn recover_locked(
+        db: &DatabaseConnection,
+        user_id: Uuid,
+        retired_keys: &[DataKey],
+        cipher: &CodeCipher,
+    ) -> Result<usize, HoofprintError> {
+        let mut recovered = 0;
+        for code in Entity::find()
+            .filter(Column::UserId.eq(user_id))
+            .filter(Column::HouseholdId.is_null())
+            .all(db)
+            .await?
+        {
+            super::code_revision::Model::recover_locked(db, code.id, retired_keys, cipher).await?;
+            super::code_secret::Model::recover_locked(db, code.id, retired_keys, cipher).await?;
+            if code.clone().decrypt(cipher).is_ok() {
+                continue;
+            }
+            let Some(opened) = retired_keys.iter().find_map(|key| {
+                code.clone()
+                    .decrypt(&CodeCipher::new(key.clone(), false))
+                    .ok()
+            }) else {
+                continue;
+            };
+            let mut code: ActiveModel = code.into();
+            code.value = Set(cipher.seal_value(opened.id, &opened.value)?);
+            code.name = Set(cipher.seal_name(opened.id, opened.name)?);
+            code.notes = Set(opened
+                .notes
+                .map(|notes| cipher.seal_notes(opened.id, &notes))
+                .transpose()?);
+            code.update(db).await?;
+            recovered += 1;
+        }
+        Ok(recovered)
+    }
+
+    /// The code's own key, which it gets the first time it's shared. Until then the code is
+    /// sealed with its owner's data key, so getting a key re-seals it, along with its revisions,
+    /// secrets and photos. `cipher` has to be the owner's, or a household member's.
+    pub(crate) async fn own_key(
+        db: &DatabaseConnection,
+        attachment_dir: Option<&Path>,
+        cipher: &CodeCipher,
+        code_id: Uuid,
+    ) -> Result<DataKey, HoofprintError> {
+        let code = Entity::find_by_id(code_id)
+            .one(db)
+            .await?
+            .ok_or_else(|| HoofprintError::NotFound(format!("Code {}", code_id)))?;
+        if let Some(wrapped) = &code.wrapped_key {
+            return cipher
+                .code_key(code_id)
+                .cloned()
+                .or_else(|| cipher.key().unwrap_key(wrapped))
+                .ok_or_else(|| HoofprintError::CodeLocked(code_id.to_string()));
+        }
+
+        let key = DataKey::generate();
+        let resealed = cipher.with_code_key(code_id, key.clone());
+        let opened = code.clone().decrypt(cipher)?;
+
+        let txn = db.begin().await?;
+        super::code_revision::Model::reseal(&txn, code_id, cipher, &resealed).await?;
+        super::code_secret::Model::reseal(&txn, code_id, cipher, &resealed).await?;
+        let files =
+            super::code_attachment::Model::reseal(&txn, attachment_dir, code_id, cipher, &resealed)
+                .await?;
+        let mut code: ActiveModel = code.into();
+        code.value = Set(resealed.seal_value(code_id, &opened.value)?);
+        code.name = Set(resealed.seal_name(code_id, opened.name)?);
+        code.notes = Set(opened
+            .notes
+            .map(|notes| resealed.seal_notes(code_id, &notes))
+            .transpose()?);
+        code.wrapped_key = Set(Some(cipher.key().wrap_key(&key)?));
+        code.update(&txn).await?;
+        txn.commit().await?;
+
+        files.commit().await?;
+        Ok(key)
+    }
+
+    /// Give a code to a household, whose members can then use it according to their roles, or
+    /// take it back from one to be the user's own. The code's key is wrapped with the
+    /// household's key or the user's data key to match.
+    pub(crate) async fn set_household(
+        db: &DatabaseConnection,
+        attachment_dir: Option<&Path>,
+        cipher: &CodeCipher,
+        code_id: Uuid,
+        user_id: Uuid,
+        household: Option<(Uuid, &DataKey)>,
+    ) -> Result<Model, HoofprintError> {
+        let key = Self::own_key(db, attachment_dir, cipher, code_id).await?;
+        let code = Entity::find_by_id(code_id)
+            .one(db)
+            .await?
+            .ok_or_else(|| HoofprintError::NotFound(format!("Code {}", code_id)))?;
+        let position = Self::next_position(db, user_id).await?;
+        let mut code: ActiveModel = code.into();
+        match household {
+            Some((household_id, household_key)) => {
+                code.household_id = Set(Some(household_id));
+                code.wrapped_key = Set(Some(household_key.wrap_key(&key)?));
+            }
+            None => {
+                code.user_id = Set(user_id);
+                code.household_id = Set(None);
+                code.wrapped_key = Set(Some(cipher.key().wrap_key(&key)?));
+                code.position = Set(position);
+            }
+        }
+        code.update(db).await.map_err(HoofprintError::from)
+    }
+
+    /// Save a change to a code, keeping a revision so it can be undone. `before` is the
+    /// decrypted code as it was before the change.
+    pub(crate) async fn update_with_revision(
+        db: &DatabaseConnection,
+        cipher: &CodeCipher,
+        before: &Model,
+        code: ActiveModel,
+        user_id: Uuid,
+        restored_from: Option<Uuid>,
+    ) -> Result<Model, HoofprintError> {
+        super::code_revision::Model::record_original(db, cipher, before).await?;
+        let updated = code.update(db).await?.decrypt(cipher)?;
+        super::code_revision::Model::record(db, cipher, &updated, user_id, restored_from).await?;
+        Ok(updated)
+    }
+
+    /// Where `today` falls in the code's validity window
+    pub fn validity(&self, today: Date) -> Validity {
+        if let Some(expires_at) = self.expires_at {
+            let days = (expires_at - today).num_days();
+            if days < 0 {
+                return Validity::Expired;
+            }
+            if days <= EXPIRY_WARNING_DAYS {
+                return Validity::ExpiresSoon(days);
+            }
+        }
+        match self.valid_from {
+            Some(valid_from) if valid_from > today => Validity::NotYetValid(valid_from),
+            _ => Validity::Valid,
+        }
+    }
+
+    /// The position that puts a new code after all of the user's others
+    pub(crate) async fn next_position(
+        db: &DatabaseConnection,
+        user_id: Uuid,
+    ) -> Result<i32, HoofprintError> {
+        let last = Entity::find()
+            .filter(Column::UserId.eq(user_id))
+            .filter(Column::HouseholdId.is_null())
+            .order_by_desc(Column::Position)
+            .one(db)
+            .await?;
+        Ok(last.map_or(0, |code| code.position.saturating_add(1)))
+    }
+
+    /// Put some of a user's codes in a new order.
+    ///
+    /// The codes swap between the places they already had, so codes that aren't listed, like ones
+    /// hidden by a tag filter, stay where they are.
+    pub async fn reorder(
+        db: &DatabaseConnection,
+        user_id: Uuid,
+        code_ids: &[Uuid],
+    ) -> Result<(), HoofprintError> {
+        let codes = Entity::find()
+            .filter(Column::UserId.eq(user_id))
+            .filter(Column::HouseholdId.is_null())
+            .order_by_asc(Column::Position)
+            .order_by_asc(Column::CreatedAt)
+            .order_by_asc(Column::Id)
+            .all(db)
+            .await?;
+
+        let mut errors = Vec::new();
+        for (index, code_id) in code_ids.iter().enumerate() {
+            if code_ids[..index].contains(code_id) {
+                errors.push(format!("Code {} is listed more than once", code_id));
+            } else if !codes.iter().any(|code| code.id == *code_id) {
+                errors.push(format!("Code {} wasn't found", code_id));
+            }
+        }
+        if !errors.is_empty() {
+            return Err(HoofprintError::ValidationError(errors));
+        }
+
+        // the places the listed codes had, in order
+        let slots: Vec<usize> = codes
+            .iter()
+            .enumerate()
+            .filter(|(_, code)| code_ids.contains(&code.id))
+            .map(|(slot, _)| slot)
+            .collect();
+        let mut order: Vec<Uuid> = codes.iter().map(|code| code.id).collect();
+        for (slot, code_id) in slots.into_iter().zip(code_ids) {
+            order[slot] = *code_id;
+        }
+
+        for (position, code_id) in order.into_iter().enumerate() {
+            let position = i32::try_from(position).map_err(|_| {
+                HoofprintError::ValidationError(vec!["Too many codes to order".to_string()])
+            })?;
+            let Some(code) = codes.iter().find(|code| code.id == code_id) else {
+                continue;
+            };
+            if code.position != position {
+                let mut code: ActiveModel = code.clone().into();
+                code.position = Set(position);
+                code.update(db).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Every user's codes that expire between `from` and `until`, soonest first, with their owners
+    pub async fn find_expiring(
+        db: &DatabaseConnection,
+        from: Date,
+        until: Date,
+    ) -> Result<Vec<(Model, Option<super::user::Model>)>, HoofprintError> {
+        Entity::find()
+            .filter(Column::ExpiresAt.between(from, until))
+            .find_also_related(super::user::Entity)
+            .order_by_asc(Column::ExpiresAt)
+            .all(db)
+            .await
+            .map_err(HoofprintError::from)
+    }
+
+    /// All of a user's own codes with their sites, favourites first and then in the user's
+    /// order. Codes they've given to a household aren't theirs any more.
+    pub async fn find_for_user(
+        db: &DatabaseConnection,
+        user_id: Uuid,
+    ) -> Result<Vec<(Model, Option<super::site::Model>)>, HoofprintError> {
+        Entity::find()
+            .filter(Column::UserId.eq(user_id))
+            .filter(Column::HouseholdId.is_null())
+            .find_also_related(super::site::Entity)
+            .order_by_desc(Column::Favourite)
+            .order_by_asc(Column::Position)
+            .order_by_asc(Column::CreatedAt)
+            .order_by_asc(Column::Id)
+            .all(db)
+            .await
+            .map_err(HoofprintError::from)
+    }
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -75,3 +473,172 @@ impl Related<super::site::Entity> for Entity {
 }
 
 impl ActiveModelBehavior for ActiveModel {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{config::Configuration, db::connect, prelude::*};
+
+    fn date(year: i32, month: u32, day: u32) -> Date {
+        Date::from_ymd_opt(year, month, day).expect("Invalid date")
+    }
+
+    #[test]
+    fn test_validity() {
+        let today = date(2026, 10, 18);
+        let mut code = Model {
+            id: Uuid::now_v7(),
+            user_id: Uuid::nil(),
+            type_: Code::QR.to_string(),
+            value: "hello".to_string(),
+            site_id: Uuid::nil(),
+            created_at: chrono::Utc::now(),
+            last_updated: None,
+            name: None,
+            render_options: RenderOptions::default(),
+            valid_from: None,
+            expires_at: None,
+            favourite: false,
+            position: 0,
+            notes: None,
+            wrapped_key: None,
+            household_id: None,
+        };
+        assert_eq!(code.validity(today), Validity::Valid);
+        assert_eq!(code.validity(today).badge(), None);
+
+        code.valid_from = Some(date(2026, 11, 1));
+        assert_eq!(
+            code.validity(today),
+            Validity::NotYetValid(date(2026, 11, 1))
+        );
+        code.valid_from = Some(today);
+        assert_eq!(code.validity(today), Validity::Valid);
+
+        code.expires_at = Some(date(2027, 10, 18));
+        assert_eq!(code.validity(today), Validity::Valid);
+        code.expires_at = Some(date(2026, 10, 27));
+        assert_eq!(
+            code.validity(today).badge().as_deref(),
+            Some("expires in 9 days")
+        );
+        code.expires_at = Some(today);
+        assert_eq!(
+            code.validity(today).badge().as_deref(),
+            Some("expires today")
+        );
+        code.expires_at = Some(date(2026, 10, 17));
+        assert!(code.validity(today).is_expired());
+        assert_eq!(code.validity(today).badge().as_deref(), Some("expired"));
+    }
+
+    #[tokio::test]
+    async fn test_find_expiring() {
+        let db = connect(Arc::new(RwLock::new(Configuration::test())))
+            .await
+            .expect("Failed to connect to test database");
+        let cipher = CodeCipher::new(DataKey::generate(), false);
+        let today = date(2026, 10, 18);
+
+        let mut ids = Vec::new();
+        for expires_at in [
+            None,
+            Some(date(2026, 10, 17)),
+            Some(date(2026, 11, 10)),
+            Some(today),
+            Some(date(2027, 1, 1)),
+        ] {
+            let code = Model::create_new(
+                db.clone(),
+                &cipher,
+                Uuid::nil(),
+                Code::QR,
+                "hello",
+                Uuid::nil(),
+                None,
+            )
+            .await
+            .expect("Failed to create code");
+            let mut code: ActiveModel = code.into();
+            code.expires_at = Set(expires_at);
+            ids.push(code.update(&db).await.expect("Failed to update code").id);
+        }
+
+        let expiring = Model::find_expiring(&db, today, date(2026, 11, 17))
+            .await
+            .expect("Failed to find expiring codes");
+        assert_eq!(
+            expiring
+                .iter()
+                .map(|(code, _user)| code.id)
+                .collect::<Vec<_>>(),
+            vec![ids[3], ids[2]]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_reorder() {
+        let db = connect(Arc::new(RwLock::new(Configuration::test())))
+            .await
+            .expect("Failed to connect to test database");
+        let cipher = CodeCipher::new(DataKey::generate(), false);
+
+        let mut ids = Vec::new();
+        for value in ["a", "b", "c", "d"] {
+            let code = Model::create_new(
+                db.clone(),
+                &cipher,
+                Uuid::nil(),
+                Code::QR,
+                value,
+                Uuid::nil(),
+                None,
+            )
+            .await
+            .expect("Failed to create code");
+            ids.push(code.id);
+        }
+        let order = async || {
+            Model::find_for_user(&db, Uuid::nil())
+                .await
+                .expect("Failed to find codes")
+                .into_iter()
+                .map(|(code, _site)| code.id)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(order().await, ids);
+
+        // only the listed codes move, between the places they had
+        Model::reorder(&db, Uuid::nil(), &[ids[3], ids[1]])
+            .await
+            .expect("Failed to reorder codes");
+        assert_eq!(order().await, vec![ids[0], ids[3], ids[2], ids[1]]);
+
+        // favourites go first
+        let mut favourite: ActiveModel = Entity::find_by_id(ids[2])
+            .one(&db)
+            .await
+            .expect("Failed to query code")
+            .expect("Code not found")
+            .into();
+        favourite.favourite = Set(true);
+        favourite.update(&db).await.expect("Failed to update code");
+        assert_eq!(order().await, vec![ids[2], ids[0], ids[3], ids[1]]);
+
+        assert!(
+            Model::reorder(&db, Uuid::nil(), &[ids[0], ids[0]])
+                .await
+                .is_err()
+        );
+        assert!(
+            Model::reorder(&db, Uuid::nil(), &[Uuid::now_v7()])
+                .await
+                .is_err()
+        );
+        assert!(
+            Model::reorder(&db, Uuid::now_v7(), &[ids[0]])
+                .await
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,371 @@
+//! Photos of the front and back of the card a code came from.
+//!
+//! Photos are sealed with the owner's data key like code values are, and kept either in the
+//! database or, when an attachment directory is configured, in a file named after the attachment.
+
+use std::path::{Path, PathBuf};
+
+use sea_orm::{ActiveValue::Set, QueryOrder, QuerySelect, entity::prelude::*, sqlx::types::chrono};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    crypto::{CodeCipher, DataKey},
+    error::HoofprintError,
+};
+
+/// Which side of the card a photo is of
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Side {
+    Front,
+    Back,
+}
+
+impl Side {
+    pub const ALL: [Side; 2] = [Side::Front, Side::Back];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Side::Front => "front",
+            Side::Back => "back",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Side::Front => "Front",
+            Side::Back => "Back",
+        }
+    }
+}
+
+impl TryFrom<&str> for Side {
+    type Error = HoofprintError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        Side::ALL
+            .into_iter()
+            .find(|side| side.as_str() == value)
+            .ok_or_else(|| HoofprintError::NotFound(format!("Side {}", value)))
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "code_attachment")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    pub code_id: Uuid,
+    /// "front" or "back", see [Side]
+    pub side: String,
+    pub content_type: String,
+    /// The size of the photo before it was sealed, in bytes
+    pub size: i64,
+    /// The sealed photo, or None when it's in the attachment directory
+    #[serde(skip_serializing)]
+    pub data: Option<Vec<u8>>,
+    pub created_at: DateTimeUtc,
+}
+
+/// Where a photo kept outside the database lives
+fn blob_path(attachment_dir: &Path, attachment_id: Uuid) -> PathBuf {
+    attachment_dir.join(attachment_id.to_string())
+}
+
+/// Re-sealed photos, written next to the files they replace and only moved over them once the
+/// database has the key they're sealed with. Any that are dropped before then are removed, which
+/// leaves the old files as they were.
+#[derive(Default)]
+pub(crate) struct StagedFiles(Vec<(PathBuf, PathBuf)>);
+
+impl StagedFiles {
+    /// Write a re-sealed photo beside `path`
+    async fn stage(&mut self, path: PathBuf, sealed: &[u8]) -> Result<(), HoofprintError> {
+        let staged = path.with_extension("resealed");
+        self.0.push((staged.clone(), path));
+        tokio::fs::write(staged, sealed).await?;
+        Ok(())
+    }
+
+    /// Move the re-sealed photos over the files they replace
+    pub(crate) async fn commit(mut self) -> Result<(), HoofprintError> {
+        // once they're being moved they're the only good copies, so they're kept if that fails
+        for (staged, path) in std::mem::take(&mut self.0) {
+            tokio::fs::rename(staged, path).await?;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for StagedFiles {
+    fn drop(&mut self) {
+        for (staged, _) in &self.0 {
+            if let Err(err) = std::fs::remove_file(staged)
+                && err.kind() != std::io::ErrorKind::NotFound
+            {
+                tracing::error!(path = %staged.display(), error = ?err, "Failed to remove re-sealed photo");
+            }
+        }
+    }
+}
+
+impl Model {
+    /// Attach a photo to one side of a code, replacing any that's already there
+    pub(crate) async fn save(
+        db: &DatabaseConnection,
+        attachment_dir: Option<&Path>,
+        cipher: &CodeCipher,
+        code_id: Uuid,
+        side: Side,
+        content_type: &str,
+        bytes: &[u8],
+    ) -> Result<Model, HoofprintError> {
+        if let Some(existing) = Self::find_side(db, code_id, side).await? {
+            existing.delete(db, attachment_dir).await?;
+        }
+
+        let id = Uuid::now_v7();
+        let sealed = cipher.seal_attachment(code_id, id, bytes)?;
+        let data = match attachment_dir {
+            Some(attachment_dir) => {
+                tokio::fs::create_dir_all(attachment_dir).await?;
+                tokio::fs::write(blob_path(attachment_dir, id), &sealed).await?;
+                None
+            }
+            None => Some(sealed),
+        };
+        ActiveModel {
+            id: Set(id),
+            code_id: Set(code_id),
+            side: Set(side.as_str().to_string()),
+            content_type: Set(content_type.to_string()),
+            size: Set(i64::try_from(bytes.len()).unwrap_or(i64::MAX)),
+            data: Set(data),
+            created_at: Set(chrono::Utc::now()),
+        }
+        .insert(db)
+        .await
+        .map_err(HoofprintError::from)
+    }
+
+    /// The sealed photo, from wherever it's kept
+    async fn read_sealed(&self, attachment_dir: Option<&Path>) -> Result<Vec<u8>, HoofprintError> {
+        match (&self.data, attachment_dir) {
+            (Some(data), _) => Ok(data.clone()),
+            (None, Some(attachment_dir)) => {
+                Ok(tokio::fs::read(blob_path(attachment_dir, self.id)).await?)
+            }
+            (None, None) => Err(HoofprintError::InternalError(format!(
+                "Attachment {} is kept in an attachment directory, but none is configured",
+                self.id
+            ))),
+        }
+    }
+
+    /// Open the photo
+    pub(crate) async fn read(
+        &self,
+        attachment_dir: Option<&Path>,
+        cipher: &CodeCipher,
+    ) -> Result<Vec<u8>, HoofprintError> {
+        let sealed = self.read_sealed(attachment_dir).await?;
+        cipher.open_attachment(self.code_id, self.id, &sealed)
+    }
+
+    /// Remove the photo, and its file if it has one
+    pub(crate) async fn delete(
+        self,
+        db: &DatabaseConnection,
+        attachment_dir: Option<&Path>,
+    ) -> Result<(), HoofprintError> {
+        if self.data.is_none()
+            && let Some(attachment_dir) = attachment_dir
+        {
+            match tokio::fs::remove_file(blob_path(attachment_dir, self.id)).await {
+                Err(err) if err.kind() != std::io::ErrorKind::NotFound => return Err(err.into()),
+                _ => {}
+            }
+        }
+        Entity::delete_by_id(self.id).exec(db).await?;
+        Ok(())
+    }
+
+    /// A code's photos, front first
+    pub async fn find_for_code(
+        db: &DatabaseConnection,
+        code_id: Uuid,
+    ) -> Result<Vec<Model>, HoofprintError> {
+        let mut attachments = Entity::find()
+            .filter(Column::CodeId.eq(code_id))
+            .all(db)
+            .await?;
+        attachments.sort_by_key(|attachment| {
+            Side::ALL
+                .iter()
+                .position(|side| side.as_str() == attachment.side)
+        });
+        Ok(attachments)
+    }
+
+    /// The photo of one side of a code, if there is one
+    pub async fn find_side(
+        db: &DatabaseConnection,
+        code_id: Uuid,
+        side: Side,
+    ) -> Result<Option<Model>, HoofprintError> {
+        Entity::find()
+            .filter(Column::CodeId.eq(code_id))
+            .filter(Column::Side.eq(side.as_str()))
+            .one(db)
+            .await
+            .map_err(HoofprintError::from)
+    }
+
+    /// Remove all of a code's photos, when it's deleted.
+    ///
+    /// Returns the files in the attachment directory the photos were kept in, to be removed once
+    /// the database is updated.
+    pub(crate) async fn clear_for_code<C: ConnectionTrait>(
+        db: &C,
+        attachment_dir: Option<&Path>,
+        code_id: Uuid,
+    ) -> Result<Vec<PathBuf>, HoofprintError> {
+        let files = match attachment_dir {
+            Some(attachment_dir) => Entity::find()
+                .filter(Column::CodeId.eq(code_id))
+                .filter(Column::Data.is_null())
+                .all(db)
+                .await?
+                .into_iter()
+                .map(|attachment| blob_path(attachment_dir, attachment.id))
+                .collect(),
+            None => Vec::new(),
+        };
+        Entity::delete_many()
+            .filter(Column::CodeId.eq(code_id))
+            .exec(db)
+            .await?;
+        Ok(files)
+    }
+
+    /// Remove files left behind by [Self::clear_for_code], once the photos are gone from the
+    /// database
+    pub(crate) async fn remove_files(files: Vec<PathBuf>) -> Result<(), HoofprintError> {
+        for path in files {
+            match tokio::fs::remove_file(path).await {
+                Err(err) if err.kind() != std::io::ErrorKind::NotFound => return Err(err.into()),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Re-seal a code's photos with another key, when the code gets a key of its own.
+    ///
+    /// Photos in the database are updated straight away. Photos in the attachment directory are
+    /// staged instead, to be moved into place once the database is updated.
+    pub(crate) async fn reseal<C: ConnectionTrait>(
+        db: &C,
+        attachment_dir: Option<&Path>,
+        code_id: Uuid,
+        from: &CodeCipher,
+        to: &CodeCipher,
+    ) -> Result<StagedFiles, HoofprintError> {
+        let mut files = StagedFiles::default();
+        for attachment in Entity::find()
+            .filter(Column::CodeId.eq(code_id))
+            .all(db)
+            .await?
+        {
+            let sealed = attachment.read_sealed(attachment_dir).await?;
+            let opened = from.open_attachment(code_id, attachment.id, &sealed)?;
+            let resealed = to.seal_attachment(code_id, attachment.id, &opened)?;
+            match (&attachment.data, attachment_dir) {
+                (None, Some(attachment_dir)) => {
+                    files
+                        .stage(blob_path(attachment_dir, attachment.id), &resealed)
+                        .await?;
+                }
+                _ => {
+                    let mut attachment: ActiveModel = attachment.into();
+                    attachment.data = Set(Some(resealed));
+                    attachment.update(db).await?;
+                }
+            }
+        }
+        Ok(files)
+    }
+
+    /// Re-seal a user's photos that were locked by a password reset, like
+    /// [super::code::Model::recover_locked] does for their codes
+    pub(crate) async fn recover_locked(
+        db: &DatabaseConnection,
+        attachment_dir: Option<&Path>,
+        user_id: Uuid,
+        retired_keys: &[DataKey],
+        cipher: &CodeCipher,
+    ) -> Result<(), HoofprintError> {
+        let code_ids: Vec<Uuid> = super::code::Entity::find()
+            .select_only()
+            .column(super::code::Column::Id)
+            .filter(super::code::Column::UserId.eq(user_id))
+            .filter(super::code::Column::HouseholdId.is_null())
+            .into_tuple()
+            .all(db)
+            .await?;
+        for attachment in Entity::find()
+            .filter(Column::CodeId.is_in(code_ids))
+            .order_by_asc(Column::Id)
+            .all(db)
+            .await?
+        {
+            let sealed = attachment.read_sealed(attachment_dir).await?;
+            if cipher
+                .open_attachment(attachment.code_id, attachment.id, &sealed)
+                .is_ok()
+            {
+                continue;
+            }
+            let Some(opened) = retired_keys.iter().find_map(|key| {
+                CodeCipher::new(key.clone(), false)
+                    .open_attachment(attachment.code_id, attachment.id, &sealed)
+                    .ok()
+            }) else {
+                continue;
+            };
+            let resealed = cipher.seal_attachment(attachment.code_id, attachment.id, &opened)?;
+            match (&attachment.data, attachment_dir) {
+                (None, Some(attachment_dir)) => {
+                    let mut files = StagedFiles::default();
+                    files
+                        .stage(blob_path(attachment_dir, attachment.id), &resealed)
+                        .await?;
+                    files.commit().await?;
+                }
+                _ => {
+                    let mut attachment: ActiveModel = attachment.into();
+                    attachment.data = Set(Some(resealed));
+                    attachment.update(db).await?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::code::Entity",
+        from = "Column::CodeId",
+        to = "super::code::Column::Id"
+    )]
+    Code,
+}
+
+impl Related<super::code::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Code.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
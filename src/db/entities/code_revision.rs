@@ -0,0 +1,239 @@
+//! A snapshot of a code, saved every time it's changed so old values can be restored
+
+use sea_orm::{ActiveValue::Set, QueryOrder, entity::prelude::*};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    crypto::{CodeCipher, DataKey, Field},
+    error::HoofprintError,
+    render::RenderOptions,
+};
+
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "code_revision")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    pub code_id: Uuid,
+    /// Who made the change
+    pub user_id: Uuid,
+    #[sea_orm(column_name = "type")]
+    pub type_: String,
+    /// Sealed like the code's value, but tied to the revision
+    pub value: String,
+    pub site_id: Uuid,
+    pub name: Option<String>,
+    #[sea_orm(column_type = "Json")]
+    pub render_options: RenderOptions,
+    /// The revision this one put back, if it was a restore
+    pub restored_from: Option<Uuid>,
+    pub created_at: DateTimeUtc,
+    /// Sealed like the code's notes, but tied to the revision
+    pub notes: Option<String>,
+    /// The first day the code could be used
+    pub valid_from: Option<Date>,
+    /// The last day the code could be used
+    pub expires_at: Option<Date>,
+}
+
+/// Seal a revision's value, name and notes, tied to the revision so they can't be swapped with
+/// another revision's or the code's own
+fn seal_fields(
+    revision: &mut ActiveModel,
+    cipher: &CodeCipher,
+    code_id: Uuid,
+    revision_id: Uuid,
+    value: &str,
+    name: Option<&str>,
+    notes: Option<&str>,
+) -> Result<(), HoofprintError> {
+    revision.value = Set(cipher.seal_field(code_id, Field::RevisionValue(revision_id), value)?);
+    revision.name = Set(name
+        .map(|name| cipher.seal_field(code_id, Field::RevisionName(revision_id), name))
+        .transpose()?);
+    revision.notes = Set(notes
+        .map(|notes| cipher.seal_field(code_id, Field::RevisionNotes(revision_id), notes))
+        .transpose()?);
+    Ok(())
+}
+
+impl Model {
+    /// Save a code's current state as a new revision, sealing its value and name again.
+    ///
+    /// `code` has to be decrypted, and the revision is dated when the code was last changed.
+    pub(crate) async fn record(
+        db: &DatabaseConnection,
+        cipher: &CodeCipher,
+        code: &super::code::Model,
+        user_id: Uuid,
+        restored_from: Option<Uuid>,
+    ) -> Result<Model, HoofprintError> {
+        let id = Uuid::now_v7();
+        let mut revision = ActiveModel {
+            id: Set(id),
+            code_id: Set(code.id),
+            user_id: Set(user_id),
+            type_: Set(code.type_.clone()),
+            site_id: Set(code.site_id),
+            render_options: Set(code.render_options.clone()),
+            restored_from: Set(restored_from),
+            created_at: Set(code.last_updated.unwrap_or(code.created_at)),
+            valid_from: Set(code.valid_from),
+            expires_at: Set(code.expires_at),
+            ..Default::default()
+        };
+        seal_fields(
+            &mut revision,
+            cipher,
+            code.id,
+            id,
+            &code.value,
+            code.name.as_deref(),
+            code.notes.as_deref(),
+        )?;
+        revision.insert(db).await.map_err(HoofprintError::from)
+    }
+
+    /// Save the state a code was in before its first change, so it can be restored.
+    ///
+    /// Codes only get revisions when they're changed, so this is a no-op after the first edit.
+    pub(crate) async fn record_original(
+        db: &DatabaseConnection,
+        cipher: &CodeCipher,
+        code: &super::code::Model,
+    ) -> Result<(), HoofprintError> {
+        let existing = Entity::find()
+            .filter(Column::CodeId.eq(code.id))
+            .count(db)
+            .await?;
+        if existing == 0 {
+            Self::record(db, cipher, code, code.user_id, None).await?;
+        }
+        Ok(())
+    }
+
+    /// Open the sealed value, name and notes, failing with [HoofprintError::CodeLocked] if they
+    /// were sealed with a key that's been retired
+    pub fn decrypt(mut self, cipher: &CodeCipher) -> Result<Model, HoofprintError> {
+        self.value = cipher.open(self.code_id, Field::RevisionValue(self.id), &self.value)?;
+        self.name = self
+            .name
+            .map(|name| cipher.open(self.code_id, Field::RevisionName(self.id), &name))
+            .transpose()?;
+        self.notes = self
+            .notes
+            .map(|notes| cipher.open(self.code_id, Field::RevisionNotes(self.id), &notes))
+            .transpose()?;
+        Ok(self)
+    }
+
+    /// A code's revisions, oldest first
+    pub async fn find_for_code(
+        db: &DatabaseConnection,
+        code_id: Uuid,
+    ) -> Result<Vec<Model>, HoofprintError> {
+        Entity::find()
+            .filter(Column::CodeId.eq(code_id))
+            .order_by_asc(Column::CreatedAt)
+            .order_by_asc(Column::Id)
+            .all(db)
+            .await
+            .map_err(HoofprintError::from)
+    }
+
+    /// Re-seal a code's revisions with another key, when the code gets a key of its own
+    pub(crate) async fn reseal<C: ConnectionTrait>(
+        db: &C,
+        code_id: Uuid,
+        from: &CodeCipher,
+        to: &CodeCipher,
+    ) -> Result<(), HoofprintError> {
+        for revision in Entity::find()
+            .filter(Column::CodeId.eq(code_id))
+            .all(db)
+            .await?
+        {
+            // locked revisions stay sealed with the key they were locked with
+            let Ok(opened) = revision.clone().decrypt(from) else {
+                continue;
+            };
+            let mut revision: ActiveModel = revision.into();
+            seal_fields(
+                &mut revision,
+                to,
+                code_id,
+                opened.id,
+                &opened.value,
+                opened.name.as_deref(),
+                opened.notes.as_deref(),
+            )?;
+            revision.update(db).await?;
+        }
+        Ok(())
+    }
+
+    /// Re-seal a code's revisions that were locked by a password reset, like
+    /// [super::code::Model::recover_locked] does for the code itself
+    pub(crate) async fn recover_locked(
+        db: &DatabaseConnection,
+        code_id: Uuid,
+        retired_keys: &[DataKey],
+        cipher: &CodeCipher,
+    ) -> Result<(), HoofprintError> {
+        for revision in Self::find_for_code(db, code_id).await? {
+            if revision.clone().decrypt(cipher).is_ok() {
+                continue;
+            }
+            let Some(opened) = retired_keys.iter().find_map(|key| {
+                revision
+                    .clone()
+                    .decrypt(&CodeCipher::new(key.clone(), false))
+                    .ok()
+            }) else {
+                continue;
+            };
+            let mut revision: ActiveModel = revision.into();
+            seal_fields(
+                &mut revision,
+                cipher,
+                code_id,
+                opened.id,
+                &opened.value,
+                opened.name.as_deref(),
+                opened.notes.as_deref(),
+            )?;
+            revision.update(db).await?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::code::Entity",
+        from = "Column::CodeId",
+        to = "super::code::Column::Id"
+    )]
+    Code,
+    #[sea_orm(
+        belongs_to = "super::user::Entity",
+        from = "Column::UserId",
+        to = "super::user::Column::Id"
+    )]
+    User,
+}
+
+impl Related<super::code::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Code.def()
+    }
+}
+
+impl Related<super::user::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::User.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,208 @@
+//! Labelled secrets that go with a code, like a gift card's PIN.
+//!
+//! Values are sealed like code values, and are only ever opened when their owner asks to see one,
+//! so they're left out of Debug output and serialisation to keep them out of logs and lists.
+
+use std::fmt::Debug;
+
+use sea_orm::{ActiveValue::Set, QueryOrder, entity::prelude::*, sqlx::types::chrono};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    crypto::{CodeCipher, DataKey, Field},
+    error::HoofprintError,
+};
+
+/// The most secrets a code can have
+pub const MAX_SECRETS_PER_CODE: usize = 10;
+/// The longest a secret's label can be
+pub const MAX_SECRET_LABEL_LENGTH: usize = 64;
+
+#[derive(Clone, PartialEq, Eq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "code_secret")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    pub code_id: Uuid,
+    /// What the secret is, like "PIN" or "CVV"
+    pub label: String,
+    /// Sealed the same way as the code's value
+    #[serde(skip_serializing)]
+    pub value: String,
+    /// Whether the owner has to enter their password again to see it
+    pub require_password: bool,
+    pub created_at: DateTimeUtc,
+}
+
+impl Debug for Model {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // never log the value, even sealed
+        f.debug_struct("Model")
+            .field("id", &self.id)
+            .field("code_id", &self.code_id)
+            .field("label", &self.label)
+            .field("require_password", &self.require_password)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Model {
+    /// Add a secret to a code
+    pub(crate) async fn add(
+        db: &DatabaseConnection,
+        cipher: &CodeCipher,
+        code_id: Uuid,
+        label: &str,
+        value: &str,
+        require_password: bool,
+    ) -> Result<Model, HoofprintError> {
+        let id = Uuid::now_v7();
+        ActiveModel {
+            id: Set(id),
+            code_id: Set(code_id),
+            label: Set(label.to_string()),
+            value: Set(cipher.seal_secret(code_id, id, value)?),
+            require_password: Set(require_password),
+            created_at: Set(chrono::Utc::now()),
+        }
+        .insert(db)
+        .await
+        .map_err(HoofprintError::from)
+    }
+
+    /// A code's secrets, in the order they were added
+    pub async fn find_for_code(
+        db: &DatabaseConnection,
+        code_id: Uuid,
+    ) -> Result<Vec<Model>, HoofprintError> {
+        Entity::find()
+            .filter(Column::CodeId.eq(code_id))
+            .order_by_asc(Column::CreatedAt)
+            .order_by_asc(Column::Id)
+            .all(db)
+            .await
+            .map_err(HoofprintError::from)
+    }
+
+    /// One of a code's secrets
+    pub async fn find_for_code_by_id(
+        db: &DatabaseConnection,
+        code_id: Uuid,
+        secret_id: Uuid,
+    ) -> Result<Model, HoofprintError> {
+        Entity::find_by_id(secret_id)
+            .filter(Column::CodeId.eq(code_id))
+            .one(db)
+            .await?
+            .ok_or_else(|| HoofprintError::NotFound(format!("Secret {}", secret_id)))
+    }
+
+    /// Open the secret's value
+    pub(crate) fn reveal(&self, cipher: &CodeCipher) -> Result<String, HoofprintError> {
+        cipher.open(self.code_id, Field::Secret(self.id), &self.value)
+    }
+
+    /// Remove all of a code's secrets, when it's deleted
+    pub(crate) async fn clear_for_code<C: ConnectionTrait>(
+        db: &C,
+        code_id: Uuid,
+    ) -> Result<(), HoofprintError> {
+        Entity::delete_many()
+            .filter(Column::CodeId.eq(code_id))
+            .exec(db)
+            .await?;
+        Ok(())
+    }
+
+    /// Re-seal a code's secrets with another key, when the code gets a key of its own
+    pub(crate) async fn reseal<C: ConnectionTrait>(
+        db: &C,
+        code_id: Uuid,
+        from: &CodeCipher,
+        to: &CodeCipher,
+    ) -> Result<(), HoofprintError> {
+        for secret in Entity::find()
+            .filter(Column::CodeId.eq(code_id))
+            .all(db)
+            .await?
+        {
+            let Ok(opened) = secret.reveal(from) else {
+                continue;
+            };
+            let secret_id = secret.id;
+            let mut secret: ActiveModel = secret.into();
+            secret.value = Set(to.seal_secret(code_id, secret_id, &opened)?);
+            secret.update(db).await?;
+        }
+        Ok(())
+    }
+
+    /// Re-seal a code's secrets that were locked by a password reset, like
+    /// [super::code::Model::recover_locked] does for the code
+    pub(crate) async fn recover_locked(
+        db: &DatabaseConnection,
+        code_id: Uuid,
+        retired_keys: &[DataKey],
+        cipher: &CodeCipher,
+    ) -> Result<(), HoofprintError> {
+        for secret in Self::find_for_code(db, code_id).await? {
+            if secret.reveal(cipher).is_ok() {
+                continue;
+            }
+            let Some(opened) = retired_keys
+                .iter()
+                .find_map(|key| secret.reveal(&CodeCipher::new(key.clone(), false)).ok())
+            else {
+                continue;
+            };
+            let secret_id = secret.id;
+            let mut secret: ActiveModel = secret.into();
+            secret.value = Set(cipher.seal_secret(code_id, secret_id, &opened)?);
+            secret.update(db).await?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::code::Entity",
+        from = "Column::CodeId",
+        to = "super::code::Column::Id"
+    )]
+    Code,
+}
+
+impl Related<super::code::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Code.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_debug_leaves_out_value() {
+        let secret = Model {
+            id: Uuid::nil(),
+            code_id: Uuid::nil(),
+            label: "PIN".to_string(),
+            value: "4321".to_string(),
+            require_password: false,
+            created_at: chrono::Utc::now(),
+        };
+        let debug = format!("{:?}", secret);
+        assert!(debug.contains("PIN"));
+        assert!(!debug.contains("4321"));
+        assert!(
+            !serde_json::to_string(&secret)
+                .expect("Failed to serialise")
+                .contains("4321")
+        );
+    }
+}
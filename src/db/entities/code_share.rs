@@ -0,0 +1,506 @@
+//! Codes shared with other users, who can view them or edit them too.
+//!
+//! Sharing a code gives it a key of its own, see [crate::crypto], which each person it's shared
+//! with holds a copy of.
+
+use std::collections::{HashMap, hash_map::Entry};
+
+use sea_orm::{
+    ActiveValue::Set, IntoActiveModel, QueryOrder, entity::prelude::*, sqlx::types::chrono,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    crypto::{CodeCipher, DataKey, PrivateKey, is_public_wrapped},
+    error::HoofprintError,
+};
+
+/// What a user can do with a code
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Permission {
+    View,
+    Edit,
+    Owner,
+}
+
+impl Permission {
+    pub fn label(self) -> &'static str {
+        match self {
+            Permission::View => "Can view",
+            Permission::Edit => "Can edit",
+            Permission::Owner => "Owner",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "code_share")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    pub code_id: Uuid,
+    /// Who the code's shared with
+    pub user_id: Uuid,
+    /// Whether they can change the code, as well as see it
+    pub can_edit: bool,
+    /// The code's key, wrapped with the user's public key until they next log in and then with
+    /// their data key
+    #[serde(skip_serializing)]
+    pub wrapped_key: String,
+    pub created_at: DateTimeUtc,
+}
+
+impl Model {
+    pub fn permission(&self) -> Permission {
+        if self.can_edit {
+            Permission::Edit
+        } else {
+            Permission::View
+        }
+    }
+
+    /// What a user can do with a code, if anything. A household's code belongs to its members
+    /// according to their roles, rather than to whoever made it.
+    pub async fn permission_for(
+        db: &DatabaseConnection,
+        code: &super::code::Model,
+        user_id: Uuid,
+    ) -> Result<Option<Permission>, HoofprintError> {
+        let owned = match code.household_id {
+            Some(household_id) => super::household_member::Model::find_for_household_and_user(
+                db,
+                household_id,
+                user_id,
+            )
+            .await?
+            .map(|member| member.role().permission()),
+            None => (code.user_id == user_id).then_some(Permission::Owner),
+        };
+        if owned == Some(Permission::Owner) {
+            return Ok(owned);
+        }
+        let shared = Self::find_for_code_and_user(db, code.id, user_id)
+            .await?
+            .map(|share| share.permission());
+        Ok(owned.max(shared))
+    }
+
+    pub async fn find_for_code_and_user(
+        db: &DatabaseConnection,
+        code_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<Option<Model>, HoofprintError> {
+        Entity::find()
+            .filter(Column::CodeId.eq(code_id))
+            .filter(Column::UserId.eq(user_id))
+            .one(db)
+            .await
+            .map_err(HoofprintError::from)
+    }
+
+    /// Who a code's shared with, in the order it was shared
+    pub async fn find_for_code(
+        db: &DatabaseConnection,
+        code_id: Uuid,
+    ) -> Result<Vec<(Model, Option<super::user::Model>)>, HoofprintError> {
+        Entity::find()
+            .filter(Column::CodeId.eq(code_id))
+            .find_also_related(super::user::Entity)
+            .order_by_asc(Column::CreatedAt)
+            .all(db)
+            .await
+            .map_err(HoofprintError::from)
+    }
+
+    /// The codes other people have shared with a user, with their sites and the names of their
+    /// owners, or households, oldest share first
+    pub async fn codes_shared_with(
+        db: &DatabaseConnection,
+        user_id: Uuid,
+    ) -> Result<Vec<SharedCode>, HoofprintError> {
+        let shares = Entity::find()
+            .filter(Column::UserId.eq(user_id))
+            .order_by_asc(Column::CreatedAt)
+            .all(db)
+            .await?;
+        let code_ids: Vec<Uuid> = shares.iter().map(|share| share.code_id).collect();
+        let codes = super::code::Entity::find()
+            .filter(super::code::Column::Id.is_in(code_ids))
+            .find_also_related(super::site::Entity)
+            .all(db)
+            .await?;
+        let owner_ids: Vec<Uuid> = codes.iter().map(|(code, _)| code.user_id).collect();
+        let owners = super::user::Entity::find()
+            .filter(super::user::Column::Id.is_in(owner_ids))
+            .all(db)
+            .await?;
+        let households = super::household::Entity::find()
+            .filter(
+                super::household::Column::Id
+                    .is_in(codes.iter().filter_map(|(code, _)| code.household_id)),
+            )
+            .all(db)
+            .await?;
+
+        Ok(shares
+            .into_iter()
+            .filter_map(|share| {
+                let (code, site) = codes.iter().find(|(code, _)| code.id == share.code_id)?;
+                let owner_name = match code.household_id {
+                    Some(household_id) => households
+                        .iter()
+                        .find(|household| household.id == household_id)?
+                        .name
+                        .clone(),
+                    None => owners
+                        .iter()
+                        .find(|owner| owner.id == code.user_id)?
+                        .display_name
+                        .clone(),
+                };
+                Some(SharedCode {
+                    code: code.clone(),
+                    site: site.clone(),
+                    owner_name,
+                })
+            })
+            .collect())
+    }
+
+    /// Share a code with someone, or change what they can do with it if it's already shared
+    /// with them. `code_key` is the code's own key, see [super::code::Model::own_key].
+    pub(crate) async fn share(
+        db: &DatabaseConnection,
+        code_id: Uuid,
+        code_key: &DataKey,
+        recipient: &super::user::Model,
+        can_edit: bool,
+    ) -> Result<Model, HoofprintError> {
+        if let Some(existing) = Self::find_for_code_and_user(db, code_id, recipient.id).await? {
+            let mut share = existing.into_active_model();
+            share.can_edit = Set(can_edit);
+            return share.update(db).await.map_err(HoofprintError::from);
+        }
+        let public_key = recipient.public_key.as_deref().ok_or_else(|| {
+            HoofprintError::ValidationError(vec![format!(
+                "{} needs to log in once before codes can be shared with them",
+                recipient.email
+            )])
+        })?;
+        ActiveModel {
+            id: Set(Uuid::now_v7()),
+            code_id: Set(code_id),
+            user_id: Set(recipient.id),
+            can_edit: Set(can_edit),
+            wrapped_key: Set(code_key.wrap_for_public_key(public_key)?),
+            created_at: Set(chrono::Utc::now()),
+        }
+        .insert(db)
+        .await
+        .map_err(HoofprintError::from)
+    }
+
+    /// Stop sharing all of a code, when it's deleted
+    pub(crate) async fn clear_for_code<C: ConnectionTrait>(
+        db: &C,
+        code_id: Uuid,
+    ) -> Result<(), HoofprintError> {
+        Entity::delete_many()
+            .filter(Column::CodeId.eq(code_id))
+            .exec(db)
+            .await?;
+        Ok(())
+    }
+
+    /// Add the keys of the given codes that have keys of their own to the user's cipher: ones
+    /// they've shared, their households' and ones shared with them. Keys that can't be unwrapped,
+    /// like after a password reset, are left out, so those codes show up as locked.
+    pub(crate) async fn load_code_keys(
+        db: &DatabaseConnection,
+        user_id: Uuid,
+        cipher: &mut CodeCipher,
+        codes: &[&super::code::Model],
+    ) -> Result<(), HoofprintError> {
+        let mut keys = UserKeys::new(user_id);
+        let mut household_keys: HashMap<Uuid, Option<DataKey>> = HashMap::new();
+        let mut unresolved = Vec::new();
+        for code in codes.iter().copied() {
+            let Some(wrapped) = code.wrapped_key.as_deref() else {
+                continue;
+            };
+            if cipher.code_key(code.id).is_some() {
+                continue;
+            }
+            let key = match code.household_id {
+                Some(household_id) => {
+                    let household_key = match household_keys.entry(household_id) {
+                        Entry::Occupied(entry) => entry.into_mut(),
+                        Entry::Vacant(entry) => {
+                            let member =
+                                super::household_member::Model::find_for_household_and_user(
+                                    db,
+                                    household_id,
+                                    user_id,
+                                )
+                                .await?;
+                            let household_key = match member {
+                                Some(member) => {
+                                    keys.unwrap(db, cipher, &member.wrapped_key).await?
+                                }
+                                None => None,
+                            };
+                            entry.insert(household_key)
+                        }
+                    };
+                    household_key
+                        .as_ref()
+                        .and_then(|household_key| household_key.unwrap_key(wrapped))
+                }
+                None if code.user_id == user_id => cipher.key().unwrap_key(wrapped),
+                None => None,
+            };
+            match key {
+                Some(key) => cipher.add_code_key(code.id, key),
+                None => unresolved.push(code.id),
+            }
+        }
+        if unresolved.is_empty() {
+            return Ok(());
+        }
+
+        // whatever's left can only be open to the user if it's been shared with them
+        for share in Entity::find()
+            .filter(Column::UserId.eq(user_id))
+            .filter(Column::CodeId.is_in(unresolved))
+            .all(db)
+            .await?
+        {
+            if let Some(key) = keys.unwrap(db, cipher, &share.wrapped_key).await? {
+                cipher.add_code_key(share.code_id, key);
+            }
+        }
+        Ok(())
+    }
+
+    /// Re-wrap the keys that were wrapped with the user's public key, for the households they've
+    /// joined and the codes shared with them since they last logged in, with their data key,
+    /// which is quicker to unwrap
+    pub(crate) async fn rewrap_public_keys(
+        db: &DatabaseConnection,
+        user: &super::user::Model,
+        data_key: &DataKey,
+    ) -> Result<(), HoofprintError> {
+        let Some(private_key) = user
+            .private_key
+            .as_deref()
+            .and_then(|sealed| data_key.open_private_key(sealed))
+        else {
+            return Ok(());
+        };
+        let rewrap = |wrapped: &str| -> Result<Option<String>, HoofprintError> {
+            if !is_public_wrapped(wrapped) {
+                return Ok(None);
+            }
+            private_key
+                .unwrap_key(wrapped)
+                .map(|key| data_key.wrap_key(&key))
+                .transpose()
+        };
+
+        for member in super::household_member::Entity::find()
+            .filter(super::household_member::Column::UserId.eq(user.id))
+            .all(db)
+            .await?
+        {
+            let Some(rewrapped) = rewrap(&member.wrapped_key)? else {
+                continue;
+            };
+            let mut member = member.into_active_model();
+            member.wrapped_key = Set(rewrapped);
+            member.update(db).await?;
+        }
+
+        for share in Entity::find()
+            .filter(Column::UserId.eq(user.id))
+            .all(db)
+            .await?
+        {
+            let Some(rewrapped) = rewrap(&share.wrapped_key)? else {
+                continue;
+            };
+            let mut share = share.into_active_model();
+            share.wrapped_key = Set(rewrapped);
+            share.update(db).await?;
+        }
+        Ok(())
+    }
+
+    /// Re-wrap a user's keys that were locked by a password reset: their private key, the keys
+    /// of codes they've shared, their households' keys and the keys of codes shared with them.
+    /// Keys wrapped with their public key while it was locked are switched over too, see
+    /// [Self::rewrap_public_keys].
+    pub(crate) async fn recover_locked_keys(
+        db: &DatabaseConnection,
+        user: &super::user::Model,
+        retired_keys: &[DataKey],
+        data_key: &DataKey,
+    ) -> Result<(), HoofprintError> {
+        let rewrap = |wrapped: &str| -> Result<Option<String>, HoofprintError> {
+            if data_key.unwrap_key(wrapped).is_some() {
+                return Ok(None);
+            }
+            retired_keys
+                .iter()
+                .find_map(|retired| retired.unwrap_key(wrapped))
+                .map(|key| data_key.wrap_key(&key))
+                .transpose()
+        };
+
+        let mut user = user.clone();
+        if let Some(sealed) = &user.private_key
+            && data_key.open_private_key(sealed).is_none()
+            && let Some(private_key) = retired_keys
+                .iter()
+                .find_map(|retired| retired.open_private_key(sealed))
+        {
+            let mut active = user.into_active_model();
+            active.private_key = Set(Some(data_key.seal_private_key(&private_key)?));
+            user = active.update(db).await?;
+        }
+
+        for code in super::code::Entity::find()
+            .filter(super::code::Column::UserId.eq(user.id))
+            .filter(super::code::Column::HouseholdId.is_null())
+            .filter(super::code::Column::WrappedKey.is_not_null())
+            .all(db)
+            .await?
+        {
+            let Some(rewrapped) = code
+                .wrapped_key
+                .as_deref()
+                .map(rewrap)
+                .transpose()?
+                .flatten()
+            else {
+                continue;
+            };
+            let mut code = code.into_active_model();
+            code.wrapped_key = Set(Some(rewrapped));
+            code.update(db).await?;
+        }
+
+        for member in super::household_member::Entity::find()
+            .filter(super::household_member::Column::UserId.eq(user.id))
+            .all(db)
+            .await?
+        {
+            if is_public_wrapped(&member.wrapped_key) {
+                continue;
+            }
+            let Some(rewrapped) = rewrap(&member.wrapped_key)? else {
+                continue;
+            };
+            let mut member = member.into_active_model();
+            member.wrapped_key = Set(rewrapped);
+            member.update(db).await?;
+        }
+
+        for share in Entity::find()
+            .filter(Column::UserId.eq(user.id))
+            .all(db)
+            .await?
+        {
+            if is_public_wrapped(&share.wrapped_key) {
+                continue;
+            }
+            let Some(rewrapped) = rewrap(&share.wrapped_key)? else {
+                continue;
+            };
+            let mut share = share.into_active_model();
+            share.wrapped_key = Set(rewrapped);
+            share.update(db).await?;
+        }
+        Self::rewrap_public_keys(db, &user, data_key).await
+    }
+}
+
+/// Unwraps keys that were wrapped for a user, opening their private key the first time one was
+/// wrapped with their public key
+pub(crate) struct UserKeys {
+    user_id: Uuid,
+    /// Looked up the first time it's needed, and `None` inside if it couldn't be opened
+    private_key: Option<Option<PrivateKey>>,
+}
+
+impl UserKeys {
+    pub(crate) fn new(user_id: Uuid) -> Self {
+        Self {
+            user_id,
+            private_key: None,
+        }
+    }
+
+    /// Unwrap a key with the user's data key, or their private key if it was wrapped with their
+    /// public key after they last logged in
+    pub(crate) async fn unwrap(
+        &mut self,
+        db: &DatabaseConnection,
+        cipher: &CodeCipher,
+        wrapped: &str,
+    ) -> Result<Option<DataKey>, HoofprintError> {
+        if !is_public_wrapped(wrapped) {
+            return Ok(cipher.key().unwrap_key(wrapped));
+        }
+        if self.private_key.is_none() {
+            self.private_key = Some(
+                super::user::Entity::find_by_id(self.user_id)
+                    .one(db)
+                    .await?
+                    .and_then(|user| user.private_key)
+                    .and_then(|sealed| cipher.key().open_private_key(&sealed)),
+            );
+        }
+        Ok(self
+            .private_key
+            .as_ref()
+            .and_then(Option::as_ref)
+            .and_then(|private_key| private_key.unwrap_key(wrapped)))
+    }
+}
+
+/// A code someone else shared with the user
+pub struct SharedCode {
+    pub code: super::code::Model,
+    pub site: Option<super::site::Model>,
+    pub owner_name: String,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::code::Entity",
+        from = "Column::CodeId",
+        to = "super::code::Column::Id"
+    )]
+    Code,
+    #[sea_orm(
+        belongs_to = "super::user::Entity",
+        from = "Column::UserId",
+        to = "super::user::Column::Id"
+    )]
+    User,
+}
+
+impl Related<super::code::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Code.def()
+    }
+}
+
+impl Related<super::user::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::User.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
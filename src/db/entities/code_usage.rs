@@ -0,0 +1,119 @@
+//! A record of a code being shown, so users can see which codes they actually use
+
+use std::collections::HashMap;
+
+use sea_orm::{ActiveValue::Set, QuerySelect, entity::prelude::*, sqlx::types::chrono};
+use serde::{Deserialize, Serialize};
+
+use crate::error::HoofprintError;
+
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "code_usage")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    pub code_id: Uuid,
+    pub user_id: Uuid,
+    pub used_at: DateTimeUtc,
+    /// Where the code was used, as "latitude,longitude" rounded to about 10km
+    pub location: Option<String>,
+}
+
+/// How much a code has been used
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct UsageStats {
+    pub times_used: i64,
+    pub last_used: DateTimeUtc,
+}
+
+impl Model {
+    /// Record a code being shown to its owner
+    pub(crate) async fn record(
+        db: &DatabaseConnection,
+        code_id: Uuid,
+        user_id: Uuid,
+        location: Option<String>,
+    ) -> Result<(), HoofprintError> {
+        ActiveModel {
+            id: Set(Uuid::now_v7()),
+            code_id: Set(code_id),
+            user_id: Set(user_id),
+            used_at: Set(chrono::Utc::now()),
+            location: Set(location),
+        }
+        .insert(db)
+        .await?;
+        Ok(())
+    }
+
+    /// How often each of the given codes has been used, leaving out ones that never have
+    pub async fn stats_for_codes(
+        db: &DatabaseConnection,
+        code_ids: &[Uuid],
+    ) -> Result<HashMap<Uuid, UsageStats>, HoofprintError> {
+        let rows: Vec<(Uuid, i64, DateTimeUtc)> = Entity::find()
+            .select_only()
+            .column(Column::CodeId)
+            .column_as(Column::Id.count(), "times_used")
+            .column_as(Column::UsedAt.max(), "last_used")
+            .filter(Column::CodeId.is_in(code_ids.iter().copied()))
+            .group_by(Column::CodeId)
+            .into_tuple()
+            .all(db)
+            .await?;
+        Ok(rows
+            .into_iter()
+            .map(|(code_id, times_used, last_used)| {
+                (
+                    code_id,
+                    UsageStats {
+                        times_used,
+                        last_used,
+                    },
+                )
+            })
+            .collect())
+    }
+
+    /// Forget every time one of a user's codes was used
+    pub(crate) async fn clear_for_user(
+        db: &DatabaseConnection,
+        user_id: Uuid,
+    ) -> Result<(), HoofprintError> {
+        Entity::delete_many()
+            .filter(Column::UserId.eq(user_id))
+            .exec(db)
+            .await?;
+        Ok(())
+    }
+
+    /// Forget every time a code was used, when it's deleted
+    pub(crate) async fn clear_for_code<C: ConnectionTrait>(
+        db: &C,
+        code_id: Uuid,
+    ) -> Result<(), HoofprintError> {
+        Entity::delete_many()
+            .filter(Column::CodeId.eq(code_id))
+            .exec(db)
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::code::Entity",
+        from = "Column::CodeId",
+        to = "super::code::Column::Id"
+    )]
+    Code,
+}
+
+impl Related<super::code::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Code.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,112 @@
+//! A household is a group of users who own a set of codes together, like a family's loyalty
+//! cards.
+//!
+//! A household has a key of its own, which every member holds a copy of, see
+//! [super::household_member]. The codes it owns have their keys wrapped with it, so anyone who
+//! joins can open them straight away. When someone leaves the household gets a new key, so they
+//! lose access to its codes through hoofprint and can't open anything it's given afterwards.
+
+use sea_orm::{
+    ActiveValue::Set, QueryOrder, TransactionTrait, entity::prelude::*, sqlx::types::chrono,
+};
+use serde::{Deserialize, Serialize};
+
+use super::household_member::Role;
+use crate::{
+    crypto::{CodeCipher, DataKey},
+    error::HoofprintError,
+};
+
+/// The longest a household's name can be
+pub const MAX_HOUSEHOLD_NAME_LENGTH: usize = 64;
+
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "household")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    pub name: String,
+    pub created_at: DateTimeUtc,
+}
+
+impl Model {
+    /// Make a household, with the user who made it as its admin
+    pub(crate) async fn create_new(
+        db: &DatabaseConnection,
+        cipher: &CodeCipher,
+        user_id: Uuid,
+        name: &str,
+    ) -> Result<Model, HoofprintError> {
+        let household_key = DataKey::generate();
+        let now = chrono::Utc::now();
+        let txn = db.begin().await?;
+        let household = ActiveModel {
+            id: Set(Uuid::now_v7()),
+            name: Set(name.to_string()),
+            created_at: Set(now),
+        }
+        .insert(&txn)
+        .await?;
+        super::household_member::ActiveModel {
+            id: Set(Uuid::now_v7()),
+            household_id: Set(household.id),
+            user_id: Set(user_id),
+            role: Set(Role::Admin.as_str().to_string()),
+            wrapped_key: Set(cipher.key().wrap_key(&household_key)?),
+            created_at: Set(now),
+        }
+        .insert(&txn)
+        .await?;
+        txn.commit().await?;
+        Ok(household)
+    }
+
+    /// The codes owned by the households a user is in, with their sites and households,
+    /// favourites first and then in order
+    pub async fn codes_for_member(
+        db: &DatabaseConnection,
+        user_id: Uuid,
+    ) -> Result<Vec<(super::code::Model, Option<super::site::Model>, Model)>, HoofprintError> {
+        let households: Vec<Model> = super::household_member::Model::find_for_user(db, user_id)
+            .await?
+            .into_iter()
+            .filter_map(|(_, household)| household)
+            .collect();
+        let codes = super::code::Entity::find()
+            .filter(
+                super::code::Column::HouseholdId
+                    .is_in(households.iter().map(|household| household.id)),
+            )
+            .find_also_related(super::site::Entity)
+            .order_by_desc(super::code::Column::Favourite)
+            .order_by_asc(super::code::Column::Position)
+            .order_by_asc(super::code::Column::CreatedAt)
+            .order_by_asc(super::code::Column::Id)
+            .all(db)
+            .await?;
+
+        Ok(codes
+            .into_iter()
+            .filter_map(|(code, site)| {
+                let household = households
+                    .iter()
+                    .find(|household| Some(household.id) == code.household_id)?;
+                Some((code, site, household.clone()))
+            })
+            .collect())
+    }
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(has_many = "super::household_member::Entity")]
+    Member,
+}
+
+impl Related<super::household_member::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Member.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
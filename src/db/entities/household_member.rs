@@ -0,0 +1,301 @@
+//! Who's in a household, and what they can do with its codes
+
+use sea_orm::{
+    ActiveValue::Set, QueryOrder, TransactionTrait, entity::prelude::*, sqlx::types::chrono,
+};
+use serde::{Deserialize, Serialize};
+
+use super::code_share::{Permission, UserKeys};
+use crate::{
+    crypto::{CodeCipher, DataKey},
+    error::HoofprintError,
+};
+
+/// What a member can do in a household
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Role {
+    /// Can see the household's codes
+    Viewer,
+    /// Can see and change the household's codes
+    Member,
+    /// Can delete and share the household's codes, and choose who's in it
+    Admin,
+}
+
+impl Role {
+    pub const ALL: [Role; 3] = [Role::Viewer, Role::Member, Role::Admin];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Role::Viewer => "viewer",
+            Role::Member => "member",
+            Role::Admin => "admin",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Role::Viewer => "Viewer",
+            Role::Member => "Member",
+            Role::Admin => "Admin",
+        }
+    }
+
+    /// What someone with this role can do with the household's codes
+    pub fn permission(self) -> Permission {
+        match self {
+            Role::Viewer => Permission::View,
+            Role::Member => Permission::Edit,
+            Role::Admin => Permission::Owner,
+        }
+    }
+}
+
+impl TryFrom<&str> for Role {
+    type Error = HoofprintError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        Role::ALL
+            .into_iter()
+            .find(|role| role.as_str() == value)
+            .ok_or_else(|| {
+                HoofprintError::ValidationError(vec![format!("Invalid role: {}", value)])
+            })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "household_member")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    pub household_id: Uuid,
+    pub user_id: Uuid,
+    /// See [Role]
+    pub role: String,
+    /// The household's key, wrapped with the member's public key until they next log in and
+    /// then with their data key
+    #[serde(skip_serializing)]
+    pub wrapped_key: String,
+    pub created_at: DateTimeUtc,
+}
+
+impl Model {
+    /// The member's role, which is the least they could have if it's not one that's known
+    pub fn role(&self) -> Role {
+        Role::try_from(self.role.as_str()).unwrap_or(Role::Viewer)
+    }
+
+    pub async fn find_for_household_and_user(
+        db: &DatabaseConnection,
+        household_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<Option<Model>, HoofprintError> {
+        Entity::find()
+            .filter(Column::HouseholdId.eq(household_id))
+            .filter(Column::UserId.eq(user_id))
+            .one(db)
+            .await
+            .map_err(HoofprintError::from)
+    }
+
+    /// Everyone in a household, in the order they joined
+    pub async fn find_for_household(
+        db: &DatabaseConnection,
+        household_id: Uuid,
+    ) -> Result<Vec<(Model, Option<super::user::Model>)>, HoofprintError> {
+        Entity::find()
+            .filter(Column::HouseholdId.eq(household_id))
+            .find_also_related(super::user::Entity)
+            .order_by_asc(Column::CreatedAt)
+            .all(db)
+            .await
+            .map_err(HoofprintError::from)
+    }
+
+    /// The households a user is in, in the order they joined them
+    pub async fn find_for_user(
+        db: &DatabaseConnection,
+        user_id: Uuid,
+    ) -> Result<Vec<(Model, Option<super::household::Model>)>, HoofprintError> {
+        Entity::find()
+            .filter(Column::UserId.eq(user_id))
+            .find_also_related(super::household::Entity)
+            .order_by_asc(Column::CreatedAt)
+            .all(db)
+            .await
+            .map_err(HoofprintError::from)
+    }
+
+    /// The household's key, unwrapped with the member's data key, or their private key if they
+    /// joined after they last logged in. `cipher` has to be the member's.
+    pub(crate) async fn household_key(
+        &self,
+        db: &DatabaseConnection,
+        cipher: &CodeCipher,
+    ) -> Result<DataKey, HoofprintError> {
+        UserKeys::new(self.user_id)
+            .unwrap(db, cipher, &self.wrapped_key)
+            .await?
+            .ok_or_else(|| HoofprintError::CodeLocked(self.household_id.to_string()))
+    }
+
+    /// Add someone to a household, or change their role if they're already in it
+    pub(crate) async fn add(
+        db: &DatabaseConnection,
+        household_id: Uuid,
+        household_key: &DataKey,
+        recipient: &super::user::Model,
+        role: Role,
+    ) -> Result<Model, HoofprintError> {
+        if let Some(existing) =
+            Self::find_for_household_and_user(db, household_id, recipient.id).await?
+        {
+            let mut member: ActiveModel = existing.into();
+            member.role = Set(role.as_str().to_string());
+            return member.update(db).await.map_err(HoofprintError::from);
+        }
+        let public_key = recipient.public_key.as_deref().ok_or_else(|| {
+            HoofprintError::ValidationError(vec![format!(
+                "{} needs to log in once before they can join a household",
+                recipient.email
+            )])
+        })?;
+        ActiveModel {
+            id: Set(Uuid::now_v7()),
+            household_id: Set(household_id),
+            user_id: Set(recipient.id),
+            role: Set(role.as_str().to_string()),
+            wrapped_key: Set(household_key.wrap_for_public_key(public_key)?),
+            created_at: Set(chrono::Utc::now()),
+        }
+        .insert(db)
+        .await
+        .map_err(HoofprintError::from)
+    }
+
+    /// Take someone out of a household. The household gets a new key, which its codes' keys are
+    /// re-wrapped with and everyone still in it gets a copy of, so the key they had can't open
+    /// anything it's given afterwards. The codes it already had keep their own keys, so they
+    /// only lose access to those through hoofprint.
+    ///
+    /// `household_key` is the household's current key, and `cipher` and `user_id` are whoever's
+    /// doing it, whose copy of the new key is wrapped with their data key if they're staying.
+    pub(crate) async fn remove(
+        db: &DatabaseConnection,
+        target: Model,
+        household_key: &DataKey,
+        cipher: &CodeCipher,
+        user_id: Uuid,
+    ) -> Result<(), HoofprintError> {
+        let household_id = target.household_id;
+        let new_key = DataKey::generate();
+
+        // deleting first takes the write lock, so nobody can join or give the household a code
+        // with the old key in the meantime
+        let txn = db.begin().await?;
+        Entity::delete_by_id(target.id).exec(&txn).await?;
+
+        for code in super::code::Entity::find()
+            .filter(super::code::Column::HouseholdId.eq(household_id))
+            .filter(super::code::Column::WrappedKey.is_not_null())
+            .all(&txn)
+            .await?
+        {
+            let key = code
+                .wrapped_key
+                .as_deref()
+                .and_then(|wrapped| household_key.unwrap_key(wrapped))
+                .ok_or_else(|| HoofprintError::CodeLocked(code.id.to_string()))?;
+            let mut code: super::code::ActiveModel = code.into();
+            code.wrapped_key = Set(Some(new_key.wrap_key(&key)?));
+            code.update(&txn).await?;
+        }
+
+        for (member, user) in Entity::find()
+            .filter(Column::HouseholdId.eq(household_id))
+            .find_also_related(super::user::Entity)
+            .all(&txn)
+            .await?
+        {
+            let wrapped_key = if member.user_id == user_id {
+                cipher.key().wrap_key(&new_key)?
+            } else {
+                let public_key = user
+                    .as_ref()
+                    .and_then(|user| user.public_key.as_deref())
+                    .ok_or_else(|| {
+                        HoofprintError::ValidationError(vec![format!(
+                            "{} needs to log in once before anyone can leave the household",
+                            user.as_ref().map_or("Someone", |user| user.email.as_str())
+                        )])
+                    })?;
+                new_key.wrap_for_public_key(public_key)?
+            };
+            let mut member: ActiveModel = member.into();
+            member.wrapped_key = Set(wrapped_key);
+            member.update(&txn).await?;
+        }
+        txn.commit().await?;
+        Ok(())
+    }
+
+    /// How many admins a household has, so it's never left without one
+    pub(crate) async fn count_admins(
+        db: &DatabaseConnection,
+        household_id: Uuid,
+    ) -> Result<u64, HoofprintError> {
+        Entity::find()
+            .filter(Column::HouseholdId.eq(household_id))
+            .filter(Column::Role.eq(Role::Admin.as_str()))
+            .count(db)
+            .await
+            .map_err(HoofprintError::from)
+    }
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::household::Entity",
+        from = "Column::HouseholdId",
+        to = "super::household::Column::Id"
+    )]
+    Household,
+    #[sea_orm(
+        belongs_to = "super::user::Entity",
+        from = "Column::UserId",
+        to = "super::user::Column::Id"
+    )]
+    User,
+}
+
+impl Related<super::household::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Household.def()
+    }
+}
+
+impl Related<super::user::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::User.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_role() {
+        for role in Role::ALL {
+            assert_eq!(Role::try_from(role.as_str()).ok(), Some(role));
+        }
+        assert!(Role::try_from("owner").is_err());
+        assert_eq!(Role::Viewer.permission(), Permission::View);
+        assert_eq!(Role::Member.permission(), Permission::Edit);
+        assert_eq!(Role::Admin.permission(), Permission::Owner);
+    }
+}
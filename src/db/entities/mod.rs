@@ -1,5 +1,16 @@
 //! Database entities used by Hoofprint
 
 pub(crate) mod code;
+pub(crate) mod code_attachment;
+pub(crate) mod code_revision;
+pub(crate) mod code_secret;
+pub(crate) mod code_share;
+pub(crate) mod code_tag;
+pub(crate) mod code_usage;
+pub(crate) mod household;
+pub(crate) mod household_member;
+pub(crate) mod share_link;
 pub(crate) mod site;
+pub(crate) mod site_logo;
+pub(crate) mod tag;
 pub(crate) mod user;
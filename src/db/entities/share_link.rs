@@ -0,0 +1,214 @@
+//! Public links to codes, which anyone can open without logging in until they expire, run out
+//! of views or are revoked.
+//!
+//! A link's token never gets stored as it is, see [crate::crypto::LinkToken]. The code's key is
+//! wrapped with a key made from the token, so the link itself is what opens the code.
+
+use sea_orm::{
+    ActiveValue::Set, QueryOrder, entity::prelude::*, sea_query::Expr, sqlx::types::chrono,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    crypto::{CodeCipher, DataKey, LinkToken},
+    error::HoofprintError,
+};
+
+/// The longest a link can last for, in hours
+pub const MAX_LINK_HOURS: u32 = 24 * 30;
+/// The most views a link can be limited to
+pub const MAX_LINK_VIEWS: i32 = 1000;
+
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "share_link")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    pub code_id: Uuid,
+    /// Who made the link
+    pub user_id: Uuid,
+    /// What the link is looked up by, see [LinkToken::lookup_hash]
+    #[serde(skip_serializing)]
+    pub token_hash: String,
+    /// The token, sealed with the code's key so the link can be shown to the code's owners again
+    #[serde(skip_serializing)]
+    pub sealed_token: String,
+    /// The code's key, wrapped with [LinkToken::key]
+    #[serde(skip_serializing)]
+    pub wrapped_key: String,
+    pub expires_at: DateTimeUtc,
+    /// How many times the link can be opened, if there's a limit
+    pub max_views: Option<i32>,
+    /// How many times the link has been opened
+    pub views: i32,
+    pub created_at: DateTimeUtc,
+}
+
+impl Model {
+    /// Whether the link can still be opened
+    pub fn is_active(&self, now: DateTimeUtc) -> bool {
+        self.expires_at > now
+            && self
+                .max_views
+                .is_none_or(|max_views| self.views < max_views)
+    }
+
+    /// The link's token, if the cipher has the code's key
+    pub fn token(&self, cipher: &CodeCipher) -> Option<String> {
+        cipher
+            .code_key(self.code_id)?
+            .open(&self.sealed_token, self.id.as_bytes())
+    }
+
+    /// Make a link to a code
+    pub(crate) async fn create(
+        db: &DatabaseConnection,
+        code_id: Uuid,
+        code_key: &DataKey,
+        user_id: Uuid,
+        expires_at: DateTimeUtc,
+        max_views: Option<i32>,
+    ) -> Result<Model, HoofprintError> {
+        let id = Uuid::now_v7();
+        let token = LinkToken::generate();
+        ActiveModel {
+            id: Set(id),
+            code_id: Set(code_id),
+            user_id: Set(user_id),
+            token_hash: Set(token.lookup_hash()),
+            sealed_token: Set(code_key.seal(token.as_str(), id.as_bytes())?),
+            wrapped_key: Set(token.key().wrap_key(code_key)?),
+            expires_at: Set(expires_at),
+            max_views: Set(max_views),
+            views: Set(0),
+            created_at: Set(chrono::Utc::now()),
+        }
+        .insert(db)
+        .await
+        .map_err(HoofprintError::from)
+    }
+
+    /// A code's links, newest first
+    pub async fn find_for_code(
+        db: &DatabaseConnection,
+        code_id: Uuid,
+    ) -> Result<Vec<Model>, HoofprintError> {
+        Entity::find()
+            .filter(Column::CodeId.eq(code_id))
+            .order_by_desc(Column::CreatedAt)
+            .all(db)
+            .await
+            .map_err(HoofprintError::from)
+    }
+
+    /// Open a link, counting the view, and return the code it's for with the code's key.
+    /// Links that don't exist, have expired or have run out of views are all
+    /// [HoofprintError::NotFound], so they can't be told apart.
+    pub(crate) async fn redeem(
+        db: &DatabaseConnection,
+        token: &LinkToken,
+    ) -> Result<(super::code::Model, DataKey), HoofprintError> {
+        let not_found = || HoofprintError::NotFound("Link".to_string());
+        let now = chrono::Utc::now();
+        let link = Entity::find()
+            .filter(Column::TokenHash.eq(token.lookup_hash()))
+            .one(db)
+            .await?
+            .ok_or_else(not_found)?;
+        if !link.is_active(now) {
+            return Err(not_found());
+        }
+
+        // counted in one statement, so a link can't be opened more often than it allows
+        let counted = Entity::update_many()
+            .col_expr(Column::Views, Expr::col(Column::Views).add(1))
+            .filter(Column::Id.eq(link.id))
+            .filter(
+                Column::MaxViews
+                    .is_null()
+                    .or(Expr::col(Column::Views).lt(Expr::col(Column::MaxViews))),
+            )
+            .exec(db)
+            .await?;
+        if counted.rows_affected == 0 {
+            return Err(not_found());
+        }
+
+        let code_key = token
+            .key()
+            .unwrap_key(&link.wrapped_key)
+            .ok_or_else(not_found)?;
+        let code = super::code::Entity::find_by_id(link.code_id)
+            .one(db)
+            .await?
+            .ok_or_else(not_found)?;
+        Ok((code, code_key))
+    }
+
+    pub(crate) async fn clear_for_code<C: ConnectionTrait>(
+        db: &C,
+        code_id: Uuid,
+    ) -> Result<(), HoofprintError> {
+        Entity::delete_many()
+            .filter(Column::CodeId.eq(code_id))
+            .exec(db)
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::code::Entity",
+        from = "Column::CodeId",
+        to = "super::code::Column::Id"
+    )]
+    Code,
+}
+
+impl Related<super::code::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Code.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_active() {
+        let now = chrono::Utc::now();
+        let link = Model {
+            id: Uuid::now_v7(),
+            code_id: Uuid::now_v7(),
+            user_id: Uuid::now_v7(),
+            token_hash: String::new(),
+            sealed_token: String::new(),
+            wrapped_key: String::new(),
+            expires_at: now + std::time::Duration::from_secs(60 * 60),
+            max_views: None,
+            views: 10,
+            created_at: now,
+        };
+        assert!(link.is_active(now));
+        assert!(!link.is_active(now + std::time::Duration::from_secs(2 * 60 * 60)));
+        assert!(
+            Model {
+                max_views: Some(11),
+                ..link.clone()
+            }
+            .is_active(now)
+        );
+        assert!(
+            !Model {
+                max_views: Some(10),
+                ..link
+            }
+            .is_active(now)
+        );
+    }
+}
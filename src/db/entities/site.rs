@@ -1,8 +1,34 @@
 //! Site entity for hoofprint
 
-use sea_orm::entity::prelude::*;
+use std::collections::HashMap;
+
+use regex::Regex;
+use sea_orm::{ActiveValue::Set, QueryOrder, QuerySelect, TransactionTrait, entity::prelude::*};
 use serde::{Deserialize, Serialize};
 
+use crate::{Code, error::HoofprintError};
+
+/// The longest a site's name can be
+pub const MAX_SITE_NAME_LENGTH: usize = 255;
+/// The longest a site's URL can be
+pub const MAX_SITE_URL_LENGTH: usize = 2048;
+/// The longest a site's instructions can be
+pub const MAX_SITE_INSTRUCTIONS_LENGTH: usize = 2000;
+/// The longest a site's value pattern can be
+pub const MAX_VALUE_PATTERN_LENGTH: usize = 255;
+
+/// A value pattern as it's matched, against the whole of a code's value
+pub fn value_regex(pattern: &str) -> Result<Regex, regex::Error> {
+    Regex::new(&format!("^(?:{})$", pattern))
+}
+
+/// Whether a brand colour is a hex colour like #1a2b3c, so it's safe to put in a stylesheet
+pub fn is_brand_colour(colour: &str) -> bool {
+    colour.len() == 7
+        && colour.starts_with('#')
+        && colour[1..].chars().all(|c| c.is_ascii_hexdigit())
+}
+
 #[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Serialize, Deserialize)]
 #[sea_orm(table_name = "site")]
 pub struct Model {
@@ -11,12 +37,191 @@ pub struct Model {
     pub name: String,
     pub url: String,
     pub created_at: TimeDateTime,
+    /// A hex colour like #1a2b3c that the site's codes are tinted with
+    pub brand_colour: Option<String>,
+    /// The type of code the site usually issues, see [Code]
+    pub default_code_type: Option<String>,
+    /// How to use the site's codes, in Markdown
+    pub instructions: Option<String>,
+    /// A regular expression that the site's code values have to match all of
+    pub value_pattern: Option<String>,
+}
+
+impl Model {
+    /// The brand colour, as long as it's one that can be used
+    pub fn brand_colour(&self) -> Option<&str> {
+        self.brand_colour
+            .as_deref()
+            .filter(|colour| is_brand_colour(colour))
+    }
+
+    /// The type of code the site usually issues, if it has one
+    pub fn default_code_type(&self) -> Option<Code> {
+        self.default_code_type
+            .as_deref()
+            .and_then(|code_type| Code::try_from(code_type).ok())
+    }
+
+    /// The pattern the site's code values have to match, if there is one. Patterns are checked
+    /// when they're saved, so one that doesn't compile is ignored rather than blocking codes.
+    pub fn value_regex(&self) -> Option<Regex> {
+        self.value_pattern
+            .as_deref()
+            .and_then(|pattern| value_regex(pattern).ok())
+    }
+
+    /// Find a site by name, ignoring case and surrounding whitespace
+    pub async fn find_by_name(
+        db: &DatabaseConnection,
+        name: &str,
+    ) -> Result<Option<Model>, HoofprintError> {
+        let name = name.trim().to_lowercase();
+        Ok(Entity::find()
+            .all(db)
+            .await?
+            .into_iter()
+            .find(|site| site.name.trim().to_lowercase() == name))
+    }
+
+    /// Find a site by name, creating it if there isn't one yet
+    pub async fn find_or_create(
+        db: &DatabaseConnection,
+        name: &str,
+    ) -> Result<Model, HoofprintError> {
+        if let Some(site) = Self::find_by_name(db, name).await? {
+            return Ok(site);
+        }
+        Self::create_new(db, name, "").await
+    }
+
+    pub async fn create_new(
+        db: &DatabaseConnection,
+        name: &str,
+        url: &str,
+    ) -> Result<Model, HoofprintError> {
+        let now = time::OffsetDateTime::now_utc();
+        ActiveModel {
+            id: Set(Uuid::now_v7()),
+            name: Set(name.trim().to_string()),
+            url: Set(url.trim().to_string()),
+            created_at: Set(TimeDateTime::new(now.date(), now.time())),
+            brand_colour: Set(None),
+            default_code_type: Set(None),
+            instructions: Set(None),
+            value_pattern: Set(None),
+        }
+        .insert(db)
+        .await
+        .map_err(HoofprintError::from)
+    }
+
+    /// Every site by name, with how many codes use each of them
+    pub async fn with_code_counts(
+        db: &DatabaseConnection,
+    ) -> Result<Vec<(Model, u64)>, HoofprintError> {
+        let counts: HashMap<Uuid, i64> = super::code::Entity::find()
+            .select_only()
+            .column(super::code::Column::SiteId)
+            .column_as(super::code::Column::Id.count(), "count")
+            .group_by(super::code::Column::SiteId)
+            .into_tuple::<(Uuid, i64)>()
+            .all(db)
+            .await?
+            .into_iter()
+            .collect();
+        Ok(Entity::find()
+            .order_by_asc(Column::Name)
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|site| {
+                let count = counts.get(&site.id).copied().unwrap_or_default();
+                (site, u64::try_from(count).unwrap_or_default())
+            })
+            .collect())
+    }
+
+    /// How many codes use the site
+    pub async fn code_count(&self, db: &DatabaseConnection) -> Result<u64, HoofprintError> {
+        super::code::Entity::find()
+            .filter(super::code::Column::SiteId.eq(self.id))
+            .count(db)
+            .await
+            .map_err(HoofprintError::from)
+    }
+
+    /// Delete the site, as long as no codes use it. Revisions of codes that used to be at the
+    /// site move to the generic site, so they can still be restored.
+    pub(crate) async fn delete(self, db: &DatabaseConnection) -> Result<(), HoofprintError> {
+        let txn = db.begin().await?;
+        // writing first holds the lock, so no code can be added to the site before it's gone
+        super::code_revision::Entity::update_many()
+            .col_expr(
+                super::code_revision::Column::SiteId,
+                Expr::value(Uuid::nil()),
+            )
+            .filter(super::code_revision::Column::SiteId.eq(self.id))
+            .exec(&txn)
+            .await?;
+        let codes = super::code::Entity::find()
+            .filter(super::code::Column::SiteId.eq(self.id))
+            .count(&txn)
+            .await?;
+        if codes > 0 {
+            // dropping the transaction rolls the revisions back
+            return Err(HoofprintError::ValidationError(vec![format!(
+                "{} code(s) still use {}, merge it into another site to move them",
+                codes, self.name
+            )]));
+        }
+        super::site_logo::Entity::delete_by_id(self.id)
+            .exec(&txn)
+            .await?;
+        Entity::delete_by_id(self.id).exec(&txn).await?;
+        txn.commit().await?;
+        Ok(())
+    }
+
+    /// Move the site's codes and their history over to another site, and then delete this one,
+    /// returning how many codes were moved
+    pub(crate) async fn merge_into(
+        self,
+        db: &DatabaseConnection,
+        into: &Model,
+    ) -> Result<u64, HoofprintError> {
+        if self.id == into.id {
+            return Err(HoofprintError::ValidationError(vec![
+                "A site can't be merged into itself".to_string(),
+            ]));
+        }
+        let txn = db.begin().await?;
+        let moved = super::code::Entity::update_many()
+            .col_expr(super::code::Column::SiteId, Expr::value(into.id))
+            .filter(super::code::Column::SiteId.eq(self.id))
+            .exec(&txn)
+            .await?
+            .rows_affected;
+        // old revisions can still be restored once the site's gone
+        super::code_revision::Entity::update_many()
+            .col_expr(super::code_revision::Column::SiteId, Expr::value(into.id))
+            .filter(super::code_revision::Column::SiteId.eq(self.id))
+            .exec(&txn)
+            .await?;
+        super::site_logo::Entity::delete_by_id(self.id)
+            .exec(&txn)
+            .await?;
+        Entity::delete_by_id(self.id).exec(&txn).await?;
+        txn.commit().await?;
+        Ok(moved)
+    }
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
     #[sea_orm(has_many = "super::code::Entity")]
     Code,
+    #[sea_orm(has_one = "super::site_logo::Entity")]
+    Logo,
 }
 
 impl Related<super::code::Entity> for Entity {
@@ -26,3 +231,26 @@ impl Related<super::code::Entity> for Entity {
 }
 
 impl ActiveModelBehavior for ActiveModel {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_site_details() {
+        assert!(is_brand_colour("#1a2B3c"));
+        for colour in ["1a2b3c", "#1a2b3", "#1a2b3cff", "#1a2b3g", "red", "#12345;"] {
+            assert!(!is_brand_colour(colour), "{colour}");
+        }
+
+        // patterns match all of a value, not just part of it
+        let pattern = value_regex(r"\d{6}").expect("Failed to compile pattern");
+        assert!(pattern.is_match("123456"));
+        assert!(!pattern.is_match("1234567"));
+        assert!(!pattern.is_match("A123456"));
+        let pattern = value_regex("a|b").expect("Failed to compile pattern");
+        assert!(pattern.is_match("b"));
+        assert!(!pattern.is_match("ab"));
+        assert!(value_regex("(").is_err());
+    }
+}
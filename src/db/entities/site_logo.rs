@@ -0,0 +1,78 @@
+//! A site's logo, which is shown with its codes. Logos aren't private like photos of cards are,
+//! so they're kept in the database as they were uploaded.
+
+use sea_orm::{
+    ActiveValue::Set, PaginatorTrait, QuerySelect, entity::prelude::*, sqlx::types::chrono,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::error::HoofprintError;
+
+/// The largest logo that can be uploaded, in bytes
+pub(crate) const MAX_LOGO_SIZE: usize = 512 * 1024;
+
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "site_logo")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub site_id: Uuid,
+    pub content_type: String,
+    #[serde(skip_serializing)]
+    pub data: Vec<u8>,
+    pub created_at: DateTimeUtc,
+}
+
+impl Model {
+    /// Give a site a logo, replacing the one it had
+    pub(crate) async fn save(
+        db: &DatabaseConnection,
+        site_id: Uuid,
+        content_type: &str,
+        data: &[u8],
+    ) -> Result<(), HoofprintError> {
+        Entity::delete_by_id(site_id).exec(db).await?;
+        ActiveModel {
+            site_id: Set(site_id),
+            content_type: Set(content_type.to_string()),
+            data: Set(data.to_vec()),
+            created_at: Set(chrono::Utc::now()),
+        }
+        .insert(db)
+        .await?;
+        Ok(())
+    }
+
+    /// Whether a site has a logo, without loading it
+    pub async fn exists(db: &DatabaseConnection, site_id: Uuid) -> Result<bool, HoofprintError> {
+        Ok(Entity::find_by_id(site_id).count(db).await? > 0)
+    }
+
+    /// The sites that have logos, without loading the logos themselves
+    pub async fn site_ids(db: &DatabaseConnection) -> Result<Vec<Uuid>, HoofprintError> {
+        Entity::find()
+            .select_only()
+            .column(Column::SiteId)
+            .into_tuple()
+            .all(db)
+            .await
+            .map_err(HoofprintError::from)
+    }
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::site::Entity",
+        from = "Column::SiteId",
+        to = "super::site::Column::Id"
+    )]
+    Site,
+}
+
+impl Related<super::site::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Site.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
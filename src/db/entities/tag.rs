@@ -0,0 +1,167 @@
+//! A label a user files their codes under, like "gym" or "library"
+
+use std::collections::HashMap;
+
+use sea_orm::{ActiveValue::Set, entity::prelude::*, sqlx::types::chrono};
+use serde::{Deserialize, Serialize};
+
+use crate::error::HoofprintError;
+
+/// The longest a tag's name can be
+pub const MAX_TAG_LENGTH: usize = 50;
+/// The most tags a single code can have
+pub const MAX_TAGS_PER_CODE: usize = 20;
+
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "tag")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub name: String,
+    pub created_at: DateTimeUtc,
+}
+
+impl Model {
+    /// The names of the user's tags on each of the given codes, sorted by name. Everyone a code's
+    /// shared with files it under their own tags.
+    pub async fn find_for_codes(
+        db: &DatabaseConnection,
+        user_id: Uuid,
+        code_ids: &[Uuid],
+    ) -> Result<HashMap<Uuid, Vec<String>>, HoofprintError> {
+        let mut tags: HashMap<Uuid, Vec<String>> = HashMap::new();
+        for (code_tag, tag) in super::code_tag::Entity::find()
+            .filter(super::code_tag::Column::CodeId.is_in(code_ids.iter().copied()))
+            .find_also_related(Entity)
+            .filter(Column::UserId.eq(user_id))
+            .all(db)
+            .await?
+        {
+            if let Some(tag) = tag {
+                tags.entry(code_tag.code_id).or_default().push(tag.name);
+            }
+        }
+        for names in tags.values_mut() {
+            names.sort_by_key(|name| name.to_lowercase());
+        }
+        Ok(tags)
+    }
+
+    /// Replace a code's tags, creating any of the user's tags that don't exist yet and removing
+    /// ones that aren't used any more. Names are matched ignoring case.
+    pub(crate) async fn set_for_code(
+        db: &DatabaseConnection,
+        user_id: Uuid,
+        code_id: Uuid,
+        names: &[String],
+    ) -> Result<(), HoofprintError> {
+        let existing = Entity::find()
+            .filter(Column::UserId.eq(user_id))
+            .all(db)
+            .await?;
+
+        super::code_tag::Entity::delete_many()
+            .filter(super::code_tag::Column::CodeId.eq(code_id))
+            .filter(super::code_tag::Column::TagId.is_in(existing.iter().map(|tag| tag.id)))
+            .exec(db)
+            .await?;
+
+        for name in names {
+            let tag = match existing
+                .iter()
+                .find(|tag| tag.name.to_lowercase() == name.to_lowercase())
+            {
+                Some(tag) => tag.clone(),
+                None => {
+                    ActiveModel {
+                        id: Set(Uuid::now_v7()),
+                        user_id: Set(user_id),
+                        name: Set(name.clone()),
+                        created_at: Set(chrono::Utc::now()),
+                    }
+                    .insert(db)
+                    .await?
+                }
+            };
+            super::code_tag::ActiveModel {
+                code_id: Set(code_id),
+                tag_id: Set(tag.id),
+            }
+            .insert(db)
+            .await?;
+        }
+
+        Self::remove_unused(db, user_id).await
+    }
+
+    /// Remove a code from everyone's tags, like when it's deleted
+    pub(crate) async fn clear_for_code<C: ConnectionTrait>(
+        db: &C,
+        code_id: Uuid,
+    ) -> Result<(), HoofprintError> {
+        let mut user_ids: Vec<Uuid> = super::code_tag::Entity::find()
+            .filter(super::code_tag::Column::CodeId.eq(code_id))
+            .find_also_related(Entity)
+            .all(db)
+            .await?
+            .into_iter()
+            .filter_map(|(_, tag)| tag.map(|tag| tag.user_id))
+            .collect();
+        user_ids.sort();
+        user_ids.dedup();
+
+        super::code_tag::Entity::delete_many()
+            .filter(super::code_tag::Column::CodeId.eq(code_id))
+            .exec(db)
+            .await?;
+        for user_id in user_ids {
+            Self::remove_unused(db, user_id).await?;
+        }
+        Ok(())
+    }
+
+    /// Delete the user's tags that aren't on any codes
+    async fn remove_unused<C: ConnectionTrait>(
+        db: &C,
+        user_id: Uuid,
+    ) -> Result<(), HoofprintError> {
+        for (tag, code_tags) in Entity::find()
+            .filter(Column::UserId.eq(user_id))
+            .find_with_related(super::code_tag::Entity)
+            .all(db)
+            .await?
+        {
+            if code_tags.is_empty() {
+                Entity::delete_by_id(tag.id).exec(db).await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::user::Entity",
+        from = "Column::UserId",
+        to = "super::user::Column::Id"
+    )]
+    User,
+    #[sea_orm(has_many = "super::code_tag::Entity")]
+    CodeTag,
+}
+
+impl Related<super::user::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::User.def()
+    }
+}
+
+impl Related<super::code_tag::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::CodeTag.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -1,6 +1,11 @@
 //! User entity for hoofprint
 
-use crate::{error::HoofprintError, get_random_password, password::hash_password};
+use crate::{
+    crypto::{DataKey, PrivateKey},
+    error::HoofprintError,
+    get_random_password,
+    password::{hash_password, verify_password},
+};
 use sea_orm::{ActiveValue, Condition, QueryOrder};
 use sea_orm::{IntoActiveModel, entity::prelude::*};
 use serde::{Deserialize, Serialize};
@@ -16,6 +21,21 @@ pub struct Model {
     pub groups: Json,
     #[serde(skip_serializing)]
     pub password: String,
+    /// The key that seals the user's codes, wrapped with their password, see [crate::crypto]
+    #[serde(skip_serializing)]
+    pub data_key: Option<String>,
+    /// Data keys retired by password resets, each still wrapped with the password from before
+    /// that reset, so the user can recover the codes it sealed
+    #[serde(skip_serializing)]
+    pub retired_data_keys: Json,
+    /// Whether to keep a record of each time one of the user's codes is shown
+    pub track_usage: bool,
+    /// PEM, for wrapping the keys of codes shared with the user
+    #[serde(skip_serializing)]
+    pub public_key: Option<String>,
+    /// Sealed with the user's data key
+    #[serde(skip_serializing)]
+    pub private_key: Option<String>,
 }
 
 impl Model {
@@ -31,14 +51,110 @@ impl Model {
             display_name: ActiveValue::Set(display_name.to_string()),
             groups: ActiveValue::Set(serde_json::json!([])),
             password: ActiveValue::NotSet,
+            data_key: ActiveValue::Set(None),
+            retired_data_keys: ActiveValue::Set(serde_json::json!([])),
+            track_usage: ActiveValue::Set(true),
+            public_key: ActiveValue::Set(None),
+            private_key: ActiveValue::Set(None),
         };
         if let Some(password) = password {
             user.password = ActiveValue::Set(hash_password(password)?);
+            user.data_key =
+                ActiveValue::Set(Some(DataKey::generate().wrap_with_password(password)?));
         };
 
         let user = user.insert(&db).await?;
         Ok(user)
     }
+
+    /// Unwrap the user's data key with their password, creating one if they don't have one yet
+    pub(crate) async fn unlock_data_key(
+        &self,
+        db: &DatabaseConnection,
+        password: &str,
+    ) -> Result<DataKey, HoofprintError> {
+        if let Some(wrapped) = &self.data_key {
+            return DataKey::unwrap_with_password(wrapped, password).ok_or_else(|| {
+                HoofprintError::InternalError(format!(
+                    "Failed to unwrap the data key for user {}",
+                    self.id
+                ))
+            });
+        }
+        let data_key = DataKey::generate();
+        let mut user = self.clone().into_active_model();
+        user.data_key = ActiveValue::Set(Some(data_key.wrap_with_password(password)?));
+        user.update(db).await?;
+        Ok(data_key)
+    }
+
+    /// Make the user a key pair, if they don't have one yet, so codes can be shared with them
+    pub(crate) async fn ensure_key_pair(
+        &self,
+        db: &DatabaseConnection,
+        data_key: &DataKey,
+    ) -> Result<(), HoofprintError> {
+        if self.public_key.is_some() {
+            return Ok(());
+        }
+        let (private_key, public_key) = PrivateKey::generate()?;
+        let mut user = self.clone().into_active_model();
+        user.public_key = ActiveValue::Set(Some(public_key));
+        user.private_key = ActiveValue::Set(Some(data_key.seal_private_key(&private_key)?));
+        user.update(db).await?;
+        Ok(())
+    }
+
+    /// The wrapped data keys retired by password resets
+    pub(crate) fn retired_data_keys(&self) -> Vec<String> {
+        self.retired_data_keys
+            .as_array()
+            .map(|keys| {
+                keys.iter()
+                    .filter_map(|key| key.as_str().map(str::to_string))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Set a password the user chose, re-wrapping their data key so their codes stay readable
+    pub(crate) fn change_password(
+        self,
+        current_password: &str,
+        new_password: &str,
+    ) -> Result<ActiveModel, HoofprintError> {
+        verify_password(current_password, &self.password)?;
+        let data_key = match &self.data_key {
+            Some(wrapped) => {
+                DataKey::unwrap_with_password(wrapped, current_password).ok_or_else(|| {
+                    HoofprintError::InternalError(format!(
+                        "Failed to unwrap the data key for user {}",
+                        self.id
+                    ))
+                })?
+            }
+            None => DataKey::generate(),
+        };
+        let mut user = self.into_active_model();
+        user.password = ActiveValue::Set(hash_password(new_password)?);
+        user.data_key = ActiveValue::Set(Some(data_key.wrap_with_password(new_password)?));
+        Ok(user)
+    }
+
+    /// Set a password chosen by someone else, like an admin.
+    ///
+    /// The data key can't be unwrapped without the old password, so it's retired instead of
+    /// re-wrapped, and a new one is made next time the user logs in. Codes sealed with the
+    /// retired key stay locked until the user recovers them with their old password.
+    pub(crate) fn reset_password(self, new_password: &str) -> Result<ActiveModel, HoofprintError> {
+        let mut retired_data_keys = self.retired_data_keys();
+        retired_data_keys.extend(self.data_key.clone());
+        let mut user = self.into_active_model();
+        user.password = ActiveValue::Set(hash_password(new_password)?);
+        user.data_key = ActiveValue::Set(None);
+        user.retired_data_keys = ActiveValue::Set(serde_json::json!(retired_data_keys));
+        Ok(user)
+    }
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -108,14 +224,13 @@ pub(crate) async fn reset_password_by_id(
 ) -> Result<String, HoofprintError> {
     let new_password = get_random_password(16);
 
-    let mut user = Entity::find_by_id(id)
+    Entity::find_by_id(id)
         .one(db)
         .await?
         .ok_or_else(|| HoofprintError::InternalError("User not found in DB".to_string()))?
-        .into_active_model();
-
-    user.password = ActiveValue::Set(hash_password(&new_password)?);
-    user.save(db).await?;
+        .reset_password(&new_password)?
+        .save(db)
+        .await?;
 
     Ok(new_password)
 }
@@ -223,6 +338,63 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_password_change_and_reset_keep_data_key() {
+        let db = setup_db().await;
+        let user = Model::create_new(
+            db.clone(),
+            "user@example.com",
+            "User Name",
+            Some("old-password"),
+        )
+        .await
+        .expect("Failed to save user");
+        let data_key = user
+            .unlock_data_key(&db, "old-password")
+            .await
+            .expect("Failed to unlock data key");
+        let sealed = data_key.seal("hello", b"test").expect("Failed to seal");
+
+        // changing the password re-wraps the same key
+        assert!(
+            user.clone()
+                .change_password("wrong-password", "new-password")
+                .is_err()
+        );
+        let user = user
+            .change_password("old-password", "new-password")
+            .expect("Failed to change password")
+            .update(&db)
+            .await
+            .expect("Failed to save user");
+        let data_key = user
+            .unlock_data_key(&db, "new-password")
+            .await
+            .expect("Failed to unlock data key");
+        assert_eq!(data_key.open(&sealed, b"test").as_deref(), Some("hello"));
+
+        // a reset retires the key, and a new one is made at the next login
+        let new_password = reset_password_by_id(&db, user.id)
+            .await
+            .expect("Password reset failed");
+        let user = Entity::find_by_id(user.id)
+            .one(&db)
+            .await
+            .expect("Failed to find user")
+            .expect("User should exist");
+        assert!(user.data_key.is_none());
+        let retired = user.retired_data_keys();
+        assert_eq!(retired.len(), 1);
+        let new_key = user
+            .unlock_data_key(&db, &new_password)
+            .await
+            .expect("Failed to unlock data key");
+        assert_eq!(new_key.open(&sealed, b"test"), None);
+        let old_key = DataKey::unwrap_with_password(&retired[0], "new-password")
+            .expect("Failed to unwrap retired key");
+        assert_eq!(old_key.open(&sealed, b"test").as_deref(), Some("hello"));
+    }
+
     #[tokio::test]
     async fn test_search_users() {
         let db = setup_db().await;
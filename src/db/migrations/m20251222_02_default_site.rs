@@ -1,4 +1,3 @@
-use sea_orm::{ActiveModelTrait, ActiveValue::Set, EntityTrait};
 use sea_orm_migration::prelude::*;
 use uuid::Uuid;
 
@@ -15,41 +14,45 @@ impl MigrationName for Migration {
 #[async_trait::async_trait]
 impl MigrationTrait for Migration {
     async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
-        // Use SeaORM entities to properly insert the default site
-        // This ensures UUIDs are stored in the correct format
-        let db = manager.get_connection();
-
-        // Check if site already exists
-        let existing = crate::db::entities::site::Entity::find_by_id(Uuid::nil())
-            .one(db)
-            .await?;
-
-        if existing.is_none() {
-            // Create the default site using ActiveModel
-            let default_site = crate::db::entities::site::ActiveModel {
-                id: Set(Uuid::nil()),
-                name: Set(GENERIC_SITE.to_string()),
-                url: Set(String::new()),
-                created_at: Set(time::PrimitiveDateTime::new(
-                    time::OffsetDateTime::now_utc().date(),
-                    time::OffsetDateTime::now_utc().time(),
-                )),
-            };
-
-            default_site.insert(db).await?;
-        }
-
-        Ok(())
+        // Built from the table as it was at this point rather than the site entity, which has
+        // columns added by later migrations. Binding the UUID keeps it in the format SeaORM
+        // stores them in.
+        let now = time::OffsetDateTime::now_utc();
+        manager
+            .exec_stmt(
+                Query::insert()
+                    .into_table(Site::Table)
+                    .columns([Site::Id, Site::Name, Site::Url, Site::CreatedAt])
+                    .values([
+                        Uuid::nil().into(),
+                        GENERIC_SITE.into(),
+                        "".into(),
+                        time::PrimitiveDateTime::new(now.date(), now.time()).into(),
+                    ])
+                    .map_err(|err| DbErr::Custom(err.to_string()))?
+                    .on_conflict(OnConflict::column(Site::Id).do_nothing().to_owned())
+                    .to_owned(),
+            )
+            .await
     }
 
     async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
-        // Delete the default site using SeaORM
-        let db = manager.get_connection();
-
-        crate::db::entities::site::Entity::delete_by_id(Uuid::nil())
-            .exec(db)
-            .await?;
-
-        Ok(())
+        manager
+            .exec_stmt(
+                Query::delete()
+                    .from_table(Site::Table)
+                    .and_where(Expr::col(Site::Id).eq(Uuid::nil()))
+                    .to_owned(),
+            )
+            .await
     }
 }
+
+#[derive(Iden)]
+pub enum Site {
+    Table,
+    Id,
+    Name,
+    Url,
+    CreatedAt,
+}
@@ -0,0 +1,44 @@
+use sea_orm_migration::prelude::*;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20261018_01_barcode_to_code128"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // "barcode" was always rendered as Code 128, so keep that symbology explicitly
+        manager
+            .exec_stmt(
+                Query::update()
+                    .table(Code::Table)
+                    .value(Code::Type, "code128")
+                    .and_where(Expr::col(Code::Type).eq("barcode"))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Only Code 128 can be mapped back, the other symbologies didn't exist before
+        manager
+            .exec_stmt(
+                Query::update()
+                    .table(Code::Table)
+                    .value(Code::Type, "barcode")
+                    .and_where(Expr::col(Code::Type).eq("code128"))
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum Code {
+    Table,
+    Type,
+}
@@ -0,0 +1,76 @@
+use sea_orm_migration::prelude::*;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20261018_04_code_revisions"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(CodeRevision::Table)
+                    .col(
+                        ColumnDef::new(CodeRevision::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(CodeRevision::CodeId).uuid().not_null())
+                    .col(ColumnDef::new(CodeRevision::UserId).uuid().not_null())
+                    .col(ColumnDef::new(CodeRevision::Type).string().not_null())
+                    .col(ColumnDef::new(CodeRevision::Value).string().not_null())
+                    .col(ColumnDef::new(CodeRevision::SiteId).uuid().not_null())
+                    .col(ColumnDef::new(CodeRevision::Name).string().null())
+                    .col(
+                        ColumnDef::new(CodeRevision::RenderOptions)
+                            .json()
+                            .not_null()
+                            .default("{}"),
+                    )
+                    .col(ColumnDef::new(CodeRevision::RestoredFrom).uuid().null())
+                    .col(
+                        ColumnDef::new(CodeRevision::CreatedAt)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_code_revision_code_id")
+                    .table(CodeRevision::Table)
+                    .col(CodeRevision::CodeId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(CodeRevision::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum CodeRevision {
+    Table,
+    Id,
+    CodeId,
+    UserId,
+    Type,
+    Value,
+    SiteId,
+    Name,
+    RenderOptions,
+    RestoredFrom,
+    CreatedAt,
+}
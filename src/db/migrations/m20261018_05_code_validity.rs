@@ -0,0 +1,58 @@
+use sea_orm_migration::prelude::*;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20261018_05_code_validity"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // SQLite can only add one column at a time
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Code::Table)
+                    .add_column(ColumnDef::new(Code::ValidFrom).date().null())
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Code::Table)
+                    .add_column(ColumnDef::new(Code::ExpiresAt).date().null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Code::Table)
+                    .drop_column(Code::ExpiresAt)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Code::Table)
+                    .drop_column(Code::ValidFrom)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum Code {
+    Table,
+    ValidFrom,
+    ExpiresAt,
+}
@@ -0,0 +1,81 @@
+use sea_orm_migration::prelude::*;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20261018_06_tags"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Tag::Table)
+                    .col(ColumnDef::new(Tag::Id).uuid().not_null().primary_key())
+                    .col(ColumnDef::new(Tag::UserId).uuid().not_null())
+                    .col(ColumnDef::new(Tag::Name).string().not_null())
+                    .col(ColumnDef::new(Tag::CreatedAt).timestamp().not_null())
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_tag_user_id_name")
+                    .table(Tag::Table)
+                    .col(Tag::UserId)
+                    .col(Tag::Name)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_table(
+                Table::create()
+                    .table(CodeTag::Table)
+                    .col(ColumnDef::new(CodeTag::CodeId).uuid().not_null())
+                    .col(ColumnDef::new(CodeTag::TagId).uuid().not_null())
+                    .primary_key(Index::create().col(CodeTag::CodeId).col(CodeTag::TagId))
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_code_tag_tag_id")
+                    .table(CodeTag::Table)
+                    .col(CodeTag::TagId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(CodeTag::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(Tag::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum Tag {
+    Table,
+    Id,
+    UserId,
+    Name,
+    CreatedAt,
+}
+
+#[derive(Iden)]
+pub enum CodeTag {
+    Table,
+    CodeId,
+    TagId,
+}
@@ -0,0 +1,84 @@
+use sea_orm_migration::prelude::*;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20261018_07_code_search"
+    }
+}
+
+/// The name a code is indexed under, leaving out names that are sealed
+const INDEXED_NAME: &str =
+    "CASE WHEN substr(new.name, 1, 10) = 'sealed:v1:' THEN NULL ELSE new.name END";
+/// The name of the site a code is at
+const INDEXED_SITE: &str = "(SELECT name FROM site WHERE site.id = new.site_id)";
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Values are always sealed, so they can't be indexed here and are matched by the app
+        // once they're decrypted. Rows are keyed by the code's ID rather than its rowid, which
+        // VACUUM is free to renumber.
+        let statements = [
+            "CREATE VIRTUAL TABLE code_search USING fts5(
+                code_id UNINDEXED,
+                name,
+                site,
+                tokenize = 'unicode61 remove_diacritics 2'
+            )"
+            .to_string(),
+            format!(
+                "CREATE TRIGGER code_search_insert AFTER INSERT ON code BEGIN
+                    INSERT INTO code_search (code_id, name, site)
+                    VALUES (new.id, {INDEXED_NAME}, {INDEXED_SITE});
+                END"
+            ),
+            format!(
+                "CREATE TRIGGER code_search_update AFTER UPDATE OF name, site_id ON code BEGIN
+                    DELETE FROM code_search WHERE code_id = old.id;
+                    INSERT INTO code_search (code_id, name, site)
+                    VALUES (new.id, {INDEXED_NAME}, {INDEXED_SITE});
+                END"
+            ),
+            "CREATE TRIGGER code_search_delete AFTER DELETE ON code BEGIN
+                DELETE FROM code_search WHERE code_id = old.id;
+            END"
+            .to_string(),
+            "CREATE TRIGGER code_search_site_update AFTER UPDATE OF name ON site BEGIN
+                UPDATE code_search SET site = new.name
+                WHERE code_id IN (SELECT id FROM code WHERE site_id = new.id);
+            END"
+            .to_string(),
+            "INSERT INTO code_search (code_id, name, site)
+            SELECT code.id,
+                CASE WHEN substr(code.name, 1, 10) = 'sealed:v1:' THEN NULL ELSE code.name END,
+                site.name
+            FROM code LEFT JOIN site ON site.id = code.site_id"
+                .to_string(),
+        ];
+        for statement in statements {
+            manager
+                .get_connection()
+                .execute_unprepared(&statement)
+                .await?;
+        }
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for statement in [
+            "DROP TRIGGER IF EXISTS code_search_site_update",
+            "DROP TRIGGER IF EXISTS code_search_delete",
+            "DROP TRIGGER IF EXISTS code_search_update",
+            "DROP TRIGGER IF EXISTS code_search_insert",
+            "DROP TABLE IF EXISTS code_search",
+        ] {
+            manager
+                .get_connection()
+                .execute_unprepared(statement)
+                .await?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,81 @@
+use sea_orm_migration::prelude::*;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20261018_08_code_order"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // SQLite can only add one column at a time
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Code::Table)
+                    .add_column(
+                        ColumnDef::new(Code::Favourite)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Code::Table)
+                    .add_column(
+                        ColumnDef::new(Code::Position)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        // existing codes keep the order they were created in
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "UPDATE code SET position = (
+                    SELECT COUNT(*) FROM code AS earlier
+                    WHERE earlier.user_id = code.user_id
+                    AND (earlier.created_at < code.created_at
+                        OR (earlier.created_at = code.created_at AND earlier.id < code.id))
+                )",
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Code::Table)
+                    .drop_column(Code::Position)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Code::Table)
+                    .drop_column(Code::Favourite)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum Code {
+    Table,
+    Favourite,
+    Position,
+}
@@ -0,0 +1,84 @@
+use sea_orm_migration::prelude::*;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20261018_09_code_usage"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(CodeUsage::Table)
+                    .col(
+                        ColumnDef::new(CodeUsage::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(CodeUsage::CodeId).uuid().not_null())
+                    .col(ColumnDef::new(CodeUsage::UserId).uuid().not_null())
+                    .col(ColumnDef::new(CodeUsage::UsedAt).timestamp().not_null())
+                    .col(ColumnDef::new(CodeUsage::Location).string().null())
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_code_usage_code_id")
+                    .table(CodeUsage::Table)
+                    .col(CodeUsage::CodeId)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(User::Table)
+                    .add_column(
+                        ColumnDef::new(User::TrackUsage)
+                            .boolean()
+                            .not_null()
+                            .default(true),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(User::Table)
+                    .drop_column(User::TrackUsage)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_table(Table::drop().table(CodeUsage::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum CodeUsage {
+    Table,
+    Id,
+    CodeId,
+    UserId,
+    UsedAt,
+    Location,
+}
+
+#[derive(Iden)]
+pub enum User {
+    Table,
+    TrackUsage,
+}
@@ -0,0 +1,119 @@
+use sea_orm_migration::prelude::*;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20261018_10_notes_and_attachments"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Code::Table)
+                    .add_column(ColumnDef::new(Code::Notes).text().null())
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(CodeRevision::Table)
+                    .add_column(ColumnDef::new(CodeRevision::Notes).text().null())
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_table(
+                Table::create()
+                    .table(CodeAttachment::Table)
+                    .col(
+                        ColumnDef::new(CodeAttachment::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(CodeAttachment::CodeId).uuid().not_null())
+                    .col(ColumnDef::new(CodeAttachment::Side).string().not_null())
+                    .col(
+                        ColumnDef::new(CodeAttachment::ContentType)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(CodeAttachment::Size)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(CodeAttachment::Data).blob().null())
+                    .col(
+                        ColumnDef::new(CodeAttachment::CreatedAt)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_code_attachment_code_id_side")
+                    .table(CodeAttachment::Table)
+                    .col(CodeAttachment::CodeId)
+                    .col(CodeAttachment::Side)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(CodeAttachment::Table).to_owned())
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(CodeRevision::Table)
+                    .drop_column(CodeRevision::Notes)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Code::Table)
+                    .drop_column(Code::Notes)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum Code {
+    Table,
+    Notes,
+}
+
+#[derive(Iden)]
+pub enum CodeRevision {
+    Table,
+    Notes,
+}
+
+#[derive(Iden)]
+pub enum CodeAttachment {
+    Table,
+    Id,
+    CodeId,
+    Side,
+    ContentType,
+    Size,
+    Data,
+    CreatedAt,
+}
@@ -0,0 +1,64 @@
+use sea_orm_migration::prelude::*;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20261018_11_code_secret"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(CodeSecret::Table)
+                    .col(
+                        ColumnDef::new(CodeSecret::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(CodeSecret::CodeId).uuid().not_null())
+                    .col(ColumnDef::new(CodeSecret::Label).string().not_null())
+                    .col(ColumnDef::new(CodeSecret::Value).text().not_null())
+                    .col(
+                        ColumnDef::new(CodeSecret::RequirePassword)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .col(ColumnDef::new(CodeSecret::CreatedAt).timestamp().not_null())
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_code_secret_code_id")
+                    .table(CodeSecret::Table)
+                    .col(CodeSecret::CodeId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(CodeSecret::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum CodeSecret {
+    Table,
+    Id,
+    CodeId,
+    Label,
+    Value,
+    RequirePassword,
+    CreatedAt,
+}
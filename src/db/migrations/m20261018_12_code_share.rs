@@ -0,0 +1,136 @@
+use sea_orm_migration::prelude::*;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20261018_12_code_share"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(User::Table)
+                    .add_column(ColumnDef::new(User::PublicKey).text().null())
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(User::Table)
+                    .add_column(ColumnDef::new(User::PrivateKey).text().null())
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Code::Table)
+                    .add_column(ColumnDef::new(Code::WrappedKey).text().null())
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_table(
+                Table::create()
+                    .table(CodeShare::Table)
+                    .col(
+                        ColumnDef::new(CodeShare::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(CodeShare::CodeId).uuid().not_null())
+                    .col(ColumnDef::new(CodeShare::UserId).uuid().not_null())
+                    .col(
+                        ColumnDef::new(CodeShare::CanEdit)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .col(ColumnDef::new(CodeShare::WrappedKey).text().not_null())
+                    .col(ColumnDef::new(CodeShare::CreatedAt).timestamp().not_null())
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_code_share_code_id_user_id")
+                    .table(CodeShare::Table)
+                    .col(CodeShare::CodeId)
+                    .col(CodeShare::UserId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_code_share_user_id")
+                    .table(CodeShare::Table)
+                    .col(CodeShare::UserId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(CodeShare::Table).to_owned())
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Code::Table)
+                    .drop_column(Code::WrappedKey)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(User::Table)
+                    .drop_column(User::PrivateKey)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(User::Table)
+                    .drop_column(User::PublicKey)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum User {
+    Table,
+    PublicKey,
+    PrivateKey,
+}
+
+#[derive(Iden)]
+pub enum Code {
+    Table,
+    WrappedKey,
+}
+
+#[derive(Iden)]
+pub enum CodeShare {
+    Table,
+    Id,
+    CodeId,
+    UserId,
+    CanEdit,
+    WrappedKey,
+    CreatedAt,
+}
@@ -0,0 +1,147 @@
+use sea_orm_migration::prelude::*;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20261018_13_household"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Household::Table)
+                    .col(
+                        ColumnDef::new(Household::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(Household::Name).string().not_null())
+                    .col(ColumnDef::new(Household::CreatedAt).timestamp().not_null())
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_table(
+                Table::create()
+                    .table(HouseholdMember::Table)
+                    .col(
+                        ColumnDef::new(HouseholdMember::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(HouseholdMember::HouseholdId)
+                            .uuid()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(HouseholdMember::UserId).uuid().not_null())
+                    .col(ColumnDef::new(HouseholdMember::Role).string().not_null())
+                    .col(
+                        ColumnDef::new(HouseholdMember::WrappedKey)
+                            .text()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(HouseholdMember::CreatedAt)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_household_member_household_id_user_id")
+                    .table(HouseholdMember::Table)
+                    .col(HouseholdMember::HouseholdId)
+                    .col(HouseholdMember::UserId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_household_member_user_id")
+                    .table(HouseholdMember::Table)
+                    .col(HouseholdMember::UserId)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Code::Table)
+                    .add_column(ColumnDef::new(Code::HouseholdId).uuid().null())
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_code_household_id")
+                    .table(Code::Table)
+                    .col(Code::HouseholdId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_code_household_id")
+                    .table(Code::Table)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Code::Table)
+                    .drop_column(Code::HouseholdId)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_table(Table::drop().table(HouseholdMember::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(Household::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum Household {
+    Table,
+    Id,
+    Name,
+    CreatedAt,
+}
+
+#[derive(Iden)]
+pub enum HouseholdMember {
+    Table,
+    Id,
+    HouseholdId,
+    UserId,
+    Role,
+    WrappedKey,
+    CreatedAt,
+}
+
+#[derive(Iden)]
+pub enum Code {
+    Table,
+    HouseholdId,
+}
@@ -0,0 +1,82 @@
+use sea_orm_migration::prelude::*;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20261018_14_share_link"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(ShareLink::Table)
+                    .col(
+                        ColumnDef::new(ShareLink::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(ShareLink::CodeId).uuid().not_null())
+                    .col(ColumnDef::new(ShareLink::UserId).uuid().not_null())
+                    .col(ColumnDef::new(ShareLink::TokenHash).string().not_null())
+                    .col(ColumnDef::new(ShareLink::SealedToken).text().not_null())
+                    .col(ColumnDef::new(ShareLink::WrappedKey).text().not_null())
+                    .col(ColumnDef::new(ShareLink::ExpiresAt).timestamp().not_null())
+                    .col(ColumnDef::new(ShareLink::MaxViews).integer().null())
+                    .col(
+                        ColumnDef::new(ShareLink::Views)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .col(ColumnDef::new(ShareLink::CreatedAt).timestamp().not_null())
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_share_link_token_hash")
+                    .table(ShareLink::Table)
+                    .col(ShareLink::TokenHash)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_share_link_code_id")
+                    .table(ShareLink::Table)
+                    .col(ShareLink::CodeId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ShareLink::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum ShareLink {
+    Table,
+    Id,
+    CodeId,
+    UserId,
+    TokenHash,
+    SealedToken,
+    WrappedKey,
+    ExpiresAt,
+    MaxViews,
+    Views,
+    CreatedAt,
+}
@@ -0,0 +1,93 @@
+use sea_orm_migration::prelude::*;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20261018_15_site_details"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // SQLite can only add one column at a time
+        for column in [
+            ColumnDef::new(Site::BrandColour).string().null().to_owned(),
+            ColumnDef::new(Site::DefaultCodeType)
+                .string()
+                .null()
+                .to_owned(),
+            ColumnDef::new(Site::Instructions).text().null().to_owned(),
+            ColumnDef::new(Site::ValuePattern)
+                .string()
+                .null()
+                .to_owned(),
+        ] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(Site::Table)
+                        .add_column(column)
+                        .to_owned(),
+                )
+                .await?;
+        }
+        manager
+            .create_table(
+                Table::create()
+                    .table(SiteLogo::Table)
+                    .col(
+                        ColumnDef::new(SiteLogo::SiteId)
+                            .uuid()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(SiteLogo::ContentType).string().not_null())
+                    .col(ColumnDef::new(SiteLogo::Data).blob().not_null())
+                    .col(ColumnDef::new(SiteLogo::CreatedAt).timestamp().not_null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(SiteLogo::Table).to_owned())
+            .await?;
+        for column in [
+            Site::ValuePattern,
+            Site::Instructions,
+            Site::DefaultCodeType,
+            Site::BrandColour,
+        ] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(Site::Table)
+                        .drop_column(column)
+                        .to_owned(),
+                )
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+pub enum Site {
+    Table,
+    BrandColour,
+    DefaultCodeType,
+    Instructions,
+    ValuePattern,
+}
+
+#[derive(Iden)]
+pub enum SiteLogo {
+    Table,
+    SiteId,
+    ContentType,
+    Data,
+    CreatedAt,
+}
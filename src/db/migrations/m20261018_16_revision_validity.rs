@@ -0,0 +1,58 @@
+use sea_orm_migration::prelude::*;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20261018_16_revision_validity"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // SQLite can only add one column at a time
+        for column in [CodeRevision::ValidFrom, CodeRevision::ExpiresAt] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(CodeRevision::Table)
+                        .add_column(ColumnDef::new(column).date().null())
+                        .to_owned(),
+                )
+                .await?;
+        }
+        // restoring a revision from before now used to leave the dates alone, so existing
+        // revisions get the code's current ones
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "UPDATE code_revision SET
+                    valid_from = (SELECT valid_from FROM code WHERE code.id = code_revision.code_id),
+                    expires_at = (SELECT expires_at FROM code WHERE code.id = code_revision.code_id)",
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for column in [CodeRevision::ExpiresAt, CodeRevision::ValidFrom] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(CodeRevision::Table)
+                        .drop_column(column)
+                        .to_owned(),
+                )
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+pub enum CodeRevision {
+    Table,
+    ValidFrom,
+    ExpiresAt,
+}
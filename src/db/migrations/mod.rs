@@ -3,6 +3,22 @@ pub(crate) mod m20251220_01;
 pub(crate) mod m20251222_02_default_site;
 pub(crate) mod m20251222_03_add_code_name;
 pub(crate) mod m20251224_01_username_to_email;
+pub(crate) mod m20261018_01_barcode_to_code128;
+pub(crate) mod m20261018_02_code_render_options;
+pub(crate) mod m20261018_03_user_data_keys;
+pub(crate) mod m20261018_04_code_revisions;
+pub(crate) mod m20261018_05_code_validity;
+pub(crate) mod m20261018_06_tags;
+pub(crate) mod m20261018_07_code_search;
+pub(crate) mod m20261018_08_code_order;
+pub(crate) mod m20261018_09_code_usage;
+pub(crate) mod m20261018_10_notes_and_attachments;
+pub(crate) mod m20261018_11_code_secret;
+pub(crate) mod m20261018_12_code_share;
+pub(crate) mod m20261018_13_household;
+pub(crate) mod m20261018_14_share_link;
+pub(crate) mod m20261018_15_site_details;
+pub(crate) mod m20261018_16_revision_validity;
 
 use sea_orm_migration::prelude::*;
 
@@ -17,6 +33,22 @@ impl MigratorTrait for Migrator {
             Box::new(super::migrations::m20251222_02_default_site::Migration),
             Box::new(super::migrations::m20251222_03_add_code_name::Migration),
             Box::new(super::migrations::m20251224_01_username_to_email::Migration),
+            Box::new(super::migrations::m20261018_01_barcode_to_code128::Migration),
+            Box::new(super::migrations::m20261018_02_code_render_options::Migration),
+            Box::new(super::migrations::m20261018_03_user_data_keys::Migration),
+            Box::new(super::migrations::m20261018_04_code_revisions::Migration),
+            Box::new(super::migrations::m20261018_05_code_validity::Migration),
+            Box::new(super::migrations::m20261018_06_tags::Migration),
+            Box::new(super::migrations::m20261018_07_code_search::Migration),
+            Box::new(super::migrations::m20261018_08_code_order::Migration),
+            Box::new(super::migrations::m20261018_09_code_usage::Migration),
+            Box::new(super::migrations::m20261018_10_notes_and_attachments::Migration),
+            Box::new(super::migrations::m20261018_11_code_secret::Migration),
+            Box::new(super::migrations::m20261018_12_code_share::Migration),
+            Box::new(super::migrations::m20261018_13_household::Migration),
+            Box::new(super::migrations::m20261018_14_share_link::Migration),
+            Box::new(super::migrations::m20261018_15_site_details::Migration),
+            Box::new(super::migrations::m20261018_16_revision_validity::Migration),
         ]
     }
 }
@@ -25,10 +57,17 @@ impl MigratorTrait for Migrator {
 mod tests {
     use std::sync::Arc;
 
-    use sea_orm_migration::MigratorTrait;
+    use sea_orm::EntityTrait;
+    use sea_orm_migration::{MigrationTrait, MigratorTrait, SchemaManager};
     use tokio::sync::RwLock;
+    use uuid::Uuid;
 
-    use crate::config::Configuration;
+    use crate::{
+        Code,
+        config::Configuration,
+        crypto::{CodeCipher, DataKey},
+        db::entities::code,
+    };
 
     #[tokio::test]
     async fn test_migrator() {
@@ -41,4 +80,47 @@ mod tests {
             .await
             .expect("Failed to run migrations");
     }
+
+    #[tokio::test]
+    async fn test_barcode_to_code128() {
+        let config = Configuration::test();
+        let db = crate::db::connect(Arc::new(RwLock::new(config)))
+            .await
+            .expect("Failed to connect to test DB");
+
+        let legacy = code::Model::create_new(
+            db.clone(),
+            &CodeCipher::new(DataKey::generate(), false),
+            Uuid::nil(),
+            Code::Code128,
+            "123456",
+            Uuid::nil(),
+            None,
+        )
+        .await
+        .expect("Failed to create code");
+        let manager = SchemaManager::new(&db);
+
+        super::m20261018_01_barcode_to_code128::Migration
+            .down(&manager)
+            .await
+            .expect("Failed to roll back migration");
+        let code_model = code::Entity::find_by_id(legacy.id)
+            .one(&db)
+            .await
+            .expect("Failed to query code")
+            .expect("Code not found");
+        assert_eq!(code_model.type_, "barcode");
+
+        super::m20261018_01_barcode_to_code128::Migration
+            .up(&manager)
+            .await
+            .expect("Failed to apply migration");
+        let code_model = code::Entity::find_by_id(legacy.id)
+            .one(&db)
+            .await
+            .expect("Failed to query code")
+            .expect("Code not found");
+        assert_eq!(code_model.type_, Code::Code128.to_string());
+    }
 }
@@ -45,6 +45,11 @@ pub async fn connect(config: SendableConfig) -> Result<DatabaseConnection, Hoofp
             email: Set(GROUP_ADMIN.to_string()),
             display_name: Set("Administrator".to_string()),
             password: Set(password.clone()),
+            data_key: Set(None),
+            retired_data_keys: Set(JsonValue::Array(Vec::new())),
+            track_usage: Set(true),
+            public_key: Set(None),
+            private_key: Set(None),
             groups: Set(JsonValue::from_str(&format!(r#"["{}"]"#, GROUP_ADMIN))?),
         };
         admin_user.insert(&db_transaction).await?;
@@ -0,0 +1,165 @@
+//! Reading codes back out of uploaded images
+
+use std::io::Cursor;
+
+use image::{ImageReader, Limits};
+use rxing::{
+    BarcodeFormat, DecodeHintValue, DecodeHints, RXingResultMetadataType, RXingResultMetadataValue,
+    helpers::detect_in_luma_with_hints,
+};
+
+use crate::{Code, error::HoofprintError, gs1};
+
+/// The largest upload we'll try to decode, in bytes
+pub(crate) const MAX_UPLOAD_SIZE: usize = 10 * 1024 * 1024;
+
+/// The largest image we'll try to decode in either direction, in pixels
+const MAX_IMAGE_DIMENSION: u32 = 8192;
+
+/// A code found in an image
+#[derive(Debug)]
+pub(crate) struct DecodedCode {
+    pub code: Code,
+    pub value: String,
+}
+
+impl TryFrom<&BarcodeFormat> for Code {
+    type Error = HoofprintError;
+
+    fn try_from(value: &BarcodeFormat) -> Result<Self, Self::Error> {
+        match value {
+            BarcodeFormat::CODE_128 => Ok(Code::Code128),
+            BarcodeFormat::QR_CODE => Ok(Code::QR),
+            BarcodeFormat::EAN_13 => Ok(Code::Ean13),
+            BarcodeFormat::EAN_8 => Ok(Code::Ean8),
+            BarcodeFormat::UPC_A => Ok(Code::UpcA),
+            BarcodeFormat::UPC_E => Ok(Code::UpcE),
+            BarcodeFormat::CODE_39 => Ok(Code::Code39),
+            BarcodeFormat::ITF => Ok(Code::Itf14),
+            BarcodeFormat::CODABAR => Ok(Code::Codabar),
+            BarcodeFormat::PDF_417 => Ok(Code::Pdf417),
+            BarcodeFormat::AZTEC => Ok(Code::Aztec),
+            BarcodeFormat::DATA_MATRIX => Ok(Code::DataMatrix),
+            other => Err(HoofprintError::InvalidCodeType(other.to_string())),
+        }
+    }
+}
+
+/// Find a code in a JPEG, PNG or WebP image.
+///
+/// This is CPU-heavy, so run it on a blocking thread.
+pub(crate) fn decode_image(bytes: &[u8]) -> Result<DecodedCode, HoofprintError> {
+    let unreadable = |err: &dyn std::fmt::Display| {
+        HoofprintError::ValidationError(vec![format!("Couldn't read the image: {}", err)])
+    };
+
+    let mut reader = ImageReader::new(Cursor::new(bytes))
+        .with_guessed_format()
+        .map_err(|err| unreadable(&err))?;
+    let mut limits = Limits::default();
+    limits.max_image_width = Some(MAX_IMAGE_DIMENSION);
+    limits.max_image_height = Some(MAX_IMAGE_DIMENSION);
+    reader.limits(limits);
+    let luma = reader.decode().map_err(|err| unreadable(&err))?.to_luma8();
+    let (width, height) = luma.dimensions();
+
+    // keep Codabar's start/stop characters so the value renders the same way again, and GS1's
+    // separators so the fields can be split back up
+    let mut hints = DecodeHints::default()
+        .with(DecodeHintValue::ReturnCodabarStartEnd(true))
+        .with(DecodeHintValue::AlsoInverted(true))
+        .with(DecodeHintValue::AssumeGs1(true));
+    let result = detect_in_luma_with_hints(luma.into_raw(), width, height, None, &mut hints)
+        .map_err(|_| {
+            HoofprintError::ValidationError(vec![
+                "Couldn't find a code in the image, try a sharper or closer photo".to_string(),
+            ])
+        })?;
+
+    let mut code = Code::try_from(result.getBarcodeFormat()).map_err(|_| {
+        HoofprintError::ValidationError(vec![format!(
+            "Found a {} code, which isn't supported",
+            result.getBarcodeFormat()
+        )])
+    })?;
+
+    let mut value = result.getText().to_string();
+
+    // GS1 symbols start with FNC1, which shows up in the symbology identifier
+    if let Some(RXingResultMetadataValue::SymbologyIdentifier(identifier)) = result
+        .getRXingResultMetadata()
+        .get(&RXingResultMetadataType::SYMBOLOGY_IDENTIFIER)
+        && matches!(identifier.as_str(), "]C1" | "]d2")
+    {
+        code = match code {
+            Code::DataMatrix => Code::Gs1DataMatrix,
+            _ => Code::Gs1Code128,
+        };
+        value = gs1::from_element_string(&value).map_err(HoofprintError::ValidationError)?;
+    }
+
+    Ok(DecodedCode { code, value })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::{CodeImage, RasterOptions, RenderOptions};
+
+    #[test]
+    fn test_decode_rendered_codes() {
+        for code in [
+            Code::QR,
+            Code::Code128,
+            Code::Ean13,
+            Code::DataMatrix,
+            Code::Gs1Code128,
+            Code::Gs1DataMatrix,
+        ] {
+            let png_bytes =
+                CodeImage::encode(&code, code.sample_value(), &RenderOptions::default())
+                    .expect("Failed to encode code")
+                    .to_png(&RasterOptions::default())
+                    .expect("Failed to render PNG");
+            let decoded = decode_image(&png_bytes).expect("Failed to decode image");
+            assert_eq!(decoded.code, code);
+            assert_eq!(decoded.value, code.sample_value());
+        }
+    }
+
+    #[test]
+    fn test_decode_with_render_options() {
+        let options = RenderOptions {
+            show_text: true,
+            invert: true,
+            rotate: true,
+            ..Default::default()
+        };
+        for code in [Code::QR, Code::Code128] {
+            let png_bytes = CodeImage::encode(&code, code.sample_value(), &options)
+                .expect("Failed to encode code")
+                .to_png(&RasterOptions::default())
+                .expect("Failed to render PNG");
+            let decoded = decode_image(&png_bytes).expect("Failed to decode image");
+            assert_eq!(decoded.value, code.sample_value());
+        }
+    }
+
+    #[test]
+    fn test_decode_bad_images() {
+        let result = decode_image(b"definitely not an image");
+        assert!(matches!(result, Err(HoofprintError::ValidationError(_))));
+
+        // a valid image with nothing in it
+        let mut blank = Vec::new();
+        let mut encoder = png::Encoder::new(&mut blank, 100, 100);
+        encoder.set_color(png::ColorType::Grayscale);
+        let mut writer = encoder.write_header().expect("Failed to write PNG header");
+        writer
+            .write_image_data(&[0xff; 100 * 100])
+            .expect("Failed to write PNG data");
+        writer.finish().expect("Failed to finish PNG");
+        let result = decode_image(&blank);
+        assert!(matches!(result, Err(HoofprintError::ValidationError(_))));
+    }
+}
@@ -27,6 +27,8 @@ pub enum HoofprintError {
     InvalidBaseUrl(String),
     InternalError(String),
     NeedToLogin,
+    /// A code sealed with a data key the user no longer has unlocked
+    CodeLocked(String),
 }
 
 impl std::fmt::Display for HoofprintError {
@@ -49,6 +51,7 @@ impl std::fmt::Display for HoofprintError {
             HoofprintError::NeedToLogin => write!(f, "Need to Login"),
             HoofprintError::MissingCsrfToken => write!(f, "Missing CSRF Token"),
             HoofprintError::InvalidCsrfToken => write!(f, "Invalid CSRF Token"),
+            HoofprintError::CodeLocked(code_id) => write!(f, "Code Locked: {}", code_id),
         }
     }
 }
@@ -96,6 +99,13 @@ impl From<argon2::password_hash::Error> for HoofprintError {
     }
 }
 
+impl From<argon2::Error> for HoofprintError {
+    fn from(err: argon2::Error) -> Self {
+        error!("Key derivation error: {}", err);
+        HoofprintError::InternalError("Key Derivation Error, check the logs!".to_string())
+    }
+}
+
 impl From<tower_sessions::session::Error> for HoofprintError {
     fn from(err: tower_sessions::session::Error) -> Self {
         error!("Session error: {}", err);
@@ -145,6 +155,33 @@ impl From<serde_json::error::Error> for HoofprintError {
     }
 }
 
+impl From<png::EncodingError> for HoofprintError {
+    fn from(err: png::EncodingError) -> Self {
+        error!("PNG encoding error: {}", err);
+        HoofprintError::InternalError("PNG Encoding Error, check the logs!".to_string())
+    }
+}
+
+impl From<openssl::error::ErrorStack> for HoofprintError {
+    fn from(err: openssl::error::ErrorStack) -> Self {
+        error!("OpenSSL error: {}", err);
+        HoofprintError::InternalError("OpenSSL Error, check the logs!".to_string())
+    }
+}
+
+impl From<zip::result::ZipError> for HoofprintError {
+    fn from(err: zip::result::ZipError) -> Self {
+        error!("Zip error: {}", err);
+        HoofprintError::InternalError("Zip Error, check the logs!".to_string())
+    }
+}
+
+impl From<axum::extract::multipart::MultipartError> for HoofprintError {
+    fn from(err: axum::extract::multipart::MultipartError) -> Self {
+        HoofprintError::ValidationError(vec![format!("Invalid upload: {}", err.body_text())])
+    }
+}
+
 impl IntoResponse for HoofprintError {
     fn into_response(self) -> Response<Body> {
         // Log the error for debugging
@@ -207,6 +244,10 @@ impl IntoResponse for HoofprintError {
                 let body = "Invalid CSRF Token";
                 (StatusCode::BAD_REQUEST, body).into_response()
             }
+            HoofprintError::CodeLocked(_) => {
+                let body = "This code was encrypted before your password was reset. Recover it with your old password from your account page.";
+                (StatusCode::LOCKED, body).into_response()
+            }
         }
     }
 }
@@ -0,0 +1,478 @@
+//! GS1 Application Identifier parsing, for GS1-128 and GS1 DataMatrix codes.
+//!
+//! Values are stored in their human-readable form with each AI in brackets, like
+//! `(01)09501101530003(17)260101(10)ABC123`, and only turned into the element string the
+//! symbology carries when the code is drawn.
+
+use crate::{Code, error::HoofprintError, validation::gs1_check_digit};
+
+/// The group separator, which ends a variable-length field in a scanned element string
+const GROUP_SEPARATOR: char = '\u{1d}';
+
+/// rxing's escape for Code 128's FNC1 character
+const CODE128_FNC1: char = '\u{f1}';
+
+/// The most data characters a GS1-128 symbol can hold
+const GS1_128_MAX_LENGTH: usize = 48;
+
+/// AIs starting with these digits have a fixed length, so never need a separator after them
+const PREDEFINED_LENGTH_PREFIXES: [&str; 22] = [
+    "00", "01", "02", "03", "04", "11", "12", "13", "14", "15", "16", "17", "18", "19", "20", "31",
+    "32", "33", "34", "35", "36", "41",
+];
+
+/// GS1's 82-character set for alphanumeric fields, apart from digits and letters
+const GS1_PUNCTUATION: &str = "!\"%&'()*+,-./:;<=>?_";
+
+/// How an AI's data is written
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Format {
+    Numeric,
+    Alphanumeric,
+    /// A YYMMDD date, where a day of 00 means the end of the month
+    Date,
+    /// A number with as many implied decimal places as the AI's last digit
+    Decimal,
+}
+
+/// The definition of a GS1 Application Identifier
+#[derive(Debug)]
+struct AiDefinition {
+    /// The AI, or its first three digits when the fourth gives the decimal places
+    prefix: &'static str,
+    /// How many digits the AI has
+    ai_length: usize,
+    /// GS1's short data title
+    title: &'static str,
+    format: Format,
+    min_length: usize,
+    max_length: usize,
+    /// Whether the data ends with a GS1 check digit
+    check_digit: bool,
+}
+
+const fn ai(
+    prefix: &'static str,
+    title: &'static str,
+    format: Format,
+    min_length: usize,
+    max_length: usize,
+) -> AiDefinition {
+    AiDefinition {
+        prefix,
+        ai_length: prefix.len(),
+        title,
+        format,
+        min_length,
+        max_length,
+        check_digit: false,
+    }
+}
+
+const fn ai_with_check_digit(
+    prefix: &'static str,
+    title: &'static str,
+    length: usize,
+) -> AiDefinition {
+    AiDefinition {
+        check_digit: true,
+        ..ai(prefix, title, Format::Numeric, length, length)
+    }
+}
+
+const fn ai_with_decimals(
+    prefix: &'static str,
+    title: &'static str,
+    min_length: usize,
+    max_length: usize,
+) -> AiDefinition {
+    AiDefinition {
+        ai_length: 4,
+        ..ai(prefix, title, Format::Decimal, min_length, max_length)
+    }
+}
+
+/// The AIs we understand, which covers what turns up on cards, labels and medicine packs
+const AI_DEFINITIONS: &[AiDefinition] = &[
+    ai_with_check_digit("00", "SSCC", 18),
+    ai_with_check_digit("01", "GTIN", 14),
+    ai_with_check_digit("02", "CONTENT", 14),
+    ai("10", "BATCH/LOT", Format::Alphanumeric, 1, 20),
+    ai("11", "PROD DATE", Format::Date, 6, 6),
+    ai("12", "DUE DATE", Format::Date, 6, 6),
+    ai("13", "PACK DATE", Format::Date, 6, 6),
+    ai("15", "BEST BEFORE", Format::Date, 6, 6),
+    ai("16", "SELL BY", Format::Date, 6, 6),
+    ai("17", "USE BY", Format::Date, 6, 6),
+    ai("20", "VARIANT", Format::Numeric, 2, 2),
+    ai("21", "SERIAL", Format::Alphanumeric, 1, 20),
+    ai("22", "CPV", Format::Alphanumeric, 1, 20),
+    ai("235", "TPX", Format::Alphanumeric, 1, 28),
+    ai("240", "ADDITIONAL ID", Format::Alphanumeric, 1, 30),
+    ai("241", "CUST. PART No.", Format::Alphanumeric, 1, 30),
+    ai("250", "SECONDARY SERIAL", Format::Alphanumeric, 1, 30),
+    ai("251", "REF. TO SOURCE", Format::Alphanumeric, 1, 30),
+    ai("253", "GDTI", Format::Alphanumeric, 13, 30),
+    ai(
+        "254",
+        "GLN EXTENSION COMPONENT",
+        Format::Alphanumeric,
+        1,
+        20,
+    ),
+    ai("30", "VAR. COUNT", Format::Numeric, 1, 8),
+    ai_with_decimals("310", "NET WEIGHT (kg)", 6, 6),
+    ai_with_decimals("320", "NET WEIGHT (lb)", 6, 6),
+    ai("37", "COUNT", Format::Numeric, 1, 8),
+    ai_with_decimals("390", "AMOUNT", 1, 15),
+    ai_with_decimals("392", "PRICE", 1, 15),
+    ai("400", "ORDER NUMBER", Format::Alphanumeric, 1, 30),
+    ai("401", "GINC", Format::Alphanumeric, 1, 30),
+    ai_with_check_digit("402", "GSIN", 17),
+    ai_with_check_digit("410", "SHIP TO LOC", 13),
+    ai_with_check_digit("413", "SHIP FOR LOC", 13),
+    ai_with_check_digit("414", "LOC No.", 13),
+    ai_with_check_digit("415", "PAY TO", 13),
+    ai("420", "SHIP TO POST", Format::Alphanumeric, 1, 20),
+    ai("422", "ORIGIN", Format::Numeric, 3, 3),
+    ai("7003", "EXPIRY TIME", Format::Numeric, 10, 10),
+    ai("8004", "GIAI", Format::Alphanumeric, 1, 30),
+    ai("8020", "REF No.", Format::Alphanumeric, 1, 25),
+    ai("8200", "PRODUCT URL", Format::Alphanumeric, 1, 70),
+    ai("90", "INTERNAL", Format::Alphanumeric, 1, 30),
+    ai("91", "INTERNAL", Format::Alphanumeric, 1, 90),
+    ai("92", "INTERNAL", Format::Alphanumeric, 1, 90),
+    ai("93", "INTERNAL", Format::Alphanumeric, 1, 90),
+    ai("94", "INTERNAL", Format::Alphanumeric, 1, 90),
+    ai("95", "INTERNAL", Format::Alphanumeric, 1, 90),
+    ai("96", "INTERNAL", Format::Alphanumeric, 1, 90),
+    ai("97", "INTERNAL", Format::Alphanumeric, 1, 90),
+    ai("98", "INTERNAL", Format::Alphanumeric, 1, 90),
+    ai("99", "INTERNAL", Format::Alphanumeric, 1, 90),
+];
+
+/// The definition for an AI at the start of `text`, if there's one we know
+fn find_definition(text: &str) -> Option<&'static AiDefinition> {
+    AI_DEFINITIONS.iter().find(|definition| {
+        text.get(..definition.ai_length).is_some_and(|ai| {
+            ai.starts_with(definition.prefix) && ai.chars().all(|c| c.is_ascii_digit())
+        })
+    })
+}
+
+/// Whether a month and day from a YYMMDD date make sense
+fn is_valid_date(data: &str) -> bool {
+    let month = data.get(2..4).and_then(|month| month.parse::<u32>().ok());
+    let day = data.get(4..6).and_then(|day| day.parse::<u32>().ok());
+    match (month, day) {
+        (Some(month), Some(day)) => (1..=12).contains(&month) && day <= 31,
+        _ => false,
+    }
+}
+
+/// One AI and its data from a GS1 value
+#[derive(Debug)]
+pub struct Gs1Element {
+    pub ai: String,
+    pub data: String,
+    definition: &'static AiDefinition,
+}
+
+impl Gs1Element {
+    /// GS1's short title for the AI
+    pub fn title(&self) -> &'static str {
+        self.definition.title
+    }
+
+    /// The data as a person would write it, with dates and decimal places filled in
+    pub fn display_value(&self) -> String {
+        match self.definition.format {
+            Format::Date => match (self.data.get(..2), self.data.get(2..4), self.data.get(4..)) {
+                (Some(year), Some(month), Some("00")) => format!("20{year}-{month} (end of month)"),
+                (Some(year), Some(month), Some(day)) => format!("20{year}-{month}-{day}"),
+                _ => self.data.clone(),
+            },
+            Format::Decimal => {
+                let decimals = self
+                    .ai
+                    .get(3..)
+                    .and_then(|digit| digit.parse::<usize>().ok())
+                    .unwrap_or_default();
+                let digits = format!("{:0>width$}", self.data, width = decimals + 1);
+                let (whole, fraction) = digits.split_at(digits.len() - decimals);
+                let whole = whole.trim_start_matches('0');
+                let whole = if whole.is_empty() { "0" } else { whole };
+                if fraction.is_empty() {
+                    whole.to_string()
+                } else {
+                    format!("{whole}.{fraction}")
+                }
+            }
+            Format::Numeric | Format::Alphanumeric => self.data.clone(),
+        }
+    }
+
+    /// Every problem with the element's data
+    fn validate(&self, errors: &mut Vec<String>) {
+        let definition = self.definition;
+        let label = format!("({}) {}", self.ai, definition.title);
+        let length = self.data.chars().count();
+
+        if definition.min_length == definition.max_length && length != definition.max_length {
+            errors.push(format!(
+                "{} must be {} characters long",
+                label, definition.max_length
+            ));
+        } else if length < definition.min_length || length > definition.max_length {
+            errors.push(format!(
+                "{} must be between {} and {} characters long",
+                label, definition.min_length, definition.max_length
+            ));
+        }
+
+        match definition.format {
+            Format::Numeric | Format::Date | Format::Decimal => {
+                if !self.data.chars().all(|c| c.is_ascii_digit()) {
+                    errors.push(format!("{} can only contain digits", label));
+                } else if definition.format == Format::Date
+                    && length == 6
+                    && !is_valid_date(&self.data)
+                {
+                    errors.push(format!("{} isn't a valid YYMMDD date", label));
+                } else if definition.check_digit
+                    && length == definition.max_length
+                    && let Some((index, check)) = self.data.char_indices().last()
+                    && gs1_check_digit(&self.data[..index]) != check.to_digit(10)
+                {
+                    errors.push(format!("{} check digit is incorrect", label));
+                }
+            }
+            Format::Alphanumeric => {
+                if let Some(c) = self
+                    .data
+                    .chars()
+                    .find(|c| !c.is_ascii_alphanumeric() && !GS1_PUNCTUATION.contains(*c))
+                {
+                    errors.push(format!("{} can't contain '{}'", label, c));
+                }
+            }
+        }
+    }
+
+    /// Whether a separator is needed after this element when another follows
+    fn needs_separator(&self) -> bool {
+        !PREDEFINED_LENGTH_PREFIXES.contains(&self.ai.get(..2).unwrap_or_default())
+    }
+}
+
+/// Parse and validate a bracketed GS1 value like `(01)09501101530003(10)ABC123`
+pub fn parse(value: &str) -> Result<Vec<Gs1Element>, Vec<String>> {
+    if !value.starts_with('(') {
+        return Err(vec![
+            "GS1 values need each application identifier in brackets, like (01)09501101530003(10)ABC123"
+                .to_string(),
+        ]);
+    }
+
+    let mut elements = Vec::new();
+    let mut errors = Vec::new();
+    // every chunk after a '(' is an AI, its ')' and then its data
+    for chunk in value.split('(').skip(1) {
+        let Some((ai, data)) = chunk.split_once(')') else {
+            errors.push(format!("({} is missing its closing bracket", chunk));
+            continue;
+        };
+        let definition = find_definition(ai).filter(|definition| definition.ai_length == ai.len());
+        match definition {
+            Some(definition) => {
+                let element = Gs1Element {
+                    ai: ai.to_string(),
+                    data: data.to_string(),
+                    definition,
+                };
+                element.validate(&mut errors);
+                elements.push(element);
+            }
+            None => errors.push(format!(
+                "({}) isn't a GS1 application identifier hoofprint knows",
+                ai
+            )),
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(elements)
+    } else {
+        Err(errors)
+    }
+}
+
+/// Join elements into the string a symbol carries, with `separator` after variable-length data
+fn element_string(elements: &[Gs1Element], separator: char) -> String {
+    let mut result = String::new();
+    for (index, element) in elements.iter().enumerate() {
+        result.push_str(&element.ai);
+        result.push_str(&element.data);
+        if index + 1 < elements.len() && element.needs_separator() {
+            result.push(separator);
+        }
+    }
+    result
+}
+
+/// Check a GS1 value for a code type, including the symbology's size limit
+pub(crate) fn validate(code: &Code, value: &str) -> Result<(), Vec<String>> {
+    let elements = parse(value)?;
+    if code == &Code::Gs1Code128 {
+        let length = element_string(&elements, CODE128_FNC1).chars().count();
+        if length > GS1_128_MAX_LENGTH {
+            return Err(vec![format!(
+                "GS1-128 codes can hold {} characters of data, this value needs {}",
+                GS1_128_MAX_LENGTH, length
+            )]);
+        }
+    }
+    Ok(())
+}
+
+/// What to hand the encoder for a value, which is the value itself unless it's GS1.
+///
+/// GS1-128 starts with FNC1 and uses it as the separator. GS1 DataMatrix gets its leading FNC1
+/// from the encoder hint, and uses the group separator.
+pub(crate) fn encoder_contents(code: &Code, value: &str) -> Result<String, HoofprintError> {
+    match code {
+        Code::Gs1Code128 => {
+            let elements = parse(value).map_err(HoofprintError::ValidationError)?;
+            Ok(format!(
+                "{}{}",
+                CODE128_FNC1,
+                element_string(&elements, CODE128_FNC1)
+            ))
+        }
+        Code::Gs1DataMatrix => {
+            let elements = parse(value).map_err(HoofprintError::ValidationError)?;
+            Ok(element_string(&elements, GROUP_SEPARATOR))
+        }
+        _ => Ok(value.to_string()),
+    }
+}
+
+/// Turn a scanned element string into the bracketed form, validating it on the way
+pub(crate) fn from_element_string(scanned: &str) -> Result<String, Vec<String>> {
+    // the Code 128 reader leaves the symbology identifier on the front
+    let mut rest = scanned
+        .strip_prefix("]C1")
+        .or_else(|| scanned.strip_prefix("]d2"))
+        .unwrap_or(scanned)
+        .trim_start_matches(GROUP_SEPARATOR);
+
+    let mut value = String::new();
+    while !rest.is_empty() {
+        let Some(definition) = find_definition(rest) else {
+            return Err(vec![format!(
+                "Couldn't find a GS1 application identifier at the start of '{}'",
+                rest
+            )]);
+        };
+        let (ai, data) = rest.split_at(definition.ai_length);
+        let (data, remainder) =
+            if PREDEFINED_LENGTH_PREFIXES.contains(&ai.get(..2).unwrap_or_default()) {
+                data.split_at(data.len().min(definition.max_length))
+            } else {
+                data.split_once(GROUP_SEPARATOR).unwrap_or((data, ""))
+            };
+        value.push_str(&format!("({ai}){data}"));
+        rest = remainder.trim_start_matches(GROUP_SEPARATOR);
+    }
+
+    parse(&value)?;
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let elements = parse("(01)09501101530003(17)260100(10)ABC123(3103)001250")
+            .expect("Failed to parse GS1 value");
+        let fields: Vec<(&str, &str, String)> = elements
+            .iter()
+            .map(|element| {
+                (
+                    element.ai.as_str(),
+                    element.title(),
+                    element.display_value(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            fields,
+            vec![
+                ("01", "GTIN", "09501101530003".to_string()),
+                ("17", "USE BY", "2026-01 (end of month)".to_string()),
+                ("10", "BATCH/LOT", "ABC123".to_string()),
+                ("3103", "NET WEIGHT (kg)", "1.250".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(parse("0109501101530003").is_err());
+        // wrong check digit
+        assert!(parse("(01)09501101530004").is_err());
+        // month 13
+        assert!(parse("(01)09501101530003(17)261301").is_err());
+        assert!(parse("(10)ABC~123").is_err());
+        assert!(parse("(10)").is_err());
+        assert!(parse("(12345)ABC").is_err());
+        assert!(parse("(01)09501101530003(10ABC").is_err());
+    }
+
+    #[test]
+    fn test_element_string() {
+        let elements =
+            parse("(10)ABC(01)09501101530003(21)XYZ").expect("Failed to parse GS1 value");
+        // no separator after the fixed-length GTIN, or after the last field
+        assert_eq!(
+            element_string(&elements, GROUP_SEPARATOR),
+            "10ABC\u{1d}010950110153000321XYZ"
+        );
+        assert_eq!(
+            encoder_contents(&Code::Gs1Code128, "(10)ABC(01)09501101530003")
+                .expect("Failed to get encoder contents"),
+            "\u{f1}10ABC\u{f1}0109501101530003"
+        );
+        assert_eq!(
+            encoder_contents(&Code::Code128, "(10)ABC").expect("Failed to get encoder contents"),
+            "(10)ABC"
+        );
+    }
+
+    #[test]
+    fn test_from_element_string() {
+        assert_eq!(
+            from_element_string("]C10109501101530003\u{1d}10ABC\u{1d}17260101"),
+            Ok("(01)09501101530003(10)ABC(17)260101".to_string())
+        );
+        assert_eq!(
+            from_element_string("010950110153000310ABC"),
+            Ok("(01)09501101530003(10)ABC".to_string())
+        );
+        assert!(from_element_string("HELLO").is_err());
+    }
+
+    #[test]
+    fn test_gs1_128_length() {
+        assert!(validate(&Code::Gs1Code128, "(01)09501101530003(10)ABC123").is_ok());
+        let long = format!(
+            "(01)09501101530003(10){}(21){}",
+            "A".repeat(20),
+            "B".repeat(20)
+        );
+        assert!(validate(&Code::Gs1Code128, &long).is_err());
+        assert!(validate(&Code::Gs1DataMatrix, &long).is_ok());
+    }
+}
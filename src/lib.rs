@@ -21,27 +21,66 @@ use crate::{db::entities, error::HoofprintError};
 pub mod cli;
 pub mod config;
 pub(crate) mod constants;
+pub mod crypto;
 pub mod db;
+pub mod decode;
 pub mod error;
+pub mod gs1;
 pub mod logging;
+pub mod notes;
 pub(crate) mod password;
+pub mod payload;
 pub mod prelude;
+pub mod print;
+pub mod render;
+pub mod search;
 #[cfg(test)]
 pub mod tests;
+pub mod validation;
+pub mod wallet;
 pub mod web;
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename = "lowercase")]
 pub enum Code {
-    Bar,
+    /// Code 128, the general-purpose 1D default
+    Code128,
     QR,
+    Ean13,
+    Ean8,
+    UpcA,
+    UpcE,
+    /// Code 39, common on library cards
+    Code39,
+    /// ITF-14, interleaved 2 of 5 on outer cartons
+    Itf14,
+    Codabar,
+    Pdf417,
+    Aztec,
+    DataMatrix,
+    /// Code 128 carrying GS1 Application Identifiers
+    Gs1Code128,
+    /// Data Matrix carrying GS1 Application Identifiers
+    Gs1DataMatrix,
 }
 
 impl std::fmt::Display for Code {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Code::Bar => write!(f, "barcode"),
+            Code::Code128 => write!(f, "code128"),
             Code::QR => write!(f, "qrcode"),
+            Code::Ean13 => write!(f, "ean13"),
+            Code::Ean8 => write!(f, "ean8"),
+            Code::UpcA => write!(f, "upca"),
+            Code::UpcE => write!(f, "upce"),
+            Code::Code39 => write!(f, "code39"),
+            Code::Itf14 => write!(f, "itf14"),
+            Code::Codabar => write!(f, "codabar"),
+            Code::Pdf417 => write!(f, "pdf417"),
+            Code::Aztec => write!(f, "aztec"),
+            Code::DataMatrix => write!(f, "datamatrix"),
+            Code::Gs1Code128 => write!(f, "gs1-128"),
+            Code::Gs1DataMatrix => write!(f, "gs1datamatrix"),
         }
     }
 }
@@ -49,22 +88,55 @@ impl std::fmt::Display for Code {
 impl HtmlSafe for Code {}
 
 impl Code {
-    pub fn as_html(&self, value: &str) -> String {
+    /// Every supported code type, in the order they're offered in forms
+    pub fn all() -> Vec<Code> {
+        vec![
+            Code::Code128,
+            Code::QR,
+            Code::Ean13,
+            Code::Ean8,
+            Code::UpcA,
+            Code::UpcE,
+            Code::Code39,
+            Code::Itf14,
+            Code::Codabar,
+            Code::Pdf417,
+            Code::Aztec,
+            Code::DataMatrix,
+            Code::Gs1Code128,
+            Code::Gs1DataMatrix,
+        ]
+    }
+
+    /// Human-friendly name for display in forms
+    pub fn label(&self) -> &'static str {
         match self {
-            Code::Bar => {
-                format!(
-                    r#"<svg class="code_image" id="barcode" data-value="{}"></svg>"#,
-                    value
-                )
-            }
-            Code::QR => {
-                format!(
-                    r#"<div class="code_image" id="qrcode" data-value="{}"></div>"#,
-                    value
-                )
-            }
+            Code::Code128 => "Code 128",
+            Code::QR => "QR Code",
+            Code::Ean13 => "EAN-13",
+            Code::Ean8 => "EAN-8",
+            Code::UpcA => "UPC-A",
+            Code::UpcE => "UPC-E",
+            Code::Code39 => "Code 39",
+            Code::Itf14 => "ITF-14",
+            Code::Codabar => "Codabar",
+            Code::Pdf417 => "PDF417",
+            Code::Aztec => "Aztec",
+            Code::DataMatrix => "Data Matrix",
+            Code::Gs1Code128 => "GS1-128",
+            Code::Gs1DataMatrix => "GS1 DataMatrix",
         }
     }
+
+    /// Render the code server-side as SVG markup
+    pub fn as_html(
+        &self,
+        value: &str,
+        options: &render::RenderOptions,
+    ) -> Result<String, HoofprintError> {
+        let image = render::CodeImage::encode(self, value, options)?;
+        Ok(image.to_svg(options.module_size.unwrap_or(self.default_module_size())))
+    }
 }
 
 impl TryFrom<&str> for Code {
@@ -72,8 +144,20 @@ impl TryFrom<&str> for Code {
 
     fn try_from(value: &str) -> Result<Self, Self::Error> {
         match value {
-            "barcode" => Ok(Code::Bar),
+            "code128" => Ok(Code::Code128),
             "qrcode" => Ok(Code::QR),
+            "ean13" => Ok(Code::Ean13),
+            "ean8" => Ok(Code::Ean8),
+            "upca" => Ok(Code::UpcA),
+            "upce" => Ok(Code::UpcE),
+            "code39" => Ok(Code::Code39),
+            "itf14" => Ok(Code::Itf14),
+            "codabar" => Ok(Code::Codabar),
+            "pdf417" => Ok(Code::Pdf417),
+            "aztec" => Ok(Code::Aztec),
+            "datamatrix" => Ok(Code::DataMatrix),
+            "gs1-128" => Ok(Code::Gs1Code128),
+            "gs1datamatrix" => Ok(Code::Gs1DataMatrix),
             _ => Err(HoofprintError::InvalidCodeType(value.to_string())),
         }
     }
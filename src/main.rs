@@ -49,6 +49,9 @@ async fn main() -> Result<ExitCode, ExitCode> {
                 hoofprint::cli::handle_user_search(db.clone(), query).await
             }
             Command::ListAllUsers => hoofprint::cli::handle_list_users(db.clone()).await,
+            Command::ExpiringCodes { days } => {
+                hoofprint::cli::handle_expiring_codes(db.clone(), days).await
+            }
         };
     }
 
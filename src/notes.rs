@@ -0,0 +1,46 @@
+//! Notes on codes, written in Markdown, for the things on a card that aren't in its barcode
+
+use pulldown_cmark::{Options, Parser, html};
+
+/// The longest notes can be, in characters
+pub const MAX_NOTES_LENGTH: usize = 10_000;
+
+/// Render notes as HTML that's safe to put straight into a page.
+///
+/// Raw HTML is cleaned out, links can't reach back through `window.opener`, and images are
+/// dropped since notes shouldn't load anything from elsewhere.
+pub fn render_markdown(markdown: &str) -> String {
+    let parser = Parser::new_ext(
+        markdown,
+        Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TABLES,
+    );
+    let mut unsafe_html = String::new();
+    html::push_html(&mut unsafe_html, parser);
+    ammonia::Builder::default()
+        .rm_tags(["img"])
+        .clean(&unsafe_html)
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_markdown() {
+        assert_eq!(
+            render_markdown("PIN is **1234**"),
+            "<p>PIN is <strong>1234</strong></p>\n"
+        );
+        let html = render_markdown(
+            "<script>alert(1)</script>\n\n[help](javascript:alert(1)) [site](https://example.com) ![x](https://example.com/x.png) <img src=x onerror=alert(1)>",
+        );
+        assert!(!html.contains("<script"));
+        assert!(!html.contains("javascript:"));
+        assert!(!html.contains("<img"));
+        assert!(!html.contains("onerror"));
+        assert!(
+            html.contains(r#"<a href="https://example.com" rel="noopener noreferrer">site</a>"#)
+        );
+    }
+}
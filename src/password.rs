@@ -34,6 +34,21 @@ pub(crate) fn verify_password(input_password: &str, db_hashed: &str) -> Result<(
         })
 }
 
+/// Derive a key from a password, for wrapping the user's data key
+pub(crate) fn derive_key(
+    password: &str,
+    salt: &[u8],
+) -> Result<[u8; crate::crypto::KEY_LENGTH], HoofprintError> {
+    let mut key = [0u8; crate::crypto::KEY_LENGTH];
+    Argon2::default()
+        .hash_password_into(password.as_bytes(), salt, &mut key)
+        .map_err(|err| {
+            error!("Failed to derive key from password: {err}");
+            HoofprintError::from(err)
+        })?;
+    Ok(key)
+}
+
 #[test]
 fn test_password_hashing() {
     let password = crate::get_random_password(16);
@@ -0,0 +1,970 @@
+//! Structured QR code payloads, like Wi-Fi logins and contact cards.
+//!
+//! A code's value is always the serialised payload, so payloads are parsed back out of it for
+//! display and editing rather than being stored separately.
+
+use std::fmt::Display;
+
+use time::{Date, Month, PrimitiveDateTime, Time};
+
+/// Characters that need a backslash in the `KEY:value;` formats used by Wi-Fi and MeCard
+const FIELD_SPECIALS: &str = "\\;,:\"";
+
+/// vCard and iCalendar put each property on its own line
+const LINE_ENDING: &str = "\r\n";
+
+/// The kinds of structured payload, in the order they're offered in forms
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PayloadKind {
+    Wifi,
+    Contact,
+    Url,
+    Geo,
+    Sms,
+    Event,
+}
+
+impl PayloadKind {
+    pub fn all() -> Vec<PayloadKind> {
+        vec![
+            PayloadKind::Wifi,
+            PayloadKind::Contact,
+            PayloadKind::Url,
+            PayloadKind::Geo,
+            PayloadKind::Sms,
+            PayloadKind::Event,
+        ]
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            PayloadKind::Wifi => "Wi-Fi network",
+            PayloadKind::Contact => "Contact card",
+            PayloadKind::Url => "Web link",
+            PayloadKind::Geo => "Location",
+            PayloadKind::Sms => "Text message",
+            PayloadKind::Event => "Calendar event",
+        }
+    }
+}
+
+impl Display for PayloadKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let kind = match self {
+            PayloadKind::Wifi => "wifi",
+            PayloadKind::Contact => "contact",
+            PayloadKind::Url => "url",
+            PayloadKind::Geo => "geo",
+            PayloadKind::Sms => "sms",
+            PayloadKind::Event => "event",
+        };
+        write!(f, "{}", kind)
+    }
+}
+
+/// How a Wi-Fi network is secured
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum WifiSecurity {
+    /// WPA, WPA2 or WPA3, which all use the same payload
+    #[default]
+    Wpa,
+    Wep,
+    None,
+}
+
+impl WifiSecurity {
+    pub fn all() -> Vec<WifiSecurity> {
+        vec![WifiSecurity::Wpa, WifiSecurity::Wep, WifiSecurity::None]
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            WifiSecurity::Wpa => "WPA/WPA2/WPA3",
+            WifiSecurity::Wep => "WEP",
+            WifiSecurity::None => "None",
+        }
+    }
+}
+
+impl Display for WifiSecurity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let security = match self {
+            WifiSecurity::Wpa => "WPA",
+            WifiSecurity::Wep => "WEP",
+            WifiSecurity::None => "nopass",
+        };
+        write!(f, "{}", security)
+    }
+}
+
+/// Which format a contact card is written in
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ContactFormat {
+    #[default]
+    VCard,
+    /// Much shorter than a vCard, so makes a less dense code
+    MeCard,
+}
+
+impl ContactFormat {
+    pub fn all() -> Vec<ContactFormat> {
+        vec![ContactFormat::VCard, ContactFormat::MeCard]
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ContactFormat::VCard => "vCard",
+            ContactFormat::MeCard => "MeCard (smaller code)",
+        }
+    }
+}
+
+impl Display for ContactFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let format = match self {
+            ContactFormat::VCard => "vcard",
+            ContactFormat::MeCard => "mecard",
+        };
+        write!(f, "{}", format)
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Wifi {
+    pub ssid: String,
+    pub password: String,
+    pub security: WifiSecurity,
+    pub hidden: bool,
+}
+
+/// A contact card, where empty fields are left out
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Contact {
+    pub format: ContactFormat,
+    pub first_name: String,
+    pub last_name: String,
+    pub organisation: String,
+    pub phone: String,
+    pub email: String,
+    pub url: String,
+    pub address: String,
+    pub note: String,
+}
+
+impl Contact {
+    /// The name to show for the contact
+    fn full_name(&self) -> String {
+        let name = format!("{} {}", self.first_name, self.last_name);
+        let name = name.trim();
+        if name.is_empty() {
+            self.organisation.clone()
+        } else {
+            name.to_string()
+        }
+    }
+}
+
+/// A calendar event, with times in the scanning device's local time zone
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Event {
+    pub summary: String,
+    pub start: PrimitiveDateTime,
+    pub end: Option<PrimitiveDateTime>,
+    pub location: String,
+    pub description: String,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Payload {
+    Wifi(Wifi),
+    Contact(Contact),
+    Url(String),
+    Geo { latitude: f64, longitude: f64 },
+    Sms { number: String, message: String },
+    Event(Event),
+}
+
+/// Backslash-escape the characters the `KEY:value;` formats reserve
+fn escape_field(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if FIELD_SPECIALS.contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Escape a vCard or iCalendar text value, which can't contain raw newlines
+fn escape_text(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' | ';' | ',' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Remove backslash escapes, turning `\n` into a newline for vCard and iCalendar text
+fn unescape(text: &str, newlines: bool) -> String {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n' | 'N') if newlines => unescaped.push('\n'),
+            Some(escaped) => unescaped.push(escaped),
+            None => unescaped.push('\\'),
+        }
+    }
+    unescaped
+}
+
+/// Split on a separator that isn't backslash-escaped, leaving escapes in place
+fn split_unescaped(text: &str, separator: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut start = 0;
+    let mut escaped = false;
+    for (index, c) in text.char_indices() {
+        if escaped {
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+        } else if c == separator {
+            parts.push(&text[start..index]);
+            start = index + c.len_utf8();
+        }
+    }
+    parts.push(&text[start..]);
+    parts
+}
+
+/// The `KEY:value` fields of a Wi-Fi or MeCard payload, with escapes left in the values
+fn parse_fields(text: &str) -> Vec<(String, &str)> {
+    split_unescaped(text, ';')
+        .into_iter()
+        .filter_map(|field| field.split_once(':'))
+        .map(|(key, value)| (key.to_ascii_uppercase(), value))
+        .collect()
+}
+
+/// The properties of a vCard or iCalendar object, as (name, raw value) pairs
+fn parse_properties(text: &str) -> Vec<(String, String)> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in text.split('\n') {
+        let line = line.trim_end_matches('\r');
+        // long lines are folded onto lines starting with whitespace
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(continuation), Some(last)) => last.push_str(continuation),
+            _ => lines.push(line.to_string()),
+        }
+    }
+    lines
+        .into_iter()
+        .filter_map(|line| {
+            let (name, value) = line.split_once(':')?;
+            // drop parameters like TEL;TYPE=cell
+            let name = name
+                .split(';')
+                .next()
+                .unwrap_or_default()
+                .to_ascii_uppercase();
+            Some((name, value.to_string()))
+        })
+        .collect()
+}
+
+/// A number from a fixed position in a date or time, which must be all digits
+fn digits(text: &str, start: usize, length: usize) -> Option<u32> {
+    let digits = text.get(start..start + length)?;
+    if digits.chars().all(|c| c.is_ascii_digit()) {
+        digits.parse().ok()
+    } else {
+        None
+    }
+}
+
+fn datetime(
+    year: u32,
+    month: u32,
+    day: u32,
+    hour: u32,
+    minute: u32,
+    second: u32,
+) -> Option<PrimitiveDateTime> {
+    let month = Month::try_from(u8::try_from(month).ok()?).ok()?;
+    let date =
+        Date::from_calendar_date(i32::try_from(year).ok()?, month, u8::try_from(day).ok()?).ok()?;
+    let time = Time::from_hms(
+        u8::try_from(hour).ok()?,
+        u8::try_from(minute).ok()?,
+        u8::try_from(second).ok()?,
+    )
+    .ok()?;
+    Some(PrimitiveDateTime::new(date, time))
+}
+
+/// Parse an iCalendar `YYYYMMDDTHHMMSS` date-time, or a `YYYYMMDD` date
+fn parse_ical_datetime(text: &str) -> Option<PrimitiveDateTime> {
+    let text = text.trim_end_matches('Z');
+    let (year, month, day) = (
+        digits(text, 0, 4)?,
+        digits(text, 4, 2)?,
+        digits(text, 6, 2)?,
+    );
+    match text.len() {
+        8 => datetime(year, month, day, 0, 0, 0),
+        15 if text.get(8..9) == Some("T") => datetime(
+            year,
+            month,
+            day,
+            digits(text, 9, 2)?,
+            digits(text, 11, 2)?,
+            digits(text, 13, 2)?,
+        ),
+        _ => None,
+    }
+}
+
+fn ical_datetime(value: PrimitiveDateTime) -> String {
+    format!(
+        "{:04}{:02}{:02}T{:02}{:02}{:02}",
+        value.year(),
+        u8::from(value.month()),
+        value.day(),
+        value.hour(),
+        value.minute(),
+        value.second()
+    )
+}
+
+/// Parse the `YYYY-MM-DDTHH:MM` value of a datetime-local input, which may have seconds
+pub fn parse_form_datetime(text: &str) -> Option<PrimitiveDateTime> {
+    let separators = [(4, "-"), (7, "-"), (10, "T"), (13, ":")];
+    if separators
+        .iter()
+        .any(|(index, separator)| text.get(*index..index + 1) != Some(*separator))
+    {
+        return None;
+    }
+    let second = match text.len() {
+        16 => 0,
+        19 if text.get(16..17) == Some(":") => digits(text, 17, 2)?,
+        _ => return None,
+    };
+    datetime(
+        digits(text, 0, 4)?,
+        digits(text, 5, 2)?,
+        digits(text, 8, 2)?,
+        digits(text, 11, 2)?,
+        digits(text, 14, 2)?,
+        second,
+    )
+}
+
+/// Format a date-time for a datetime-local input
+pub fn form_datetime(value: PrimitiveDateTime) -> String {
+    let mut formatted = format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}",
+        value.year(),
+        u8::from(value.month()),
+        value.day(),
+        value.hour(),
+        value.minute()
+    );
+    if value.second() != 0 {
+        formatted.push_str(&format!(":{:02}", value.second()));
+    }
+    formatted
+}
+
+/// Format a date-time for people to read
+fn display_datetime(value: PrimitiveDateTime) -> String {
+    form_datetime(value).replacen('T', " ", 1)
+}
+
+/// Whether text is a plausible phone number
+fn is_phone_number(text: &str) -> bool {
+    text.chars().any(|c| c.is_ascii_digit())
+        && text
+            .chars()
+            .all(|c| c.is_ascii_digit() || matches!(c, '+' | '-' | ' ' | '(' | ')'))
+}
+
+impl Payload {
+    pub fn kind(&self) -> PayloadKind {
+        match self {
+            Payload::Wifi(_) => PayloadKind::Wifi,
+            Payload::Contact(_) => PayloadKind::Contact,
+            Payload::Url(_) => PayloadKind::Url,
+            Payload::Geo { .. } => PayloadKind::Geo,
+            Payload::Sms { .. } => PayloadKind::Sms,
+            Payload::Event(_) => PayloadKind::Event,
+        }
+    }
+
+    /// Check the payload makes sense, returning every problem found
+    pub fn validate(&self) -> Result<(), Vec<String>> {
+        let mut errors = Vec::new();
+        match self {
+            Payload::Wifi(wifi) => {
+                if wifi.ssid.is_empty() {
+                    errors.push("Wi-Fi network name cannot be empty".to_string());
+                } else if wifi.ssid.len() > 32 {
+                    errors.push("Wi-Fi network names can be at most 32 bytes long".to_string());
+                }
+                let length = wifi.password.chars().count();
+                match wifi.security {
+                    WifiSecurity::Wpa if !(8..=63).contains(&length) => {
+                        errors.push("WPA passwords must be 8 to 63 characters long".to_string())
+                    }
+                    WifiSecurity::Wep if ![5, 10, 13, 26].contains(&length) => errors.push(
+                        "WEP passwords must be 5 or 13 characters, or 10 or 26 hex digits"
+                            .to_string(),
+                    ),
+                    _ => {}
+                }
+            }
+            Payload::Contact(contact) => {
+                if contact.full_name().is_empty() {
+                    errors.push("Contacts need a name or an organisation".to_string());
+                }
+                if !contact.phone.is_empty() && !is_phone_number(&contact.phone) {
+                    errors.push(format!("{} isn't a phone number", contact.phone));
+                }
+                if !contact.email.is_empty() && !contact.email.contains('@') {
+                    errors.push(format!("{} isn't an email address", contact.email));
+                }
+            }
+            Payload::Url(url) => match url::Url::parse(url) {
+                Ok(parsed) if matches!(parsed.scheme(), "http" | "https") => {}
+                _ => errors.push("Links must be a full http:// or https:// address".to_string()),
+            },
+            Payload::Geo {
+                latitude,
+                longitude,
+            } => {
+                if !(-90.0..=90.0).contains(latitude) {
+                    errors.push("Latitude must be between -90 and 90".to_string());
+                }
+                if !(-180.0..=180.0).contains(longitude) {
+                    errors.push("Longitude must be between -180 and 180".to_string());
+                }
+            }
+            Payload::Sms { number, .. } => {
+                if !is_phone_number(number) {
+                    errors.push(
+                        "Phone numbers can only contain digits, spaces, +, - and brackets"
+                            .to_string(),
+                    );
+                }
+            }
+            Payload::Event(event) => {
+                if event.summary.is_empty() {
+                    errors.push("Events need a title".to_string());
+                }
+                if event.end.is_some_and(|end| end < event.start) {
+                    errors.push("Events can't end before they start".to_string());
+                }
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Read a payload back out of a code's value, if it's one we can build
+    pub fn parse(value: &str) -> Option<Payload> {
+        let upper = value.get(..11).unwrap_or(value).to_ascii_uppercase();
+        if let Some(fields) = value.strip_prefix("WIFI:") {
+            Self::parse_wifi(fields)
+        } else if let Some(fields) = value.strip_prefix("MECARD:") {
+            Self::parse_mecard(fields)
+        } else if upper.starts_with("BEGIN:VCARD") {
+            Self::parse_vcard(value)
+        } else if value.contains("BEGIN:VEVENT") {
+            Self::parse_event(value)
+        } else if upper.starts_with("GEO:") {
+            let coordinates = value.get(4..)?.split('?').next()?;
+            let mut parts = coordinates.split(',');
+            let latitude = parts.next()?.trim().parse().ok()?;
+            let longitude = parts.next()?.trim().parse().ok()?;
+            Some(Payload::Geo {
+                latitude,
+                longitude,
+            })
+        } else if upper.starts_with("SMSTO:") {
+            let (number, message) = value
+                .get(6..)?
+                .split_once(':')
+                .unwrap_or((value.get(6..)?, ""));
+            Some(Payload::Sms {
+                number: number.to_string(),
+                message: message.to_string(),
+            })
+        } else if (upper.starts_with("HTTP://") || upper.starts_with("HTTPS://"))
+            && !value.contains(char::is_whitespace)
+        {
+            Some(Payload::Url(value.to_string()))
+        } else {
+            None
+        }
+        .filter(|payload| payload.validate().is_ok())
+    }
+
+    fn parse_wifi(fields: &str) -> Option<Payload> {
+        let mut wifi = Wifi {
+            security: WifiSecurity::None,
+            ..Default::default()
+        };
+        for (key, value) in parse_fields(fields) {
+            match key.as_str() {
+                "S" => wifi.ssid = unescape(value, false),
+                "P" => wifi.password = unescape(value, false),
+                "T" => {
+                    wifi.security = match value.to_ascii_uppercase().as_str() {
+                        "WEP" => WifiSecurity::Wep,
+                        "" | "NOPASS" => WifiSecurity::None,
+                        _ => WifiSecurity::Wpa,
+                    }
+                }
+                "H" => wifi.hidden = value.eq_ignore_ascii_case("true"),
+                _ => {}
+            }
+        }
+        Some(Payload::Wifi(wifi))
+    }
+
+    fn parse_mecard(fields: &str) -> Option<Payload> {
+        let mut contact = Contact {
+            format: ContactFormat::MeCard,
+            ..Default::default()
+        };
+        for (key, value) in parse_fields(fields) {
+            match key.as_str() {
+                "N" => {
+                    let mut names = split_unescaped(value, ',').into_iter();
+                    contact.last_name = unescape(names.next().unwrap_or_default(), false);
+                    contact.first_name = unescape(names.next().unwrap_or_default(), false);
+                }
+                "ORG" => contact.organisation = unescape(value, false),
+                "TEL" => contact.phone = unescape(value, false),
+                "EMAIL" => contact.email = unescape(value, false),
+                "URL" => contact.url = unescape(value, false),
+                "ADR" => contact.address = unescape(value, false),
+                "NOTE" => contact.note = unescape(value, false),
+                _ => {}
+            }
+        }
+        Some(Payload::Contact(contact))
+    }
+
+    fn parse_vcard(value: &str) -> Option<Payload> {
+        let mut contact = Contact::default();
+        let mut full_name = String::new();
+        for (name, value) in parse_properties(value) {
+            match name.as_str() {
+                "N" => {
+                    let mut names = split_unescaped(&value, ';').into_iter();
+                    contact.last_name = unescape(names.next().unwrap_or_default(), true);
+                    contact.first_name = unescape(names.next().unwrap_or_default(), true);
+                }
+                "FN" => full_name = unescape(&value, true),
+                "ORG" => contact.organisation = unescape(&value, true),
+                "TEL" => contact.phone = unescape(&value, true),
+                "EMAIL" => contact.email = unescape(&value, true),
+                "URL" => contact.url = unescape(&value, true),
+                "ADR" => {
+                    let parts: Vec<String> = split_unescaped(&value, ';')
+                        .into_iter()
+                        .filter(|part| !part.is_empty())
+                        .map(|part| unescape(part, true))
+                        .collect();
+                    contact.address = parts.join(", ");
+                }
+                "NOTE" => contact.note = unescape(&value, true),
+                _ => {}
+            }
+        }
+        if contact.full_name().is_empty() {
+            contact.first_name = full_name;
+        }
+        Some(Payload::Contact(contact))
+    }
+
+    fn parse_event(value: &str) -> Option<Payload> {
+        let mut summary = String::new();
+        let mut start = None;
+        let mut end = None;
+        let mut location = String::new();
+        let mut description = String::new();
+        for (name, value) in parse_properties(value) {
+            match name.as_str() {
+                "SUMMARY" => summary = unescape(&value, true),
+                "DTSTART" => start = parse_ical_datetime(&value),
+                "DTEND" => end = parse_ical_datetime(&value),
+                "LOCATION" => location = unescape(&value, true),
+                "DESCRIPTION" => description = unescape(&value, true),
+                _ => {}
+            }
+        }
+        Some(Payload::Event(Event {
+            summary,
+            start: start?,
+            end,
+            location,
+            description,
+        }))
+    }
+
+    /// The payload's fields as (label, value) pairs for display, leaving out empty ones
+    pub fn fields(&self) -> Vec<(&'static str, String)> {
+        let fields = match self {
+            Payload::Wifi(wifi) => vec![
+                ("Network", wifi.ssid.clone()),
+                ("Security", wifi.security.label().to_string()),
+                ("Password", wifi.password.clone()),
+                (
+                    "Hidden network",
+                    if wifi.hidden { "Yes" } else { "" }.to_string(),
+                ),
+            ],
+            Payload::Contact(contact) => vec![
+                (
+                    "Name",
+                    format!("{} {}", contact.first_name, contact.last_name)
+                        .trim()
+                        .to_string(),
+                ),
+                ("Organisation", contact.organisation.clone()),
+                ("Phone", contact.phone.clone()),
+                ("Email", contact.email.clone()),
+                ("Website", contact.url.clone()),
+                ("Address", contact.address.clone()),
+                ("Note", contact.note.clone()),
+            ],
+            Payload::Url(url) => vec![("Link", url.clone())],
+            Payload::Geo {
+                latitude,
+                longitude,
+            } => vec![
+                ("Latitude", latitude.to_string()),
+                ("Longitude", longitude.to_string()),
+            ],
+            Payload::Sms { number, message } => {
+                vec![("Number", number.clone()), ("Message", message.clone())]
+            }
+            Payload::Event(event) => vec![
+                ("Title", event.summary.clone()),
+                ("Starts", display_datetime(event.start)),
+                ("Ends", event.end.map(display_datetime).unwrap_or_default()),
+                ("Location", event.location.clone()),
+                ("Description", event.description.clone()),
+            ],
+        };
+        fields
+            .into_iter()
+            .filter(|(_, value)| !value.is_empty())
+            .collect()
+    }
+}
+
+impl Display for Payload {
+    /// Serialise the payload into the text the QR code carries
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Payload::Wifi(wifi) => {
+                write!(
+                    f,
+                    "WIFI:T:{};S:{};",
+                    wifi.security,
+                    escape_field(&wifi.ssid)
+                )?;
+                if wifi.security != WifiSecurity::None {
+                    write!(f, "P:{};", escape_field(&wifi.password))?;
+                }
+                if wifi.hidden {
+                    write!(f, "H:true;")?;
+                }
+                write!(f, ";")
+            }
+            Payload::Contact(contact) if contact.format == ContactFormat::MeCard => {
+                write!(f, "MECARD:")?;
+                if !contact.first_name.is_empty() || !contact.last_name.is_empty() {
+                    write!(f, "N:{}", escape_field(&contact.last_name))?;
+                    if !contact.first_name.is_empty() {
+                        write!(f, ",{}", escape_field(&contact.first_name))?;
+                    }
+                    write!(f, ";")?;
+                }
+                for (key, value) in [
+                    ("ORG", &contact.organisation),
+                    ("TEL", &contact.phone),
+                    ("EMAIL", &contact.email),
+                    ("URL", &contact.url),
+                    ("ADR", &contact.address),
+                    ("NOTE", &contact.note),
+                ] {
+                    if !value.is_empty() {
+                        write!(f, "{}:{};", key, escape_field(value))?;
+                    }
+                }
+                write!(f, ";")
+            }
+            Payload::Contact(contact) => {
+                let mut lines = vec![
+                    "BEGIN:VCARD".to_string(),
+                    "VERSION:3.0".to_string(),
+                    format!(
+                        "N:{};{};;;",
+                        escape_text(&contact.last_name),
+                        escape_text(&contact.first_name)
+                    ),
+                    format!("FN:{}", escape_text(&contact.full_name())),
+                ];
+                for (name, value) in [
+                    ("ORG", &contact.organisation),
+                    ("TEL", &contact.phone),
+                    ("EMAIL", &contact.email),
+                    ("URL", &contact.url),
+                ] {
+                    if !value.is_empty() {
+                        lines.push(format!("{}:{}", name, escape_text(value)));
+                    }
+                }
+                if !contact.address.is_empty() {
+                    // the whole address goes in the street component
+                    lines.push(format!("ADR:;;{};;;;", escape_text(&contact.address)));
+                }
+                if !contact.note.is_empty() {
+                    lines.push(format!("NOTE:{}", escape_text(&contact.note)));
+                }
+                lines.push("END:VCARD".to_string());
+                write!(f, "{}", lines.join(LINE_ENDING))
+            }
+            Payload::Url(url) => write!(f, "{}", url),
+            Payload::Geo {
+                latitude,
+                longitude,
+            } => write!(f, "geo:{},{}", latitude, longitude),
+            Payload::Sms { number, message } => write!(f, "SMSTO:{}:{}", number, message),
+            Payload::Event(event) => {
+                let mut lines = vec![
+                    "BEGIN:VEVENT".to_string(),
+                    format!("SUMMARY:{}", escape_text(&event.summary)),
+                    format!("DTSTART:{}", ical_datetime(event.start)),
+                ];
+                if let Some(end) = event.end {
+                    lines.push(format!("DTEND:{}", ical_datetime(end)));
+                }
+                if !event.location.is_empty() {
+                    lines.push(format!("LOCATION:{}", escape_text(&event.location)));
+                }
+                if !event.description.is_empty() {
+                    lines.push(format!("DESCRIPTION:{}", escape_text(&event.description)));
+                }
+                lines.push("END:VEVENT".to_string());
+                write!(f, "{}", lines.join(LINE_ENDING))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use time::macros::datetime;
+
+    use super::*;
+
+    fn samples() -> Vec<Payload> {
+        vec![
+            Payload::Wifi(Wifi {
+                ssid: "Office; 5GHz".to_string(),
+                password: r#"pa:ss,wo"rd\"#.to_string(),
+                security: WifiSecurity::Wpa,
+                hidden: true,
+            }),
+            Payload::Wifi(Wifi {
+                ssid: "Guest".to_string(),
+                password: String::new(),
+                security: WifiSecurity::None,
+                hidden: false,
+            }),
+            Payload::Contact(Contact {
+                format: ContactFormat::VCard,
+                first_name: "Ada".to_string(),
+                last_name: "Lovelace".to_string(),
+                organisation: "Analytical Engines, Ltd; London".to_string(),
+                phone: "+44 20 7946 0000".to_string(),
+                email: "ada@example.com".to_string(),
+                url: "https://example.com".to_string(),
+                address: "12 St James's Square, London".to_string(),
+                note: "First line\nSecond line".to_string(),
+            }),
+            Payload::Contact(Contact {
+                format: ContactFormat::MeCard,
+                first_name: "Ada".to_string(),
+                last_name: "Lovelace".to_string(),
+                phone: "+44 20 7946 0000".to_string(),
+                address: "12 St James's Square, London".to_string(),
+                ..Default::default()
+            }),
+            Payload::Url("https://example.com/wifi?room=2".to_string()),
+            Payload::Geo {
+                latitude: -33.8568,
+                longitude: 151.2153,
+            },
+            Payload::Sms {
+                number: "+61 400 000 000".to_string(),
+                message: "Running late: 10 minutes".to_string(),
+            },
+            Payload::Event(Event {
+                summary: "Team lunch, level 3".to_string(),
+                start: datetime!(2026-11-02 12:30),
+                end: Some(datetime!(2026-11-02 13:30)),
+                location: "Kitchen".to_string(),
+                description: String::new(),
+            }),
+        ]
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        for payload in samples() {
+            assert!(payload.validate().is_ok(), "{:?} should be valid", payload);
+            let value = payload.to_string();
+            assert_eq!(Payload::parse(&value), Some(payload), "{}", value);
+        }
+    }
+
+    #[test]
+    fn test_serialise() {
+        let payloads = samples();
+        assert_eq!(
+            payloads[0].to_string(),
+            r#"WIFI:T:WPA;S:Office\; 5GHz;P:pa\:ss\,wo\"rd\\;H:true;;"#
+        );
+        assert_eq!(payloads[1].to_string(), "WIFI:T:nopass;S:Guest;;");
+        assert!(
+            payloads[2]
+                .to_string()
+                .contains("\r\nORG:Analytical Engines\\, Ltd\\; London\r\n")
+        );
+        assert!(
+            payloads[2]
+                .to_string()
+                .contains("\r\nNOTE:First line\\nSecond line\r\n")
+        );
+        assert_eq!(
+            payloads[3].to_string(),
+            r#"MECARD:N:Lovelace,Ada;TEL:+44 20 7946 0000;ADR:12 St James's Square\, London;;"#
+        );
+        assert_eq!(payloads[5].to_string(), "geo:-33.8568,151.2153");
+        assert!(
+            payloads[7]
+                .to_string()
+                .contains("\r\nDTSTART:20261102T123000\r\n")
+        );
+    }
+
+    #[test]
+    fn test_parse_other_generators() {
+        // lowercase keys, a vCard 4 with parameters and folded lines, and a wrapped event
+        assert_eq!(
+            Payload::parse("WIFI:s:Cafe;t:WPA2;p:espresso!;;").map(|payload| payload.fields()),
+            Some(vec![
+                ("Network", "Cafe".to_string()),
+                ("Security", "WPA/WPA2/WPA3".to_string()),
+                ("Password", "espresso!".to_string()),
+            ])
+        );
+        let vcard = "BEGIN:VCARD\nVERSION:4.0\nFN:Grace Hopper\nTEL;TYPE=cell:+1 555 0100\nNOTE:A very long\n  note\nEND:VCARD";
+        let Some(Payload::Contact(contact)) = Payload::parse(vcard) else {
+            panic!("Failed to parse vCard");
+        };
+        assert_eq!(contact.first_name, "Grace Hopper");
+        assert_eq!(contact.phone, "+1 555 0100");
+        assert_eq!(contact.note, "A very long note");
+        let event = "BEGIN:VCALENDAR\r\nBEGIN:VEVENT\r\nSUMMARY:Launch\r\nDTSTART:20261225\r\nEND:VEVENT\r\nEND:VCALENDAR";
+        let Some(Payload::Event(event)) = Payload::parse(event) else {
+            panic!("Failed to parse event");
+        };
+        assert_eq!(event.start, datetime!(2026-12-25 0:00));
+
+        assert_eq!(Payload::parse("hello"), None);
+        assert_eq!(Payload::parse("geo:91,0"), None);
+        assert_eq!(Payload::parse("https://example.com/a b"), None);
+    }
+
+    #[test]
+    fn test_validate() {
+        let wifi = |password: &str, security| {
+            Payload::Wifi(Wifi {
+                ssid: "Office".to_string(),
+                password: password.to_string(),
+                security,
+                hidden: false,
+            })
+        };
+        assert!(wifi("short", WifiSecurity::Wpa).validate().is_err());
+        assert!(wifi("12345", WifiSecurity::Wep).validate().is_ok());
+        assert!(wifi("", WifiSecurity::None).validate().is_ok());
+        assert!(
+            Payload::Url("ftp://example.com".to_string())
+                .validate()
+                .is_err()
+        );
+        assert!(
+            Payload::Sms {
+                number: "call me".to_string(),
+                message: String::new(),
+            }
+            .validate()
+            .is_err()
+        );
+        assert!(
+            Payload::Event(Event {
+                summary: "Backwards".to_string(),
+                start: datetime!(2026-11-02 12:30),
+                end: Some(datetime!(2026-11-02 11:30)),
+                location: String::new(),
+                description: String::new(),
+            })
+            .validate()
+            .is_err()
+        );
+        assert!(Payload::Contact(Contact::default()).validate().is_err());
+    }
+
+    #[test]
+    fn test_form_datetime() {
+        assert_eq!(
+            parse_form_datetime("2026-11-02T12:30"),
+            Some(datetime!(2026-11-02 12:30))
+        );
+        assert_eq!(
+            parse_form_datetime("2026-11-02T12:30:15"),
+            Some(datetime!(2026-11-02 12:30:15))
+        );
+        assert_eq!(parse_form_datetime("2026-02-30T12:30"), None);
+        assert_eq!(parse_form_datetime("2026-11-02 12:30"), None);
+        assert_eq!(
+            form_datetime(datetime!(2026-11-02 12:30)),
+            "2026-11-02T12:30"
+        );
+    }
+}
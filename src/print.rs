@@ -0,0 +1,382 @@
+//! Printable PDF exports of codes
+
+use pdf_writer::{Content, Name, Pdf, Rect, Ref, Str};
+use serde::Deserialize;
+
+use crate::{
+    Code,
+    constants::GENERIC_SITE,
+    render::{CodeImage, RenderOptions},
+};
+
+/// The MIME type for PDF documents
+pub(crate) const MIME_TYPE_PDF: &str = "application/pdf";
+
+/// PDF points per millimetre
+const MM: f32 = 72.0 / 25.4;
+
+/// ISO/IEC 7810 ID-1, the size of a credit card
+const CARD_WIDTH: f32 = 85.6 * MM;
+const CARD_HEIGHT: f32 = 53.98 * MM;
+
+/// Space around the edge of the page, which most printers can't reach anyway
+const PAGE_MARGIN: f32 = 10.0 * MM;
+/// Space between cards, leaving room to cut them out
+const CARD_GAP: f32 = 4.0 * MM;
+/// Space inside each card's border
+const CARD_PADDING: f32 = 3.0 * MM;
+
+const TITLE_SIZE: f32 = 10.0;
+const SUBTITLE_SIZE: f32 = 8.0;
+const VALUE_SIZE: f32 = 8.0;
+
+/// The standard fonts every PDF viewer has, so nothing needs embedding
+const FONT_REGULAR: Name = Name(b"F1");
+const FONT_BOLD: Name = Name(b"F2");
+const FONT_MONO: Name = Name(b"F3");
+
+/// Paper sizes the wallet sheet can be printed on
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum PaperSize {
+    #[default]
+    A4,
+    Letter,
+}
+
+impl PaperSize {
+    pub fn all() -> Vec<PaperSize> {
+        vec![PaperSize::A4, PaperSize::Letter]
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            PaperSize::A4 => "A4",
+            PaperSize::Letter => "US Letter",
+        }
+    }
+
+    /// Width and height in points
+    fn dimensions(self) -> (f32, f32) {
+        match self {
+            PaperSize::A4 => (210.0 * MM, 297.0 * MM),
+            PaperSize::Letter => (8.5 * 72.0, 11.0 * 72.0),
+        }
+    }
+
+    /// How many cards fit across and down a page
+    fn grid(self) -> (usize, usize) {
+        let (width, height) = self.dimensions();
+        let fit = |space: f32, size: f32| {
+            (((space - 2.0 * PAGE_MARGIN + CARD_GAP) / (size + CARD_GAP)).floor() as usize).max(1)
+        };
+        (fit(width, CARD_WIDTH), fit(height, CARD_HEIGHT))
+    }
+}
+
+impl std::fmt::Display for PaperSize {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PaperSize::A4 => write!(f, "a4"),
+            PaperSize::Letter => write!(f, "letter"),
+        }
+    }
+}
+
+/// One code's card on the wallet sheet
+pub(crate) struct WalletCard {
+    pub code: Code,
+    pub value: String,
+    pub name: Option<String>,
+    pub site_name: String,
+    pub options: RenderOptions,
+}
+
+/// Convert text to the WinAnsi bytes the standard fonts expect, replacing anything they can't show
+fn pdf_text(text: &str) -> Vec<u8> {
+    text.chars()
+        .map(|c| match u32::from(c) {
+            code @ (0x20..=0x7e | 0xa0..=0xff) => code as u8,
+            _ => b'?',
+        })
+        .collect()
+}
+
+/// Shorten text to fit in a width, assuming glyphs are at most 0.6em wide
+fn fit_text(text: &str, width: f32, font_size: f32) -> String {
+    let max_chars = (width / (0.6 * font_size)).floor() as usize;
+    if text.chars().count() <= max_chars {
+        text.to_string()
+    } else {
+        let mut fitted: String = text.chars().take(max_chars.saturating_sub(3)).collect();
+        fitted.push_str("...");
+        fitted
+    }
+}
+
+fn draw_text(content: &mut Content, font: Name, size: f32, x: f32, y: f32, text: &str) {
+    content
+        .begin_text()
+        .set_font(font, size)
+        .next_line(x, y)
+        .show(Str(&pdf_text(text)))
+        .end_text();
+}
+
+/// Draw a card with its bottom-left corner at (x, y)
+fn draw_card(content: &mut Content, card: &WalletCard, x: f32, y: f32) {
+    // dashed cutting guide
+    content
+        .save_state()
+        .set_stroke_gray(0.6)
+        .set_line_width(0.5)
+        .set_dash_pattern([3.0, 2.0], 0.0)
+        .rect(x, y, CARD_WIDTH, CARD_HEIGHT)
+        .stroke()
+        .restore_state();
+
+    let inner_x = x + CARD_PADDING;
+    let inner_width = CARD_WIDTH - 2.0 * CARD_PADDING;
+    let mut top = y + CARD_HEIGHT - CARD_PADDING;
+
+    let title = card.name.as_deref().unwrap_or(card.code.label());
+    top -= TITLE_SIZE;
+    content.set_fill_gray(0.0);
+    draw_text(
+        content,
+        FONT_BOLD,
+        TITLE_SIZE,
+        inner_x,
+        top,
+        &fit_text(title, inner_width, TITLE_SIZE),
+    );
+    if card.site_name != GENERIC_SITE {
+        top -= SUBTITLE_SIZE + 2.0;
+        content.set_fill_gray(0.35);
+        draw_text(
+            content,
+            FONT_REGULAR,
+            SUBTITLE_SIZE,
+            inner_x,
+            top,
+            &fit_text(&card.site_name, inner_width, SUBTITLE_SIZE),
+        );
+    }
+
+    let bottom = y + CARD_PADDING;
+    content.set_fill_gray(0.0);
+    draw_text(
+        content,
+        FONT_MONO,
+        VALUE_SIZE,
+        inner_x,
+        bottom,
+        &fit_text(&card.value, inner_width, VALUE_SIZE),
+    );
+
+    // the code gets whatever space is left between the text
+    let area_bottom = bottom + VALUE_SIZE + 2.0 * MM;
+    let area_top = top - 2.0 * MM;
+    let area_height = area_top - area_bottom;
+
+    // the value is always printed on the card, and cards are already landscape
+    let options = RenderOptions {
+        show_text: false,
+        rotate: false,
+        ..card.options.clone()
+    };
+    let image = match CodeImage::encode(&card.code, &card.value, &options) {
+        Ok(image) => image,
+        Err(err) => {
+            tracing::warn!(
+                "Couldn't render {} for the wallet sheet: {}",
+                card.code,
+                err
+            );
+            draw_text(
+                content,
+                FONT_REGULAR,
+                SUBTITLE_SIZE,
+                inner_x,
+                area_bottom + area_height / 2.0,
+                "Can't draw this code, use the value below",
+            );
+            return;
+        }
+    };
+
+    let (width, height) = (image.width() as f32, image.height() as f32);
+    if card.code.is_linear() {
+        // bars stretch to fill the height
+        let module = inner_width / width;
+        draw_background(
+            content,
+            &image,
+            inner_x,
+            area_bottom,
+            inner_width,
+            area_height,
+        );
+        for (run_x, length) in image.dark_runs(0) {
+            content.rect(
+                inner_x + run_x as f32 * module,
+                area_bottom,
+                length as f32 * module,
+                area_height,
+            );
+        }
+    } else {
+        let module = (inner_width / width).min(area_height / height);
+        let origin_x = inner_x + (inner_width - width * module) / 2.0;
+        let origin_y = area_bottom + (area_height - height * module) / 2.0;
+        draw_background(
+            content,
+            &image,
+            origin_x,
+            origin_y,
+            width * module,
+            height * module,
+        );
+        for row in 0..image.height() {
+            // PDF's y axis points up
+            let row_y = origin_y + (height - 1.0 - row as f32) * module;
+            for (run_x, length) in image.dark_runs(row) {
+                content.rect(
+                    origin_x + run_x as f32 * module,
+                    row_y,
+                    length as f32 * module,
+                    module,
+                );
+            }
+        }
+    }
+    content.fill_nonzero();
+    content.set_fill_gray(0.0);
+}
+
+/// Fill the area behind an inverted code, leaving the modules to be drawn in white
+fn draw_background(content: &mut Content, image: &CodeImage, x: f32, y: f32, w: f32, h: f32) {
+    if image.is_inverted() {
+        content.rect(x, y, w, h);
+        content.fill_nonzero();
+        content.set_fill_gray(1.0);
+    }
+}
+
+/// Lay codes out as credit-card-sized cards to print and cut out
+pub(crate) fn wallet_sheet(cards: &[WalletCard], paper: PaperSize) -> Vec<u8> {
+    let (page_width, page_height) = paper.dimensions();
+    let (columns, rows) = paper.grid();
+
+    // centre the grid on the page
+    let grid_width = columns as f32 * (CARD_WIDTH + CARD_GAP) - CARD_GAP;
+    let grid_height = rows as f32 * (CARD_HEIGHT + CARD_GAP) - CARD_GAP;
+    let left = (page_width - grid_width) / 2.0;
+    let top = page_height - (page_height - grid_height) / 2.0;
+
+    let catalog_id = Ref::new(1);
+    let page_tree_id = Ref::new(2);
+    let font_ids = [
+        (FONT_REGULAR, Ref::new(3), Name(b"Helvetica")),
+        (FONT_BOLD, Ref::new(4), Name(b"Helvetica-Bold")),
+        (FONT_MONO, Ref::new(5), Name(b"Courier")),
+    ];
+    let mut next_id = Ref::new(6);
+
+    let mut pdf = Pdf::new();
+    pdf.catalog(catalog_id).pages(page_tree_id);
+    for (_, font_id, base_font) in font_ids {
+        pdf.type1_font(font_id)
+            .base_font(base_font)
+            .encoding_predefined(Name(b"WinAnsiEncoding"));
+    }
+
+    let per_page = columns * rows;
+    // always have at least one page, even if it's empty
+    let pages: Vec<&[WalletCard]> = if cards.is_empty() {
+        vec![&[]]
+    } else {
+        cards.chunks(per_page).collect()
+    };
+
+    let mut page_ids = Vec::with_capacity(pages.len());
+    for page_cards in pages {
+        let page_id = next_id.bump();
+        let content_id = next_id.bump();
+        page_ids.push(page_id);
+
+        let mut content = Content::new();
+        for (index, card) in page_cards.iter().enumerate() {
+            let (column, row) = (index % columns, index / columns);
+            let x = left + column as f32 * (CARD_WIDTH + CARD_GAP);
+            let y = top - (row + 1) as f32 * CARD_HEIGHT - row as f32 * CARD_GAP;
+            draw_card(&mut content, card, x, y);
+        }
+        pdf.stream(content_id, &content.finish());
+
+        let mut page = pdf.page(page_id);
+        page.parent(page_tree_id)
+            .media_box(Rect::new(0.0, 0.0, page_width, page_height))
+            .contents(content_id);
+        let mut resources = page.resources();
+        let mut fonts = resources.fonts();
+        for (name, font_id, _) in font_ids {
+            fonts.pair(name, font_id);
+        }
+    }
+
+    let page_count = page_ids.len() as i32;
+    pdf.pages(page_tree_id).kids(page_ids).count(page_count);
+    pdf.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_cards(count: usize) -> Vec<WalletCard> {
+        Code::all()
+            .into_iter()
+            .cycle()
+            .take(count)
+            .map(|code| WalletCard {
+                value: code.sample_value().to_string(),
+                name: Some(format!("{} card", code.label())),
+                site_name: "Café Ünïcode ☕".to_string(),
+                options: RenderOptions::default(),
+                code,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_paper_grid() {
+        assert_eq!(PaperSize::A4.grid(), (2, 4));
+        assert_eq!(PaperSize::Letter.grid(), (2, 4));
+    }
+
+    #[test]
+    fn test_wallet_sheet() {
+        let pdf = wallet_sheet(&sample_cards(3), PaperSize::A4);
+        assert!(pdf.starts_with(b"%PDF-"));
+        let text = String::from_utf8_lossy(&pdf);
+        assert_eq!(text.matches("/Type /Page\n").count(), 1);
+        assert!(text.contains("/MediaBox [0 0 595.27"));
+
+        // nine cards need a second page
+        let pdf = wallet_sheet(&sample_cards(9), PaperSize::Letter);
+        let text = String::from_utf8_lossy(&pdf);
+        assert!(text.contains("/Count 2"));
+        assert!(text.contains("/MediaBox [0 0 612 792]"));
+
+        let pdf = wallet_sheet(&[], PaperSize::A4);
+        assert!(String::from_utf8_lossy(&pdf).contains("/Count 1"));
+    }
+
+    #[test]
+    fn test_pdf_text() {
+        assert_eq!(pdf_text("Café ☕"), b"Caf\xe9 ?");
+        assert_eq!(fit_text("short", 100.0, 10.0), "short");
+        assert_eq!(fit_text(&"x".repeat(50), 60.0, 10.0), "xxxxxxx...");
+    }
+}
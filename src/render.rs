@@ -0,0 +1,618 @@
+//! Server-side rendering of codes into images
+
+use std::fmt::{Display, Write};
+
+use font8x8::{BASIC_FONTS, UnicodeFonts};
+use rxing::{
+    BarcodeFormat, EncodeHintValue, EncodeHints, MultiFormatWriter, Writer, common::BitMatrix,
+};
+use sea_orm::FromJsonQueryResult;
+use serde::{Deserialize, Serialize};
+
+use crate::{Code, error::HoofprintError, gs1};
+
+/// How tall the bars of a 1D code are, in modules
+const LINEAR_BAR_HEIGHT: u32 = 50;
+
+/// How tall the band for a 1D code's printed value is, in modules
+const TEXT_BAND_HEIGHT: u32 = 12;
+
+/// The font size of a 1D code's printed value in SVGs, in modules
+const SVG_TEXT_SIZE: u32 = 9;
+
+/// The size of a glyph in the bitmap font used for PNGs, in pixels
+const GLYPH_SIZE: u32 = 8;
+
+/// The MIME type for SVG images
+pub(crate) const MIME_TYPE_SVG: &str = "image/svg+xml";
+
+/// The MIME type for PNG images
+pub(crate) const MIME_TYPE_PNG: &str = "image/png";
+
+/// The largest PNG we'll generate in either direction, in pixels
+pub(crate) const MAX_PNG_DIMENSION: u32 = 4096;
+
+/// Options for rasterising a code
+#[derive(Debug, Default)]
+pub(crate) struct RasterOptions {
+    /// Pixels per module, picked from `width` or the symbology's default if unset
+    pub module_size: Option<u32>,
+    /// Total image width in pixels, the code is centred if it's narrower
+    pub width: Option<u32>,
+    /// Resolution to record in the image metadata
+    pub dpi: Option<u32>,
+}
+
+/// QR code error correction levels, by how much of the code can be damaged and still read
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ErrorCorrection {
+    L,
+    M,
+    Q,
+    #[default]
+    H,
+}
+
+impl ErrorCorrection {
+    pub fn all() -> Vec<ErrorCorrection> {
+        vec![
+            ErrorCorrection::L,
+            ErrorCorrection::M,
+            ErrorCorrection::Q,
+            ErrorCorrection::H,
+        ]
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ErrorCorrection::L => "Low (7%)",
+            ErrorCorrection::M => "Medium (15%)",
+            ErrorCorrection::Q => "Quartile (25%)",
+            ErrorCorrection::H => "High (30%)",
+        }
+    }
+}
+
+impl Display for ErrorCorrection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let level = match self {
+            ErrorCorrection::L => "L",
+            ErrorCorrection::M => "M",
+            ErrorCorrection::Q => "Q",
+            ErrorCorrection::H => "H",
+        };
+        write!(f, "{}", level)
+    }
+}
+
+/// How a code is drawn, saved with each code
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, FromJsonQueryResult)]
+#[serde(default)]
+pub struct RenderOptions {
+    /// Error correction level, only used by QR codes
+    pub error_correction: ErrorCorrection,
+    /// Pixels per module, the symbology's default if unset
+    pub module_size: Option<u32>,
+    /// Quiet zone around the code in modules, the symbology's default if unset
+    pub quiet_zone: Option<u32>,
+    /// Print the value under the bars of 1D codes
+    pub show_text: bool,
+    /// Draw light modules on a dark background
+    pub invert: bool,
+    /// Turn the code a quarter turn, so a long barcode fills a phone held upright
+    pub rotate: bool,
+}
+
+impl Code {
+    fn barcode_format(&self) -> BarcodeFormat {
+        match self {
+            Code::Code128 | Code::Gs1Code128 => BarcodeFormat::CODE_128,
+            Code::QR => BarcodeFormat::QR_CODE,
+            Code::Ean13 => BarcodeFormat::EAN_13,
+            Code::Ean8 => BarcodeFormat::EAN_8,
+            Code::UpcA => BarcodeFormat::UPC_A,
+            Code::UpcE => BarcodeFormat::UPC_E,
+            Code::Code39 => BarcodeFormat::CODE_39,
+            Code::Itf14 => BarcodeFormat::ITF,
+            Code::Codabar => BarcodeFormat::CODABAR,
+            Code::Pdf417 => BarcodeFormat::PDF_417,
+            Code::Aztec => BarcodeFormat::AZTEC,
+            Code::DataMatrix | Code::Gs1DataMatrix => BarcodeFormat::DATA_MATRIX,
+        }
+    }
+
+    /// Whether the code is a single row of bars
+    pub fn is_linear(&self) -> bool {
+        !matches!(
+            self,
+            Code::QR | Code::Pdf417 | Code::Aztec | Code::DataMatrix | Code::Gs1DataMatrix
+        )
+    }
+
+    /// The quiet zone the symbology's spec asks for, in modules
+    pub fn quiet_zone(&self) -> u32 {
+        match self {
+            Code::QR => 4,
+            Code::Pdf417 => 2,
+            Code::Aztec | Code::DataMatrix | Code::Gs1DataMatrix => 1,
+            _ => 10,
+        }
+    }
+
+    /// How many pixels a module gets when no size has been asked for
+    pub fn default_module_size(&self) -> u32 {
+        if self.is_linear() { 3 } else { 8 }
+    }
+}
+
+/// Escape text for use in SVG or HTML markup
+pub(crate) fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// A line of text drawn with an 8x8 bitmap font, centred in a band of a raster image
+struct TextRaster {
+    glyphs: Vec<[u8; 8]>,
+    scale: u32,
+    left: u32,
+    top: u32,
+}
+
+impl TextRaster {
+    /// Fit text into a band `width` by `height` pixels, starting `top` pixels down
+    fn new(text: &str, width: u32, top: u32, height: u32) -> Self {
+        let mut glyphs: Vec<[u8; 8]> = text
+            .chars()
+            .filter_map(|c| BASIC_FONTS.get(c).or_else(|| BASIC_FONTS.get('?')))
+            .collect();
+
+        // as big as fits in the band, shrinking for long values
+        let mut scale = (height * 3 / 4 / GLYPH_SIZE).max(1);
+        while scale > 1 && glyphs.len() as u32 * GLYPH_SIZE * scale > width {
+            scale -= 1;
+        }
+        glyphs.truncate((width / (GLYPH_SIZE * scale)) as usize);
+
+        let text_width = glyphs.len() as u32 * GLYPH_SIZE * scale;
+        Self {
+            glyphs,
+            scale,
+            left: width.saturating_sub(text_width) / 2,
+            top: top + height.saturating_sub(GLYPH_SIZE * scale) / 2,
+        }
+    }
+
+    /// Whether the pixel at (x, y) is part of a glyph
+    fn is_set(&self, x: u32, y: u32) -> bool {
+        let (Some(x), Some(y)) = (x.checked_sub(self.left), y.checked_sub(self.top)) else {
+            return false;
+        };
+        let (column, row) = (x / self.scale, y / self.scale);
+        if row >= GLYPH_SIZE {
+            return false;
+        }
+        // bit 0 is the leftmost pixel of each row
+        self.glyphs
+            .get((column / GLYPH_SIZE) as usize)
+            .is_some_and(|glyph| glyph[row as usize] & (1 << (column % GLYPH_SIZE)) != 0)
+    }
+}
+
+/// An encoded code, ready to be drawn in whatever output format
+pub(crate) struct CodeImage {
+    code: Code,
+    modules: BitMatrix,
+    quiet_zone: u32,
+    /// The value, if it's printed under the bars
+    text: Option<String>,
+    invert: bool,
+    rotate: bool,
+}
+
+impl CodeImage {
+    /// Encode a value as the given code type
+    pub(crate) fn encode(
+        code: &Code,
+        value: &str,
+        options: &RenderOptions,
+    ) -> Result<Self, HoofprintError> {
+        // we add our own quiet zone when drawing
+        let mut hints = EncodeHints::default().with(EncodeHintValue::Margin("0".to_string()));
+        if code == &Code::QR {
+            hints = hints.with(EncodeHintValue::ErrorCorrection(
+                options.error_correction.to_string(),
+            ));
+        }
+        if code == &Code::Gs1DataMatrix {
+            // GS1 needs the minimal encoder, which is the one that handles FNC1
+            hints = hints
+                .with(EncodeHintValue::Gs1Format(true))
+                .with(EncodeHintValue::DataMatrixCompact(true));
+        }
+        let contents = gs1::encoder_contents(code, value)?;
+        let modules = MultiFormatWriter
+            .encode_with_hints(&contents, &code.barcode_format(), 0, 0, &hints)
+            .map_err(|err| {
+                HoofprintError::ValidationError(vec![format!(
+                    "Can't encode value as {}: {}",
+                    code.label(),
+                    err
+                )])
+            })?;
+
+        Ok(Self {
+            code: code.clone(),
+            modules,
+            quiet_zone: options.quiet_zone.unwrap_or(code.quiet_zone()),
+            text: (options.show_text && code.is_linear()).then(|| value.to_string()),
+            invert: options.invert,
+            rotate: options.rotate,
+        })
+    }
+
+    /// Replace the quiet zone, in modules
+    pub(crate) fn with_quiet_zone(mut self, quiet_zone: u32) -> Self {
+        self.quiet_zone = quiet_zone;
+        self
+    }
+
+    /// Whether light modules are drawn on a dark background
+    pub(crate) fn is_inverted(&self) -> bool {
+        self.invert
+    }
+
+    /// Width in modules, including the quiet zone
+    pub(crate) fn width(&self) -> u32 {
+        self.modules.getWidth() + self.quiet_zone * 2
+    }
+
+    /// Height of the code itself in modules, including the quiet zone
+    fn code_height(&self) -> u32 {
+        if self.code.is_linear() {
+            LINEAR_BAR_HEIGHT
+        } else {
+            self.modules.getHeight() + self.quiet_zone * 2
+        }
+    }
+
+    /// Height in modules, including the quiet zone and any printed value
+    pub(crate) fn height(&self) -> u32 {
+        match self.text {
+            Some(_) => self.code_height() + TEXT_BAND_HEIGHT,
+            None => self.code_height(),
+        }
+    }
+
+    /// Whether the module at (x, y) is dark, coordinates include the quiet zone
+    pub(crate) fn is_dark(&self, x: u32, y: u32) -> bool {
+        // bars run the full height of a 1D code, so only the first row matters
+        let y = if self.code.is_linear() {
+            (y < LINEAR_BAR_HEIGHT).then_some(0)
+        } else {
+            y.checked_sub(self.quiet_zone)
+        };
+        match (x.checked_sub(self.quiet_zone), y) {
+            (Some(x), Some(y)) => self.modules.try_get(x, y).unwrap_or(false),
+            _ => false,
+        }
+    }
+
+    /// Runs of dark modules on a row, as (start, length) pairs
+    pub(crate) fn dark_runs(&self, y: u32) -> Vec<(u32, u32)> {
+        let mut runs = Vec::new();
+        let mut start = None;
+        for x in 0..=self.width() {
+            match (start, x < self.width() && self.is_dark(x, y)) {
+                (None, true) => start = Some(x),
+                (Some(run_start), false) => {
+                    runs.push((run_start, x - run_start));
+                    start = None;
+                }
+                _ => {}
+            }
+        }
+        runs
+    }
+
+    /// The (dark, light) colours to draw with
+    fn colours(&self) -> (&'static str, &'static str) {
+        if self.invert {
+            ("#ffffff", "#000000")
+        } else {
+            ("#000000", "#ffffff")
+        }
+    }
+
+    /// Render as a standalone SVG document, with each module `module_size` pixels wide
+    pub(crate) fn to_svg(&self, module_size: u32) -> String {
+        let (width, height) = (self.width(), self.height());
+        let mut path = String::new();
+        if self.code.is_linear() {
+            // every row is the same, so draw each bar once at full height
+            for (x, length) in self.dark_runs(0) {
+                let _ = write!(path, "M{x},0h{length}v{LINEAR_BAR_HEIGHT}h-{length}z");
+            }
+        } else {
+            for y in 0..height {
+                for (x, length) in self.dark_runs(y) {
+                    let _ = write!(path, "M{x},{y}h{length}v1h-{length}z");
+                }
+            }
+        }
+
+        let (dark, light) = self.colours();
+        let mut body = format!(
+            r#"<rect width="{width}" height="{height}" fill="{light}"/><path fill="{dark}" d="{path}"/>"#
+        );
+        if let Some(text) = &self.text {
+            let _ = write!(
+                body,
+                r#"<text x="{}" y="{}" font-family="monospace" font-size="{SVG_TEXT_SIZE}" text-anchor="middle" fill="{dark}">{}</text>"#,
+                f64::from(width) / 2.0,
+                LINEAR_BAR_HEIGHT + TEXT_BAND_HEIGHT - 2,
+                escape_xml(text),
+            );
+        }
+
+        // a quarter turn clockwise, moved back into view
+        let (width, height) = if self.rotate {
+            body = format!(r#"<g transform="translate({height} 0) rotate(90)">{body}</g>"#);
+            (height, width)
+        } else {
+            (width, height)
+        };
+
+        format!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" class="code_image" width="{}" height="{}" viewBox="0 0 {width} {height}" shape-rendering="crispEdges">{body}</svg>"#,
+            width * module_size,
+            height * module_size,
+        )
+    }
+
+    /// Render as a greyscale PNG
+    pub(crate) fn to_png(&self, options: &RasterOptions) -> Result<Vec<u8>, HoofprintError> {
+        // `width` is across the finished image, which is the code's height once it's rotated
+        let across = if self.rotate {
+            self.height()
+        } else {
+            self.width()
+        };
+        let module_size = match (options.module_size, options.width) {
+            (Some(module_size), _) => module_size,
+            (None, Some(width)) => (width / across).max(1),
+            (None, None) => self.code.default_module_size(),
+        };
+        let (code_width, code_height) = (self.width() * module_size, self.height() * module_size);
+        let (across, along) = if self.rotate {
+            (code_height, code_width)
+        } else {
+            (code_width, code_height)
+        };
+        let image_width = options.width.unwrap_or(across);
+        let image_height = along;
+
+        if image_width < across {
+            return Err(HoofprintError::ValidationError(vec![format!(
+                "This code needs an image at least {} pixels wide",
+                across
+            )]));
+        }
+        if image_width > MAX_PNG_DIMENSION || image_height > MAX_PNG_DIMENSION {
+            return Err(HoofprintError::ValidationError(vec![format!(
+                "Images can't be larger than {}x{} pixels",
+                MAX_PNG_DIMENSION, MAX_PNG_DIMENSION
+            )]));
+        }
+
+        let text = self.text.as_ref().map(|text| {
+            TextRaster::new(
+                text,
+                code_width,
+                self.code_height() * module_size,
+                TEXT_BAND_HEIGHT * module_size,
+            )
+        });
+        let is_dark = |x: u32, y: u32| {
+            self.is_dark(x / module_size, y / module_size)
+                || text.as_ref().is_some_and(|text| text.is_set(x, y))
+        };
+
+        let offset = (image_width - across) / 2;
+        let mut pixels = Vec::with_capacity((image_width * image_height) as usize);
+        for y in 0..image_height {
+            for x in 0..image_width {
+                let dark = x >= offset
+                    && x < offset + across
+                    && if self.rotate {
+                        // a quarter turn clockwise
+                        is_dark(y, code_height - 1 - (x - offset))
+                    } else {
+                        is_dark(x - offset, y)
+                    };
+                pixels.push(if dark != self.invert { 0x00 } else { 0xff });
+            }
+        }
+
+        let mut png_bytes = Vec::new();
+        let mut encoder = png::Encoder::new(&mut png_bytes, image_width, image_height);
+        encoder.set_color(png::ColorType::Grayscale);
+        encoder.set_depth(png::BitDepth::Eight);
+        if let Some(dpi) = options.dpi {
+            // PNG stores resolution in pixels per metre
+            let pixels_per_metre = (f64::from(dpi) / 0.0254).round() as u32;
+            encoder.set_pixel_dims(Some(png::PixelDimensions {
+                xppu: pixels_per_metre,
+                yppu: pixels_per_metre,
+                unit: png::Unit::Meter,
+            }));
+        }
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&pixels)?;
+        writer.finish()?;
+
+        Ok(png_bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_all_types() {
+        for code in Code::all() {
+            let image = CodeImage::encode(&code, code.sample_value(), &RenderOptions::default())
+                .expect("Failed to encode code");
+            let svg = image.to_svg(code.default_module_size());
+            assert!(svg.starts_with("<svg"), "{} didn't render to svg", code);
+            assert!(svg.contains("<path"));
+        }
+    }
+
+    #[test]
+    fn test_quiet_zone() {
+        let image = CodeImage::encode(&Code::QR, "hello", &RenderOptions::default())
+            .expect("Failed to encode QR code");
+        // version 1 QR codes are 21 modules square
+        assert_eq!(image.width(), 21 + 8);
+        assert_eq!(image.height(), 21 + 8);
+        assert!(!image.is_dark(0, 0));
+        assert!(!image.is_dark(3, 3));
+        // the top-left finder pattern starts right after the quiet zone
+        assert!(image.is_dark(4, 4));
+    }
+
+    #[test]
+    fn test_encode_invalid_value() {
+        let result = CodeImage::encode(&Code::Ean13, "not-a-number", &RenderOptions::default());
+        assert!(matches!(result, Err(HoofprintError::ValidationError(_))));
+    }
+
+    #[test]
+    fn test_png() {
+        let image = CodeImage::encode(&Code::QR, "hello", &RenderOptions::default())
+            .expect("Failed to encode QR code");
+        let png_bytes = image
+            .to_png(&RasterOptions {
+                width: Some(300),
+                dpi: Some(300),
+                ..Default::default()
+            })
+            .expect("Failed to render PNG");
+
+        let decoder = png::Decoder::new(std::io::Cursor::new(png_bytes));
+        let reader = decoder.read_info().expect("Failed to read PNG");
+        let info = reader.info();
+        assert_eq!(info.width, 300);
+        // 29 modules fit 10 pixels each into 300 pixels
+        assert_eq!(info.height, 290);
+        let pixel_dims = info.pixel_dims.expect("Missing pixel dimensions");
+        assert_eq!(pixel_dims.xppu, 11811);
+        assert_eq!(pixel_dims.unit, png::Unit::Meter);
+    }
+
+    #[test]
+    fn test_error_correction() {
+        let value = "https://example.com/membership/1234567890";
+        let dense = CodeImage::encode(&Code::QR, value, &RenderOptions::default())
+            .expect("Failed to encode QR code");
+        let sparse = CodeImage::encode(
+            &Code::QR,
+            value,
+            &RenderOptions {
+                error_correction: ErrorCorrection::L,
+                ..Default::default()
+            },
+        )
+        .expect("Failed to encode QR code");
+        assert!(sparse.width() < dense.width());
+    }
+
+    #[test]
+    fn test_show_text() {
+        let options = RenderOptions {
+            show_text: true,
+            ..Default::default()
+        };
+        let image =
+            CodeImage::encode(&Code::Code128, "A<B", &options).expect("Failed to encode code");
+        assert_eq!(image.height(), LINEAR_BAR_HEIGHT + TEXT_BAND_HEIGHT);
+        // no bars in the text band
+        assert!(image.dark_runs(LINEAR_BAR_HEIGHT).is_empty());
+        assert!(image.to_svg(3).contains(">A&lt;B</text>"));
+
+        // 2D codes don't get text
+        let image =
+            CodeImage::encode(&Code::QR, "hello", &options).expect("Failed to encode QR code");
+        assert_eq!(image.height(), 21 + 8);
+        assert!(!image.to_svg(8).contains("<text"));
+
+        let raster = TextRaster::new("ABC", 300, 150, 36);
+        assert_eq!(raster.scale, 3);
+        assert_eq!(raster.left, 114);
+        assert_eq!(raster.top, 156);
+        // a long value shrinks to fit, then gets cut off
+        let raster = TextRaster::new(&"9".repeat(100), 300, 0, 36);
+        assert_eq!(raster.scale, 1);
+        assert_eq!(raster.glyphs.len(), 37);
+    }
+
+    #[test]
+    fn test_invert_and_rotate() {
+        let options = RenderOptions {
+            invert: true,
+            rotate: true,
+            ..Default::default()
+        };
+        let image = CodeImage::encode(&Code::Ean13, "5901234123457", &options)
+            .expect("Failed to encode code");
+        let svg = image.to_svg(1);
+        assert!(svg.contains(&format!(
+            r#"width="{}" height="{}""#,
+            image.height(),
+            image.width()
+        )));
+        assert!(svg.contains("rotate(90)"));
+        assert!(svg.contains(r##"<path fill="#ffffff""##));
+
+        let png_bytes = image
+            .to_png(&RasterOptions {
+                module_size: Some(1),
+                ..Default::default()
+            })
+            .expect("Failed to render PNG");
+        let decoder = png::Decoder::new(std::io::Cursor::new(png_bytes));
+        let mut reader = decoder.read_info().expect("Failed to read PNG");
+        assert_eq!(reader.info().width, image.height());
+        assert_eq!(reader.info().height, image.width());
+        let mut pixels = vec![0; reader.output_buffer_size().expect("Unknown buffer size")];
+        reader
+            .next_frame(&mut pixels)
+            .expect("Failed to read PNG data");
+        // the quiet zone is dark
+        assert_eq!(pixels.first(), Some(&0x00));
+    }
+
+    #[test]
+    fn test_png_too_narrow() {
+        let image = CodeImage::encode(&Code::Ean13, "5901234123457", &RenderOptions::default())
+            .expect("Failed to encode code")
+            .with_quiet_zone(0);
+        let result = image.to_png(&RasterOptions {
+            width: Some(50),
+            ..Default::default()
+        });
+        assert!(matches!(result, Err(HoofprintError::ValidationError(_))));
+    }
+}
@@ -0,0 +1,284 @@
+//! Searching a user's codes, their households' codes and the codes shared with them, by name,
+//! value, site and notes.
+//!
+//! Names and site names are indexed in the `code_search` FTS5 table, which triggers keep in step
+//! with the `code` and `site` tables, and matches there are ranked with bm25. Values and notes are
+//! sealed at rest, as are names when `encrypt_code_names` is set, so they can't be indexed;
+//! they're matched against the user's codes once they've been decrypted instead.
+
+use sea_orm::{ConnectionTrait, DatabaseConnection, DbBackend, Statement};
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::{
+    crypto::CodeCipher,
+    db::entities::{code, code_share, household},
+    error::HoofprintError,
+    render::escape_xml,
+};
+
+/// The most results a search returns
+pub const MAX_RESULTS: usize = 50;
+/// Words past this many are ignored
+pub const MAX_TERMS: usize = 10;
+
+/// A code that matched a search
+#[derive(Debug, Serialize)]
+pub struct SearchResult {
+    pub code_id: Uuid,
+    pub name: Option<String>,
+    pub site_name: String,
+    pub value: String,
+    /// The name, HTML-escaped with the matches wrapped in `<mark>`
+    pub name_html: Option<String>,
+    /// The site name, HTML-escaped with the matches wrapped in `<mark>`
+    pub site_name_html: String,
+    /// The value, HTML-escaped with the matches wrapped in `<mark>`
+    pub value_html: String,
+}
+
+/// The lowercased words of a query
+pub fn terms(query: &str) -> Vec<String> {
+    query
+        .split_whitespace()
+        .take(MAX_TERMS)
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// An FTS5 query matching every term as a prefix, quoted so nothing in them is read as syntax.
+///
+/// Terms without any letters or digits are left out, because FTS5 has nothing to match them to.
+fn fts_query(terms: &[String]) -> Option<String> {
+    let phrases: Vec<String> = terms
+        .iter()
+        .filter(|term| term.chars().any(char::is_alphanumeric))
+        .map(|term| format!("\"{}\"*", term.replace('"', "\"\"")))
+        .collect();
+    (!phrases.is_empty()).then(|| phrases.join(" "))
+}
+
+/// The codes the user can see with names or sites matching the FTS5 query, best first
+async fn ranked_matches(
+    db: &DatabaseConnection,
+    user_id: Uuid,
+    fts_query: String,
+) -> Result<Vec<Uuid>, HoofprintError> {
+    let rows = db
+        .query_all(Statement::from_sql_and_values(
+            DbBackend::Sqlite,
+            "SELECT code.id AS id FROM code_search
+            JOIN code ON code.id = code_search.code_id
+            WHERE code_search MATCH ?
+            AND ((code.user_id = ? AND code.household_id IS NULL)
+                OR code.household_id IN (SELECT household_id FROM household_member WHERE user_id = ?)
+                OR code.id IN (SELECT code_id FROM code_share WHERE user_id = ?))
+            ORDER BY bm25(code_search, 0.0, 2.0, 1.0)",
+            [
+                fts_query.into(),
+                user_id.into(),
+                user_id.into(),
+                user_id.into(),
+            ],
+        ))
+        .await?;
+    rows.iter()
+        .map(|row| row.try_get::<Uuid>("", "id").map_err(HoofprintError::from))
+        .collect()
+}
+
+/// Search a user's codes, their households' codes and the codes shared with them, returning up to [MAX_RESULTS] of them.
+///
+/// Codes whose value or name is exactly the query come first, then codes matched by the index in
+/// rank order, then codes that only matched once decrypted. Locked codes are left out.
+pub async fn search_codes(
+    db: &DatabaseConnection,
+    cipher: &mut CodeCipher,
+    user_id: Uuid,
+    query: &str,
+) -> Result<Vec<SearchResult>, HoofprintError> {
+    let terms = terms(query);
+    if terms.is_empty() {
+        return Ok(Vec::new());
+    }
+    let ranked = match fts_query(&terms) {
+        Some(fts_query) => ranked_matches(db, user_id, fts_query).await?,
+        None => Vec::new(),
+    };
+    let whole_query = query.trim().to_lowercase();
+
+    let mut codes = code::Model::find_for_user(db, user_id).await?;
+    codes.extend(
+        household::Model::codes_for_member(db, user_id)
+            .await?
+            .into_iter()
+            .map(|(code_model, site_model, _)| (code_model, site_model)),
+    );
+    for shared in code_share::Model::codes_shared_with(db, user_id).await? {
+        if !codes
+            .iter()
+            .any(|(code_model, _)| code_model.id == shared.code.id)
+        {
+            codes.push((shared.code, shared.site));
+        }
+    }
+
+    let code_models: Vec<&code::Model> = codes.iter().map(|(code, _)| code).collect();
+    code_share::Model::load_code_keys(db, user_id, cipher, &code_models).await?;
+
+    let mut results = Vec::new();
+    for (code_model, site_model) in codes {
+        let code_model = match code_model.decrypt(cipher) {
+            Ok(code_model) => code_model,
+            Err(HoofprintError::CodeLocked(_)) => continue,
+            Err(err) => return Err(err),
+        };
+        let site_name = site_model
+            .map(|site| site.name)
+            .unwrap_or_else(|| "Unknown Site".to_string());
+
+        let exact = code_model.value.to_lowercase() == whole_query
+            || code_model
+                .name
+                .as_deref()
+                .is_some_and(|name| name.to_lowercase() == whole_query);
+        let rank = ranked.iter().position(|id| *id == code_model.id);
+        let fields = [
+            code_model.name.as_deref().unwrap_or_default(),
+            site_name.as_str(),
+            code_model.value.as_str(),
+            code_model.notes.as_deref().unwrap_or_default(),
+        ];
+        let found = terms
+            .iter()
+            .all(|term| fields.iter().any(|field| contains(field, term)));
+        let order = match (exact, rank, found) {
+            (true, _, _) => (0, 0),
+            (false, Some(rank), _) => (1, rank),
+            (false, None, true) => (2, 0),
+            (false, None, false) => continue,
+        };
+
+        results.push((
+            order,
+            SearchResult {
+                code_id: code_model.id,
+                name_html: code_model
+                    .name
+                    .as_deref()
+                    .map(|name| highlight(name, &terms)),
+                site_name_html: highlight(&site_name, &terms),
+                value_html: highlight(&code_model.value, &terms),
+                name: code_model.name,
+                site_name,
+                value: code_model.value,
+            },
+        ));
+    }
+    results.sort_by_cached_key(|(order, result)| {
+        (
+            *order,
+            result.name.as_deref().map(str::to_lowercase),
+            result.code_id,
+        )
+    });
+    Ok(results
+        .into_iter()
+        .take(MAX_RESULTS)
+        .map(|(_, result)| result)
+        .collect())
+}
+
+/// How many bytes at the start of `text` match `term`, ignoring case
+fn match_len(text: &str, term: &str) -> Option<usize> {
+    let mut chars = text.char_indices();
+    let mut len = 0;
+    for term_char in term.chars() {
+        let (index, c) = chars.next()?;
+        if !c.to_lowercase().eq(term_char.to_lowercase()) {
+            return None;
+        }
+        len = index + c.len_utf8();
+    }
+    Some(len)
+}
+
+/// Whether `text` contains `term`, ignoring case
+fn contains(text: &str, term: &str) -> bool {
+    text.char_indices()
+        .any(|(start, _)| match_len(&text[start..], term).is_some())
+}
+
+/// Escape text for HTML, wrapping the parts that match any of the terms in `<mark>`
+pub fn highlight(text: &str, terms: &[String]) -> String {
+    let mut marks: Vec<(usize, usize)> = Vec::new();
+    for (start, _) in text.char_indices() {
+        let Some(len) = terms
+            .iter()
+            .filter(|term| !term.is_empty())
+            .filter_map(|term| match_len(&text[start..], term))
+            .max()
+        else {
+            continue;
+        };
+        let end = start + len;
+        match marks.last_mut() {
+            Some(last) if start <= last.1 => last.1 = last.1.max(end),
+            _ => marks.push((start, end)),
+        }
+    }
+
+    let mut html = String::with_capacity(text.len());
+    let mut position = 0;
+    for (start, end) in marks {
+        html.push_str(&escape_xml(&text[position..start]));
+        html.push_str("<mark>");
+        html.push_str(&escape_xml(&text[start..end]));
+        html.push_str("</mark>");
+        position = end;
+    }
+    html.push_str(&escape_xml(&text[position..]));
+    html
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fts_query() {
+        assert_eq!(
+            fts_query(&terms("Gym  CARD")),
+            Some("\"gym\"* \"card\"*".to_string())
+        );
+        assert_eq!(
+            fts_query(&terms("say \"hi\" -")),
+            Some("\"say\"* \"\"\"hi\"\"\"*".to_string())
+        );
+        assert_eq!(fts_query(&terms("- *")), None);
+        assert_eq!(fts_query(&terms("   ")), None);
+        assert_eq!(terms("a b c d e f g h i j k l").len(), MAX_TERMS);
+    }
+
+    #[test]
+    fn test_highlight() {
+        let terms = terms("gym");
+        assert_eq!(highlight("My Gym", &terms), "My <mark>Gym</mark>");
+        assert_eq!(highlight("nothing", &terms), "nothing");
+        assert_eq!(
+            highlight("<b>gymgym</b>", &terms),
+            "&lt;b&gt;<mark>gymgym</mark>&lt;/b&gt;"
+        );
+        // overlapping terms are merged into one mark
+        assert_eq!(
+            highlight("Library card", &super::terms("libr rary")),
+            "<mark>Library</mark> card"
+        );
+        assert_eq!(
+            highlight("Café Crème", &super::terms("CRÈME")),
+            "Café <mark>Crème</mark>"
+        );
+        assert!(contains("Library", "brar"));
+        assert!(!contains("Library", "cards"));
+    }
+}
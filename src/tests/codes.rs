@@ -1,54 +1,50 @@
 use super::{TEST_USER_EMAIL, TEST_USER_PASSWORD};
 
+use std::io::{Cursor, Write};
+
+use axum_test::multipart::{MultipartForm, Part};
 use sea_orm::EntityTrait;
 use uuid::Uuid;
+use zip::write::SimpleFileOptions;
 
 use crate::{
     Code,
-    db::entities::code,
+    config::Configuration,
+    crypto::{CodeCipher, DataKey, is_sealed},
+    db::entities::{
+        code, code_attachment, code_revision, code_secret, code_usage, site, tag, user,
+    },
     prelude::Urls,
-    tests::{setup_test_server, setup_test_user},
-    web::{auth::LoginForm, forms::CreateCodeForm},
+    print::MIME_TYPE_PDF,
+    render::{
+        CodeImage, ErrorCorrection, MIME_TYPE_PNG, MIME_TYPE_SVG, RasterOptions, RenderOptions,
+    },
+    tests::{
+        create_code, create_code_with, create_notes_test_code, log_in_as, setup_test_server,
+        setup_test_server_with_config, setup_test_user, test_user_cipher,
+    },
+    wallet::{MIME_TYPE_PKPASS, test_signing_certificate},
+    web::{
+        account::PreferencesForm,
+        auth::LoginForm,
+        forms::{
+            CreateCodeForm, CreatePayloadForm, FavouriteForm, PayloadForm, ReorderRequest,
+            RevealSecretForm,
+        },
+    },
 };
 
 #[tokio::test]
 async fn test_code_create() {
-    let (server, db) = setup_test_server().await;
+    let (mut server, db) = setup_test_server().await;
     let _user = setup_test_user(db.clone()).await;
 
-    let response = server
-        .post(Urls::Login.as_ref())
-        .form(&LoginForm {
-            email: TEST_USER_EMAIL.to_string(),
-            password: TEST_USER_PASSWORD.to_string(),
-            error: None,
-            success: None,
-        })
-        .await;
-    dbg!(&response);
-    assert_eq!(response.status_code(), 303);
+    log_in_as(&mut server, TEST_USER_EMAIL, TEST_USER_PASSWORD).await;
 
     // create a new code
-    let response = server
-        .post(Urls::Create.as_ref())
-        .form(&CreateCodeForm {
-            code_type: Code::Bar.to_string(),
-            code_value: "123456".to_string(),
-            site_id: "00000000-0000-0000-0000-000000000000".to_string(),
-            code_name: Some("Test Code".to_string()),
-        })
-        .await;
-    dbg!(&response);
-    assert_eq!(response.status_code(), 303);
-    let location = response
-        .headers()
-        .get("Location")
-        .expect("Location header missing")
-        .to_str()
-        .expect("Failed to convert Location header to str");
-    assert!(location.starts_with("/view/"));
-    let code = location.trim_start_matches("/view/");
-    let code_uuid = uuid::Uuid::parse_str(code).expect("Failed to parse code UUID");
+    let code = create_code(&server, Some("Test Code"), "123456").await;
+    let location = format!("/view/{}", code);
+    let code_uuid = uuid::Uuid::parse_str(&code).expect("Failed to parse code UUID");
     let code_in_db = code::Entity::find_by_id(code_uuid)
         .one(&db)
         .await
@@ -56,8 +52,43 @@ async fn test_code_create() {
         .expect("Code not found in database");
     assert_eq!(code_in_db.id, code_uuid);
     // check we can get the code view page
-    let response = server.get(location).await;
+    let response = server.get(&location).await;
+    assert_eq!(response.status_code(), 200);
+    response.assert_text_contains("<svg");
+
+    // and the standalone image
+    let response = server.get(&format!("{}/image.svg", location)).await;
     assert_eq!(response.status_code(), 200);
+    assert_eq!(
+        response
+            .headers()
+            .get("Content-Type")
+            .expect("Content-Type header missing"),
+        MIME_TYPE_SVG
+    );
+    assert!(response.text().starts_with("<svg"));
+
+    let response = server
+        .get(&format!("{}/image.png", location))
+        .add_query_param("width", 400)
+        .add_query_param("quiet_zone", 2)
+        .add_query_param("dpi", 300)
+        .await;
+    assert_eq!(response.status_code(), 200);
+    assert_eq!(
+        response
+            .headers()
+            .get("Content-Type")
+            .expect("Content-Type header missing"),
+        MIME_TYPE_PNG
+    );
+    assert!(response.as_bytes().starts_with(b"\x89PNG"));
+
+    let response = server
+        .get(&format!("{}/image.png", location))
+        .add_query_param("module_size", 1000)
+        .await;
+    assert_eq!(response.status_code(), 400);
 
     let response = server.get(Urls::Home.as_ref()).await;
     assert_eq!(response.status_code(), 200);
@@ -68,8 +99,9 @@ async fn test_code_create() {
     // try to view a code that doesn't belong to us
     let admin_code = code::Model::create_new(
         db.clone(),
+        &CodeCipher::new(DataKey::generate(), false),
         Uuid::nil(),
-        Code::Bar,
+        Code::Code128,
         "hello-admin",
         uuid::Uuid::nil(),
         Some("Admin Code"),
@@ -79,4 +111,1534 @@ async fn test_code_create() {
 
     let response = server.get(&format!("/view/{}", admin_code.id)).await;
     assert_eq!(response.status_code(), 404); // because it's not our code!
+    let response = server
+        .get(&format!("/view/{}/image.svg", admin_code.id))
+        .await;
+    assert_eq!(response.status_code(), 404);
+    let response = server
+        .get(&format!("/view/{}/image.png", admin_code.id))
+        .await;
+    assert_eq!(response.status_code(), 404);
+}
+
+#[tokio::test]
+async fn test_edit_render_options() {
+    let (mut server, db) = setup_test_server().await;
+    log_in_as(&mut server, TEST_USER_EMAIL, TEST_USER_PASSWORD).await;
+
+    let code_id = Uuid::parse_str(&create_code(&server, None, "123456").await)
+        .expect("Failed to parse code UUID");
+    let code_model = code::Entity::find_by_id(code_id)
+        .one(&db)
+        .await
+        .expect("Failed to query code")
+        .expect("Code not found");
+    assert_eq!(code_model.render_options, RenderOptions::default());
+
+    let response = server
+        .post(&format!("/edit/{}", code_model.id))
+        .form(&[
+            ("code_type", Code::Code128.to_string()),
+            ("code_value", "123456".to_string()),
+            ("site_id", Uuid::nil().to_string()),
+            ("error_correction", "L".to_string()),
+            ("module_size", "2".to_string()),
+            ("quiet_zone", "".to_string()),
+            ("show_text", "on".to_string()),
+            ("rotate", "on".to_string()),
+        ])
+        .await;
+    assert_eq!(response.status_code(), 303);
+
+    let code_model = code::Entity::find_by_id(code_model.id)
+        .one(&db)
+        .await
+        .expect("Failed to query code")
+        .expect("Code not found");
+    assert_eq!(
+        code_model.render_options,
+        RenderOptions {
+            error_correction: ErrorCorrection::L,
+            module_size: Some(2),
+            show_text: true,
+            rotate: true,
+            ..Default::default()
+        }
+    );
+
+    // the edit page shows what's saved
+    let response = server.get(&format!("/edit/{}", code_model.id)).await;
+    assert_eq!(response.status_code(), 200);
+    response.assert_text_contains(r#"<option value="L" selected>"#);
+    response.assert_text_contains(r#"value="2""#);
+
+    // and every renderer uses the options
+    let response = server.get(&format!("/view/{}", code_model.id)).await;
+    response.assert_text_contains("rotate(90)");
+    response.assert_text_contains(">123456</text>");
+    let response = server
+        .get(&format!("/view/{}/image.svg", code_model.id))
+        .await;
+    response.assert_text_contains("rotate(90)");
+    let image = CodeImage::encode(&Code::Code128, "123456", &code_model.render_options)
+        .expect("Failed to encode code");
+    let response = server
+        .get(&format!("/view/{}/image.png", code_model.id))
+        .await;
+    assert_eq!(response.status_code(), 200);
+    let decoder = png::Decoder::new(Cursor::new(response.as_bytes().to_vec()));
+    let reader = decoder.read_info().expect("Failed to read PNG");
+    assert_eq!(reader.info().width, image.height() * 2);
+    assert_eq!(reader.info().height, image.width() * 2);
+
+    let response = server
+        .post(&format!("/edit/{}", code_model.id))
+        .form(&[
+            ("code_type", Code::Code128.to_string()),
+            ("code_value", "123456".to_string()),
+            ("site_id", Uuid::nil().to_string()),
+            ("module_size", "100".to_string()),
+        ])
+        .await;
+    assert_eq!(response.status_code(), 400);
+}
+
+#[tokio::test]
+async fn test_gs1_code() {
+    let (mut server, _db) = setup_test_server().await;
+    log_in_as(&mut server, TEST_USER_EMAIL, TEST_USER_PASSWORD).await;
+
+    let form = |code_type: Code, code_value: &str| CreateCodeForm {
+        code_type: code_type.to_string(),
+        code_value: code_value.to_string(),
+        site_id: Uuid::nil().to_string(),
+        ..Default::default()
+    };
+
+    let response = server
+        .post(Urls::Create.as_ref())
+        .form(&form(
+            Code::Gs1Code128,
+            "(01)09501101530003(17)260131(10)ABC123",
+        ))
+        .await;
+    assert_eq!(response.status_code(), 303);
+    let location = response
+        .headers()
+        .get("Location")
+        .expect("Location header missing")
+        .to_str()
+        .expect("Failed to convert Location header to str")
+        .to_string();
+
+    let response = server.get(&location).await;
+    assert_eq!(response.status_code(), 200);
+    response.assert_text_contains("<svg");
+    response.assert_text_contains("<td>USE BY</td>");
+    response.assert_text_contains(r#"<td class="code_value">2026-01-31</td>"#);
+    response.assert_text_contains(r#"<td class="code_value">ABC123</td>"#);
+
+    // a plain Code 128 value that looks like GS1 isn't parsed
+    let response = server
+        .post(Urls::Create.as_ref())
+        .form(&form(Code::Code128, "(01)09501101530003"))
+        .await;
+    let response = server
+        .get(
+            response
+                .headers()
+                .get("Location")
+                .expect("Location header missing")
+                .to_str()
+                .expect("Failed to convert Location header to str"),
+        )
+        .await;
+    assert!(!response.text().contains("gs1_table"));
+
+    // bad check digit
+    let response = server
+        .post(Urls::Create.as_ref())
+        .form(&form(Code::Gs1DataMatrix, "(01)09501101530004"))
+        .await;
+    assert_eq!(response.status_code(), 400);
+    response.assert_text_contains("(01) GTIN check digit is incorrect");
+}
+
+/// An unsigned Wallet pass with a QR code
+fn test_pkpass() -> Vec<u8> {
+    let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+    zip.start_file("pass.json", SimpleFileOptions::default())
+        .expect("Failed to start file");
+    zip.write_all(
+        br#"{"organizationName": "Corner Shop", "description": "Corner Shop rewards", "barcodes": [{"format": "PKBarcodeFormatQR", "message": "rewards-42", "messageEncoding": "iso-8859-1"}]}"#,
+    )
+    .expect("Failed to write file");
+    zip.finish().expect("Failed to finish zip").into_inner()
+}
+
+#[tokio::test]
+async fn test_scan_upload() {
+    let (mut server, db) = setup_test_server().await;
+
+    log_in_as(&mut server, TEST_USER_EMAIL, TEST_USER_PASSWORD).await;
+
+    let png_bytes = CodeImage::encode(&Code::Ean13, "5901234123457", &RenderOptions::default())
+        .expect("Failed to encode code")
+        .to_png(&RasterOptions::default())
+        .expect("Failed to render PNG");
+    let response = server
+        .post(Urls::ScanUpload.as_ref())
+        .multipart(
+            MultipartForm::new().add_part(
+                "upload",
+                Part::bytes(png_bytes)
+                    .file_name("receipt.png")
+                    .mime_type(MIME_TYPE_PNG),
+            ),
+        )
+        .await;
+    assert_eq!(response.status_code(), 200);
+    // the detected code is pre-filled for the user to confirm
+    response.assert_text_contains(r#"name="code_type" value="ean13""#);
+    response.assert_text_contains(r#"name="code_value" value="5901234123457""#);
+
+    // confirming it saves the code through the normal scan form
+    let response = server
+        .post(Urls::Scan.as_ref())
+        .form(&CreateCodeForm {
+            code_type: Code::Ean13.to_string(),
+            code_value: "5901234123457".to_string(),
+            site_id: Uuid::nil().to_string(),
+            code_name: Some("Uploaded".to_string()),
+            ..Default::default()
+        })
+        .await;
+    assert_eq!(response.status_code(), 303);
+
+    // Wallet passes are read too, proposing their organisation as a new site
+    let response = server
+        .post(Urls::ScanUpload.as_ref())
+        .multipart(
+            MultipartForm::new().add_part(
+                "upload",
+                Part::bytes(test_pkpass())
+                    .file_name("rewards.pkpass")
+                    .mime_type(MIME_TYPE_PKPASS),
+            ),
+        )
+        .await;
+    assert_eq!(response.status_code(), 200);
+    response.assert_text_contains(r#"name="code_type" value="qrcode""#);
+    response.assert_text_contains(r#"name="code_value" value="rewards-42""#);
+    response.assert_text_contains(r#"value="Corner Shop rewards""#);
+    response.assert_text_contains(r#"name="new_site_name""#);
+
+    let response = server
+        .post(Urls::Scan.as_ref())
+        .form(&CreateCodeForm {
+            code_type: Code::QR.to_string(),
+            code_value: "rewards-42".to_string(),
+            site_id: Uuid::nil().to_string(),
+            code_name: Some("Corner Shop rewards".to_string()),
+            new_site_name: Some("Corner Shop".to_string()),
+            ..Default::default()
+        })
+        .await;
+    assert_eq!(response.status_code(), 303);
+    let corner_shop = site::Model::find_by_name(&db, "corner shop")
+        .await
+        .expect("Failed to query sites")
+        .expect("Site should have been created");
+
+    // the second time round the site is matched instead
+    let response = server
+        .post(Urls::ScanUpload.as_ref())
+        .multipart(
+            MultipartForm::new().add_part(
+                "upload",
+                Part::bytes(test_pkpass())
+                    .file_name("rewards.pkpass")
+                    .mime_type(MIME_TYPE_PKPASS),
+            ),
+        )
+        .await;
+    assert_eq!(response.status_code(), 200);
+    assert!(!response.text().contains(r#"name="new_site_name""#));
+    response.assert_text_contains(format!(r#"value="{}" selected"#, corner_shop.id));
+
+    // images without a code come back to the scan page with an error
+    let response = server
+        .post(Urls::ScanUpload.as_ref())
+        .multipart(MultipartForm::new().add_part(
+            "upload",
+            Part::bytes(b"not an image".to_vec()).file_name("nope.png"),
+        ))
+        .await;
+    assert_eq!(response.status_code(), 200);
+    response.assert_text_contains("Couldn&#39;t read the image");
+
+    // and an empty upload is rejected
+    let response = server
+        .post(Urls::ScanUpload.as_ref())
+        .multipart(MultipartForm::new().add_text("other", "value"))
+        .await;
+    assert_eq!(response.status_code(), 400);
+}
+
+#[tokio::test]
+async fn test_wallet_sheet() {
+    let (mut server, db) = setup_test_server().await;
+
+    log_in_as(&mut server, TEST_USER_EMAIL, TEST_USER_PASSWORD).await;
+
+    // nothing to print yet
+    let response = server.get(Urls::WalletSheetPdf.as_ref()).await;
+    assert_eq!(response.status_code(), 400);
+
+    let mut code_ids = Vec::new();
+    for (code, value) in [(Code::QR, "hello"), (Code::Ean13, "5901234123457")] {
+        let code_id = create_code_with(
+            &server,
+            CreateCodeForm {
+                code_type: code.to_string(),
+                code_value: value.to_string(),
+                site_id: Uuid::nil().to_string(),
+                code_name: Some(format!("{} code", code.label())),
+                ..Default::default()
+            },
+        )
+        .await;
+        code_ids.push(code_id);
+    }
+
+    let response = server.get(Urls::WalletSheet.as_ref()).await;
+    assert_eq!(response.status_code(), 200);
+    for code_id in &code_ids {
+        response.assert_text_contains(code_id);
+    }
+
+    let response = server
+        .get(Urls::WalletSheetPdf.as_ref())
+        .add_query_param("paper", "letter")
+        .add_query_param("code", &code_ids[0])
+        .await;
+    assert_eq!(response.status_code(), 200);
+    assert_eq!(
+        response
+            .headers()
+            .get("Content-Type")
+            .expect("Content-Type header missing"),
+        MIME_TYPE_PDF
+    );
+    assert!(response.as_bytes().starts_with(b"%PDF-"));
+
+    // other users' codes can't be picked
+    let admin_code = code::Model::create_new(
+        db.clone(),
+        &CodeCipher::new(DataKey::generate(), false),
+        Uuid::nil(),
+        Code::Code128,
+        "hello-admin",
+        Uuid::nil(),
+        Some("Admin Code"),
+    )
+    .await
+    .expect("Failed to create admin code");
+    let response = server
+        .get(Urls::WalletSheetPdf.as_ref())
+        .add_query_param("code", admin_code.id)
+        .await;
+    assert_eq!(response.status_code(), 400);
+
+    // unticking every code doesn't print all of them
+    let response = server
+        .get(Urls::WalletSheetPdf.as_ref())
+        .add_query_param("selected", 1)
+        .add_query_param("paper", "a4")
+        .await;
+    assert_eq!(response.status_code(), 400);
+    response.assert_text_contains("No codes to put on the wallet sheet");
+    server
+        .get(Urls::WalletSheet.as_ref())
+        .await
+        .assert_text_contains(r#"name="selected""#);
+
+    let response = server
+        .get(Urls::WalletSheetPdf.as_ref())
+        .add_query_param("paper", "a3")
+        .await;
+    assert_eq!(response.status_code(), 400);
+}
+
+#[tokio::test]
+async fn test_wallet_pass() {
+    let (certificate_pem, key_pem) = test_signing_certificate();
+    let temp_dir = std::env::temp_dir();
+    let certificate_path = temp_dir.join(format!("hoofprint-pass-{}.pem", Uuid::now_v7()));
+    let key_path = temp_dir.join(format!("hoofprint-pass-key-{}.pem", Uuid::now_v7()));
+    std::fs::write(&certificate_path, certificate_pem).expect("Failed to write certificate");
+    std::fs::write(&key_path, key_pem).expect("Failed to write key");
+
+    let config = Configuration {
+        pass_type_id: Some("pass.test.hoofprint".to_string()),
+        pass_certificate: Some(certificate_path.clone()),
+        pass_key: Some(key_path.clone()),
+        ..Configuration::test()
+    };
+    let (mut server, _db) = setup_test_server_with_config(config).await;
+
+    log_in_as(&mut server, TEST_USER_EMAIL, TEST_USER_PASSWORD).await;
+
+    let code_id = create_code_with(
+        &server,
+        CreateCodeForm {
+            code_type: Code::QR.to_string(),
+            code_value: "member-1234".to_string(),
+            site_id: Uuid::nil().to_string(),
+            code_name: Some("Gym".to_string()),
+            ..Default::default()
+        },
+    )
+    .await;
+    let location = format!("/view/{}", code_id);
+
+    let response = server.get(&location).await;
+    response.assert_text_contains("Add to Apple Wallet");
+
+    let response = server.get(&format!("{}/pass.pkpass", location)).await;
+    assert_eq!(response.status_code(), 200);
+    assert_eq!(
+        response
+            .headers()
+            .get("Content-Type")
+            .expect("Content-Type header missing"),
+        MIME_TYPE_PKPASS
+    );
+    // passes are zip files
+    assert!(response.as_bytes().starts_with(b"PK"));
+
+    let _ = std::fs::remove_file(certificate_path);
+    let _ = std::fs::remove_file(key_path);
+
+    // without signing set up there's no pass to download
+    let (mut server, _db) = setup_test_server().await;
+    log_in_as(&mut server, TEST_USER_EMAIL, TEST_USER_PASSWORD).await;
+    let code_id = create_code_with(
+        &server,
+        CreateCodeForm {
+            code_type: Code::QR.to_string(),
+            code_value: "member-1234".to_string(),
+            site_id: Uuid::nil().to_string(),
+            ..Default::default()
+        },
+    )
+    .await;
+    let location = format!("/view/{}", code_id);
+    let response = server.get(&location).await;
+    assert!(!response.text().contains("Add to Apple Wallet"));
+    let response = server.get(&format!("{}/pass.pkpass", location)).await;
+    assert_eq!(response.status_code(), 404);
+}
+
+#[tokio::test]
+async fn test_payload_code() {
+    let (mut server, db) = setup_test_server().await;
+    log_in_as(&mut server, TEST_USER_EMAIL, TEST_USER_PASSWORD).await;
+
+    let response = server.get(Urls::Create.as_ref()).await;
+    response.assert_text_contains(r#"<form method="post" action="/create/payload">"#);
+
+    let response = server
+        .post(Urls::CreatePayload.as_ref())
+        .form(&CreatePayloadForm {
+            site_id: Uuid::nil().to_string(),
+            code_name: Some("Home Wi-Fi".to_string()),
+            payload: PayloadForm {
+                payload_type: "wifi".to_string(),
+                wifi_ssid: "Home;Net".to_string(),
+                wifi_password: "correct horse".to_string(),
+                wifi_security: "WPA".to_string(),
+                ..Default::default()
+            },
+        })
+        .await;
+    assert_eq!(response.status_code(), 303);
+    let location = response
+        .headers()
+        .get("Location")
+        .expect("Location header missing")
+        .to_str()
+        .expect("Failed to convert Location header to str")
+        .to_string();
+    let code_id = location.trim_start_matches("/view/").to_string();
+
+    let saved = code::Entity::find_by_id(Uuid::parse_str(&code_id).expect("Invalid code ID"))
+        .one(&db)
+        .await
+        .expect("Failed to query code")
+        .expect("Code not found");
+    assert_eq!(saved.type_, Code::QR.to_string());
+    let cipher = test_user_cipher(&db).await;
+    assert_eq!(
+        saved
+            .clone()
+            .decrypt(&cipher)
+            .expect("Failed to decrypt")
+            .value,
+        r"WIFI:T:WPA;S:Home\;Net;P:correct horse;;"
+    );
+
+    let response = server.get(&location).await;
+    response.assert_text_contains("<caption>Wi-Fi network</caption>");
+    response.assert_text_contains("<td>Home;Net</td>");
+
+    // the edit page fills the builder back in
+    let response = server.get(&format!("/edit/{}", code_id)).await;
+    response.assert_text_contains(format!(r#"action="/edit/{}/payload""#, code_id));
+    response.assert_text_contains(r#"value="correct horse""#);
+
+    let response = server
+        .post(&format!("/edit/{}/payload", code_id))
+        .form(&PayloadForm {
+            payload_type: "wifi".to_string(),
+            wifi_ssid: "Home;Net".to_string(),
+            wifi_security: "nopass".to_string(),
+            ..Default::default()
+        })
+        .await;
+    assert_eq!(response.status_code(), 303);
+    let saved = code::Entity::find_by_id(saved.id)
+        .one(&db)
+        .await
+        .expect("Failed to query code")
+        .expect("Code not found");
+    assert!(saved.last_updated.is_some());
+    assert_eq!(
+        saved.decrypt(&cipher).expect("Failed to decrypt").value,
+        r"WIFI:T:nopass;S:Home\;Net;;"
+    );
+
+    // invalid fields are rejected
+    let response = server
+        .post(&format!("/edit/{}/payload", code_id))
+        .form(&PayloadForm {
+            payload_type: "url".to_string(),
+            url: "not a link".to_string(),
+            ..Default::default()
+        })
+        .await;
+    assert_eq!(response.status_code(), 400);
+
+    // other codes don't get the builder
+    let code_id = create_code(&server, None, "https://example.com").await;
+    let location = format!("/view/{}", code_id);
+    assert!(!server.get(&location).await.text().contains("payload_table"));
+    let response = server
+        .post(&format!("/edit/{}/payload", code_id))
+        .form(&PayloadForm {
+            payload_type: "url".to_string(),
+            url: "https://example.com".to_string(),
+            ..Default::default()
+        })
+        .await;
+    assert_eq!(response.status_code(), 400);
+}
+
+#[tokio::test]
+async fn test_code_encryption() {
+    let (server, db) = setup_test_server().await;
+    let login = |password: String| {
+        server.post(Urls::Login.as_ref()).form(&LoginForm {
+            email: TEST_USER_EMAIL.to_string(),
+            password,
+            error: None,
+            success: None,
+        })
+    };
+    assert_eq!(
+        login(TEST_USER_PASSWORD.to_string()).await.status_code(),
+        303
+    );
+
+    let code_id = create_code(&server, Some("Library"), "member-8675309").await;
+    let location = format!("/view/{}", code_id);
+    let code_id = Uuid::parse_str(&code_id).expect("Invalid code ID");
+
+    let saved = code::Entity::find_by_id(code_id)
+        .one(&db)
+        .await
+        .expect("Failed to query code")
+        .expect("Code not found");
+    assert!(is_sealed(&saved.value));
+    assert!(!saved.value.contains("member-8675309"));
+    // names are only sealed when it's turned on
+    assert_eq!(saved.name.as_deref(), Some("Library"));
+    server
+        .get(&location)
+        .await
+        .assert_text_contains("member-8675309");
+
+    // an admin reset locks the codes saved before it
+    let new_password = user::reset_password_by_email(&db, TEST_USER_EMAIL)
+        .await
+        .expect("Failed to reset password");
+    server.get(Urls::Logout.as_ref()).await;
+    assert_eq!(login(new_password.clone()).await.status_code(), 303);
+    let response = server.get(Urls::Home.as_ref()).await;
+    response.assert_text_contains("Recover them with your old password");
+    assert!(!response.text().contains("member-8675309"));
+    assert_eq!(server.get(&location).await.status_code(), 423);
+
+    let response = server
+        .post(Urls::AccountRecover.as_ref())
+        .form(&[("old_password", "not my password")])
+        .await;
+    response.assert_text_contains("That password didn&#39;t unlock any codes");
+    let response = server
+        .post(Urls::AccountRecover.as_ref())
+        .form(&[("old_password", TEST_USER_PASSWORD)])
+        .await;
+    response.assert_text_contains("Recovered 1 code(s).");
+    server
+        .get(&location)
+        .await
+        .assert_text_contains("member-8675309");
+    let response = server.get(Urls::Account.as_ref()).await;
+    assert!(!response.text().contains("Recover codes"));
+
+    // changing the password keeps the same key
+    let response = server
+        .post(Urls::AccountPassword.as_ref())
+        .form(&[
+            ("current_password", "wrong"),
+            ("new_password", "correct horse"),
+            ("confirm_password", "correct horse"),
+        ])
+        .await;
+    response.assert_text_contains("Current password is incorrect");
+    let response = server
+        .post(Urls::AccountPassword.as_ref())
+        .form(&[
+            ("current_password", new_password.as_str()),
+            ("new_password", "correct horse"),
+            ("confirm_password", "correct horse"),
+        ])
+        .await;
+    response.assert_text_contains("Password changed.");
+    server.get(Urls::Logout.as_ref()).await;
+    assert_eq!(login("correct horse".to_string()).await.status_code(), 303);
+    server
+        .get(&location)
+        .await
+        .assert_text_contains("member-8675309");
+}
+
+#[tokio::test]
+async fn test_code_history() {
+    let (mut server, db) = setup_test_server().await;
+    log_in_as(&mut server, TEST_USER_EMAIL, TEST_USER_PASSWORD).await;
+
+    let code_id = create_code(&server, Some("Gym"), "card-0001").await;
+    let location = format!("/view/{}", code_id);
+    let code_id = Uuid::parse_str(&code_id).expect("Invalid code ID");
+
+    let response = server.get(&format!("{}/history", location)).await;
+    assert_eq!(response.status_code(), 200);
+    response.assert_text_contains("hasn't been changed");
+
+    let response = server
+        .post(&format!("/edit/{}", code_id))
+        .form(&[
+            ("code_type", Code::Code128.to_string()),
+            ("code_value", "card-0002".to_string()),
+            ("site_id", Uuid::nil().to_string()),
+            ("code_name", "Gym".to_string()),
+            ("expires_at", "2030-01-31".to_string()),
+        ])
+        .await;
+    assert_eq!(response.status_code(), 303);
+
+    // the first edit keeps what the code was created as
+    let revisions = code_revision::Model::find_for_code(&db, code_id)
+        .await
+        .expect("Failed to query revisions");
+    assert_eq!(revisions.len(), 2);
+    assert!(revisions.iter().all(|revision| is_sealed(&revision.value)));
+    let response = server.get(&format!("{}/history", location)).await;
+    response.assert_text_contains(r#"<del class="code_value">card-0001</del>"#);
+    response.assert_text_contains("card-0002");
+    response.assert_text_contains("2030-01-31");
+    response.assert_text_contains("Test User");
+    response.assert_text_contains(format!(
+        "/view/{}/history/{}/restore",
+        code_id, revisions[0].id
+    ));
+    assert!(
+        !response
+            .text()
+            .contains(&format!("/history/{}/restore", revisions[1].id))
+    );
+
+    // restoring adds a revision rather than removing any
+    let response = server
+        .post(&format!(
+            "/view/{}/history/{}/restore",
+            code_id, revisions[0].id
+        ))
+        .await;
+    assert_eq!(response.status_code(), 303);
+    server
+        .get(&location)
+        .await
+        .assert_text_contains("card-0001");
+    let revisions = code_revision::Model::find_for_code(&db, code_id)
+        .await
+        .expect("Failed to query revisions");
+    assert_eq!(revisions.len(), 3);
+    assert_eq!(revisions[2].restored_from, Some(revisions[0].id));
+    // the dates go back too
+    assert_eq!(
+        revisions[1]
+            .expires_at
+            .map(|date| date.to_string())
+            .as_deref(),
+        Some("2030-01-31")
+    );
+    assert_eq!(revisions[2].expires_at, None);
+    let restored = code::Entity::find_by_id(code_id)
+        .one(&db)
+        .await
+        .expect("Failed to query code")
+        .expect("Code not found");
+    assert_eq!(restored.expires_at, None);
+    server
+        .get(&format!("{}/history", location))
+        .await
+        .assert_text_contains("Restored the revision from");
+
+    let response = server
+        .post(&format!(
+            "/view/{}/history/{}/restore",
+            code_id,
+            Uuid::nil()
+        ))
+        .await;
+    assert_eq!(response.status_code(), 404);
+
+    // deleting the code deletes its history
+    let response = server.post(&format!("/delete/{}", code_id)).await;
+    assert_eq!(response.status_code(), 303);
+    assert!(
+        code_revision::Model::find_for_code(&db, code_id)
+            .await
+            .expect("Failed to query revisions")
+            .is_empty()
+    );
+}
+
+#[tokio::test]
+async fn test_code_expiry() {
+    let (mut server, db) = setup_test_server().await;
+    log_in_as(&mut server, TEST_USER_EMAIL, TEST_USER_PASSWORD).await;
+
+    let today = code::today();
+    let days_from_today = |days: usize| {
+        today
+            .iter_days()
+            .nth(days)
+            .expect("Date out of range")
+            .to_string()
+    };
+    let mut locations = Vec::new();
+    for (name, expires_at) in [
+        ("Old Gym", "2020-01-31".to_string()),
+        ("Library", days_from_today(5)),
+    ] {
+        let code_id = create_code_with(
+            &server,
+            CreateCodeForm {
+                code_type: Code::Code128.to_string(),
+                code_value: name.to_uppercase(),
+                site_id: Uuid::nil().to_string(),
+                code_name: Some(name.to_string()),
+                valid_from: Some("".to_string()),
+                expires_at: Some(expires_at),
+                ..Default::default()
+            },
+        )
+        .await;
+        locations.push(format!("/view/{}", code_id));
+    }
+
+    // expired codes go to the bottom
+    let response = server.get(Urls::Home.as_ref()).await;
+    let text = response.text();
+    let library = text.find("Library").expect("Library code missing");
+    let old_gym = text.find("Old Gym").expect("Old Gym code missing");
+    assert!(library < old_gym);
+    response.assert_text_contains("expires in 5 days");
+    response.assert_text_contains(">expired</span>");
+
+    let response = server.get(&locations[0]).await;
+    response.assert_text_contains("Expires 2020-01-31");
+    response.assert_text_contains(">expired</span>");
+
+    // the edit page keeps the dates
+    let code_id = locations[1].trim_start_matches("/view/");
+    let response = server.get(&format!("/edit/{}", code_id)).await;
+    response.assert_text_contains(format!(r#"value="{}""#, days_from_today(5)));
+    let response = server
+        .post(&format!("/edit/{}", code_id))
+        .form(&[
+            ("code_type", Code::Code128.to_string()),
+            ("code_value", "LIBRARY".to_string()),
+            ("site_id", Uuid::nil().to_string()),
+            ("valid_from", days_from_today(2)),
+            ("expires_at", days_from_today(1)),
+        ])
+        .await;
+    assert_eq!(response.status_code(), 400);
+    let response = server
+        .post(&format!("/edit/{}", code_id))
+        .form(&[
+            ("code_type", Code::Code128.to_string()),
+            ("code_value", "LIBRARY".to_string()),
+            ("site_id", Uuid::nil().to_string()),
+            ("valid_from", days_from_today(2)),
+            ("expires_at", "".to_string()),
+        ])
+        .await;
+    assert_eq!(response.status_code(), 303);
+    let saved = code::Entity::find_by_id(Uuid::parse_str(code_id).expect("Invalid code ID"))
+        .one(&db)
+        .await
+        .expect("Failed to query code")
+        .expect("Code not found");
+    assert_eq!(saved.expires_at, None);
+    assert!(matches!(
+        saved.validity(today),
+        code::Validity::NotYetValid(_)
+    ));
+}
+
+#[tokio::test]
+async fn test_code_tags() {
+    let (mut server, db) = setup_test_server().await;
+    log_in_as(&mut server, TEST_USER_EMAIL, TEST_USER_PASSWORD).await;
+
+    let mut code_ids = Vec::new();
+    for (name, tags) in [
+        ("Climbing Gym", Some("Sport, Travel")),
+        ("Airline", Some("travel")),
+        ("Library", None),
+    ] {
+        let code_id = create_code_with(
+            &server,
+            CreateCodeForm {
+                code_type: Code::Code128.to_string(),
+                code_value: name.to_uppercase(),
+                site_id: Uuid::nil().to_string(),
+                code_name: Some(name.to_string()),
+                tags: tags.map(str::to_string),
+                ..Default::default()
+            },
+        )
+        .await;
+        code_ids.push(code_id);
+    }
+
+    // tags are matched ignoring case, and counted in the sidebar
+    let tags = tag::Entity::find()
+        .all(&db)
+        .await
+        .expect("Failed to query tags");
+    assert_eq!(tags.len(), 2);
+    let response = server.get(Urls::Home.as_ref()).await;
+    response.assert_text_contains(r#"<span class="tag_chip">Sport</span>"#);
+    response.assert_text_contains(
+        r#"<a href="/?tag=Travel">Travel</a> <span class="tag_count">2</span>"#,
+    );
+    response.assert_text_contains(r#"All codes</a> <span class="tag_count">3</span>"#);
+
+    let response = server
+        .get(Urls::Home.as_ref())
+        .add_query_param("tag", "TRAVEL")
+        .await;
+    response.assert_text_contains("Climbing Gym");
+    response.assert_text_contains("Airline");
+    assert!(!response.text().contains("LIBRARY"));
+    let response = server
+        .get(Urls::Home.as_ref())
+        .add_query_param("tag", "nothing")
+        .await;
+    response.assert_text_contains("No codes are tagged nothing");
+
+    // editing replaces the tags, and tags nobody uses any more are removed
+    let response = server.get(&format!("/edit/{}", code_ids[0])).await;
+    response.assert_text_contains(r#"value="Sport, Travel""#);
+    let response = server
+        .post(&format!("/edit/{}", code_ids[0]))
+        .form(&[
+            ("code_type", Code::Code128.to_string()),
+            ("code_value", "CLIMBING GYM".to_string()),
+            ("site_id", Uuid::nil().to_string()),
+            ("tags", "travel".to_string()),
+        ])
+        .await;
+    assert_eq!(response.status_code(), 303);
+    let tags = tag::Entity::find()
+        .all(&db)
+        .await
+        .expect("Failed to query tags");
+    assert_eq!(
+        tags.into_iter().map(|tag| tag.name).collect::<Vec<_>>(),
+        vec!["Travel".to_string()]
+    );
+
+    let response = server
+        .post(&format!("/edit/{}", code_ids[2]))
+        .form(&[
+            ("code_type", Code::Code128.to_string()),
+            ("code_value", "LIBRARY".to_string()),
+            ("site_id", Uuid::nil().to_string()),
+            ("tags", "x".repeat(51)),
+        ])
+        .await;
+    assert_eq!(response.status_code(), 400);
+
+    for code_id in &code_ids {
+        let response = server.post(&format!("/delete/{}", code_id)).await;
+        assert_eq!(response.status_code(), 303);
+    }
+    assert!(
+        tag::Entity::find()
+            .all(&db)
+            .await
+            .expect("Failed to query tags")
+            .is_empty()
+    );
+}
+
+#[tokio::test]
+async fn test_code_search() {
+    let (mut server, db) = setup_test_server().await;
+    log_in_as(&mut server, TEST_USER_EMAIL, TEST_USER_PASSWORD).await;
+
+    let mut code_ids = Vec::new();
+    for (name, value, site_name) in [
+        ("Climbing <Gym>", "GYM-0001", Some("Boulder Barn")),
+        ("Library", "LIB-42", Some("City Library")),
+        ("Gym locker", "LOCKER-7", None),
+    ] {
+        let code_id = create_code_with(
+            &server,
+            CreateCodeForm {
+                code_type: Code::Code128.to_string(),
+                code_value: value.to_string(),
+                site_id: Uuid::nil().to_string(),
+                code_name: Some(name.to_string()),
+                new_site_name: site_name.map(str::to_string),
+                ..Default::default()
+            },
+        )
+        .await;
+        code_ids.push(code_id);
+    }
+    // someone else's code never shows up
+    let other_user = user::Model::create_new(db.clone(), "other@example.com", "Other", None)
+        .await
+        .expect("Failed to create other user");
+    code::Model::create_new(
+        db.clone(),
+        &CodeCipher::new(DataKey::generate(), false),
+        other_user.id,
+        Code::Code128,
+        "GYM-9999",
+        Uuid::nil(),
+        Some("Other gym"),
+    )
+    .await
+    .expect("Failed to create other user's code");
+
+    server
+        .get(Urls::Home.as_ref())
+        .await
+        .assert_text_contains(r#"action="/search""#);
+
+    // names are matched by word prefix, and highlighted with the rest escaped
+    let response = server
+        .get(Urls::Search.as_ref())
+        .add_query_param("q", "gym")
+        .await;
+    assert_eq!(response.status_code(), 200);
+    response.assert_text_contains("Climbing &lt;<mark>Gym</mark>&gt;");
+    response.assert_text_contains("<mark>Gym</mark> locker");
+    response.assert_text_contains("<mark>GYM</mark>-0001");
+    assert!(!response.text().contains("GYM-9999"));
+    assert!(!response.text().contains("LIB-42"));
+
+    // values are matched once they're decrypted, and an exact match comes first
+    let response = server
+        .get(Urls::SearchJson.as_ref())
+        .add_query_param("q", "locker-7")
+        .await;
+    assert_eq!(response.status_code(), 200);
+    let json: serde_json::Value = response.json();
+    assert_eq!(json["query"], "locker-7");
+    let results = json["results"].as_array().expect("Results missing");
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0]["code_id"], code_ids[2].as_str());
+    assert_eq!(results[0]["value_html"], "<mark>LOCKER-7</mark>");
+
+    let json: serde_json::Value = server
+        .get(Urls::SearchJson.as_ref())
+        .add_query_param("q", "gym-0001")
+        .await
+        .json();
+    let results = json["results"].as_array().expect("Results missing");
+    assert_eq!(results[0]["code_id"], code_ids[0].as_str());
+
+    // site names are indexed, and renaming a site updates the index
+    let json: serde_json::Value = server
+        .get(Urls::SearchJson.as_ref())
+        .add_query_param("q", "city libr")
+        .await
+        .json();
+    let results = json["results"].as_array().expect("Results missing");
+    assert_eq!(results.len(), 1);
+    assert_eq!(
+        results[0]["site_name_html"],
+        "<mark>City</mark> <mark>Libr</mark>ary"
+    );
+
+    let library_site = site::Model::find_by_name(&db, "City Library")
+        .await
+        .expect("Failed to query site")
+        .expect("Site not found");
+    let mut library_site: site::ActiveModel = library_site.into();
+    library_site.name = sea_orm::ActiveValue::Set("Central Stacks".to_string());
+    sea_orm::ActiveModelTrait::update(library_site, &db)
+        .await
+        .expect("Failed to rename site");
+    let json: serde_json::Value = server
+        .get(Urls::SearchJson.as_ref())
+        .add_query_param("q", "stacks")
+        .await
+        .json();
+    assert_eq!(
+        json["results"].as_array().expect("Results missing").len(),
+        1
+    );
+
+    // deleting a code takes it out of the index
+    let response = server.post(&format!("/delete/{}", code_ids[1])).await;
+    assert_eq!(response.status_code(), 303);
+    let json: serde_json::Value = server
+        .get(Urls::SearchJson.as_ref())
+        .add_query_param("q", "stacks")
+        .await
+        .json();
+    assert!(
+        json["results"]
+            .as_array()
+            .expect("Results missing")
+            .is_empty()
+    );
+
+    let response = server
+        .get(Urls::Search.as_ref())
+        .add_query_param("q", "nowhere")
+        .await;
+    response.assert_text_contains("Nothing matched nowhere.");
+}
+
+#[tokio::test]
+async fn test_code_favourites_and_order() {
+    let (mut server, _db) = setup_test_server().await;
+    log_in_as(&mut server, TEST_USER_EMAIL, TEST_USER_PASSWORD).await;
+
+    let mut code_ids = Vec::new();
+    for value in ["FIRST-CODE", "SECOND-CODE", "THIRD-CODE"] {
+        let code_id = create_code(&server, None, value).await;
+        code_ids.push(code_id);
+    }
+    let homepage_order = async || {
+        let text = server.get(Urls::Home.as_ref()).await.text();
+        let mut values = ["FIRST-CODE", "SECOND-CODE", "THIRD-CODE"];
+        values.sort_by_key(|value| text.find(value));
+        values
+    };
+
+    // new codes go at the end
+    assert_eq!(
+        homepage_order().await,
+        ["FIRST-CODE", "SECOND-CODE", "THIRD-CODE"]
+    );
+
+    let response = server
+        .post(Urls::Reorder.as_ref())
+        .json(&ReorderRequest {
+            code_ids: code_ids
+                .iter()
+                .rev()
+                .map(|id| Uuid::parse_str(id).expect("Invalid code ID"))
+                .collect(),
+        })
+        .await;
+    assert_eq!(response.status_code(), 204);
+    assert_eq!(
+        homepage_order().await,
+        ["THIRD-CODE", "SECOND-CODE", "FIRST-CODE"]
+    );
+
+    // codes that aren't yours can't be ordered
+    let response = server
+        .post(Urls::Reorder.as_ref())
+        .json(&ReorderRequest {
+            code_ids: vec![Uuid::now_v7()],
+        })
+        .await;
+    assert_eq!(response.status_code(), 400);
+
+    // favourites come first, whatever the order
+    let response = server
+        .post(&format!("/favourite/{}", code_ids[0]))
+        .form(&FavouriteForm { favourite: true })
+        .await;
+    assert_eq!(response.status_code(), 303);
+    server
+        .get(&format!("/view/{}", code_ids[0]))
+        .await
+        .assert_text_contains("Remove from Favourites");
+    server
+        .get(Urls::Home.as_ref())
+        .await
+        .assert_text_contains(r#"<div class="code_favourite" title="Favourite">"#);
+    assert_eq!(
+        homepage_order().await,
+        ["FIRST-CODE", "THIRD-CODE", "SECOND-CODE"]
+    );
+
+    server
+        .post(&format!("/favourite/{}", code_ids[0]))
+        .form(&FavouriteForm { favourite: false })
+        .await;
+    assert_eq!(
+        homepage_order().await,
+        ["THIRD-CODE", "SECOND-CODE", "FIRST-CODE"]
+    );
+}
+
+#[tokio::test]
+async fn test_code_usage() {
+    let (mut server, db) = setup_test_server().await;
+    log_in_as(&mut server, TEST_USER_EMAIL, TEST_USER_PASSWORD).await;
+
+    let mut code_ids = Vec::new();
+    for value in ["RARELY-USED", "OFTEN-USED"] {
+        let code_id = create_code(&server, None, value).await;
+        code_ids.push(code_id);
+    }
+
+    // every view is recorded, with the location rounded if the client sends one
+    server.get(&format!("/view/{}", code_ids[0])).await;
+    for _ in 0..2 {
+        server
+            .get(&format!("/view/{}", code_ids[1]))
+            .add_query_param("location", "-33.8688,151.2093")
+            .await;
+    }
+    let usage = code_usage::Entity::find()
+        .all(&db)
+        .await
+        .expect("Failed to query usage");
+    assert_eq!(usage.len(), 3);
+    assert_eq!(
+        usage
+            .iter()
+            .filter(|usage| usage.location.as_deref() == Some("-33.9,151.2"))
+            .count(),
+        2
+    );
+    // images aren't a use of the code
+    server
+        .get(&format!("/view/{}/image.svg", code_ids[0]))
+        .await;
+    assert_eq!(
+        code_usage::Entity::find()
+            .all(&db)
+            .await
+            .expect("Failed to query usage")
+            .len(),
+        3
+    );
+
+    let response = server.get(Urls::Home.as_ref()).await;
+    response.assert_text_contains("Used once, on ");
+    response.assert_text_contains("Used 2 times, last on ");
+    let text = response.text();
+    assert!(text.find("RARELY-USED") < text.find("OFTEN-USED"));
+
+    let response = server
+        .get(Urls::Home.as_ref())
+        .add_query_param("sort", "most_used")
+        .await;
+    response.assert_text_contains("<strong>Most used</strong>");
+    let text = response.text();
+    assert!(text.find("OFTEN-USED") < text.find("RARELY-USED"));
+
+    // opting out forgets what was recorded, and stops recording
+    let response = server
+        .post(Urls::AccountPreferences.as_ref())
+        .form(&PreferencesForm { track_usage: None })
+        .await;
+    response.assert_text_contains("Preferences saved.");
+    server.get(&format!("/view/{}", code_ids[0])).await;
+    assert!(
+        code_usage::Entity::find()
+            .all(&db)
+            .await
+            .expect("Failed to query usage")
+            .is_empty()
+    );
+    assert!(
+        !server
+            .get(Urls::Home.as_ref())
+            .await
+            .text()
+            .contains("Used once")
+    );
+
+    server
+        .post(Urls::AccountPreferences.as_ref())
+        .form(&PreferencesForm {
+            track_usage: Some("on".to_string()),
+        })
+        .await
+        .assert_text_contains(r#"name="track_usage" checked"#);
+    server.get(&format!("/view/{}", code_ids[0])).await;
+    assert_eq!(
+        code_usage::Entity::find()
+            .all(&db)
+            .await
+            .expect("Failed to query usage")
+            .len(),
+        1
+    );
+}
+
+/// Log in as the test user and create a code, returning its ID
+/// Upload a photo of one side of a code's card
+async fn upload_photo(
+    server: &axum_test::TestServer,
+    code_id: &str,
+    side: &str,
+    bytes: Vec<u8>,
+) -> axum_test::TestResponse {
+    server
+        .post(&format!("/edit/{}/attachments", code_id))
+        .multipart(
+            MultipartForm::new().add_text("side", side).add_part(
+                "photo",
+                Part::bytes(bytes)
+                    .file_name("card.png")
+                    .mime_type(MIME_TYPE_PNG),
+            ),
+        )
+        .await
+}
+
+#[tokio::test]
+async fn test_code_notes_and_photos() {
+    let (mut server, db) = setup_test_server().await;
+    let code_id = create_notes_test_code(&mut server).await;
+
+    // notes are rendered from Markdown, with anything dangerous stripped out
+    let response = server
+        .post(&format!("/edit/{}", code_id))
+        .form(&[
+            ("code_type", Code::Code128.to_string()),
+            ("code_value", "LIBRARY-42".to_string()),
+            ("site_id", Uuid::nil().to_string()),
+            ("code_name", "Library card".to_string()),
+            (
+                "notes",
+                "PIN is **1234**\n\n<script>alert(1)</script>[click](javascript:alert(1))"
+                    .to_string(),
+            ),
+        ])
+        .await;
+    assert_eq!(response.status_code(), 303);
+    let response = server.get(&format!("/view/{}", code_id)).await;
+    response.assert_text_contains("PIN is <strong>1234</strong>");
+    assert!(!response.text().contains("<script>alert"));
+    assert!(!response.text().contains(r#"href="javascript:"#));
+    // they're sealed like the value
+    let code_model = code::Entity::find_by_id(Uuid::parse_str(&code_id).expect("Invalid code ID"))
+        .one(&db)
+        .await
+        .expect("Failed to query code")
+        .expect("Code missing");
+    assert!(code_model.notes.as_deref().is_some_and(is_sealed));
+    // and they can be searched once decrypted
+    server
+        .get(Urls::Search.as_ref())
+        .add_query_param("q", "1234")
+        .await
+        .assert_text_contains("Library card");
+    // the edit page shows the Markdown as it was written
+    server
+        .get(&format!("/edit/{}", code_id))
+        .await
+        .assert_text_contains("PIN is **1234**");
+
+    let png_bytes = CodeImage::encode(&Code::QR, "front of card", &RenderOptions::default())
+        .expect("Failed to encode code")
+        .to_png(&RasterOptions::default())
+        .expect("Failed to render PNG");
+    let response = upload_photo(&server, &code_id, "front", png_bytes.clone()).await;
+    assert_eq!(response.status_code(), 303);
+    let response = server
+        .get(&format!("/view/{}/attachments/front", code_id))
+        .await;
+    assert_eq!(response.status_code(), 200);
+    assert_eq!(response.header("Content-Type"), MIME_TYPE_PNG);
+    assert_eq!(response.as_bytes().to_vec(), png_bytes);
+    server
+        .get(&format!("/view/{}", code_id))
+        .await
+        .assert_text_contains(format!(r#"src="/view/{}/attachments/front""#, code_id));
+    // nothing's been uploaded for the back
+    assert_eq!(
+        server
+            .get(&format!("/view/{}/attachments/back", code_id))
+            .await
+            .status_code(),
+        404
+    );
+    // only images browsers show as they are can be uploaded
+    let response = upload_photo(&server, &code_id, "back", b"<svg></svg>".to_vec()).await;
+    assert_eq!(response.status_code(), 400);
+    let response = upload_photo(&server, &code_id, "middle", png_bytes.clone()).await;
+    assert_eq!(response.status_code(), 400);
+
+    // other users can't see the photo
+    let other_user = user::Model::create_new(db.clone(), "other@example.com", "Other", None)
+        .await
+        .expect("Failed to create other user");
+    let other_code = code::Model::create_new(
+        db.clone(),
+        &CodeCipher::new(DataKey::generate(), false),
+        other_user.id,
+        Code::Code128,
+        "OTHER-1",
+        Uuid::nil(),
+        None,
+    )
+    .await
+    .expect("Failed to create other user's code");
+    assert_eq!(
+        upload_photo(&server, &other_code.id.to_string(), "front", png_bytes)
+            .await
+            .status_code(),
+        404
+    );
+    server.get(Urls::Logout.as_ref()).await;
+    assert_ne!(
+        server
+            .get(&format!("/view/{}/attachments/front", code_id))
+            .await
+            .status_code(),
+        200
+    );
+    create_notes_test_code(&mut server).await;
+
+    let response = server
+        .post(&format!("/edit/{}/attachments/front/delete", code_id))
+        .await;
+    assert_eq!(response.status_code(), 303);
+    assert_eq!(
+        server
+            .get(&format!("/view/{}/attachments/front", code_id))
+            .await
+            .status_code(),
+        404
+    );
+}
+
+#[tokio::test]
+async fn test_code_photos_in_attachment_dir() {
+    let attachment_dir = std::env::temp_dir().join(format!("hoofprint-{}", Uuid::now_v7()));
+    let mut config = Configuration::test();
+    config.attachment_dir = Some(attachment_dir.clone());
+    let (mut server, db) = setup_test_server_with_config(config).await;
+    let code_id = create_notes_test_code(&mut server).await;
+
+    let png_bytes = CodeImage::encode(&Code::QR, "back of card", &RenderOptions::default())
+        .expect("Failed to encode code")
+        .to_png(&RasterOptions::default())
+        .expect("Failed to render PNG");
+    let response = upload_photo(&server, &code_id, "back", png_bytes.clone()).await;
+    assert_eq!(response.status_code(), 303);
+
+    // the file is sealed, not the photo as it was uploaded
+    let files: Vec<_> = std::fs::read_dir(&attachment_dir)
+        .expect("Failed to read attachment dir")
+        .collect::<Result<_, _>>()
+        .expect("Failed to list attachment dir");
+    assert_eq!(files.len(), 1);
+    let stored = std::fs::read(files[0].path()).expect("Failed to read attachment");
+    assert_ne!(stored, png_bytes);
+    assert_eq!(
+        server
+            .get(&format!("/view/{}/attachments/back", code_id))
+            .await
+            .as_bytes()
+            .to_vec(),
+        png_bytes
+    );
+
+    // giving the code a key of its own re-seals the file in place, without leaving anything behind
+    let response = server
+        .post(&format!("/view/{}/links", code_id))
+        .form(&[("hours", "24"), ("max_views", "")])
+        .await;
+    assert_eq!(response.status_code(), 303);
+    let files: Vec<_> = std::fs::read_dir(&attachment_dir)
+        .expect("Failed to read attachment dir")
+        .collect::<Result<_, _>>()
+        .expect("Failed to list attachment dir");
+    assert_eq!(files.len(), 1);
+    assert_ne!(
+        std::fs::read(files[0].path()).expect("Failed to read attachment"),
+        stored
+    );
+    assert_eq!(
+        server
+            .get(&format!("/view/{}/attachments/back", code_id))
+            .await
+            .as_bytes()
+            .to_vec(),
+        png_bytes
+    );
+
+    // deleting the code removes its photos too
+    let response = server.post(&format!("/delete/{}", code_id)).await;
+    assert_eq!(response.status_code(), 303);
+    assert_eq!(
+        std::fs::read_dir(&attachment_dir)
+            .expect("Failed to read attachment dir")
+            .count(),
+        0
+    );
+    assert!(
+        code_attachment::Model::find_for_code(
+            &db,
+            Uuid::parse_str(&code_id).expect("Invalid code ID")
+        )
+        .await
+        .expect("Failed to query photos")
+        .is_empty()
+    );
+    std::fs::remove_dir_all(&attachment_dir).expect("Failed to clean up attachment dir");
+}
+
+#[tokio::test]
+async fn test_code_secrets() {
+    let (mut server, db) = setup_test_server().await;
+    let code_id = create_notes_test_code(&mut server).await;
+
+    for (label, value, require_password) in [("PIN", "4321", false), ("CVV", "987", true)] {
+        let mut form = vec![("label", label), ("value", value)];
+        if require_password {
+            form.push(("require_password", "on"));
+        }
+        let response = server
+            .post(&format!("/edit/{}/secrets", code_id))
+            .form(&form)
+            .await;
+        assert_eq!(response.status_code(), 303);
+    }
+    let response = server
+        .post(&format!("/edit/{}/secrets", code_id))
+        .form(&[("label", ""), ("value", "1")])
+        .await;
+    assert_eq!(response.status_code(), 400);
+
+    let secrets =
+        code_secret::Model::find_for_code(&db, Uuid::parse_str(&code_id).expect("Invalid code ID"))
+            .await
+            .expect("Failed to query secrets");
+    assert_eq!(secrets.len(), 2);
+    // values are sealed like the code's value
+    assert!(secrets.iter().all(|secret| is_sealed(&secret.value)));
+
+    // they're masked on the code's page, and left out of lists
+    let response = server.get(&format!("/view/{}", code_id)).await;
+    response.assert_text_contains("PIN");
+    response.assert_text_contains("CVV");
+    assert!(!response.text().contains("4321"));
+    assert!(!response.text().contains("987"));
+    for url in [
+        Urls::Home.as_ref(),
+        "/search?q=library",
+        "/search.json?q=library",
+    ] {
+        let text = server.get(url).await.text();
+        assert!(!text.contains("4321"), "{url} shows a secret");
+    }
+
+    let response = server
+        .post(&format!("/view/{}/secrets/{}", code_id, secrets[0].id))
+        .form(&RevealSecretForm::default())
+        .await;
+    assert_eq!(response.status_code(), 200);
+    assert_eq!(response.header("Cache-Control"), "no-store");
+    response.assert_text_contains("4321");
+
+    // some need the user's password entered again first
+    let reveal_cvv = format!("/view/{}/secrets/{}", code_id, secrets[1].id);
+    let response = server
+        .post(&reveal_cvv)
+        .form(&RevealSecretForm::default())
+        .await;
+    response.assert_text_contains("Enter your password to see this");
+    assert!(!response.text().contains("987"));
+    let response = server
+        .post(&reveal_cvv)
+        .form(&[("password", "not the password")])
+        .await;
+    response.assert_text_contains("That password is incorrect");
+    assert!(!response.text().contains("987"));
+    let response = server
+        .post(&reveal_cvv)
+        .form(&[("password", TEST_USER_PASSWORD)])
+        .await;
+    response.assert_text_contains("987");
+
+    // another code's secret can't be reached through this one
+    let other_code_id = create_notes_test_code(&mut server).await;
+    let response = server
+        .post(&format!(
+            "/view/{}/secrets/{}",
+            other_code_id, secrets[0].id
+        ))
+        .form(&RevealSecretForm::default())
+        .await;
+    assert_eq!(response.status_code(), 404);
+
+    let response = server
+        .post(&format!(
+            "/edit/{}/secrets/{}/delete",
+            code_id, secrets[0].id
+        ))
+        .await;
+    assert_eq!(response.status_code(), 303);
+    assert!(
+        !server
+            .get(&format!("/view/{}", code_id))
+            .await
+            .text()
+            .contains("PIN")
+    );
 }
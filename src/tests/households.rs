@@ -0,0 +1,238 @@
+use super::{TEST_USER_EMAIL, TEST_USER_PASSWORD};
+
+use sea_orm::EntityTrait;
+use uuid::Uuid;
+
+use crate::{
+    Code,
+    crypto::DataKey,
+    db::entities::{code, household_member, user},
+    prelude::Urls,
+    tests::{create_notes_test_code, log_in_as, setup_test_server},
+};
+
+#[tokio::test]
+async fn test_households() {
+    let (mut server, db) = setup_test_server().await;
+    for (email, name) in [
+        ("partner@example.com", "Partner"),
+        ("stranger@example.com", "Stranger"),
+    ] {
+        user::Model::create_new(db.clone(), email, name, Some("password"))
+            .await
+            .expect("Failed to create user");
+        log_in_as(&mut server, email, "password").await;
+    }
+    log_in_as(&mut server, TEST_USER_EMAIL, TEST_USER_PASSWORD).await;
+    let code_id = create_notes_test_code(&mut server).await;
+    let view_url = format!("/view/{}", code_id);
+
+    let response = server
+        .post(Urls::Households.as_ref())
+        .form(&[("name", "The Smiths")])
+        .await;
+    assert_eq!(response.status_code(), 303);
+    let household_url = response
+        .header("location")
+        .to_str()
+        .expect("Bad location")
+        .to_string();
+    let household_id = household_url
+        .rsplit('/')
+        .next()
+        .expect("No household ID")
+        .to_string();
+    server
+        .get(Urls::Households.as_ref())
+        .await
+        .assert_text_contains("The Smiths");
+    let response = server
+        .post(&format!("{}/members", household_url))
+        .form(&[("email", "partner@example.com"), ("role", "viewer")])
+        .await;
+    assert_eq!(response.status_code(), 303);
+
+    // the code's still the user's own until it's given to the household
+    log_in_as(&mut server, "partner@example.com", "password").await;
+    assert_eq!(server.get(&view_url).await.status_code(), 404);
+    server
+        .get(&household_url)
+        .await
+        .assert_text_contains("0 code(s)");
+
+    log_in_as(&mut server, TEST_USER_EMAIL, TEST_USER_PASSWORD).await;
+    let response = server
+        .post(&format!("{}/household", view_url))
+        .form(&[("household_id", household_id.as_str())])
+        .await;
+    assert_eq!(response.status_code(), 303);
+    let code_model = code::Entity::find_by_id(Uuid::parse_str(&code_id).expect("Invalid code ID"))
+        .one(&db)
+        .await
+        .expect("Failed to query code")
+        .expect("Code not found");
+    assert_eq!(
+        code_model.household_id.map(|id| id.to_string()),
+        Some(household_id.clone())
+    );
+    let response = server.get(&view_url).await;
+    response.assert_text_contains("LIBRARY-42");
+    response.assert_text_contains("Owned by The Smiths");
+    server
+        .get(Urls::Home.as_ref())
+        .await
+        .assert_text_contains("LIBRARY-42");
+
+    // a viewer sees the household's codes straight away, but can't change them
+    log_in_as(&mut server, "partner@example.com", "password").await;
+    let response = server.get(Urls::Home.as_ref()).await;
+    response.assert_text_contains("LIBRARY-42");
+    response.assert_text_contains("Owned by The Smiths");
+    server
+        .get(&view_url)
+        .await
+        .assert_text_contains("LIBRARY-42");
+    server
+        .get("/search.json?q=library")
+        .await
+        .assert_text_contains(&code_id);
+    let edit_form = [
+        ("code_type", Code::Code128.to_string()),
+        ("code_value", "LIBRARY-43".to_string()),
+        ("site_id", Uuid::nil().to_string()),
+    ];
+    assert_eq!(
+        server
+            .post(&format!("/edit/{}", code_id))
+            .form(&edit_form)
+            .await
+            .status_code(),
+        403
+    );
+    assert_eq!(
+        server
+            .post(&format!("{}/members", household_url))
+            .form(&[("email", "stranger@example.com"), ("role", "viewer")])
+            .await
+            .status_code(),
+        403
+    );
+
+    log_in_as(&mut server, TEST_USER_EMAIL, TEST_USER_PASSWORD).await;
+    let members = household_member::Model::find_for_household(
+        &db,
+        Uuid::parse_str(&household_id).expect("Invalid household ID"),
+    )
+    .await
+    .expect("Failed to query members");
+    let (admin, partner) = (&members[0].0, &members[1].0);
+    // the household can't be left without an admin
+    let response = server
+        .post(&format!("{}/members/{}/remove", household_url, admin.id))
+        .await;
+    assert_eq!(response.status_code(), 400);
+    let response = server
+        .post(&format!("{}/members/{}", household_url, partner.id))
+        .form(&[("role", "member")])
+        .await;
+    assert_eq!(response.status_code(), 303);
+
+    // members can change the household's codes, but only admins can delete them
+    log_in_as(&mut server, "partner@example.com", "password").await;
+    assert_eq!(
+        server
+            .post(&format!("/edit/{}", code_id))
+            .form(&edit_form)
+            .await
+            .status_code(),
+        303
+    );
+    assert_eq!(
+        server
+            .post(&format!("/delete/{}", code_id))
+            .await
+            .status_code(),
+        403
+    );
+
+    log_in_as(&mut server, "stranger@example.com", "password").await;
+    assert_eq!(server.get(&household_url).await.status_code(), 404);
+    assert_eq!(server.get(&view_url).await.status_code(), 404);
+
+    // someone who leaves loses access straight away
+    log_in_as(&mut server, TEST_USER_EMAIL, TEST_USER_PASSWORD).await;
+    let response = server
+        .post(&format!("{}/members", household_url))
+        .form(&[("email", "stranger@example.com"), ("role", "viewer")])
+        .await;
+    assert_eq!(response.status_code(), 303);
+    let partner_user = user::Entity::find_by_id(partner.user_id)
+        .one(&db)
+        .await
+        .expect("Failed to query partner")
+        .expect("Partner not found");
+    let partner_data_key = DataKey::unwrap_with_password(
+        partner_user
+            .data_key
+            .as_deref()
+            .expect("Partner has no data key"),
+        "password",
+    )
+    .expect("Failed to unwrap partner's data key");
+    let old_household_key = household_member::Entity::find_by_id(partner.id)
+        .one(&db)
+        .await
+        .expect("Failed to query partner's membership")
+        .and_then(|member| partner_data_key.unwrap_key(&member.wrapped_key))
+        .expect("Failed to unwrap partner's household key");
+    let response = server
+        .post(&format!("{}/members/{}/remove", household_url, partner.id))
+        .await;
+    assert_eq!(response.status_code(), 303);
+    server
+        .get(&view_url)
+        .await
+        .assert_text_contains("LIBRARY-43");
+    // the household has a new key, which the key they had can't be used for
+    let code_model = code::Entity::find_by_id(Uuid::parse_str(&code_id).expect("Invalid code ID"))
+        .one(&db)
+        .await
+        .expect("Failed to query code")
+        .expect("Code not found");
+    assert!(
+        old_household_key
+            .unwrap_key(code_model.wrapped_key.as_deref().expect("Code has no key"))
+            .is_none()
+    );
+    // everyone who's left still has a copy of it
+    log_in_as(&mut server, "stranger@example.com", "password").await;
+    server
+        .get(&view_url)
+        .await
+        .assert_text_contains("LIBRARY-43");
+    server
+        .get(&household_url)
+        .await
+        .assert_text_contains("loses access to the household's codes through hoofprint");
+
+    log_in_as(&mut server, "partner@example.com", "password").await;
+    assert_eq!(server.get(&view_url).await.status_code(), 404);
+    assert!(
+        !server
+            .get(Urls::Home.as_ref())
+            .await
+            .text()
+            .contains("LIBRARY")
+    );
+
+    // and the code can be taken back out of the household
+    log_in_as(&mut server, TEST_USER_EMAIL, TEST_USER_PASSWORD).await;
+    let response = server
+        .post(&format!("{}/household", view_url))
+        .form(&[("household_id", "")])
+        .await;
+    assert_eq!(response.status_code(), 303);
+    let response = server.get(&view_url).await;
+    response.assert_text_contains("LIBRARY-43");
+    assert!(!response.text().contains("Owned by The Smiths"));
+}
@@ -5,12 +5,18 @@ use sea_orm::DatabaseConnection;
 use tokio::sync::RwLock;
 
 use crate::{
+    Code,
     config::Configuration,
+    crypto::{CodeCipher, DataKey},
     db::{connect, entities::user},
-    web::{AppState, server_inner},
+    web::{AppState, auth::LoginForm, forms::CreateCodeForm, server_inner},
 };
 
 pub mod codes;
+pub mod households;
+pub mod share_links;
+pub mod shares;
+pub mod sites;
 
 pub(crate) const TEST_USER_NAME: &str = "Test User";
 pub(crate) const TEST_USER_EMAIL: &str = "test@example.com";
@@ -27,13 +33,35 @@ pub(crate) async fn setup_test_user(db: DatabaseConnection) -> user::Model {
     .expect("Failed to create test user")
 }
 
+/// The test user's unlocked data key, for checking what's stored in the database
+pub(crate) async fn test_user_cipher(db: &DatabaseConnection) -> CodeCipher {
+    let user = user::Entity::find()
+        .filter(user::Column::Email.eq(TEST_USER_EMAIL))
+        .one(db)
+        .await
+        .expect("Failed to query test user")
+        .expect("Test user not found");
+    let data_key = DataKey::unwrap_with_password(
+        user.data_key.as_deref().expect("Test user has no data key"),
+        TEST_USER_PASSWORD,
+    )
+    .expect("Failed to unwrap test user's data key");
+    CodeCipher::new(data_key, false)
+}
+
 pub(crate) async fn setup_test_server() -> (TestServer, DatabaseConnection) {
-    let config = Arc::new(RwLock::new(Configuration::test()));
-    let db = connect(config)
+    setup_test_server_with_config(Configuration::test()).await
+}
+
+pub(crate) async fn setup_test_server_with_config(
+    config: Configuration,
+) -> (TestServer, DatabaseConnection) {
+    let config = Arc::new(RwLock::new(config));
+    let db = connect(config.clone())
         .await
         .expect("Failed to connect to test database");
 
-    let apptest = AppState::test_with_db(db.clone()).await;
+    let apptest = AppState::new(db.clone(), config).await;
     let _user = setup_test_user(db.clone()).await;
 
     let (app_server, _cleanup_task) = server_inner(apptest.clone())
@@ -45,3 +73,57 @@ pub(crate) async fn setup_test_server() -> (TestServer, DatabaseConnection) {
 
     (server, db)
 }
+
+/// Log in, instead of whoever was logged in before. Returns the response, whose cookies a test
+/// can switch back to the session with later.
+pub(crate) async fn log_in_as(
+    server: &mut TestServer,
+    email: &str,
+    password: &str,
+) -> axum_test::TestResponse {
+    server.clear_cookies();
+    let response = server
+        .post(Urls::Login.as_ref())
+        .form(&LoginForm {
+            email: email.to_string(),
+            password: password.to_string(),
+            error: None,
+            success: None,
+        })
+        .await;
+    assert_eq!(response.status_code(), 303);
+    response
+}
+
+/// Make a Code 128 code without a site for whoever's logged in, returning its ID
+pub(crate) async fn create_code(server: &TestServer, name: Option<&str>, value: &str) -> String {
+    create_code_with(
+        server,
+        CreateCodeForm {
+            code_type: Code::Code128.to_string(),
+            code_value: value.to_string(),
+            site_id: Uuid::nil().to_string(),
+            code_name: name.map(str::to_string),
+            ..Default::default()
+        },
+    )
+    .await
+}
+
+/// Make a code from a filled in create form, returning its ID
+pub(crate) async fn create_code_with(server: &TestServer, form: CreateCodeForm) -> String {
+    let response = server.post(Urls::Create.as_ref()).form(&form).await;
+    assert_eq!(response.status_code(), 303);
+    response
+        .header("Location")
+        .to_str()
+        .expect("Failed to convert Location header to str")
+        .trim_start_matches("/view/")
+        .to_string()
+}
+
+/// Log in as the test user and make them a library card, returning its ID
+pub(crate) async fn create_notes_test_code(server: &mut TestServer) -> String {
+    log_in_as(server, TEST_USER_EMAIL, TEST_USER_PASSWORD).await;
+    create_code(server, Some("Library card"), "LIBRARY-42").await
+}
@@ -0,0 +1,146 @@
+use super::{TEST_USER_EMAIL, TEST_USER_PASSWORD};
+
+use sea_orm::{ActiveModelTrait, ActiveValue::Set};
+use uuid::Uuid;
+
+use crate::{
+    db::entities::{share_link, user},
+    tests::{create_notes_test_code, log_in_as, setup_test_server},
+};
+
+/// The paths of the public links on a code's page, newest first
+async fn public_link_paths(server: &axum_test::TestServer, code_id: &str) -> Vec<String> {
+    let page = server.get(&format!("/view/{}", code_id)).await.text();
+    page.match_indices("/s/")
+        .map(|(start, _)| {
+            let token: String = page[start + 3..]
+                .chars()
+                .take_while(|c| c.is_ascii_alphanumeric() || *c == '-' || *c == '_')
+                .collect();
+            format!("/s/{}", token)
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn test_share_links() {
+    let (mut server, db) = setup_test_server().await;
+    user::Model::create_new(
+        db.clone(),
+        "stranger@example.com",
+        "Stranger",
+        Some("password"),
+    )
+    .await
+    .expect("Failed to create stranger");
+    let code_id = create_notes_test_code(&mut server).await;
+    let links_url = format!("/view/{}/links", code_id);
+    let response = server
+        .post(&format!("/edit/{}/secrets", code_id))
+        .form(&[("label", "PIN"), ("value", "4321")])
+        .await;
+    assert_eq!(response.status_code(), 303);
+
+    for (hours, max_views) in [("0", ""), ("24", "0"), ("100000", "")] {
+        let response = server
+            .post(&links_url)
+            .form(&[("hours", hours), ("max_views", max_views)])
+            .await;
+        assert_eq!(
+            response.status_code(),
+            400,
+            "{hours} hours, {max_views} views"
+        );
+    }
+    let response = server
+        .post(&links_url)
+        .form(&[("hours", "24"), ("max_views", "2")])
+        .await;
+    assert_eq!(response.status_code(), 303);
+    let paths = public_link_paths(&server, &code_id).await;
+    assert_eq!(paths.len(), 1);
+    let limited = paths[0].clone();
+
+    // the link works without logging in, and only shows the code
+    server.clear_cookies();
+    let response = server.get(&limited).await;
+    assert_eq!(response.status_code(), 200);
+    response.assert_text_contains("LIBRARY-42");
+    response.assert_text_contains("Library card");
+    response.assert_text_contains("<svg");
+    assert!(!response.text().contains("PIN"));
+    assert_eq!(response.header("Cache-Control"), "no-store");
+    assert_eq!(response.header("Referrer-Policy"), "no-referrer");
+    assert_eq!(server.get(&limited).await.status_code(), 200);
+    // and stops working once it's used up
+    assert_eq!(server.get(&limited).await.status_code(), 404);
+    assert_eq!(server.get("/s/not-a-link").await.status_code(), 404);
+    assert_eq!(
+        server
+            .get(&format!("/s/{}", "A".repeat(43)))
+            .await
+            .status_code(),
+        404
+    );
+
+    // nobody else can make or revoke the code's links
+    log_in_as(&mut server, "stranger@example.com", "password").await;
+    let response = server
+        .post(&links_url)
+        .form(&[("hours", "24"), ("max_views", "")])
+        .await;
+    assert_eq!(response.status_code(), 404);
+
+    log_in_as(&mut server, TEST_USER_EMAIL, TEST_USER_PASSWORD).await;
+    let response = server.get(&format!("/view/{}", code_id)).await;
+    response.assert_text_contains("2 / 2 views");
+    response.assert_text_contains("No longer works");
+    let response = server
+        .post(&links_url)
+        .form(&[("hours", "1"), ("max_views", "")])
+        .await;
+    assert_eq!(response.status_code(), 303);
+    let paths = public_link_paths(&server, &code_id).await;
+    assert_eq!(paths.len(), 2);
+    let unlimited = paths[0].clone();
+    assert_ne!(unlimited, limited);
+    for _ in 0..3 {
+        assert_eq!(server.get(&unlimited).await.status_code(), 200);
+    }
+
+    // an expired link doesn't work
+    let code_uuid = Uuid::parse_str(&code_id).expect("Invalid code ID");
+    let links = share_link::Model::find_for_code(&db, code_uuid)
+        .await
+        .expect("Failed to query links");
+    assert_eq!(links.len(), 2);
+    // the token itself isn't stored
+    let token = unlimited.trim_start_matches("/s/");
+    assert!(!links[0].token_hash.contains(token));
+    assert!(!links[0].sealed_token.contains(token));
+    let mut expired: share_link::ActiveModel = links[0].clone().into();
+    expired.expires_at = Set(links[0].created_at);
+    expired.update(&db).await.expect("Failed to expire link");
+    assert_eq!(server.get(&unlimited).await.status_code(), 404);
+
+    // a revoked link is gone
+    let response = server
+        .post(&format!("/view/{}/links/{}/revoke", code_id, links[1].id))
+        .await;
+    assert_eq!(response.status_code(), 303);
+    assert_eq!(public_link_paths(&server, &code_id).await.len(), 1);
+    let response = server
+        .post(&format!("/view/{}/links/{}/revoke", code_id, links[1].id))
+        .await;
+    assert_eq!(response.status_code(), 404);
+
+    // and deleting the code takes its links with it
+    let response = server.post(&format!("/delete/{}", code_id)).await;
+    assert_eq!(response.status_code(), 303);
+    assert!(
+        share_link::Model::find_for_code(&db, code_uuid)
+            .await
+            .expect("Failed to query links")
+            .is_empty()
+    );
+}
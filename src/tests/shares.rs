@@ -0,0 +1,220 @@
+use super::{TEST_USER_EMAIL, TEST_USER_PASSWORD};
+
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
+use uuid::Uuid;
+
+use crate::{
+    Code,
+    crypto::is_public_wrapped,
+    db::entities::{code, code_secret, code_share, user},
+    prelude::Urls,
+    tests::{create_notes_test_code, log_in_as, setup_test_server},
+    web::forms::RevealSecretForm,
+};
+
+#[tokio::test]
+async fn test_code_sharing() {
+    let (mut server, db) = setup_test_server().await;
+    user::Model::create_new(
+        db.clone(),
+        "friend@example.com",
+        "Friend",
+        Some("friend password"),
+    )
+    .await
+    .expect("Failed to create friend");
+    user::Model::create_new(
+        db.clone(),
+        "stranger@example.com",
+        "Stranger",
+        Some("password"),
+    )
+    .await
+    .expect("Failed to create stranger");
+    let code_id = create_notes_test_code(&mut server).await;
+    let share_url = format!("/view/{}/shares", code_id);
+    let response = server
+        .post(&format!("/edit/{}/secrets", code_id))
+        .form(&[("label", "PIN"), ("value", "4321")])
+        .await;
+    assert_eq!(response.status_code(), 303);
+
+    // people have to have logged in once, so they've got a key to share with
+    let response = server
+        .post(&share_url)
+        .form(&[("email", "friend@example.com"), ("permission", "view")])
+        .await;
+    assert_eq!(response.status_code(), 400);
+    response.assert_text_contains("needs to log in once");
+    for (email, status) in [("nobody@example.com", 400), (TEST_USER_EMAIL, 400)] {
+        let response = server
+            .post(&share_url)
+            .form(&[("email", email), ("permission", "view")])
+            .await;
+        assert_eq!(response.status_code(), status, "sharing with {email}");
+    }
+
+    let friend_cookies = log_in_as(&mut server, "friend@example.com", "friend password")
+        .await
+        .cookies();
+    log_in_as(&mut server, "stranger@example.com", "password").await;
+    log_in_as(&mut server, TEST_USER_EMAIL, TEST_USER_PASSWORD).await;
+    let response = server
+        .post(&share_url)
+        .form(&[("email", "friend@example.com"), ("permission", "view")])
+        .await;
+    assert_eq!(response.status_code(), 303);
+
+    // the friend can use it straight away, without their key being re-wrapped until they next
+    // log in
+    server.clear_cookies();
+    server.add_cookies(friend_cookies);
+    server
+        .get(Urls::Home.as_ref())
+        .await
+        .assert_text_contains("LIBRARY-42");
+    let friend_share = || async {
+        code_share::Entity::find()
+            .filter(
+                code_share::Column::CodeId.eq(Uuid::parse_str(&code_id).expect("Invalid code ID")),
+            )
+            .one(&db)
+            .await
+            .expect("Failed to query share")
+            .expect("Share not found")
+    };
+    assert!(is_public_wrapped(&friend_share().await.wrapped_key));
+    log_in_as(&mut server, "friend@example.com", "friend password").await;
+    assert!(!is_public_wrapped(&friend_share().await.wrapped_key));
+    log_in_as(&mut server, TEST_USER_EMAIL, TEST_USER_PASSWORD).await;
+
+    // sharing gives the code a key of its own, so the owner can still read it
+    let code_model = code::Entity::find_by_id(Uuid::parse_str(&code_id).expect("Invalid code ID"))
+        .one(&db)
+        .await
+        .expect("Failed to query code")
+        .expect("Code not found");
+    assert!(code_model.wrapped_key.is_some());
+    let response = server.get(&format!("/view/{}", code_id)).await;
+    response.assert_text_contains("LIBRARY-42");
+    response.assert_text_contains("Friend");
+    response.assert_text_contains("Can view");
+    response.assert_text_contains("Revoking takes the code out of their hoofprint");
+
+    log_in_as(&mut server, "friend@example.com", "friend password").await;
+    let response = server.get(Urls::Home.as_ref()).await;
+    response.assert_text_contains("LIBRARY-42");
+    response.assert_text_contains("Owned by Test User");
+    let response = server.get(&format!("/view/{}", code_id)).await;
+    assert_eq!(response.status_code(), 200);
+    response.assert_text_contains("LIBRARY-42");
+    assert!(!response.text().contains(&format!("/edit/{}", code_id)));
+    // the code's secrets were re-sealed with its key when it was shared
+    let secrets =
+        code_secret::Model::find_for_code(&db, Uuid::parse_str(&code_id).expect("Invalid code ID"))
+            .await
+            .expect("Failed to query secrets");
+    server
+        .post(&format!("/view/{}/secrets/{}", code_id, secrets[0].id))
+        .form(&RevealSecretForm::default())
+        .await
+        .assert_text_contains("4321");
+    server
+        .get("/search.json?q=library")
+        .await
+        .assert_text_contains(&code_id);
+    assert_eq!(
+        server
+            .get(&format!("/edit/{}", code_id))
+            .await
+            .status_code(),
+        403
+    );
+    let edit_form = [
+        ("code_type", Code::Code128.to_string()),
+        ("code_value", "LIBRARY-43".to_string()),
+        ("site_id", Uuid::nil().to_string()),
+    ];
+    assert_eq!(
+        server
+            .post(&format!("/edit/{}", code_id))
+            .form(&edit_form)
+            .await
+            .status_code(),
+        403
+    );
+    // only the owner can share it on
+    assert_eq!(
+        server
+            .post(&share_url)
+            .form(&[("email", "stranger@example.com"), ("permission", "view")])
+            .await
+            .status_code(),
+        403
+    );
+
+    log_in_as(&mut server, TEST_USER_EMAIL, TEST_USER_PASSWORD).await;
+    let response = server
+        .post(&share_url)
+        .form(&[("email", "friend@example.com"), ("permission", "edit")])
+        .await;
+    assert_eq!(response.status_code(), 303);
+
+    log_in_as(&mut server, "friend@example.com", "friend password").await;
+    let response = server
+        .post(&format!("/edit/{}", code_id))
+        .form(&edit_form)
+        .await;
+    assert_eq!(response.status_code(), 303);
+    // but they still can't delete it
+    assert_eq!(
+        server
+            .post(&format!("/delete/{}", code_id))
+            .await
+            .status_code(),
+        403
+    );
+
+    log_in_as(&mut server, "stranger@example.com", "password").await;
+    assert_eq!(
+        server
+            .get(&format!("/view/{}", code_id))
+            .await
+            .status_code(),
+        404
+    );
+    assert!(
+        !server
+            .get(Urls::Home.as_ref())
+            .await
+            .text()
+            .contains("LIBRARY")
+    );
+
+    log_in_as(&mut server, TEST_USER_EMAIL, TEST_USER_PASSWORD).await;
+    server
+        .get(&format!("/view/{}", code_id))
+        .await
+        .assert_text_contains("LIBRARY-43");
+    let shares =
+        code_share::Model::find_for_code(&db, Uuid::parse_str(&code_id).expect("Invalid code ID"))
+            .await
+            .expect("Failed to query shares");
+    assert_eq!(shares.len(), 1);
+    let response = server
+        .post(&format!(
+            "/view/{}/shares/{}/revoke",
+            code_id, shares[0].0.id
+        ))
+        .await;
+    assert_eq!(response.status_code(), 303);
+
+    log_in_as(&mut server, "friend@example.com", "friend password").await;
+    assert_eq!(
+        server
+            .get(&format!("/view/{}", code_id))
+            .await
+            .status_code(),
+        404
+    );
+}
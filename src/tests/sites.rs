@@ -0,0 +1,442 @@
+use super::{TEST_USER_EMAIL, TEST_USER_PASSWORD};
+
+use axum_test::multipart::{MultipartForm, Part};
+use sea_orm::{ActiveModelTrait, ActiveValue::Set, ColumnTrait, EntityTrait, QueryFilter};
+use uuid::Uuid;
+
+use crate::{
+    Code,
+    db::entities::{code, code_revision, site, user},
+    prelude::Urls,
+    render::{CodeImage, MIME_TYPE_PNG, RasterOptions, RenderOptions},
+    tests::{create_notes_test_code, log_in_as, setup_test_server},
+    web::forms::CreateCodeForm,
+};
+
+#[tokio::test]
+async fn test_admin_sites() {
+    let (mut server, db) = setup_test_server().await;
+    user::Model::create_new(
+        db.clone(),
+        "stranger@example.com",
+        "Stranger",
+        Some("password"),
+    )
+    .await
+    .expect("Failed to create stranger");
+    let test_user = user::Entity::find()
+        .filter(user::Column::Email.eq(TEST_USER_EMAIL))
+        .one(&db)
+        .await
+        .expect("Failed to query test user")
+        .expect("Test user not found");
+    let mut test_user: user::ActiveModel = test_user.into();
+    test_user.groups = Set(serde_json::json!(["admin"]));
+    test_user.update(&db).await.expect("Failed to make admin");
+
+    // only admins can manage sites
+    log_in_as(&mut server, "stranger@example.com", "password").await;
+    assert_eq!(
+        server.get(Urls::AdminSites.as_ref()).await.status_code(),
+        403
+    );
+    let response = server
+        .post(Urls::AdminSites.as_ref())
+        .form(&[("name", "Costco"), ("url", "")])
+        .await;
+    assert_eq!(response.status_code(), 403);
+
+    let code_id = create_notes_test_code(&mut server).await;
+    // the sites pages' forms carry the session's CSRF token
+    let response = server
+        .post(Urls::AdminSites.as_ref())
+        .form(&[("name", "Costco"), ("url", ""), ("csrf_token", "forged")])
+        .await;
+    assert_eq!(response.status_code(), 400);
+    let csrf_token = sites_csrf_token(&server).await;
+    for (name, url) in [
+        ("City Library", "https://library.example.com"),
+        ("Library", ""),
+        ("Costco", ""),
+    ] {
+        let response = server
+            .post(Urls::AdminSites.as_ref())
+            .form(&[("name", name), ("url", url), ("csrf_token", &csrf_token)])
+            .await;
+        assert_eq!(response.status_code(), 303, "creating {name}");
+    }
+    for (name, url) in [(" city library ", ""), ("Gym", "ftp://gym.example.com")] {
+        let response = server
+            .post(Urls::AdminSites.as_ref())
+            .form(&[("name", name), ("url", url), ("csrf_token", &csrf_token)])
+            .await;
+        assert_eq!(response.status_code(), 400, "creating {name}");
+    }
+    let find_site = |name: &'static str| {
+        let db = db.clone();
+        async move {
+            site::Model::find_by_name(&db, name)
+                .await
+                .expect("Failed to query sites")
+                .expect("Site not found")
+        }
+    };
+    let library = find_site("City Library").await;
+    let duplicate = find_site("Library").await;
+    let costco = find_site("Costco").await;
+    let response = server.get(Urls::AdminSites.as_ref()).await;
+    response.assert_text_contains("City Library");
+    response.assert_text_contains("https://library.example.com");
+
+    // the duplicate's codes move to the site it's merged into, history and all
+    let response = server
+        .post(&format!("/edit/{}", code_id))
+        .form(&[
+            ("code_type", Code::Code128.to_string()),
+            ("code_value", "LIBRARY-42".to_string()),
+            ("site_id", duplicate.id.to_string()),
+            ("code_name", "Library card".to_string()),
+        ])
+        .await;
+    assert_eq!(response.status_code(), 303);
+    let site_url = format!("{}/{}", Urls::AdminSites.as_ref(), duplicate.id);
+    server
+        .get(&site_url)
+        .await
+        .assert_text_contains("1 code(s) use this site");
+    let response = server
+        .post(&format!("{}/delete", site_url))
+        .form(&[("csrf_token", &csrf_token)])
+        .await;
+    assert_eq!(response.status_code(), 400);
+    response.assert_text_contains("merge it into another site");
+    let response = server
+        .post(&format!("{}/merge", site_url))
+        .form(&[
+            ("site_id", duplicate.id.to_string()),
+            ("csrf_token", csrf_token.clone()),
+        ])
+        .await;
+    assert_eq!(response.status_code(), 400);
+    let response = server
+        .post(&format!("{}/merge", site_url))
+        .form(&[
+            ("site_id", library.id.to_string()),
+            ("csrf_token", csrf_token.clone()),
+        ])
+        .await;
+    assert_eq!(response.status_code(), 303);
+    assert_eq!(server.get(&site_url).await.status_code(), 404);
+    let code_uuid = Uuid::parse_str(&code_id).expect("Invalid code ID");
+    let code_model = code::Entity::find_by_id(code_uuid)
+        .one(&db)
+        .await
+        .expect("Failed to query code")
+        .expect("Code not found");
+    assert_eq!(code_model.site_id, library.id);
+    let revisions = code_revision::Entity::find()
+        .filter(code_revision::Column::CodeId.eq(code_uuid))
+        .all(&db)
+        .await
+        .expect("Failed to query revisions");
+    assert!(!revisions.is_empty());
+    assert!(
+        revisions
+            .iter()
+            .all(|revision| revision.site_id != duplicate.id)
+    );
+    server
+        .get(&format!("/view/{}/history", code_id))
+        .await
+        .assert_text_contains("City Library");
+
+    // sites can be renamed, but not to another site's name
+    let library_url = format!("{}/{}", Urls::AdminSites.as_ref(), library.id);
+    let response = server
+        .post(&library_url)
+        .form(&[("name", "costco"), ("url", ""), ("csrf_token", &csrf_token)])
+        .await;
+    assert_eq!(response.status_code(), 400);
+    let response = server
+        .post(&library_url)
+        .form(&[
+            ("name", "Central Library"),
+            ("url", ""),
+            ("csrf_token", &csrf_token),
+        ])
+        .await;
+    assert_eq!(response.status_code(), 303);
+    server
+        .get(&format!("/view/{}/history", code_id))
+        .await
+        .assert_text_contains("Central Library");
+    server
+        .get("/search.json?q=central")
+        .await
+        .assert_text_contains(&code_id);
+
+    // a site that's in use can't be deleted, and the generic site can't be deleted at all
+    assert_eq!(
+        server
+            .post(&format!("{}/delete", library_url))
+            .form(&[("csrf_token", &csrf_token)])
+            .await
+            .status_code(),
+        400
+    );
+    let generic_url = format!("{}/{}", Urls::AdminSites.as_ref(), Uuid::nil());
+    assert_eq!(
+        server
+            .post(&format!("{}/delete", generic_url))
+            .form(&[("csrf_token", &csrf_token)])
+            .await
+            .status_code(),
+        400
+    );
+    let response = server
+        .post(&format!("{}/merge", generic_url))
+        .form(&[
+            ("site_id", costco.id.to_string()),
+            ("csrf_token", csrf_token.clone()),
+        ])
+        .await;
+    assert_eq!(response.status_code(), 400);
+    let response = server
+        .post(&format!(
+            "{}/{}/delete",
+            Urls::AdminSites.as_ref(),
+            costco.id
+        ))
+        .form(&[("csrf_token", &csrf_token)])
+        .await;
+    assert_eq!(response.status_code(), 303);
+    assert!(
+        site::Model::find_by_name(&db, "Costco")
+            .await
+            .expect("Failed to query sites")
+            .is_none()
+    );
+
+    // revisions at a deleted site move to the generic site, so they can still be restored
+    let response = server
+        .post(Urls::AdminSites.as_ref())
+        .form(&[("name", "Gym"), ("url", ""), ("csrf_token", &csrf_token)])
+        .await;
+    assert_eq!(response.status_code(), 303);
+    let gym = find_site("Gym").await;
+    for (code_value, site_id) in [("GYM-7", gym.id), ("LIBRARY-42", library.id)] {
+        let response = server
+            .post(&format!("/edit/{}", code_id))
+            .form(&[
+                ("code_type", Code::Code128.to_string()),
+                ("code_value", code_value.to_string()),
+                ("site_id", site_id.to_string()),
+            ])
+            .await;
+        assert_eq!(response.status_code(), 303);
+    }
+    let gym_revision = code_revision::Entity::find()
+        .filter(code_revision::Column::CodeId.eq(code_uuid))
+        .filter(code_revision::Column::SiteId.eq(gym.id))
+        .one(&db)
+        .await
+        .expect("Failed to query revisions")
+        .expect("Revision at the gym not found");
+    let response = server
+        .post(&format!("{}/{}/delete", Urls::AdminSites.as_ref(), gym.id))
+        .form(&[("csrf_token", &csrf_token)])
+        .await;
+    assert_eq!(response.status_code(), 303);
+    let response = server
+        .post(&format!(
+            "/view/{}/history/{}/restore",
+            code_id, gym_revision.id
+        ))
+        .await;
+    assert_eq!(response.status_code(), 303);
+    let code_model = code::Entity::find_by_id(code_uuid)
+        .one(&db)
+        .await
+        .expect("Failed to query code")
+        .expect("Code not found");
+    assert_eq!(code_model.site_id, Uuid::nil());
+    server
+        .get(&format!("/view/{}", code_id))
+        .await
+        .assert_text_contains("GYM-7");
+}
+
+/// The CSRF token the sites pages' forms carry for the logged in admin
+async fn sites_csrf_token(server: &axum_test::TestServer) -> String {
+    let page = server.get(Urls::AdminSites.as_ref()).await.text();
+    let (_, rest) = page
+        .split_once(r#"name="csrf_token" value=""#)
+        .expect("CSRF token missing");
+    rest.split('"').next().unwrap_or_default().to_string()
+}
+
+#[tokio::test]
+async fn test_site_details() {
+    let (mut server, db) = setup_test_server().await;
+    let test_user = user::Entity::find()
+        .filter(user::Column::Email.eq(TEST_USER_EMAIL))
+        .one(&db)
+        .await
+        .expect("Failed to query test user")
+        .expect("Test user not found");
+    let mut test_user: user::ActiveModel = test_user.into();
+    test_user.groups = Set(serde_json::json!(["admin"]));
+    test_user.update(&db).await.expect("Failed to make admin");
+    log_in_as(&mut server, TEST_USER_EMAIL, TEST_USER_PASSWORD).await;
+    let csrf_token = sites_csrf_token(&server).await;
+
+    let response = server
+        .post(Urls::AdminSites.as_ref())
+        .form(&[
+            ("name", "City Library"),
+            ("url", ""),
+            ("csrf_token", &csrf_token),
+        ])
+        .await;
+    assert_eq!(response.status_code(), 303);
+    let library = site::Model::find_by_name(&db, "City Library")
+        .await
+        .expect("Failed to query sites")
+        .expect("Site not found");
+    let site_url = format!("{}/{}", Urls::AdminSites.as_ref(), library.id);
+    let details = |value_pattern: &'static str| {
+        [
+            ("name", "City Library".to_string()),
+            ("url", String::new()),
+            ("brand_colour", "#1A2B3C".to_string()),
+            ("default_code_type", Code::Code39.to_string()),
+            ("instructions", "Show it at the *front desk*".to_string()),
+            ("value_pattern", value_pattern.to_string()),
+            ("csrf_token", csrf_token.clone()),
+        ]
+    };
+    let response = server.post(&site_url).form(&details("(")).await;
+    assert_eq!(response.status_code(), 400);
+    let response = server.post(&site_url).form(&details(r"LIB-\d{4}")).await;
+    assert_eq!(response.status_code(), 303);
+    let site_class = format!("site_{}", library.id.simple());
+
+    // adding a code from the site starts off with its usual type
+    let response = server
+        .get(&format!("{}?site_id={}", Urls::Create.as_ref(), library.id))
+        .await;
+    response.assert_text_contains(format!(r#"value="{}" selected"#, library.id));
+    response.assert_text_contains(format!(r#"value="{}" selected"#, Code::Code39));
+    let response = server
+        .get(&format!("{}?site_id={}", Urls::Scan.as_ref(), library.id))
+        .await;
+    response.assert_text_contains(format!(r#"data-code-type="{}""#, Code::Code39));
+
+    // the site's codes have to match its pattern
+    let create_form = |code_value: &str| CreateCodeForm {
+        code_type: Code::Code39.to_string(),
+        code_value: code_value.to_string(),
+        site_id: library.id.to_string(),
+        ..Default::default()
+    };
+    let response = server
+        .post(Urls::Create.as_ref())
+        .form(&create_form("LIBRARY-42"))
+        .await;
+    assert_eq!(response.status_code(), 400);
+    response.assert_text_contains("look like a City Library code");
+    let response = server
+        .post(Urls::Create.as_ref())
+        .form(&create_form("LIB-1234"))
+        .await;
+    assert_eq!(response.status_code(), 303);
+    let code_url = response
+        .headers()
+        .get("Location")
+        .expect("Location header missing")
+        .to_str()
+        .expect("Failed to convert Location header to str")
+        .to_string();
+    let code_id = code_url.trim_start_matches("/view/");
+    let response = server
+        .post(&format!("/edit/{}", code_id))
+        .form(&[
+            ("code_type", Code::Code39.to_string()),
+            ("code_value", "LIB-12".to_string()),
+            ("site_id", library.id.to_string()),
+        ])
+        .await;
+    assert_eq!(response.status_code(), 400);
+
+    // the code's page has the site's instructions, tinted with its colour
+    let response = server.get(&code_url).await;
+    response.assert_text_contains("<em>front desk</em>");
+    response.assert_text_contains(&site_class);
+    response.assert_text_contains(format!("?site_id={}", library.id));
+    let response = server.get(Urls::SiteStyles.as_ref()).await;
+    assert_eq!(response.status_code(), 200);
+    response.assert_text_contains(format!(
+        ".{} {{ border-top: 0.35rem solid #1a2b3c;",
+        site_class
+    ));
+    server
+        .get(Urls::Home.as_ref())
+        .await
+        .assert_text_contains(&site_class);
+
+    // logos have to be images, and are only shown to people who are logged in
+    let logo_url = format!("/sites/{}/logo", library.id);
+    assert_eq!(server.get(&logo_url).await.status_code(), 404);
+    let upload_logo = |bytes: Vec<u8>| {
+        server.post(&format!("{}/logo", site_url)).multipart(
+            MultipartForm::new()
+                .add_text("csrf_token", csrf_token.clone())
+                .add_part(
+                    "logo",
+                    Part::bytes(bytes)
+                        .file_name("logo.png")
+                        .mime_type(MIME_TYPE_PNG),
+                ),
+        )
+    };
+    assert_eq!(
+        upload_logo(b"<svg></svg>".to_vec()).await.status_code(),
+        400
+    );
+    let png_bytes = CodeImage::encode(&Code::QR, "logo", &RenderOptions::default())
+        .expect("Failed to encode code")
+        .to_png(&RasterOptions::default())
+        .expect("Failed to render PNG");
+    assert_eq!(upload_logo(png_bytes.clone()).await.status_code(), 303);
+    let response = server.get(&logo_url).await;
+    assert_eq!(response.status_code(), 200);
+    assert_eq!(response.header("Content-Type"), MIME_TYPE_PNG);
+    assert_eq!(response.as_bytes().to_vec(), png_bytes);
+    server.get(&code_url).await.assert_text_contains(&logo_url);
+    server
+        .get(Urls::Home.as_ref())
+        .await
+        .assert_text_contains(&logo_url);
+
+    server.post(Urls::Logout.as_ref()).await;
+    assert_ne!(server.get(&logo_url).await.status_code(), 200);
+    assert_ne!(
+        server.get(Urls::SiteStyles.as_ref()).await.status_code(),
+        200
+    );
+
+    log_in_as(&mut server, TEST_USER_EMAIL, TEST_USER_PASSWORD).await;
+    // a new session gets a new token
+    let response = server
+        .post(&format!("{}/logo/delete", site_url))
+        .form(&[("csrf_token", &csrf_token)])
+        .await;
+    assert_eq!(response.status_code(), 400);
+    let csrf_token = sites_csrf_token(&server).await;
+    let response = server
+        .post(&format!("{}/logo/delete", site_url))
+        .form(&[("csrf_token", &csrf_token)])
+        .await;
+    assert_eq!(response.status_code(), 303);
+    assert_eq!(server.get(&logo_url).await.status_code(), 404);
+}
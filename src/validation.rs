@@ -0,0 +1,314 @@
+//! Symbology-aware validation of code values
+
+use crate::{Code, gs1};
+
+/// Characters Code 39 can encode, apart from the `*` start/stop character
+const CODE39_ALPHABET: &str = "0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ -.$/+%";
+
+/// Characters allowed between Codabar's start and stop characters
+const CODABAR_ALPHABET: &str = "0123456789-$:/.+";
+
+/// Codabar start/stop characters
+const CODABAR_GUARDS: &str = "ABCD";
+
+/// The GS1 mod-10 check digit for a string of digits, as used by EAN/UPC/ITF-14
+pub(crate) fn gs1_check_digit(digits: &str) -> Option<u32> {
+    let mut sum = 0;
+    // weights alternate 3, 1, 3... from the rightmost digit
+    for (index, c) in digits.chars().rev().enumerate() {
+        let digit = c.to_digit(10)?;
+        sum += if index % 2 == 0 { digit * 3 } else { digit };
+    }
+    Some((10 - sum % 10) % 10)
+}
+
+/// Whether the last digit is the correct GS1 check digit for the rest
+fn has_valid_gs1_check_digit(digits: &str) -> bool {
+    match digits.char_indices().last() {
+        Some((index, check)) => gs1_check_digit(&digits[..index])
+            .is_some_and(|expected| check.to_digit(10) == Some(expected)),
+        None => false,
+    }
+}
+
+/// Expand a UPC-E value (number system and six digits, no check digit) to UPC-A
+fn upce_to_upca(digits: &str) -> Option<String> {
+    let d: Vec<char> = digits.chars().collect();
+    if d.len() != 7 || !matches!(d[0], '0' | '1') {
+        return None;
+    }
+    let (ns, body) = (d[0], &d[1..]);
+    let expanded: String = match body[5] {
+        '0'..='2' => [&body[0..2], &[body[5], '0', '0', '0', '0'], &body[2..5]].concat(),
+        '3' => [&body[0..3], &['0', '0', '0', '0', '0'], &body[3..5]].concat(),
+        '4' => [&body[0..4], &['0', '0', '0', '0', '0'], &body[4..5]].concat(),
+        _ => [&body[0..5], &['0', '0', '0', '0'], &body[5..6]].concat(),
+    }
+    .into_iter()
+    .collect();
+    Some(format!("{ns}{expanded}"))
+}
+
+/// The check digit for a UPC-E value given without one
+fn upce_check_digit(digits: &str) -> Option<u32> {
+    gs1_check_digit(&upce_to_upca(digits)?)
+}
+
+/// Whether a value is a valid ISBN-10, which allows `X` as a check digit worth ten
+fn is_isbn10(value: &str) -> bool {
+    let chars: Vec<char> = value.chars().collect();
+    if chars.len() != 10 {
+        return false;
+    }
+    let mut sum = 0;
+    for (index, c) in chars.iter().enumerate() {
+        let digit = match (index, c) {
+            (9, 'X' | 'x') => 10,
+            (_, c) => match c.to_digit(10) {
+                Some(digit) => digit,
+                None => return false,
+            },
+        };
+        sum += digit * (10 - index as u32);
+    }
+    sum % 11 == 0
+}
+
+/// Strip the separators people tend to type into numeric codes
+fn strip_separators(value: &str) -> String {
+    value.chars().filter(|c| !matches!(c, ' ' | '-')).collect()
+}
+
+impl Code {
+    /// The full length of a numeric code including its check digit, for the symbologies that have one
+    fn check_digit_length(&self) -> Option<usize> {
+        match self {
+            Code::Ean13 => Some(13),
+            Code::Ean8 => Some(8),
+            Code::UpcA => Some(12),
+            Code::UpcE => Some(8),
+            Code::Itf14 => Some(14),
+            _ => None,
+        }
+    }
+
+    /// The check digit for a numeric value given without one
+    fn check_digit_for(&self, digits: &str) -> Option<u32> {
+        match self {
+            Code::UpcE => upce_check_digit(digits),
+            _ => gs1_check_digit(digits),
+        }
+    }
+
+    /// Suggest a corrected value when the input is recognisably incomplete.
+    ///
+    /// This adds a missing check digit, drops spaces and hyphens from numeric codes, and turns
+    /// ISBN-10s into the ISBN-13 an EAN-13 needs.
+    pub fn complete_value(&self, value: &str) -> Option<String> {
+        let full_length = self.check_digit_length()?;
+        let digits = strip_separators(value);
+
+        if self == &Code::Ean13 && is_isbn10(&digits) {
+            let isbn13 = format!("978{}", &digits[..9]);
+            let check = gs1_check_digit(&isbn13)?;
+            return Some(format!("{isbn13}{check}"));
+        }
+
+        if !digits.chars().all(|c| c.is_ascii_digit()) {
+            return None;
+        }
+        if digits.len() == full_length - 1 {
+            let check = self.check_digit_for(&digits)?;
+            Some(format!("{digits}{check}"))
+        } else if digits.len() == full_length
+            && digits != value
+            && self.validate_value(&digits).is_ok()
+        {
+            Some(digits)
+        } else {
+            None
+        }
+    }
+
+    /// Check that a value can be encoded in this symbology, returning every problem found
+    pub fn validate_value(&self, value: &str) -> Result<(), Vec<String>> {
+        let mut errors = Vec::new();
+
+        match self {
+            Code::Ean13 | Code::Ean8 | Code::UpcA | Code::UpcE | Code::Itf14 => {
+                let full_length = self.check_digit_length().unwrap_or_default();
+                if !value.chars().all(|c| c.is_ascii_digit()) {
+                    errors.push(format!("{} values can only contain digits", self.label()));
+                } else if value.len() != full_length {
+                    errors.push(format!(
+                        "{} values must be {} digits long, including the check digit",
+                        self.label(),
+                        full_length
+                    ));
+                } else if self == &Code::UpcE && !value.starts_with(['0', '1']) {
+                    errors.push("UPC-E values must start with 0 or 1".to_string());
+                } else {
+                    let (body, check) = value.split_at(full_length - 1);
+                    let valid = match self {
+                        Code::UpcE => upce_check_digit(body)
+                            .is_some_and(|expected| check == expected.to_string()),
+                        _ => has_valid_gs1_check_digit(value),
+                    };
+                    if !valid {
+                        errors.push(format!("{} check digit is incorrect", self.label()));
+                    }
+                }
+            }
+            Code::Code39 => {
+                if let Some(c) = value.chars().find(|c| !CODE39_ALPHABET.contains(*c)) {
+                    errors.push(format!(
+                        "Code 39 can't encode '{}', only uppercase letters, digits, spaces and - . $ / + %",
+                        c
+                    ));
+                }
+            }
+            Code::Codabar => {
+                let first = value.chars().next().filter(|c| CODABAR_GUARDS.contains(*c));
+                let last = value.chars().last().filter(|c| CODABAR_GUARDS.contains(*c));
+                let body = match (first, last) {
+                    (Some(_), Some(_)) if value.len() >= 2 => &value[1..value.len() - 1],
+                    (None, None) => value,
+                    _ => {
+                        errors.push(
+                            "Codabar values must have a start and stop character (A-D) at both ends, or neither"
+                                .to_string(),
+                        );
+                        ""
+                    }
+                };
+                if let Some(c) = body.chars().find(|c| !CODABAR_ALPHABET.contains(*c)) {
+                    errors.push(format!(
+                        "Codabar can't encode '{}', only digits and - $ : / . +",
+                        c
+                    ));
+                }
+            }
+            Code::Code128 => {
+                if let Some(c) = value.chars().find(|c| !c.is_ascii()) {
+                    errors.push(format!(
+                        "Code 128 can't encode '{}', only ASCII characters",
+                        c
+                    ));
+                }
+            }
+            Code::Gs1Code128 | Code::Gs1DataMatrix => {
+                if let Err(gs1_errors) = gs1::validate(self, value) {
+                    errors.extend(gs1_errors);
+                }
+            }
+            // 2D codes can carry arbitrary text
+            Code::QR | Code::Pdf417 | Code::Aztec | Code::DataMatrix => {}
+        }
+
+        if !errors.is_empty()
+            && let Some(suggestion) = self.complete_value(value)
+        {
+            errors.push(format!("Did you mean {}?", suggestion));
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// A valid value for this code type, for tests
+    #[cfg(test)]
+    pub(crate) fn sample_value(&self) -> &'static str {
+        match self {
+            Code::Ean13 => "5901234123457",
+            Code::Ean8 => "96385074",
+            Code::UpcA => "036000291452",
+            Code::UpcE => "01234565",
+            Code::Itf14 => "00012345678905",
+            Code::Codabar => "A12345B",
+            Code::Gs1Code128 | Code::Gs1DataMatrix => "(01)09501101530003(17)260101(10)ABC123",
+            _ => "HELLO-123",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gs1_check_digit() {
+        assert_eq!(gs1_check_digit("590123412345"), Some(7));
+        assert_eq!(gs1_check_digit("9638507"), Some(4));
+        assert_eq!(gs1_check_digit("03600029145"), Some(2));
+        assert_eq!(gs1_check_digit("0001234567890"), Some(5));
+        assert_eq!(gs1_check_digit("12a"), None);
+    }
+
+    #[test]
+    fn test_validate_check_digits() {
+        assert!(Code::Ean13.validate_value("5901234123457").is_ok());
+        assert!(Code::Ean13.validate_value("5901234123458").is_err());
+        assert!(Code::Ean8.validate_value("96385074").is_ok());
+        assert!(Code::Ean8.validate_value("96385075").is_err());
+        assert!(Code::UpcA.validate_value("036000291452").is_ok());
+        assert!(Code::UpcA.validate_value("036000291453").is_err());
+        assert!(Code::UpcE.validate_value("01234565").is_ok());
+        assert!(Code::UpcE.validate_value("01234566").is_err());
+        assert!(Code::UpcE.validate_value("21234565").is_err());
+        assert!(Code::Itf14.validate_value("00012345678905").is_ok());
+        assert!(Code::Itf14.validate_value("00012345678906").is_err());
+        // ISBN-13s are EAN-13s
+        assert!(Code::Ean13.validate_value("9780306406157").is_ok());
+    }
+
+    #[test]
+    fn test_complete_value() {
+        assert_eq!(
+            Code::Ean13.complete_value("590123412345"),
+            Some("5901234123457".to_string())
+        );
+        assert_eq!(
+            Code::UpcE.complete_value("0123456"),
+            Some("01234565".to_string())
+        );
+        assert_eq!(
+            Code::Ean13.complete_value("590 1234 12345"),
+            Some("5901234123457".to_string())
+        );
+        assert_eq!(
+            Code::Ean13.complete_value("590-1234-12345-7"),
+            Some("5901234123457".to_string())
+        );
+        // ISBN-10 to ISBN-13
+        assert_eq!(
+            Code::Ean13.complete_value("0-306-40615-2"),
+            Some("9780306406157".to_string())
+        );
+        assert_eq!(Code::Ean13.complete_value("5901234123457"), None);
+        assert_eq!(Code::Code128.complete_value("12345"), None);
+    }
+
+    #[test]
+    fn test_missing_check_digit_suggestion() {
+        let errors = Code::Ean13
+            .validate_value("590123412345")
+            .expect_err("Missing check digit should fail");
+        assert!(errors.contains(&"Did you mean 5901234123457?".to_string()));
+    }
+
+    #[test]
+    fn test_validate_alphabets() {
+        assert!(Code::Code39.validate_value("ABC-123").is_ok());
+        assert!(Code::Code39.validate_value("abc-123").is_err());
+        assert!(Code::Codabar.validate_value("A12345B").is_ok());
+        assert!(Code::Codabar.validate_value("12345").is_ok());
+        assert!(Code::Codabar.validate_value("A12345").is_err());
+        assert!(Code::Codabar.validate_value("A12E45B").is_err());
+        assert!(Code::Code128.validate_value("Hello, world!").is_ok());
+        assert!(Code::Code128.validate_value("héllo").is_err());
+        assert!(Code::QR.validate_value("héllo").is_ok());
+    }
+}
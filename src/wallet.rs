@@ -0,0 +1,669 @@
+//! Apple Wallet passes
+
+use std::{
+    collections::BTreeMap,
+    io::{Cursor, Read, Write},
+};
+
+use openssl::{
+    nid::Nid,
+    pkcs7::{Pkcs7, Pkcs7Flags},
+    pkey::{PKey, Private},
+    sha::sha1,
+    stack::Stack,
+    x509::X509,
+};
+use serde::{Deserialize, Serialize};
+use zip::{CompressionMethod, ZipArchive, ZipWriter, write::SimpleFileOptions};
+
+use crate::{
+    Code,
+    config::Configuration,
+    db::entities::{code, site},
+    error::HoofprintError,
+};
+
+/// The MIME type for Wallet passes
+pub(crate) const MIME_TYPE_PKPASS: &str = "application/vnd.apple.pkpass";
+
+/// Wallet's names for the barcode formats it can show
+pub(crate) const PK_BARCODE_QR: &str = "PKBarcodeFormatQR";
+pub(crate) const PK_BARCODE_PDF417: &str = "PKBarcodeFormatPDF417";
+pub(crate) const PK_BARCODE_AZTEC: &str = "PKBarcodeFormatAztec";
+pub(crate) const PK_BARCODE_CODE128: &str = "PKBarcodeFormatCode128";
+
+/// The largest `pass.json` we'll read when importing, in bytes
+const MAX_PASS_JSON_SIZE: u64 = 1024 * 1024;
+
+/// Passes need an icon, so they get the hoofprint logo
+const PASS_ICON: &[u8] = include_bytes!("../static/img/128x128logo.png");
+
+impl Code {
+    /// The Wallet barcode format to show this code as.
+    ///
+    /// Wallet only supports four formats. Showing any other code as one of them would put a
+    /// different barcode on the pass than the one on the card, which scanners that only read
+    /// EAN/UPC reject, so those codes can't be exported. GS1 codes can't be shown without the
+    /// FNC1 characters that mark them as GS1.
+    pub fn pass_barcode_format(&self) -> Option<&'static str> {
+        match self {
+            Code::QR => Some(PK_BARCODE_QR),
+            Code::Pdf417 => Some(PK_BARCODE_PDF417),
+            Code::Aztec => Some(PK_BARCODE_AZTEC),
+            Code::Code128 => Some(PK_BARCODE_CODE128),
+            Code::Ean13
+            | Code::Ean8
+            | Code::UpcA
+            | Code::UpcE
+            | Code::Code39
+            | Code::Itf14
+            | Code::Codabar
+            | Code::DataMatrix
+            | Code::Gs1Code128
+            | Code::Gs1DataMatrix => None,
+        }
+    }
+
+    /// The code type for one of Wallet's barcode formats
+    pub fn from_pass_barcode_format(format: &str) -> Option<Code> {
+        match format {
+            PK_BARCODE_QR => Some(Code::QR),
+            PK_BARCODE_PDF417 => Some(Code::Pdf417),
+            PK_BARCODE_AZTEC => Some(Code::Aztec),
+            PK_BARCODE_CODE128 => Some(Code::Code128),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PassBarcode {
+    format: &'static str,
+    message: String,
+    message_encoding: &'static str,
+    alt_text: String,
+}
+
+#[derive(Debug, Serialize)]
+struct PassField {
+    key: &'static str,
+    label: &'static str,
+    value: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct StoreCard {
+    primary_fields: Vec<PassField>,
+    secondary_fields: Vec<PassField>,
+    back_fields: Vec<PassField>,
+}
+
+/// The `pass.json` at the root of a pass
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PassJson {
+    format_version: u8,
+    pass_type_identifier: String,
+    team_identifier: String,
+    serial_number: String,
+    organization_name: String,
+    description: String,
+    logo_text: String,
+    foreground_color: &'static str,
+    background_color: &'static str,
+    label_color: &'static str,
+    barcodes: Vec<PassBarcode>,
+    /// Only read by iOS 8 and earlier, which don't know about `barcodes`
+    barcode: PassBarcode,
+    store_card: StoreCard,
+}
+
+/// A barcode as found in an imported pass
+#[derive(Debug, Deserialize)]
+struct ImportedBarcode {
+    format: String,
+    message: String,
+}
+
+/// The parts of an imported pass's `pass.json` we care about
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ImportedPassJson {
+    organization_name: String,
+    description: Option<String>,
+    #[serde(default)]
+    barcodes: Vec<ImportedBarcode>,
+    barcode: Option<ImportedBarcode>,
+}
+
+/// A code read from a `.pkpass` file
+#[derive(Debug)]
+pub(crate) struct ImportedPass {
+    pub code: Code,
+    pub value: String,
+    pub organization_name: String,
+    pub description: Option<String>,
+}
+
+/// Whether some bytes look like a `.pkpass`, which is a zip file
+pub(crate) fn is_pkpass(bytes: &[u8]) -> bool {
+    bytes.starts_with(b"PK\x03\x04")
+}
+
+/// Read the code out of a `.pkpass` file.
+///
+/// The signature isn't checked, people are importing passes they were sent, not trusting them.
+pub(crate) fn read_pass(bytes: &[u8]) -> Result<ImportedPass, HoofprintError> {
+    let invalid = |reason: &str| {
+        HoofprintError::ValidationError(vec![format!(
+            "That isn't a valid .pkpass file: {}",
+            reason
+        )])
+    };
+
+    let mut archive = ZipArchive::new(Cursor::new(bytes)).map_err(|_| invalid("not a zip file"))?;
+    let pass_json = archive
+        .by_name("pass.json")
+        .map_err(|_| invalid("pass.json is missing"))?;
+    if pass_json.size() > MAX_PASS_JSON_SIZE {
+        return Err(invalid("pass.json is too large"));
+    }
+    let mut contents = Vec::new();
+    pass_json
+        .take(MAX_PASS_JSON_SIZE)
+        .read_to_end(&mut contents)
+        .map_err(|_| invalid("pass.json couldn't be read"))?;
+
+    // some pass generators add a byte order mark
+    let contents = contents.strip_prefix(b"\xef\xbb\xbf").unwrap_or(&contents);
+    let pass: ImportedPassJson =
+        serde_json::from_slice(contents).map_err(|err| invalid(&err.to_string()))?;
+
+    // `barcodes` is in order of preference, `barcode` is the pre-iOS 9 version
+    let all_barcodes: Vec<&ImportedBarcode> =
+        pass.barcodes.iter().chain(pass.barcode.as_ref()).collect();
+    if all_barcodes.is_empty() {
+        return Err(HoofprintError::ValidationError(vec![
+            "That pass doesn't have a barcode".to_string(),
+        ]));
+    }
+    let (code, barcode) = all_barcodes
+        .iter()
+        .find_map(|barcode| {
+            Code::from_pass_barcode_format(&barcode.format).map(|code| (code, barcode))
+        })
+        .ok_or_else(|| {
+            HoofprintError::ValidationError(vec![format!(
+                "That pass's barcode format ({}) isn't supported",
+                all_barcodes
+                    .iter()
+                    .map(|barcode| barcode.format.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )])
+        })?;
+
+    Ok(ImportedPass {
+        code,
+        value: barcode.message.clone(),
+        organization_name: pass.organization_name,
+        description: pass
+            .description
+            .filter(|description| !description.is_empty()),
+    })
+}
+
+/// Signs Wallet passes with the configured certificate
+pub(crate) struct PassSigner {
+    pass_type_id: String,
+    team_id: String,
+    certificate: X509,
+    key: PKey<Private>,
+    chain: Stack<X509>,
+}
+
+impl PassSigner {
+    /// Build a signer from PEM-encoded certificates and key.
+    ///
+    /// The team identifier comes from the certificate's OU, as it does in Apple-issued pass certificates.
+    pub(crate) fn new(
+        pass_type_id: &str,
+        certificate_pem: &[u8],
+        key_pem: &[u8],
+        wwdr_certificate_pem: Option<&[u8]>,
+    ) -> Result<Self, HoofprintError> {
+        let certificate = X509::from_pem(certificate_pem)?;
+        let key = PKey::private_key_from_pem(key_pem)?;
+        let mut chain = Stack::new()?;
+        if let Some(wwdr_certificate_pem) = wwdr_certificate_pem {
+            chain.push(X509::from_pem(wwdr_certificate_pem)?)?;
+        }
+
+        let team_id = certificate
+            .subject_name()
+            .entries_by_nid(Nid::ORGANIZATIONALUNITNAME)
+            .next()
+            .and_then(|entry| entry.data().to_string().ok())
+            .ok_or_else(|| {
+                HoofprintError::InternalError(
+                    "Pass signing certificate has no team identifier (OU)".to_string(),
+                )
+            })?;
+
+        Ok(Self {
+            pass_type_id: pass_type_id.to_string(),
+            team_id,
+            certificate,
+            key,
+            chain,
+        })
+    }
+
+    /// Load the signer from the configured files, or `None` if Wallet passes aren't set up
+    pub(crate) async fn from_config(
+        config: &Configuration,
+    ) -> Result<Option<Self>, HoofprintError> {
+        let (pass_type_id, certificate, key) = match (
+            &config.pass_type_id,
+            &config.pass_certificate,
+            &config.pass_key,
+        ) {
+            (Some(pass_type_id), Some(certificate), Some(key)) => (pass_type_id, certificate, key),
+            (None, None, None) => return Ok(None),
+            _ => {
+                return Err(HoofprintError::InternalError(
+                    "Wallet passes need a pass type ID, certificate and key configured".to_string(),
+                ));
+            }
+        };
+
+        let certificate_pem = tokio::fs::read(certificate).await?;
+        let key_pem = tokio::fs::read(key).await?;
+        let wwdr_certificate_pem = match &config.pass_wwdr_certificate {
+            Some(path) => Some(tokio::fs::read(path).await?),
+            None => None,
+        };
+
+        Self::new(
+            pass_type_id,
+            &certificate_pem,
+            &key_pem,
+            wwdr_certificate_pem.as_deref(),
+        )
+        .map(Some)
+    }
+
+    /// A detached PKCS#7 signature over the manifest
+    fn sign(&self, manifest: &[u8]) -> Result<Vec<u8>, HoofprintError> {
+        let signature = Pkcs7::sign(
+            &self.certificate,
+            &self.key,
+            &self.chain,
+            manifest,
+            Pkcs7Flags::BINARY | Pkcs7Flags::DETACHED,
+        )?;
+        Ok(signature.to_der()?)
+    }
+
+    /// Build a signed store card pass for a code
+    pub(crate) fn build_pass(
+        &self,
+        code_model: &code::Model,
+        site_model: &site::Model,
+    ) -> Result<Vec<u8>, HoofprintError> {
+        let code = Code::try_from(code_model)?;
+        let format = code.pass_barcode_format().ok_or_else(|| {
+            HoofprintError::ValidationError(vec![format!(
+                "Apple Wallet can't show {} codes",
+                code.label()
+            )])
+        })?;
+
+        let barcode = || PassBarcode {
+            format,
+            message: code_model.value.clone(),
+            // Wallet expects ISO-8859-1 unless told otherwise
+            message_encoding: if code_model.value.chars().all(|c| u32::from(c) <= 0xff) {
+                "iso-8859-1"
+            } else {
+                "utf-8"
+            },
+            alt_text: code_model.value.clone(),
+        };
+        let title = code_model
+            .name
+            .clone()
+            .unwrap_or_else(|| site_model.name.clone());
+
+        let pass = PassJson {
+            format_version: 1,
+            pass_type_identifier: self.pass_type_id.clone(),
+            team_identifier: self.team_id.clone(),
+            serial_number: code_model.id.to_string(),
+            organization_name: site_model.name.clone(),
+            description: format!("{} ({})", title, code.label()),
+            logo_text: site_model.name.clone(),
+            foreground_color: "rgb(0, 0, 0)",
+            background_color: "rgb(255, 255, 255)",
+            label_color: "rgb(90, 90, 90)",
+            barcodes: vec![barcode()],
+            barcode: barcode(),
+            store_card: StoreCard {
+                primary_fields: vec![PassField {
+                    key: "name",
+                    label: "Name",
+                    value: title,
+                }],
+                secondary_fields: vec![PassField {
+                    key: "site",
+                    label: "Site",
+                    value: site_model.name.clone(),
+                }],
+                back_fields: vec![
+                    PassField {
+                        key: "value",
+                        label: code.label(),
+                        value: code_model.value.clone(),
+                    },
+                    PassField {
+                        key: "source",
+                        label: "Exported from",
+                        value: "HoofPrint".to_string(),
+                    },
+                ],
+            },
+        };
+
+        let mut files: BTreeMap<&str, Vec<u8>> = BTreeMap::new();
+        files.insert("pass.json", serde_json::to_vec_pretty(&pass)?);
+        files.insert("icon.png", PASS_ICON.to_vec());
+        files.insert("icon@2x.png", PASS_ICON.to_vec());
+        files.insert("logo.png", PASS_ICON.to_vec());
+
+        // the manifest lists the SHA-1 of every file, and it's what gets signed
+        let manifest: BTreeMap<&str, String> = files
+            .iter()
+            .map(|(name, contents)| (*name, hex(&sha1(contents))))
+            .collect();
+        let manifest = serde_json::to_vec_pretty(&manifest)?;
+        let signature = self.sign(&manifest)?;
+        files.insert("manifest.json", manifest);
+        files.insert("signature", signature);
+
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+        for (name, contents) in files {
+            zip.start_file(name, options)?;
+            zip.write_all(&contents)?;
+        }
+        Ok(zip.finish()?.into_inner())
+    }
+}
+
+/// Lowercase hex encoding
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// A self-signed certificate and key for signing passes in tests, as PEM
+#[cfg(test)]
+pub(crate) fn test_signing_certificate() -> (Vec<u8>, Vec<u8>) {
+    use openssl::{
+        asn1::Asn1Time, bn::BigNum, ec::EcGroup, ec::EcKey, hash::MessageDigest,
+        x509::X509NameBuilder,
+    };
+
+    let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).expect("Failed to get curve");
+    let key =
+        PKey::from_ec_key(EcKey::generate(&group).expect("Failed to generate key")).expect("Key");
+
+    let mut name = X509NameBuilder::new().expect("Failed to create name");
+    name.append_entry_by_nid(Nid::COMMONNAME, "Pass Type ID: pass.test.hoofprint")
+        .expect("Failed to set CN");
+    name.append_entry_by_nid(Nid::ORGANIZATIONALUNITNAME, "TEAMID1234")
+        .expect("Failed to set OU");
+    let name = name.build();
+
+    let mut builder = X509::builder().expect("Failed to create certificate builder");
+    builder.set_version(2).expect("Failed to set version");
+    builder
+        .set_serial_number(
+            &BigNum::from_u32(1)
+                .and_then(|serial| serial.to_asn1_integer())
+                .expect("Failed to make serial number"),
+        )
+        .expect("Failed to set serial number");
+    builder
+        .set_subject_name(&name)
+        .expect("Failed to set subject");
+    builder
+        .set_issuer_name(&name)
+        .expect("Failed to set issuer");
+    builder.set_pubkey(&key).expect("Failed to set public key");
+    builder
+        .set_not_before(&Asn1Time::days_from_now(0).expect("Failed to make time"))
+        .expect("Failed to set not before");
+    builder
+        .set_not_after(&Asn1Time::days_from_now(1).expect("Failed to make time"))
+        .expect("Failed to set not after");
+    builder
+        .sign(&key, MessageDigest::sha256())
+        .expect("Failed to sign certificate");
+
+    (
+        builder
+            .build()
+            .to_pem()
+            .expect("Failed to encode certificate"),
+        key.private_key_to_pem_pkcs8()
+            .expect("Failed to encode key"),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use openssl::x509::store::X509StoreBuilder;
+    use sea_orm::sqlx::types::chrono;
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::render::RenderOptions;
+
+    fn read_file(archive: &mut zip::ZipArchive<Cursor<Vec<u8>>>, name: &str) -> Vec<u8> {
+        let mut contents = Vec::new();
+        archive
+            .by_name(name)
+            .expect("File missing from pass")
+            .read_to_end(&mut contents)
+            .expect("Failed to read file from pass");
+        contents
+    }
+
+    #[test]
+    fn test_build_pass() {
+        let (certificate_pem, key_pem) = test_signing_certificate();
+        let signer = PassSigner::new("pass.test.hoofprint", &certificate_pem, &key_pem, None)
+            .expect("Failed to create signer");
+        assert_eq!(signer.team_id, "TEAMID1234");
+
+        let code_model = code::Model {
+            id: Uuid::now_v7(),
+            user_id: Uuid::nil(),
+            type_: Code::Code128.to_string(),
+            value: "5901234123457".to_string(),
+            site_id: Uuid::nil(),
+            created_at: chrono::Utc::now(),
+            last_updated: None,
+            name: Some("Loyalty card".to_string()),
+            render_options: RenderOptions::default(),
+            valid_from: None,
+            expires_at: None,
+            favourite: false,
+            position: 0,
+            notes: None,
+            wrapped_key: None,
+            household_id: None,
+        };
+        let site_model = site::Model {
+            id: Uuid::nil(),
+            name: "Corner Shop".to_string(),
+            url: "https://example.com".to_string(),
+            created_at: time::macros::datetime!(2026-01-01 0:00),
+            brand_colour: None,
+            default_code_type: None,
+            instructions: None,
+            value_pattern: None,
+        };
+        let pkpass = signer
+            .build_pass(&code_model, &site_model)
+            .expect("Failed to build pass");
+
+        // an EAN-13 shown as Code 128 isn't the barcode on the card, so it isn't exported
+        let ean13 = code::Model {
+            type_: Code::Ean13.to_string(),
+            ..code_model.clone()
+        };
+        assert!(matches!(
+            signer.build_pass(&ean13, &site_model),
+            Err(HoofprintError::ValidationError(_))
+        ));
+
+        let mut archive =
+            zip::ZipArchive::new(Cursor::new(pkpass)).expect("Pass should be a zip file");
+        let pass_json: serde_json::Value =
+            serde_json::from_slice(&read_file(&mut archive, "pass.json"))
+                .expect("pass.json should be JSON");
+        assert_eq!(pass_json["passTypeIdentifier"], "pass.test.hoofprint");
+        assert_eq!(pass_json["teamIdentifier"], "TEAMID1234");
+        assert_eq!(pass_json["organizationName"], "Corner Shop");
+        assert_eq!(pass_json["serialNumber"], code_model.id.to_string());
+        assert_eq!(pass_json["barcodes"][0]["format"], PK_BARCODE_CODE128);
+        assert_eq!(pass_json["barcodes"][0]["message"], "5901234123457");
+        assert_eq!(pass_json["barcode"]["message"], "5901234123457");
+        assert_eq!(
+            pass_json["storeCard"]["primaryFields"][0]["value"],
+            "Loyalty card"
+        );
+
+        // every file is in the manifest with the right hash
+        let manifest = read_file(&mut archive, "manifest.json");
+        let hashes: BTreeMap<String, String> =
+            serde_json::from_slice(&manifest).expect("manifest.json should be JSON");
+        for name in ["pass.json", "icon.png", "icon@2x.png", "logo.png"] {
+            assert_eq!(hashes[name], hex(&sha1(&read_file(&mut archive, name))));
+        }
+
+        // and the signature covers the manifest
+        let signature =
+            Pkcs7::from_der(&read_file(&mut archive, "signature")).expect("Invalid signature");
+        let certificate = X509::from_pem(&certificate_pem).expect("Invalid certificate");
+        let mut store = X509StoreBuilder::new().expect("Failed to create store");
+        store
+            .add_cert(certificate)
+            .expect("Failed to add certificate");
+        let store = store.build();
+        let certs = Stack::new().expect("Failed to create stack");
+        signature
+            .verify(&certs, &store, Some(&manifest), None, Pkcs7Flags::BINARY)
+            .expect("Signature should verify");
+    }
+
+    #[test]
+    fn test_read_pass() {
+        let (certificate_pem, key_pem) = test_signing_certificate();
+        let signer = PassSigner::new("pass.test.hoofprint", &certificate_pem, &key_pem, None)
+            .expect("Failed to create signer");
+        let code_model = code::Model {
+            id: Uuid::now_v7(),
+            user_id: Uuid::nil(),
+            type_: Code::QR.to_string(),
+            value: "member-1234".to_string(),
+            site_id: Uuid::nil(),
+            created_at: chrono::Utc::now(),
+            last_updated: None,
+            name: Some("Gym".to_string()),
+            render_options: RenderOptions::default(),
+            valid_from: None,
+            expires_at: None,
+            favourite: false,
+            position: 0,
+            notes: None,
+            wrapped_key: None,
+            household_id: None,
+        };
+        let site_model = site::Model {
+            id: Uuid::nil(),
+            name: "The Gym".to_string(),
+            url: String::new(),
+            created_at: time::macros::datetime!(2026-01-01 0:00),
+            brand_colour: None,
+            default_code_type: None,
+            instructions: None,
+            value_pattern: None,
+        };
+        let pkpass = signer
+            .build_pass(&code_model, &site_model)
+            .expect("Failed to build pass");
+        assert!(is_pkpass(&pkpass));
+
+        // our own passes round trip
+        let imported = read_pass(&pkpass).expect("Failed to read pass");
+        assert_eq!(imported.code, Code::QR);
+        assert_eq!(imported.value, "member-1234");
+        assert_eq!(imported.organization_name, "The Gym");
+        assert_eq!(imported.description.as_deref(), Some("Gym (QR Code)"));
+    }
+
+    #[test]
+    fn test_read_unsigned_pass() {
+        let make_pass = |pass_json: &str| {
+            let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+            zip.start_file("pass.json", SimpleFileOptions::default())
+                .expect("Failed to start file");
+            zip.write_all(pass_json.as_bytes())
+                .expect("Failed to write file");
+            zip.finish().expect("Failed to finish zip").into_inner()
+        };
+
+        // an old-style pass with only `barcode`, and no manifest or signature
+        let imported = read_pass(&make_pass(
+            r#"{"organizationName": "Corner Shop", "barcode": {"format": "PKBarcodeFormatCode128", "message": "12345", "messageEncoding": "iso-8859-1"}}"#,
+        ))
+        .expect("Failed to read pass");
+        assert_eq!(imported.code, Code::Code128);
+        assert_eq!(imported.value, "12345");
+        assert_eq!(imported.description, None);
+
+        // unsupported formats are skipped in favour of later ones
+        let imported = read_pass(&make_pass(
+            r#"{"organizationName": "Corner Shop", "barcodes": [{"format": "PKBarcodeFormatMystery", "message": "a"}, {"format": "PKBarcodeFormatPDF417", "message": "b"}]}"#,
+        ))
+        .expect("Failed to read pass");
+        assert_eq!(imported.code, Code::Pdf417);
+        assert_eq!(imported.value, "b");
+
+        let result = read_pass(&make_pass(r#"{"organizationName": "Corner Shop"}"#));
+        assert!(matches!(result, Err(HoofprintError::ValidationError(_))));
+        let result = read_pass(&make_pass("not json"));
+        assert!(matches!(result, Err(HoofprintError::ValidationError(_))));
+        let result = read_pass(b"PK\x03\x04 but not really");
+        assert!(matches!(result, Err(HoofprintError::ValidationError(_))));
+    }
+
+    #[test]
+    fn test_pass_barcode_format() {
+        assert_eq!(Code::QR.pass_barcode_format(), Some(PK_BARCODE_QR));
+        assert_eq!(Code::Ean13.pass_barcode_format(), None);
+        assert_eq!(Code::UpcA.pass_barcode_format(), None);
+        assert_eq!(Code::Code39.pass_barcode_format(), None);
+        assert_eq!(Code::DataMatrix.pass_barcode_format(), None);
+        for code in [Code::QR, Code::Pdf417, Code::Aztec, Code::Code128] {
+            let format = code
+                .pass_barcode_format()
+                .expect("Format should be supported");
+            assert_eq!(Code::from_pass_barcode_format(format), Some(code));
+        }
+    }
+}
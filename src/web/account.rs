@@ -0,0 +1,182 @@
+use axum::Form;
+use sea_orm::{ActiveModelTrait, ActiveValue::Set, IntoActiveModel};
+
+use crate::{
+    crypto::DataKey,
+    db::entities::{code, code_attachment, code_share, code_usage, user},
+    prelude::*,
+    web::attachments::attachment_dir,
+};
+
+#[derive(Template, WebTemplate)]
+#[template(path = "account.html")]
+pub(crate) struct AccountPage {
+    pub(crate) user_name: String,
+    pub(crate) user_email: String,
+    /// How many data keys were retired by password resets, and might still lock codes
+    pub(crate) retired_keys: usize,
+    /// Whether each time a code is shown is recorded
+    pub(crate) track_usage: bool,
+    pub(crate) error: Option<String>,
+    pub(crate) success: Option<String>,
+}
+
+impl AccountPage {
+    fn new(user: &user::Model) -> Self {
+        Self {
+            user_name: user.display_name.clone(),
+            user_email: user.email.clone(),
+            retired_keys: user.retired_data_keys().len(),
+            track_usage: user.track_usage,
+            error: None,
+            success: None,
+        }
+    }
+}
+
+async fn get_user(app_state: &AppState, session: &Session) -> Result<user::Model, HoofprintError> {
+    let auth = app_state.get_authenticated_user(session).await?;
+    user::Entity::find_by_id(auth.user_id)
+        .one(&app_state.db)
+        .await?
+        .ok_or(HoofprintError::NeedToLogin)
+}
+
+pub(crate) async fn account_get(
+    State(app_state): State<AppState>,
+    session: Session,
+) -> Result<AccountPage, HoofprintError> {
+    let user = get_user(&app_state, &session).await?;
+    Ok(AccountPage::new(&user))
+}
+
+#[derive(Deserialize)]
+pub(crate) struct PasswordForm {
+    current_password: String,
+    new_password: String,
+    confirm_password: String,
+}
+
+pub(crate) async fn password_post(
+    State(app_state): State<AppState>,
+    session: Session,
+    Form(form): Form<PasswordForm>,
+) -> Result<AccountPage, HoofprintError> {
+    let user = get_user(&app_state, &session).await?;
+    let mut page = AccountPage::new(&user);
+
+    if form.new_password.trim().is_empty() {
+        page.error = Some("New password is required".to_string());
+        return Ok(page);
+    }
+    if form.new_password != form.confirm_password {
+        page.error = Some("New passwords don't match".to_string());
+        return Ok(page);
+    }
+
+    match user.change_password(&form.current_password, &form.new_password) {
+        Ok(user) => {
+            let user = user.update(&app_state.db).await?;
+            info!(email=%user.email, "User changed their password");
+            page.success = Some("Password changed.".to_string());
+        }
+        Err(HoofprintError::Authentication) => {
+            page.error = Some("Current password is incorrect".to_string());
+        }
+        Err(err) => return Err(err),
+    }
+    Ok(page)
+}
+
+#[derive(Deserialize)]
+pub(crate) struct RecoverForm {
+    old_password: String,
+}
+
+/// Unlock codes that were sealed before a password reset, using the password from before it
+pub(crate) async fn recover_post(
+    State(app_state): State<AppState>,
+    session: Session,
+    Form(form): Form<RecoverForm>,
+) -> Result<AccountPage, HoofprintError> {
+    let mut cipher = app_state.get_code_cipher(&session).await?;
+    let user = get_user(&app_state, &session).await?;
+
+    let (unwrapped, still_retired): (Vec<_>, Vec<_>) = user
+        .retired_data_keys()
+        .into_iter()
+        .map(|wrapped| {
+            let key = DataKey::unwrap_with_password(&wrapped, &form.old_password);
+            (wrapped, key)
+        })
+        .partition(|(_, key)| key.is_some());
+    let keys: Vec<DataKey> = unwrapped.into_iter().filter_map(|(_, key)| key).collect();
+
+    if keys.is_empty() {
+        let mut page = AccountPage::new(&user);
+        page.error = Some("That password didn't unlock any codes".to_string());
+        return Ok(page);
+    }
+
+    // shared codes have keys of their own, which have to be unlocked before the codes can be
+    code_share::Model::recover_locked_keys(&app_state.db, &user, &keys, cipher.key()).await?;
+    let codes = code::Model::find_for_user(&app_state.db, user.id).await?;
+    code_share::Model::load_code_keys(
+        &app_state.db,
+        user.id,
+        &mut cipher,
+        &codes.iter().map(|(code, _)| code).collect::<Vec<_>>(),
+    )
+    .await?;
+    let recovered = code::Model::recover_locked(&app_state.db, user.id, &keys, &cipher).await?;
+    code_attachment::Model::recover_locked(
+        &app_state.db,
+        attachment_dir(&app_state).await.as_deref(),
+        user.id,
+        &keys,
+        &cipher,
+    )
+    .await?;
+
+    let mut user = user.into_active_model();
+    user.retired_data_keys = Set(serde_json::json!(
+        still_retired
+            .into_iter()
+            .map(|(wrapped, _)| wrapped)
+            .collect::<Vec<_>>()
+    ));
+    let user = user.update(&app_state.db).await?;
+    info!(email=%user.email, recovered, "User recovered locked codes");
+
+    let mut page = AccountPage::new(&user);
+    page.success = Some(format!("Recovered {} code(s).", recovered));
+    Ok(page)
+}
+
+#[derive(Deserialize, Serialize)]
+pub(crate) struct PreferencesForm {
+    /// A checkbox, so it's only sent when it's ticked
+    pub(crate) track_usage: Option<String>,
+}
+
+/// Save the user's preferences. Turning usage tracking off also forgets the usage recorded so far.
+pub(crate) async fn preferences_post(
+    State(app_state): State<AppState>,
+    session: Session,
+    Form(form): Form<PreferencesForm>,
+) -> Result<AccountPage, HoofprintError> {
+    let user = get_user(&app_state, &session).await?;
+    let track_usage = form.track_usage.is_some();
+
+    if !track_usage {
+        code_usage::Model::clear_for_user(&app_state.db, user.id).await?;
+    }
+    let mut user = user.into_active_model();
+    user.track_usage = Set(track_usage);
+    let user = user.update(&app_state.db).await?;
+    info!(email=%user.email, track_usage, "User changed their preferences");
+
+    let mut page = AccountPage::new(&user);
+    page.success = Some("Preferences saved.".to_string());
+    Ok(page)
+}
@@ -1,7 +1,18 @@
 //! Admin UI handlers
-use sea_orm::{ActiveModelTrait, IntoActiveModel, Order, QueryOrder};
+use axum::extract::{Multipart, Path};
+use image::ImageFormat;
+use sea_orm::{ActiveModelTrait, ActiveValue::Set, Order, QueryOrder};
 
-use crate::{constants::PASSWORD_DEFAULT_LENGTH, get_random_password, prelude::*};
+use crate::{
+    constants::PASSWORD_DEFAULT_LENGTH,
+    db::entities::{
+        site,
+        site_logo::{self, MAX_LOGO_SIZE},
+    },
+    get_random_password,
+    prelude::*,
+    web::forms::{AdminActionForm, SiteForm, SiteMergeForm},
+};
 
 #[derive(Template, WebTemplate)]
 #[template(path = "admin_dashboard.html")]
@@ -111,10 +122,11 @@ pub(crate) async fn password_reset_post(
         Some(user) => {
             // reset the user's password to a default value
             let password = get_random_password(PASSWORD_DEFAULT_LENGTH);
-            let hashed_password = crate::password::hash_password(&password)?;
-            let mut user_active = user.clone().into_active_model();
-            user_active.password.set_if_not_equals(hashed_password);
-            let updated_user = user_active.update(&app_state.db).await?;
+            let updated_user = user
+                .clone()
+                .reset_password(&password)?
+                .update(&app_state.db)
+                .await?;
             info!(admin_user = %auth_user.email, user_email = %user.email, "Admin reset password for user");
             Ok(AdminPasswordResetComplete {
                 user_email: updated_user.email,
@@ -126,3 +138,312 @@ pub(crate) async fn password_reset_post(
         None => Err(HoofprintError::NotFound("User not found".into())),
     }
 }
+
+/// Where the sites pages' CSRF token is kept, apart from the password reset's one-off token
+const SITES_CSRF_TOKEN: &str = "sites_csrf_token";
+
+/// The CSRF token for the sites pages' forms, issuing one if the session doesn't have one yet.
+/// It's kept for the session rather than used up, since each page has several forms.
+async fn sites_csrf_token(session: &Session) -> Result<String, HoofprintError> {
+    if let Some(token) = session.get::<String>(SITES_CSRF_TOKEN).await? {
+        return Ok(token);
+    }
+    let token = Uuid::now_v7().to_string();
+    session.insert(SITES_CSRF_TOKEN, token.clone()).await?;
+    Ok(token)
+}
+
+/// Check the CSRF token that one of the sites pages' forms sent back
+async fn check_sites_csrf_token(session: &Session, csrf_token: &str) -> Result<(), HoofprintError> {
+    match session.get::<String>(SITES_CSRF_TOKEN).await? {
+        Some(token) if token == csrf_token => Ok(()),
+        Some(_) => Err(HoofprintError::InvalidCsrfToken),
+        None => Err(HoofprintError::MissingCsrfToken),
+    }
+}
+
+/// A site, as it's listed to admins
+pub(crate) struct SiteItem {
+    pub(crate) id: Uuid,
+    pub(crate) name: String,
+    pub(crate) url: String,
+    /// How many codes use the site
+    pub(crate) codes: u64,
+}
+
+#[derive(Template, WebTemplate)]
+#[template(path = "admin_sites.html")]
+pub(crate) struct AdminSitesPage {
+    pub sites: Vec<SiteItem>,
+    pub csrf_token: String,
+}
+
+pub(crate) async fn sites_get(
+    State(app_state): State<AppState>,
+    session: Session,
+) -> Result<AdminSitesPage, HoofprintError> {
+    let sites = site::Model::with_code_counts(&app_state.db)
+        .await?
+        .into_iter()
+        .map(|(site, codes)| SiteItem {
+            id: site.id,
+            name: site.name,
+            url: site.url,
+            codes,
+        })
+        .collect();
+    Ok(AdminSitesPage {
+        sites,
+        csrf_token: sites_csrf_token(&session).await?,
+    })
+}
+
+/// Refuse a site name that another site already has, ignoring case
+async fn ensure_unique_name(
+    app_state: &AppState,
+    name: &str,
+    site_id: Option<Uuid>,
+) -> Result<(), HoofprintError> {
+    match site::Model::find_by_name(&app_state.db, name).await? {
+        Some(existing) if Some(existing.id) != site_id => Err(HoofprintError::ValidationError(
+            vec![format!("There's already a site called {}", existing.name)],
+        )),
+        _ => Ok(()),
+    }
+}
+
+#[instrument(level = "debug", skip(app_state, session, form))]
+pub(crate) async fn sites_post(
+    State(app_state): State<AppState>,
+    session: Session,
+    Form(form): Form<SiteForm>,
+) -> Result<Redirect, HoofprintError> {
+    let auth_user = app_state.get_authenticated_user(&session).await?;
+    check_sites_csrf_token(&session, &form.csrf_token).await?;
+    form.validate()?;
+    ensure_unique_name(&app_state, &form.name, None).await?;
+    let site = site::Model::create_new(&app_state.db, &form.name, &form.url).await?;
+    info!(admin_user = %auth_user.email, site_id = %site.id, "Admin created site");
+    Ok(Redirect::to(Urls::AdminSites.as_ref()))
+}
+
+async fn get_site(app_state: &AppState, site_id_str: &str) -> Result<site::Model, HoofprintError> {
+    let site_id = Uuid::parse_str(site_id_str)
+        .map_err(|_| HoofprintError::NotFound(format!("Invalid site ID: {}", site_id_str)))?;
+    site::Entity::find_by_id(site_id)
+        .one(&app_state.db)
+        .await?
+        .ok_or_else(|| HoofprintError::NotFound(format!("Site {}", site_id)))
+}
+
+#[derive(Template, WebTemplate)]
+#[template(path = "admin_site.html")]
+pub(crate) struct AdminSitePage {
+    pub site: SiteItem,
+    /// The site's brand colour, default code type, instructions and value pattern
+    pub details: site::Model,
+    pub has_logo: bool,
+    pub csrf_token: String,
+    /// The sites this one could be merged into
+    pub others: Vec<SiteItem>,
+}
+
+impl AdminSitePage {
+    /// The generic site is what codes fall back to, so it's always kept
+    pub fn is_generic(&self) -> bool {
+        self.site.id.is_nil()
+    }
+}
+
+#[instrument(level = "debug", skip(app_state, session))]
+pub(crate) async fn site_get(
+    State(app_state): State<AppState>,
+    Path(site_id_str): Path<String>,
+    session: Session,
+) -> Result<AdminSitePage, HoofprintError> {
+    let site_model = get_site(&app_state, &site_id_str).await?;
+    let (sites, others): (Vec<SiteItem>, Vec<SiteItem>) =
+        site::Model::with_code_counts(&app_state.db)
+            .await?
+            .into_iter()
+            .map(|(site, codes)| SiteItem {
+                id: site.id,
+                name: site.name,
+                url: site.url,
+                codes,
+            })
+            .partition(|site| site.id == site_model.id);
+    let site = sites
+        .into_iter()
+        .next()
+        .ok_or_else(|| HoofprintError::NotFound(format!("Site {}", site_model.id)))?;
+    let has_logo = site_logo::Model::exists(&app_state.db, site_model.id).await?;
+    Ok(AdminSitePage {
+        site,
+        others,
+        details: site_model,
+        has_logo,
+        csrf_token: sites_csrf_token(&session).await?,
+    })
+}
+
+#[instrument(level = "debug", skip(app_state, session, form))]
+pub(crate) async fn site_post(
+    State(app_state): State<AppState>,
+    Path(site_id_str): Path<String>,
+    session: Session,
+    Form(form): Form<SiteForm>,
+) -> Result<Redirect, HoofprintError> {
+    let auth_user = app_state.get_authenticated_user(&session).await?;
+    check_sites_csrf_token(&session, &form.csrf_token).await?;
+    let site_model = get_site(&app_state, &site_id_str).await?;
+    form.validate()?;
+    ensure_unique_name(&app_state, &form.name, Some(site_model.id)).await?;
+
+    let site_id = site_model.id;
+    let mut site_model: site::ActiveModel = site_model.into();
+    site_model.name = Set(form.name.trim().to_string());
+    site_model.url = Set(form.url.trim().to_string());
+    site_model.brand_colour = Set(form.brand_colour());
+    site_model.default_code_type = Set(form.default_code_type());
+    site_model.instructions = Set(form.instructions());
+    site_model.value_pattern = Set(form.value_pattern());
+    site_model.update(&app_state.db).await?;
+    info!(admin_user = %auth_user.email, site_id = %site_id, "Admin changed site");
+
+    Ok(Redirect::to(Urls::AdminSites.as_ref()))
+}
+
+/// Delete a site, as long as no codes use it, see [site::Model::delete]
+#[instrument(level = "debug", skip(app_state, session, form))]
+pub(crate) async fn site_delete_post(
+    State(app_state): State<AppState>,
+    Path(site_id_str): Path<String>,
+    session: Session,
+    Form(form): Form<AdminActionForm>,
+) -> Result<Redirect, HoofprintError> {
+    let auth_user = app_state.get_authenticated_user(&session).await?;
+    check_sites_csrf_token(&session, &form.csrf_token).await?;
+    let site_model = get_site(&app_state, &site_id_str).await?;
+    if site_model.id.is_nil() {
+        return Err(HoofprintError::ValidationError(vec![format!(
+            "{} can't be deleted",
+            site_model.name
+        )]));
+    }
+    let site_id = site_model.id;
+    site_model.delete(&app_state.db).await?;
+    info!(admin_user = %auth_user.email, site_id = %site_id, "Admin deleted site");
+
+    Ok(Redirect::to(Urls::AdminSites.as_ref()))
+}
+
+/// Merge a duplicate site into another one, moving its codes over and deleting it
+#[instrument(level = "debug", skip(app_state, session, form))]
+pub(crate) async fn site_merge_post(
+    State(app_state): State<AppState>,
+    Path(site_id_str): Path<String>,
+    session: Session,
+    Form(form): Form<SiteMergeForm>,
+) -> Result<Redirect, HoofprintError> {
+    let auth_user = app_state.get_authenticated_user(&session).await?;
+    check_sites_csrf_token(&session, &form.csrf_token).await?;
+    let site_model = get_site(&app_state, &site_id_str).await?;
+    if site_model.id.is_nil() {
+        return Err(HoofprintError::ValidationError(vec![format!(
+            "{} can't be merged into another site",
+            site_model.name
+        )]));
+    }
+    let into_id = form.site_id()?;
+    let into = site::Entity::find_by_id(into_id)
+        .one(&app_state.db)
+        .await?
+        .ok_or_else(|| {
+            HoofprintError::ValidationError(vec![format!("Site {} not found", into_id)])
+        })?;
+
+    let site_id = site_model.id;
+    let moved = site_model.merge_into(&app_state.db, &into).await?;
+    info!(admin_user = %auth_user.email, site_id = %site_id, into = %into.id, moved, "Admin merged site");
+
+    Ok(Redirect::to(Urls::AdminSites.as_ref()))
+}
+
+/// Give a site a logo, replacing the one it had
+#[instrument(level = "debug", skip(app_state, session, multipart))]
+pub(crate) async fn site_logo_post(
+    State(app_state): State<AppState>,
+    Path(site_id_str): Path<String>,
+    session: Session,
+    mut multipart: Multipart,
+) -> Result<Redirect, HoofprintError> {
+    let auth_user = app_state.get_authenticated_user(&session).await?;
+    let site_model = get_site(&app_state, &site_id_str).await?;
+
+    let mut csrf_token = None;
+    let mut logo = None;
+    while let Some(field) = multipart.next_field().await? {
+        match field.name() {
+            Some("csrf_token") => csrf_token = Some(field.text().await?),
+            Some("logo") => logo = Some(field.bytes().await?),
+            _ => {}
+        }
+    }
+    check_sites_csrf_token(&session, csrf_token.as_deref().unwrap_or_default()).await?;
+    let logo = logo.filter(|bytes| !bytes.is_empty()).ok_or_else(|| {
+        HoofprintError::ValidationError(vec!["Choose a logo to upload".to_string()])
+    })?;
+    if logo.len() > MAX_LOGO_SIZE {
+        return Err(HoofprintError::ValidationError(vec![format!(
+            "Logos must be {}KB or smaller",
+            MAX_LOGO_SIZE / 1024
+        )]));
+    }
+    // only formats browsers show as they are, so nothing else gets served back
+    let format = image::guess_format(&logo)
+        .ok()
+        .filter(|format| {
+            matches!(
+                format,
+                ImageFormat::Jpeg | ImageFormat::Png | ImageFormat::WebP
+            )
+        })
+        .ok_or_else(|| {
+            HoofprintError::ValidationError(vec![
+                "Logos must be JPEG, PNG or WebP images".to_string(),
+            ])
+        })?;
+
+    site_logo::Model::save(&app_state.db, site_model.id, format.to_mime_type(), &logo).await?;
+    info!(admin_user = %auth_user.email, site_id = %site_model.id, "Admin changed site logo");
+
+    Ok(Redirect::to(&format!(
+        "{}/{}",
+        Urls::AdminSites.as_ref(),
+        site_model.id
+    )))
+}
+
+/// Remove a site's logo
+#[instrument(level = "debug", skip(app_state, session, form))]
+pub(crate) async fn site_logo_delete_post(
+    State(app_state): State<AppState>,
+    Path(site_id_str): Path<String>,
+    session: Session,
+    Form(form): Form<AdminActionForm>,
+) -> Result<Redirect, HoofprintError> {
+    let auth_user = app_state.get_authenticated_user(&session).await?;
+    check_sites_csrf_token(&session, &form.csrf_token).await?;
+    let site_model = get_site(&app_state, &site_id_str).await?;
+    site_logo::Entity::delete_by_id(site_model.id)
+        .exec(&app_state.db)
+        .await?;
+    info!(admin_user = %auth_user.email, site_id = %site_model.id, "Admin removed site logo");
+
+    Ok(Redirect::to(&format!(
+        "{}/{}",
+        Urls::AdminSites.as_ref(),
+        site_model.id
+    )))
+}
@@ -0,0 +1,167 @@
+//! Photos of the front and back of the card a code came from
+
+use std::path::PathBuf;
+
+use axum::{
+    extract::{Multipart, Path},
+    http::header::{CACHE_CONTROL, CONTENT_DISPOSITION, CONTENT_TYPE, X_CONTENT_TYPE_OPTIONS},
+};
+use image::ImageFormat;
+
+use crate::{
+    db::entities::{
+        code_attachment::{self, Side},
+        code_share::Permission,
+    },
+    prelude::*,
+    web::views::get_code,
+};
+
+/// The largest photo that can be attached, in bytes
+pub(crate) const MAX_ATTACHMENT_SIZE: usize = 5 * 1024 * 1024;
+
+/// Where photos are kept outside the database, if they are
+pub(crate) async fn attachment_dir(app_state: &AppState) -> Option<PathBuf> {
+    app_state.config.read().await.attachment_dir.clone()
+}
+
+/// Serve a photo of a code's card, as long as the code belongs to the user
+#[instrument(level = "debug", skip(app_state, session))]
+pub(crate) async fn attachment_get(
+    State(app_state): State<AppState>,
+    Path((code_id_str, side)): Path<(String, String)>,
+    session: Session,
+) -> Result<impl IntoResponse, HoofprintError> {
+    let auth = app_state.get_authenticated_user(&session).await?;
+    let mut cipher = app_state.get_code_cipher(&session).await?;
+    let (code_model, _site_model, _permission) = get_code(
+        &app_state,
+        &mut cipher,
+        auth.user_id,
+        &code_id_str,
+        Permission::View,
+    )
+    .await?;
+    let side = Side::try_from(side.as_str())?;
+
+    let attachment = code_attachment::Model::find_side(&app_state.db, code_model.id, side)
+        .await?
+        .ok_or_else(|| HoofprintError::NotFound(format!("{} photo", side.label())))?;
+    let bytes = attachment
+        .read(attachment_dir(&app_state).await.as_deref(), &cipher)
+        .await?;
+
+    Ok((
+        [
+            (CONTENT_TYPE, attachment.content_type.clone()),
+            (
+                CONTENT_DISPOSITION,
+                format!("inline; filename=\"{}-{}\"", code_model.id, side.as_str()),
+            ),
+            // the photo is as private as the code
+            (CACHE_CONTROL, "private, no-store".to_string()),
+            (X_CONTENT_TYPE_OPTIONS, "nosniff".to_string()),
+        ],
+        bytes,
+    ))
+}
+
+/// Attach a photo to the front or back of a code, replacing the one that's there
+#[instrument(level = "debug", skip(app_state, session, multipart))]
+pub(crate) async fn attachment_upload_post(
+    State(app_state): State<AppState>,
+    Path(code_id_str): Path<String>,
+    session: Session,
+    mut multipart: Multipart,
+) -> Result<Redirect, HoofprintError> {
+    let auth = app_state.get_authenticated_user(&session).await?;
+    let mut cipher = app_state.get_code_cipher(&session).await?;
+    let (code_model, _site_model, _permission) = get_code(
+        &app_state,
+        &mut cipher,
+        auth.user_id,
+        &code_id_str,
+        Permission::Edit,
+    )
+    .await?;
+
+    let mut side = None;
+    let mut photo = None;
+    while let Some(field) = multipart.next_field().await? {
+        match field.name() {
+            Some("side") => side = Some(field.text().await?),
+            Some("photo") => photo = Some(field.bytes().await?),
+            _ => {}
+        }
+    }
+    let side = Side::try_from(side.as_deref().unwrap_or_default()).map_err(|_| {
+        HoofprintError::ValidationError(vec!["Choose which side of the card it is".to_string()])
+    })?;
+    let photo = photo.filter(|bytes| !bytes.is_empty()).ok_or_else(|| {
+        HoofprintError::ValidationError(vec!["Choose a photo to upload".to_string()])
+    })?;
+    if photo.len() > MAX_ATTACHMENT_SIZE {
+        return Err(HoofprintError::ValidationError(vec![format!(
+            "Photos must be {}MB or smaller",
+            MAX_ATTACHMENT_SIZE / 1024 / 1024
+        )]));
+    }
+    // only formats browsers show as they are, so nothing else gets served back
+    let format = image::guess_format(&photo)
+        .ok()
+        .filter(|format| {
+            matches!(
+                format,
+                ImageFormat::Jpeg | ImageFormat::Png | ImageFormat::WebP
+            )
+        })
+        .ok_or_else(|| {
+            HoofprintError::ValidationError(vec![
+                "Photos must be JPEG, PNG or WebP images".to_string(),
+            ])
+        })?;
+
+    code_attachment::Model::save(
+        &app_state.db,
+        attachment_dir(&app_state).await.as_deref(),
+        &cipher,
+        code_model.id,
+        side,
+        format.to_mime_type(),
+        &photo,
+    )
+    .await?;
+    info!(code_id = %code_model.id, side = side.as_str(), "Attached a photo to a code");
+
+    Ok(Redirect::to(&format!("/edit/{}", code_model.id)))
+}
+
+/// Remove the photo from one side of a code
+#[instrument(level = "debug", skip(app_state, session))]
+pub(crate) async fn attachment_delete_post(
+    State(app_state): State<AppState>,
+    Path((code_id_str, side)): Path<(String, String)>,
+    session: Session,
+) -> Result<Redirect, HoofprintError> {
+    let auth = app_state.get_authenticated_user(&session).await?;
+    let mut cipher = app_state.get_code_cipher(&session).await?;
+    let (code_model, _site_model, _permission) = get_code(
+        &app_state,
+        &mut cipher,
+        auth.user_id,
+        &code_id_str,
+        Permission::Edit,
+    )
+    .await?;
+    let side = Side::try_from(side.as_str())?;
+
+    if let Some(attachment) =
+        code_attachment::Model::find_side(&app_state.db, code_model.id, side).await?
+    {
+        attachment
+            .delete(&app_state.db, attachment_dir(&app_state).await.as_deref())
+            .await?;
+    }
+
+    Ok(Redirect::to(&format!("/edit/{}", code_model.id)))
+}
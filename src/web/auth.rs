@@ -2,7 +2,13 @@
 
 use std::collections::HashMap;
 
-use crate::{constants::Urls, db::entities::user, password::verify_password, prelude::*};
+use crate::{
+    constants::Urls,
+    crypto::CodeCipher,
+    db::entities::{code, code_share, user},
+    password::verify_password,
+    prelude::*,
+};
 
 use axum::{
     Form,
@@ -13,6 +19,8 @@ use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
 use tower_sessions::Session;
 
 pub(crate) const AUTH_USER_ID: &str = "user_id";
+/// The user's data key, wrapped with [AppState]'s session key
+pub(crate) const AUTH_DATA_KEY: &str = "data_key";
 
 /// Extractor for authenticated user information
 #[derive(Debug, Clone)]
@@ -120,11 +128,28 @@ pub(crate) async fn post_login(
                 }
                 Ok(()) => {
                     info!(email=%form.email, "User authenticated successfully");
+                    let data_key = user.unlock_data_key(&app_state.db, &form.password).await?;
+                    user.ensure_key_pair(&app_state.db, &data_key).await?;
+                    // keys for households and codes shared since the last login are switched
+                    // over to the data key, which is quicker to unwrap
+                    code_share::Model::rewrap_public_keys(&app_state.db, &user, &data_key).await?;
+                    let cipher =
+                        CodeCipher::new(data_key, app_state.config.read().await.encrypt_code_names);
+                    // codes saved before encryption are sealed the first time their owner logs in
+                    let sealed =
+                        code::Model::seal_plaintext(&app_state.db, user.id, &cipher).await?;
+                    if sealed > 0 {
+                        info!(email=%form.email, sealed, "Encrypted codes saved before encryption");
+                    }
                     session.clear().await;
                     session
                         .insert(AUTH_USER_ID, user.id.to_string())
                         .await
                         .inspect_err(|err| error!("Failed to insert user session!: {err}"))?;
+                    session
+                        .insert(AUTH_DATA_KEY, app_state.session_key.wrap_key(cipher.key())?)
+                        .await
+                        .inspect_err(|err| error!("Failed to insert data key!: {err}"))?;
                     session
                         .save()
                         .await
@@ -0,0 +1,132 @@
+//! Exporting a user's codes for use away from hoofprint
+
+use crate::prelude::*;
+
+use axum::{
+    extract::RawQuery,
+    http::header::{CONTENT_DISPOSITION, CONTENT_TYPE},
+};
+
+use crate::{
+    Code,
+    db::entities::{code, code_share},
+    print::{MIME_TYPE_PDF, WalletCard, wallet_sheet},
+    web::forms::WalletSheetQuery,
+};
+
+#[derive(Template, WebTemplate)]
+#[template(path = "wallet_sheet.html")]
+pub(crate) struct WalletSheetPage {
+    codes: Vec<WalletSheetItem>,
+}
+
+struct WalletSheetItem {
+    id: Uuid,
+    code_value: String,
+    code_name: Option<String>,
+    site_name: String,
+}
+
+/// Pick which codes go on the wallet sheet, and what paper it's for
+#[instrument(level = "debug", skip_all)]
+pub(crate) async fn wallet_sheet_get(
+    State(app_state): State<AppState>,
+    session: Session,
+) -> Result<WalletSheetPage, HoofprintError> {
+    let auth = app_state.get_authenticated_user(&session).await?;
+    let mut cipher = app_state.get_code_cipher(&session).await?;
+
+    // codes locked by a password reset can't be printed
+    let codes = code::Model::find_for_user(&app_state.db, auth.user_id).await?;
+    code_share::Model::load_code_keys(
+        &app_state.db,
+        auth.user_id,
+        &mut cipher,
+        &codes.iter().map(|(code, _)| code).collect::<Vec<_>>(),
+    )
+    .await?;
+    let codes = codes
+        .into_iter()
+        .filter_map(|(code_model, site_model)| {
+            let code_model = code_model.decrypt(&cipher).ok()?;
+            Some(WalletSheetItem {
+                id: code_model.id,
+                code_value: code_model.value,
+                code_name: code_model.name,
+                site_name: site_model
+                    .map(|s| s.name)
+                    .unwrap_or_else(|| "Unknown Site".to_string()),
+            })
+        })
+        .collect();
+
+    Ok(WalletSheetPage { codes })
+}
+
+/// A PDF of the user's codes as credit-card-sized cards, for a paper backup
+#[instrument(level = "debug", skip_all)]
+pub(crate) async fn wallet_sheet_pdf(
+    State(app_state): State<AppState>,
+    session: Session,
+    RawQuery(query): RawQuery,
+) -> Result<impl IntoResponse, HoofprintError> {
+    let auth = app_state.get_authenticated_user(&session).await?;
+    let mut cipher = app_state.get_code_cipher(&session).await?;
+    let query = WalletSheetQuery::parse(query.as_deref())?;
+
+    let mut codes = code::Model::find_for_user(&app_state.db, auth.user_id).await?;
+    codes.retain(|(code_model, _)| query.includes(code_model.id));
+    code_share::Model::load_code_keys(
+        &app_state.db,
+        auth.user_id,
+        &mut cipher,
+        &codes.iter().map(|(code, _)| code).collect::<Vec<_>>(),
+    )
+    .await?;
+    let cards: Vec<WalletCard> = codes
+        .into_iter()
+        .filter_map(|(code_model, site_model)| {
+            let code_id = code_model.id;
+            let (code, code_model) = match Code::try_from(&code_model)
+                .and_then(|code| Ok((code, code_model.decrypt(&cipher)?)))
+            {
+                Ok(decrypted) => decrypted,
+                Err(err) => {
+                    error!("Skipping code {} on wallet sheet: {}", code_id, err);
+                    return None;
+                }
+            };
+            Some(WalletCard {
+                code,
+                value: code_model.value,
+                name: code_model.name,
+                options: code_model.render_options,
+                site_name: site_model
+                    .map(|s| s.name)
+                    .unwrap_or_else(|| "Unknown Site".to_string()),
+            })
+        })
+        .collect();
+
+    if cards.is_empty() {
+        return Err(HoofprintError::ValidationError(vec![
+            "No codes to put on the wallet sheet".to_string(),
+        ]));
+    }
+
+    let pdf_bytes = wallet_sheet(&cards, query.paper);
+
+    Ok((
+        [
+            (CONTENT_TYPE, MIME_TYPE_PDF.to_string()),
+            (
+                CONTENT_DISPOSITION,
+                format!(
+                    "attachment; filename=\"hoofprint-wallet-{}.pdf\"",
+                    query.paper
+                ),
+            ),
+        ],
+        pdf_bytes,
+    ))
+}
@@ -1,23 +1,85 @@
 //! Form structures and validation for hoofprint web application
 
-use crate::{Code, prelude::*};
+use crate::{
+    Code,
+    db::entities::{
+        code_secret::MAX_SECRET_LABEL_LENGTH,
+        household::MAX_HOUSEHOLD_NAME_LENGTH,
+        household_member::Role,
+        share_link::{MAX_LINK_HOURS, MAX_LINK_VIEWS},
+        site::{
+            self, MAX_SITE_INSTRUCTIONS_LENGTH, MAX_SITE_NAME_LENGTH, MAX_SITE_URL_LENGTH,
+            MAX_VALUE_PATTERN_LENGTH, is_brand_colour,
+        },
+        tag::{MAX_TAG_LENGTH, MAX_TAGS_PER_CODE},
+    },
+    notes::MAX_NOTES_LENGTH,
+    payload::{
+        Contact, ContactFormat, Event, Payload, PayloadKind, Wifi, WifiSecurity, form_datetime,
+        parse_form_datetime,
+    },
+    prelude::*,
+    print::PaperSize,
+    render::{ErrorCorrection, MAX_PNG_DIMENSION, RenderOptions},
+};
 
-#[derive(Debug, Deserialize, Serialize)]
+use sea_orm::prelude::Date;
+
+/// The validation message listing every accepted code type
+fn invalid_code_type_message() -> String {
+    let types: Vec<String> = Code::all().iter().map(|code| code.to_string()).collect();
+    format!("Code type must be one of: {}", types.join(", "))
+}
+
+/// The value to save, with a missing check digit calculated if the user asked for it
+fn completed_value(code_type: &str, code_value: &str, add_check_digit: bool) -> String {
+    if add_check_digit
+        && let Ok(code) = Code::try_from(code_type)
+        && let Some(completed) = code.complete_value(code_value)
+    {
+        return completed;
+    }
+    code_value.to_string()
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
 pub(crate) struct CreateCodeForm {
     pub(crate) code_type: String,
     pub(crate) code_value: String,
     pub(crate) site_id: String,
     pub(crate) code_name: Option<String>,
+    /// Set when the user asked for a missing check digit to be calculated
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) add_check_digit: Option<String>,
+    /// A site to create (or match by name) instead of using `site_id`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) new_site_name: Option<String>,
+    /// The first day the code can be used, blank for no start
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) valid_from: Option<String>,
+    /// The last day the code can be used, blank if it doesn't expire
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) expires_at: Option<String>,
+    /// Comma separated tags to file the code under
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) tags: Option<String>,
+}
+
+/// A message for a value that doesn't match its site's pattern, if the site has one
+fn value_pattern_error(site: Option<&site::Model>, value: &str) -> Option<String> {
+    let site = site?;
+    let pattern = site.value_regex()?;
+    (!pattern.is_match(value)).then(|| format!("That doesn't look like a {} code", site.name))
 }
 
 impl CreateCodeForm {
-    /// Validate the form data
-    pub fn validate(&self) -> Result<(), HoofprintError> {
+    /// Validate the form data, along with the pattern of the existing site the code's for
+    pub fn validate(&self, site: Option<&site::Model>) -> Result<(), HoofprintError> {
         let mut errors = Vec::new();
 
         // Validate code_type
         if Code::try_from(self.code_type.as_str()).is_err() {
-            errors.push("Code type must be either 'barcode' or 'qrcode'".to_string());
+            errors.push(invalid_code_type_message());
         }
 
         // Validate code_value
@@ -25,6 +87,12 @@ impl CreateCodeForm {
             errors.push("Code value cannot be empty".to_string());
         } else if self.code_value.len() > 255 {
             errors.push("Code value must be 255 characters or less".to_string());
+        } else if let Ok(code) = Code::try_from(self.code_type.as_str())
+            && let Err(value_errors) = code.validate_value(&self.completed_value())
+        {
+            errors.extend(value_errors);
+        } else if let Some(error) = value_pattern_error(site, &self.completed_value()) {
+            errors.push(error);
         }
 
         // Validate site_id is a valid UUID
@@ -39,6 +107,23 @@ impl CreateCodeForm {
             errors.push("Code name must be 255 characters or less".to_string());
         }
 
+        if let Some(new_site_name) = self.new_site_name()
+            && new_site_name.len() > MAX_SITE_NAME_LENGTH
+        {
+            errors.push(format!(
+                "Site name must be {} characters or less",
+                MAX_SITE_NAME_LENGTH
+            ));
+        }
+
+        if let Err(date_errors) = self.validity_dates() {
+            errors.extend(date_errors);
+        }
+
+        if let Err(tag_errors) = self.tags() {
+            errors.extend(tag_errors);
+        }
+
         if errors.is_empty() {
             Ok(())
         } else {
@@ -46,10 +131,31 @@ impl CreateCodeForm {
         }
     }
 
-    /// Parse the site_id as a UUID after validation
-    pub fn parse_site_id(&self) -> Result<Uuid, HoofprintError> {
-        Uuid::parse_str(&self.site_id)
-            .map_err(|_| HoofprintError::ValidationError(vec!["Invalid site ID".to_string()]))
+    /// The value to save, with a missing check digit added if the user asked for it
+    pub fn completed_value(&self) -> String {
+        completed_value(
+            &self.code_type,
+            &self.code_value,
+            self.add_check_digit.is_some(),
+        )
+    }
+
+    /// The name of a site to use instead of `site_id`, if one was given
+    pub fn new_site_name(&self) -> Option<&str> {
+        self.new_site_name
+            .as_deref()
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+    }
+
+    /// Parse the dates the code can be used between
+    pub fn validity_dates(&self) -> Result<(Option<Date>, Option<Date>), Vec<String>> {
+        validity_dates(self.valid_from.as_deref(), self.expires_at.as_deref())
+    }
+
+    /// The tags to file the code under
+    pub fn tags(&self) -> Result<Vec<String>, Vec<String>> {
+        parse_tags(self.tags.as_deref())
     }
 }
 
@@ -59,16 +165,129 @@ pub struct EditCodeForm {
     pub code_value: String,
     pub site_id: String,
     pub code_name: Option<String>,
+    /// Set when the user asked for a missing check digit to be calculated
+    #[serde(default)]
+    pub add_check_digit: Option<String>,
+    /// QR error correction level, L, M, Q or H
+    #[serde(default)]
+    pub error_correction: Option<String>,
+    /// Pixels per module, blank for the symbology's default
+    #[serde(default)]
+    pub module_size: Option<String>,
+    /// Quiet zone in modules, blank for the symbology's default
+    #[serde(default)]
+    pub quiet_zone: Option<String>,
+    #[serde(default)]
+    pub show_text: Option<String>,
+    #[serde(default)]
+    pub invert: Option<String>,
+    #[serde(default)]
+    pub rotate: Option<String>,
+    /// The first day the code can be used, blank for no start
+    #[serde(default)]
+    pub valid_from: Option<String>,
+    /// The last day the code can be used, blank if it doesn't expire
+    #[serde(default)]
+    pub expires_at: Option<String>,
+    /// Comma separated tags to file the code under
+    #[serde(default)]
+    pub tags: Option<String>,
+    /// Markdown notes, blank for none
+    #[serde(default)]
+    pub notes: Option<String>,
+}
+
+/// Parse an optional number from a form field, where blank means unset
+fn parse_optional_number(value: Option<&str>, field: &str) -> Result<Option<u32>, String> {
+    match value.map(str::trim).filter(|value| !value.is_empty()) {
+        Some(value) => value
+            .parse()
+            .map(Some)
+            .map_err(|_| format!("{} must be a whole number", field)),
+        None => Ok(None),
+    }
+}
+
+/// Split a comma separated list of tags, dropping blanks and repeats
+fn parse_tags(tags: Option<&str>) -> Result<Vec<String>, Vec<String>> {
+    let mut names: Vec<String> = Vec::new();
+    for name in tags.unwrap_or_default().split(',').map(str::trim) {
+        if !name.is_empty()
+            && !names
+                .iter()
+                .any(|existing| existing.to_lowercase() == name.to_lowercase())
+        {
+            names.push(name.to_string());
+        }
+    }
+
+    let mut errors = Vec::new();
+    if names.len() > MAX_TAGS_PER_CODE {
+        errors.push(format!(
+            "A code can have at most {} tags",
+            MAX_TAGS_PER_CODE
+        ));
+    }
+    if let Some(name) = names
+        .iter()
+        .find(|name| name.chars().count() > MAX_TAG_LENGTH)
+    {
+        errors.push(format!(
+            "Tag \"{}\" must be {} characters or less",
+            name, MAX_TAG_LENGTH
+        ));
+    }
+    if errors.is_empty() {
+        Ok(names)
+    } else {
+        Err(errors)
+    }
+}
+
+/// Parse an optional date from a form field, where blank means unset
+fn parse_optional_date(value: Option<&str>, field: &str) -> Result<Option<Date>, String> {
+    match value.map(str::trim).filter(|value| !value.is_empty()) {
+        Some(value) => Date::parse_from_str(value, "%Y-%m-%d")
+            .map(Some)
+            .map_err(|_| format!("{} must be a date like 2026-12-31", field)),
+        None => Ok(None),
+    }
+}
+
+/// Parse the dates a code can be used between, checking they're the right way around
+fn validity_dates(
+    valid_from: Option<&str>,
+    expires_at: Option<&str>,
+) -> Result<(Option<Date>, Option<Date>), Vec<String>> {
+    let mut errors = Vec::new();
+    let valid_from = parse_optional_date(valid_from, "Valid from").unwrap_or_else(|err| {
+        errors.push(err);
+        None
+    });
+    let expires_at = parse_optional_date(expires_at, "Expiry date").unwrap_or_else(|err| {
+        errors.push(err);
+        None
+    });
+    if let (Some(valid_from), Some(expires_at)) = (valid_from, expires_at)
+        && expires_at < valid_from
+    {
+        errors.push("Expiry date can't be before the valid from date".to_string());
+    }
+    if errors.is_empty() {
+        Ok((valid_from, expires_at))
+    } else {
+        Err(errors)
+    }
 }
 
 impl EditCodeForm {
     /// Validate the form data (same rules as CreateCodeForm)
-    pub fn validate(&self) -> Result<(), HoofprintError> {
+    pub fn validate(&self, site: Option<&site::Model>) -> Result<(), HoofprintError> {
         let mut errors = Vec::new();
 
         // Validate code_type
-        if self.code_type != "barcode" && self.code_type != "qrcode" {
-            errors.push("Code type must be either 'barcode' or 'qrcode'".to_string());
+        if Code::try_from(self.code_type.as_str()).is_err() {
+            errors.push(invalid_code_type_message());
         }
 
         // Validate code_value
@@ -76,6 +295,12 @@ impl EditCodeForm {
             errors.push("Code value cannot be empty".to_string());
         } else if self.code_value.len() > 255 {
             errors.push("Code value must be 255 characters or less".to_string());
+        } else if let Ok(code) = Code::try_from(self.code_type.as_str())
+            && let Err(value_errors) = code.validate_value(&self.completed_value())
+        {
+            errors.extend(value_errors);
+        } else if let Some(error) = value_pattern_error(site, &self.completed_value()) {
+            errors.push(error);
         }
 
         // Validate site_id is a valid UUID
@@ -90,6 +315,27 @@ impl EditCodeForm {
             errors.push("Code name must be 255 characters or less".to_string());
         }
 
+        if let Err(option_errors) = self.render_options() {
+            errors.extend(option_errors);
+        }
+
+        if let Err(date_errors) = self.validity_dates() {
+            errors.extend(date_errors);
+        }
+
+        if let Err(tag_errors) = self.tags() {
+            errors.extend(tag_errors);
+        }
+
+        if let Some(ref notes) = self.notes
+            && notes.len() > MAX_NOTES_LENGTH
+        {
+            errors.push(format!(
+                "Notes must be {} characters or less",
+                MAX_NOTES_LENGTH
+            ));
+        }
+
         if errors.is_empty() {
             Ok(())
         } else {
@@ -97,6 +343,84 @@ impl EditCodeForm {
         }
     }
 
+    /// The notes to save, None when they're blank
+    pub fn notes(&self) -> Option<String> {
+        self.notes
+            .as_deref()
+            .filter(|notes| !notes.trim().is_empty())
+            .map(str::to_string)
+    }
+
+    /// The value to save, with a missing check digit added if the user asked for it
+    pub fn completed_value(&self) -> String {
+        completed_value(
+            &self.code_type,
+            &self.code_value,
+            self.add_check_digit.is_some(),
+        )
+    }
+
+    /// Parse and validate the rendering options
+    pub fn render_options(&self) -> Result<RenderOptions, Vec<String>> {
+        let mut errors = Vec::new();
+
+        let error_correction = match self.error_correction.as_deref() {
+            None | Some("") => ErrorCorrection::default(),
+            Some(level) => ErrorCorrection::all()
+                .into_iter()
+                .find(|option| option.to_string() == level)
+                .unwrap_or_else(|| {
+                    errors.push("Error correction must be one of: L, M, Q, H".to_string());
+                    ErrorCorrection::default()
+                }),
+        };
+
+        let module_size = parse_optional_number(self.module_size.as_deref(), "Module size")
+            .unwrap_or_else(|err| {
+                errors.push(err);
+                None
+            });
+        if let Some(module_size) = module_size
+            && !(1..=64).contains(&module_size)
+        {
+            errors.push("Module size must be between 1 and 64 pixels".to_string());
+        }
+
+        let quiet_zone = parse_optional_number(self.quiet_zone.as_deref(), "Quiet zone")
+            .unwrap_or_else(|err| {
+                errors.push(err);
+                None
+            });
+        if let Some(quiet_zone) = quiet_zone
+            && quiet_zone > 50
+        {
+            errors.push("Quiet zone must be 50 modules or less".to_string());
+        }
+
+        if errors.is_empty() {
+            Ok(RenderOptions {
+                error_correction,
+                module_size,
+                quiet_zone,
+                show_text: self.show_text.is_some(),
+                invert: self.invert.is_some(),
+                rotate: self.rotate.is_some(),
+            })
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Parse the dates the code can be used between
+    pub fn validity_dates(&self) -> Result<(Option<Date>, Option<Date>), Vec<String>> {
+        validity_dates(self.valid_from.as_deref(), self.expires_at.as_deref())
+    }
+
+    /// The tags to file the code under
+    pub fn tags(&self) -> Result<Vec<String>, Vec<String>> {
+        parse_tags(self.tags.as_deref())
+    }
+
     /// Parse the site_id as a UUID after validation
     pub fn parse_site_id(&self) -> Result<Uuid, HoofprintError> {
         Uuid::parse_str(&self.site_id)
@@ -104,6 +428,752 @@ impl EditCodeForm {
     }
 }
 
+/// The fields of the structured payload builder, where only the chosen type's fields are used
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub(crate) struct PayloadForm {
+    /// Which kind of payload to build, see [PayloadKind]
+    pub(crate) payload_type: String,
+    pub(crate) wifi_ssid: String,
+    pub(crate) wifi_password: String,
+    pub(crate) wifi_security: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) wifi_hidden: Option<String>,
+    pub(crate) contact_format: String,
+    pub(crate) contact_first_name: String,
+    pub(crate) contact_last_name: String,
+    pub(crate) contact_organisation: String,
+    pub(crate) contact_phone: String,
+    pub(crate) contact_email: String,
+    pub(crate) contact_url: String,
+    pub(crate) contact_address: String,
+    pub(crate) contact_note: String,
+    pub(crate) url: String,
+    pub(crate) geo_latitude: String,
+    pub(crate) geo_longitude: String,
+    pub(crate) sms_number: String,
+    pub(crate) sms_message: String,
+    pub(crate) event_summary: String,
+    /// Start time from a datetime-local input
+    pub(crate) event_start: String,
+    /// Optional end time from a datetime-local input
+    pub(crate) event_end: String,
+    pub(crate) event_location: String,
+    pub(crate) event_description: String,
+}
+
+/// Parse a coordinate from a form field
+fn parse_coordinate(value: &str, field: &str) -> Result<f64, String> {
+    value
+        .trim()
+        .parse::<f64>()
+        .ok()
+        .filter(|value| value.is_finite())
+        .ok_or_else(|| format!("{} must be a number", field))
+}
+
+impl PayloadForm {
+    /// Build and validate the payload the form describes
+    pub fn payload(&self) -> Result<Payload, Vec<String>> {
+        let kind = PayloadKind::all()
+            .into_iter()
+            .find(|kind| kind.to_string() == self.payload_type)
+            .ok_or_else(|| vec!["Choose what kind of code to build".to_string()])?;
+
+        let payload = match kind {
+            PayloadKind::Wifi => {
+                let security = WifiSecurity::all()
+                    .into_iter()
+                    .find(|security| security.to_string() == self.wifi_security)
+                    .ok_or_else(|| vec!["Choose how the Wi-Fi network is secured".to_string()])?;
+                Payload::Wifi(Wifi {
+                    ssid: self.wifi_ssid.clone(),
+                    password: match security {
+                        WifiSecurity::None => String::new(),
+                        _ => self.wifi_password.clone(),
+                    },
+                    security,
+                    hidden: self.wifi_hidden.is_some(),
+                })
+            }
+            PayloadKind::Contact => Payload::Contact(Contact {
+                format: ContactFormat::all()
+                    .into_iter()
+                    .find(|format| format.to_string() == self.contact_format)
+                    .unwrap_or_default(),
+                first_name: self.contact_first_name.trim().to_string(),
+                last_name: self.contact_last_name.trim().to_string(),
+                organisation: self.contact_organisation.trim().to_string(),
+                phone: self.contact_phone.trim().to_string(),
+                email: self.contact_email.trim().to_string(),
+                url: self.contact_url.trim().to_string(),
+                address: self.contact_address.trim().to_string(),
+                note: self.contact_note.trim().to_string(),
+            }),
+            PayloadKind::Url => Payload::Url(self.url.trim().to_string()),
+            PayloadKind::Geo => {
+                let latitude = parse_coordinate(&self.geo_latitude, "Latitude");
+                let longitude = parse_coordinate(&self.geo_longitude, "Longitude");
+                match (latitude, longitude) {
+                    (Ok(latitude), Ok(longitude)) => Payload::Geo {
+                        latitude,
+                        longitude,
+                    },
+                    (latitude, longitude) => {
+                        return Err([latitude.err(), longitude.err()]
+                            .into_iter()
+                            .flatten()
+                            .collect());
+                    }
+                }
+            }
+            PayloadKind::Sms => Payload::Sms {
+                number: self.sms_number.trim().to_string(),
+                message: self.sms_message.clone(),
+            },
+            PayloadKind::Event => {
+                let start = parse_form_datetime(&self.event_start)
+                    .ok_or_else(|| vec!["Events need a start date and time".to_string()])?;
+                let end = match self.event_end.as_str() {
+                    "" => None,
+                    end => Some(parse_form_datetime(end).ok_or_else(|| {
+                        vec!["The event's end must be a date and time".to_string()]
+                    })?),
+                };
+                Payload::Event(Event {
+                    summary: self.event_summary.trim().to_string(),
+                    start,
+                    end,
+                    location: self.event_location.trim().to_string(),
+                    description: self.event_description.trim().to_string(),
+                })
+            }
+        };
+        payload.validate()?;
+        Ok(payload)
+    }
+
+    /// The QR code value for the payload, checked the same way as a typed-in value
+    pub fn value(&self) -> Result<String, HoofprintError> {
+        let value = self
+            .payload()
+            .map_err(HoofprintError::ValidationError)?
+            .to_string();
+        if value.len() > 255 {
+            return Err(HoofprintError::ValidationError(vec![
+                "That's more than a code can hold, try fewer fields or a MeCard".to_string(),
+            ]));
+        }
+        Code::QR
+            .validate_value(&value)
+            .map_err(HoofprintError::ValidationError)?;
+        Ok(value)
+    }
+}
+
+impl From<&Payload> for PayloadForm {
+    /// Fill in the form from an existing payload, so it can be edited
+    fn from(payload: &Payload) -> Self {
+        let mut form = PayloadForm {
+            payload_type: payload.kind().to_string(),
+            ..Default::default()
+        };
+        match payload {
+            Payload::Wifi(wifi) => {
+                form.wifi_ssid = wifi.ssid.clone();
+                form.wifi_password = wifi.password.clone();
+                form.wifi_security = wifi.security.to_string();
+                form.wifi_hidden = wifi.hidden.then(|| "on".to_string());
+            }
+            Payload::Contact(contact) => {
+                form.contact_format = contact.format.to_string();
+                form.contact_first_name = contact.first_name.clone();
+                form.contact_last_name = contact.last_name.clone();
+                form.contact_organisation = contact.organisation.clone();
+                form.contact_phone = contact.phone.clone();
+                form.contact_email = contact.email.clone();
+                form.contact_url = contact.url.clone();
+                form.contact_address = contact.address.clone();
+                form.contact_note = contact.note.clone();
+            }
+            Payload::Url(url) => form.url = url.clone(),
+            Payload::Geo {
+                latitude,
+                longitude,
+            } => {
+                form.geo_latitude = latitude.to_string();
+                form.geo_longitude = longitude.to_string();
+            }
+            Payload::Sms { number, message } => {
+                form.sms_number = number.clone();
+                form.sms_message = message.clone();
+            }
+            Payload::Event(event) => {
+                form.event_summary = event.summary.clone();
+                form.event_start = form_datetime(event.start);
+                form.event_end = event.end.map(form_datetime).unwrap_or_default();
+                form.event_location = event.location.clone();
+                form.event_description = event.description.clone();
+            }
+        }
+        form
+    }
+}
+
+/// Creating a QR code from the payload builder
+#[derive(Debug, Deserialize, Serialize)]
+pub(crate) struct CreatePayloadForm {
+    pub(crate) site_id: String,
+    pub(crate) code_name: Option<String>,
+    #[serde(flatten)]
+    pub(crate) payload: PayloadForm,
+}
+
+impl CreatePayloadForm {
+    /// Validate everything but the payload, which is checked when it's serialised
+    pub fn validate(&self) -> Result<(), HoofprintError> {
+        let mut errors = Vec::new();
+        if Uuid::parse_str(&self.site_id).is_err() {
+            errors.push("Site ID must be a valid UUID".to_string());
+        }
+        if let Some(ref name) = self.code_name
+            && name.len() > 255
+        {
+            errors.push("Code name must be 255 characters or less".to_string());
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(HoofprintError::ValidationError(errors))
+        }
+    }
+}
+
+/// The homepage sort that puts the most used codes first
+pub(crate) const SORT_MOST_USED: &str = "most_used";
+
+/// Query parameters for the homepage
+#[derive(Debug, Default, Deserialize)]
+pub(crate) struct HomepageQuery {
+    /// Only show codes with this tag
+    pub tag: Option<String>,
+    /// How to sort the codes, the user's own order unless it's [SORT_MOST_USED]
+    pub sort: Option<String>,
+}
+
+impl HomepageQuery {
+    /// The tag to filter by, if there is one
+    pub fn tag(&self) -> Option<&str> {
+        self.tag
+            .as_deref()
+            .map(str::trim)
+            .filter(|tag| !tag.is_empty())
+    }
+
+    /// Whether to put the most used codes first
+    pub fn most_used(&self) -> bool {
+        self.sort.as_deref() == Some(SORT_MOST_USED)
+    }
+}
+
+/// Query parameters for viewing a code
+#[derive(Debug, Default, Deserialize)]
+pub(crate) struct ViewCodeQuery {
+    /// Where the code is being used, as "latitude,longitude", if the client wants to say
+    pub location: Option<String>,
+}
+
+impl ViewCodeQuery {
+    /// The location rounded to one decimal place, about 10km, or None if it isn't a valid one
+    pub fn coarse_location(&self) -> Option<String> {
+        let (latitude, longitude) = self.location.as_deref()?.split_once(',')?;
+        let latitude: f64 = latitude.trim().parse().ok()?;
+        let longitude: f64 = longitude.trim().parse().ok()?;
+        if !(-90.0..=90.0).contains(&latitude) || !(-180.0..=180.0).contains(&longitude) {
+            return None;
+        }
+        // rounding can leave -0.0, which should read the same as 0.0
+        let round = |degrees: f64| (degrees * 10.0).round() / 10.0 + 0.0;
+        Some(format!("{:.1},{:.1}", round(latitude), round(longitude)))
+    }
+}
+
+/// Query parameters for adding a code, to start it off at a site
+#[derive(Debug, Default, Deserialize)]
+pub(crate) struct NewCodeQuery {
+    pub site_id: Option<String>,
+}
+
+impl NewCodeQuery {
+    /// The site to start the code off at, if it's a valid ID
+    pub fn site_id(&self) -> Option<Uuid> {
+        Uuid::parse_str(self.site_id.as_deref()?.trim()).ok()
+    }
+}
+
+/// Marking a code as a favourite, or not
+#[derive(Debug, Deserialize, Serialize)]
+pub(crate) struct FavouriteForm {
+    pub favourite: bool,
+}
+
+/// A new order for some of the user's codes, first to last
+#[derive(Debug, Deserialize, Serialize)]
+pub(crate) struct ReorderRequest {
+    pub code_ids: Vec<Uuid>,
+}
+
+/// A secret to add to a code, like a PIN
+#[derive(Deserialize, Serialize)]
+pub(crate) struct SecretForm {
+    pub label: String,
+    pub value: String,
+    /// A checkbox, so it's only sent when it's ticked
+    #[serde(default)]
+    pub require_password: Option<String>,
+}
+
+impl std::fmt::Debug for SecretForm {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // never log the value
+        f.debug_struct("SecretForm")
+            .field("label", &self.label)
+            .field("require_password", &self.require_password)
+            .finish_non_exhaustive()
+    }
+}
+
+impl SecretForm {
+    pub fn validate(&self) -> Result<(), HoofprintError> {
+        let mut errors = Vec::new();
+        if self.label.trim().is_empty() {
+            errors.push("Secret label cannot be empty".to_string());
+        } else if self.label.trim().chars().count() > MAX_SECRET_LABEL_LENGTH {
+            errors.push(format!(
+                "Secret labels must be {} characters or less",
+                MAX_SECRET_LABEL_LENGTH
+            ));
+        }
+        if self.value.is_empty() {
+            errors.push("Secret value cannot be empty".to_string());
+        } else if self.value.len() > 255 {
+            errors.push("Secret value must be 255 characters or less".to_string());
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(HoofprintError::ValidationError(errors))
+        }
+    }
+}
+
+/// Asking to see a secret, with the user's password if the secret needs it
+#[derive(Default, Deserialize, Serialize)]
+#[serde(default)]
+pub(crate) struct RevealSecretForm {
+    pub password: Option<String>,
+}
+
+impl std::fmt::Debug for RevealSecretForm {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RevealSecretForm").finish_non_exhaustive()
+    }
+}
+
+/// Sharing a code with another user
+#[derive(Debug, Deserialize, Serialize)]
+pub(crate) struct ShareForm {
+    pub email: String,
+    /// "view" or "edit"
+    pub permission: String,
+}
+
+impl ShareForm {
+    /// Whether the code's being shared so it can be edited, as well as viewed
+    pub fn can_edit(&self) -> Result<bool, HoofprintError> {
+        match self.permission.as_str() {
+            "view" => Ok(false),
+            "edit" => Ok(true),
+            other => Err(HoofprintError::ValidationError(vec![format!(
+                "Invalid permission: {}",
+                other
+            )])),
+        }
+    }
+}
+
+/// Making a household
+#[derive(Debug, Deserialize, Serialize)]
+pub(crate) struct HouseholdForm {
+    pub name: String,
+}
+
+impl HouseholdForm {
+    pub fn validate(&self) -> Result<(), HoofprintError> {
+        let name = self.name.trim();
+        if name.is_empty() {
+            return Err(HoofprintError::ValidationError(vec![
+                "Household name cannot be empty".to_string(),
+            ]));
+        }
+        if name.chars().count() > MAX_HOUSEHOLD_NAME_LENGTH {
+            return Err(HoofprintError::ValidationError(vec![format!(
+                "Household names must be {} characters or less",
+                MAX_HOUSEHOLD_NAME_LENGTH
+            )]));
+        }
+        Ok(())
+    }
+}
+
+/// Adding someone to a household
+#[derive(Debug, Deserialize, Serialize)]
+pub(crate) struct HouseholdMemberForm {
+    pub email: String,
+    /// See [Role]
+    pub role: String,
+}
+
+impl HouseholdMemberForm {
+    pub fn role(&self) -> Result<Role, HoofprintError> {
+        Role::try_from(self.role.as_str())
+    }
+}
+
+/// Changing a household member's role
+#[derive(Debug, Deserialize, Serialize)]
+pub(crate) struct HouseholdRoleForm {
+    /// See [Role]
+    pub role: String,
+}
+
+impl HouseholdRoleForm {
+    pub fn role(&self) -> Result<Role, HoofprintError> {
+        Role::try_from(self.role.as_str())
+    }
+}
+
+/// Giving a code to a household, or taking it back
+#[derive(Debug, Deserialize, Serialize)]
+pub(crate) struct CodeHouseholdForm {
+    /// Empty to make the code the user's own
+    pub household_id: String,
+}
+
+impl CodeHouseholdForm {
+    pub fn household_id(&self) -> Result<Option<Uuid>, HoofprintError> {
+        if self.household_id.is_empty() {
+            return Ok(None);
+        }
+        Uuid::parse_str(&self.household_id).map(Some).map_err(|_| {
+            HoofprintError::ValidationError(vec![format!(
+                "Invalid household ID: {}",
+                self.household_id
+            )])
+        })
+    }
+}
+
+/// Making a public link to a code
+#[derive(Debug, Deserialize, Serialize)]
+pub(crate) struct ShareLinkForm {
+    /// How many hours the link lasts for
+    pub hours: String,
+    /// How many times the link can be opened, empty for no limit
+    pub max_views: String,
+}
+
+impl ShareLinkForm {
+    /// How long the link lasts for and how many times it can be opened
+    pub fn validate(&self) -> Result<(u32, Option<i32>), HoofprintError> {
+        let mut errors = Vec::new();
+
+        let hours = match self.hours.trim().parse::<u32>() {
+            Ok(hours) if (1..=MAX_LINK_HOURS).contains(&hours) => hours,
+            _ => {
+                errors.push(format!(
+                    "Links must last between 1 and {} hours",
+                    MAX_LINK_HOURS
+                ));
+                0
+            }
+        };
+
+        let max_views = match self.max_views.trim() {
+            "" => None,
+            max_views => match max_views.parse::<i32>() {
+                Ok(max_views) if (1..=MAX_LINK_VIEWS).contains(&max_views) => Some(max_views),
+                _ => {
+                    errors.push(format!(
+                        "Links can be limited to between 1 and {} views",
+                        MAX_LINK_VIEWS
+                    ));
+                    None
+                }
+            },
+        };
+
+        if errors.is_empty() {
+            Ok((hours, max_views))
+        } else {
+            Err(HoofprintError::ValidationError(errors))
+        }
+    }
+}
+
+/// Adding or changing a site
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub(crate) struct SiteForm {
+    pub name: String,
+    /// The site's website, which can be empty
+    pub url: String,
+    /// A hex colour like #1a2b3c, or empty for none
+    #[serde(default)]
+    pub brand_colour: String,
+    /// See [Code], or empty for none
+    #[serde(default)]
+    pub default_code_type: String,
+    /// Markdown, or empty for none
+    #[serde(default)]
+    pub instructions: String,
+    /// A regular expression, or empty for none
+    #[serde(default)]
+    pub value_pattern: String,
+    pub csrf_token: String,
+}
+
+/// A field that's None when it's left empty
+fn optional_field(value: &str) -> Option<String> {
+    Some(value.trim())
+        .filter(|value| !value.is_empty())
+        .map(str::to_string)
+}
+
+impl SiteForm {
+    pub fn brand_colour(&self) -> Option<String> {
+        optional_field(&self.brand_colour).map(|colour| colour.to_lowercase())
+    }
+
+    pub fn default_code_type(&self) -> Option<String> {
+        optional_field(&self.default_code_type)
+    }
+
+    pub fn instructions(&self) -> Option<String> {
+        optional_field(&self.instructions)
+    }
+
+    pub fn value_pattern(&self) -> Option<String> {
+        optional_field(&self.value_pattern)
+    }
+
+    pub fn validate(&self) -> Result<(), HoofprintError> {
+        let mut errors = Vec::new();
+
+        let name = self.name.trim();
+        if name.is_empty() {
+            errors.push("Site name cannot be empty".to_string());
+        } else if name.len() > MAX_SITE_NAME_LENGTH {
+            errors.push(format!(
+                "Site name must be {} characters or less",
+                MAX_SITE_NAME_LENGTH
+            ));
+        }
+
+        let url = self.url.trim();
+        if !(url.is_empty() || url.starts_with("https://") || url.starts_with("http://")) {
+            errors.push("Site URL must start with http:// or https://".to_string());
+        } else if url.len() > MAX_SITE_URL_LENGTH {
+            errors.push(format!(
+                "Site URL must be {} characters or less",
+                MAX_SITE_URL_LENGTH
+            ));
+        }
+
+        if let Some(colour) = self.brand_colour()
+            && !is_brand_colour(&colour)
+        {
+            errors.push("Brand colour must be a hex colour like #1a2b3c".to_string());
+        }
+
+        if let Some(code_type) = self.default_code_type()
+            && Code::try_from(code_type.as_str()).is_err()
+        {
+            errors.push(invalid_code_type_message());
+        }
+
+        if let Some(instructions) = self.instructions()
+            && instructions.chars().count() > MAX_SITE_INSTRUCTIONS_LENGTH
+        {
+            errors.push(format!(
+                "Instructions must be {} characters or less",
+                MAX_SITE_INSTRUCTIONS_LENGTH
+            ));
+        }
+
+        if let Some(pattern) = self.value_pattern() {
+            if pattern.len() > MAX_VALUE_PATTERN_LENGTH {
+                errors.push(format!(
+                    "Value pattern must be {} characters or less",
+                    MAX_VALUE_PATTERN_LENGTH
+                ));
+            } else if let Err(err) = site::value_regex(&pattern) {
+                errors.push(format!(
+                    "Value pattern isn't a valid regular expression: {}",
+                    err
+                ));
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(HoofprintError::ValidationError(errors))
+        }
+    }
+}
+
+/// Merging a duplicate site into another one
+#[derive(Debug, Deserialize, Serialize)]
+pub(crate) struct SiteMergeForm {
+    /// The site that's kept
+    pub site_id: String,
+    pub csrf_token: String,
+}
+
+/// An admin action that only needs the page's CSRF token, like deleting a site
+#[derive(Debug, Deserialize, Serialize)]
+pub(crate) struct AdminActionForm {
+    pub csrf_token: String,
+}
+
+impl SiteMergeForm {
+    pub fn site_id(&self) -> Result<Uuid, HoofprintError> {
+        Uuid::parse_str(&self.site_id).map_err(|_| {
+            HoofprintError::ValidationError(vec![format!("Invalid site ID: {}", self.site_id)])
+        })
+    }
+}
+
+/// Query parameters for searching codes
+#[derive(Debug, Default, Deserialize)]
+pub(crate) struct SearchQuery {
+    pub q: Option<String>,
+}
+
+impl SearchQuery {
+    /// What to search for, if anything
+    pub fn q(&self) -> Option<&str> {
+        self.q.as_deref().map(str::trim).filter(|q| !q.is_empty())
+    }
+}
+
+/// Query parameters for exporting a code as a PNG
+#[derive(Debug, Default, Deserialize)]
+pub(crate) struct PngExportQuery {
+    /// Total image width in pixels
+    pub(crate) width: Option<u32>,
+    /// Pixels per module
+    pub(crate) module_size: Option<u32>,
+    /// Quiet zone around the code, in modules
+    pub(crate) quiet_zone: Option<u32>,
+    /// Resolution to record in the image metadata
+    pub(crate) dpi: Option<u32>,
+}
+
+impl PngExportQuery {
+    /// Validate the query parameters
+    pub fn validate(&self) -> Result<(), HoofprintError> {
+        let mut errors = Vec::new();
+
+        if let Some(width) = self.width
+            && !(1..=MAX_PNG_DIMENSION).contains(&width)
+        {
+            errors.push(format!(
+                "Width must be between 1 and {} pixels",
+                MAX_PNG_DIMENSION
+            ));
+        }
+
+        if let Some(module_size) = self.module_size
+            && !(1..=64).contains(&module_size)
+        {
+            errors.push("Module size must be between 1 and 64 pixels".to_string());
+        }
+
+        if let Some(quiet_zone) = self.quiet_zone
+            && quiet_zone > 50
+        {
+            errors.push("Quiet zone must be 50 modules or less".to_string());
+        }
+
+        if let Some(dpi) = self.dpi
+            && !(1..=2400).contains(&dpi)
+        {
+            errors.push("DPI must be between 1 and 2400".to_string());
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(HoofprintError::ValidationError(errors))
+        }
+    }
+}
+
+/// Query parameters for the wallet sheet PDF
+///
+/// `code` can be repeated, which serde_urlencoded can't handle, so this is parsed by hand.
+#[derive(Debug, Default)]
+pub(crate) struct WalletSheetQuery {
+    pub(crate) paper: PaperSize,
+    /// The codes to include, or all of them if none were picked. The form always sends
+    /// `selected`, so unticking every code picks none rather than all of them.
+    pub(crate) codes: Option<Vec<Uuid>>,
+}
+
+impl WalletSheetQuery {
+    /// Whether a code goes on the sheet
+    pub fn includes(&self, code_id: Uuid) -> bool {
+        self.codes
+            .as_ref()
+            .is_none_or(|codes| codes.contains(&code_id))
+    }
+}
+
+impl WalletSheetQuery {
+    /// Parse and validate a raw query string
+    pub fn parse(query: Option<&str>) -> Result<Self, HoofprintError> {
+        let mut result = Self::default();
+        let mut errors = Vec::new();
+
+        for (key, value) in url::form_urlencoded::parse(query.unwrap_or_default().as_bytes()) {
+            match key.as_ref() {
+                "paper" => match PaperSize::all()
+                    .into_iter()
+                    .find(|paper| paper.to_string() == value)
+                {
+                    Some(paper) => result.paper = paper,
+                    None => errors.push("Paper size must be a4 or letter".to_string()),
+                },
+                "code" => match Uuid::parse_str(&value) {
+                    Ok(code_id) => result.codes.get_or_insert_default().push(code_id),
+                    Err(_) => errors.push(format!("Invalid code ID: {}", value)),
+                },
+                "selected" => {
+                    result.codes.get_or_insert_default();
+                }
+                _ => {}
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(result)
+        } else {
+            Err(HoofprintError::ValidationError(errors))
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -111,57 +1181,550 @@ mod tests {
     #[test]
     fn test_validate_code_type() {
         let form = CreateCodeForm {
-            code_type: "barcode".to_string(),
+            code_type: "code128".to_string(),
             code_value: "123456".to_string(),
             site_id: "00000000-0000-0000-0000-000000000000".to_string(),
             code_name: None,
+            add_check_digit: None,
+            new_site_name: None,
+            valid_from: None,
+            expires_at: None,
+            tags: None,
         };
-        assert!(form.validate().is_ok());
+        assert!(form.validate(None).is_ok());
 
         let form = CreateCodeForm {
             code_type: "qrcode".to_string(),
             code_value: "123456".to_string(),
             site_id: "00000000-0000-0000-0000-000000000000".to_string(),
             code_name: None,
+            add_check_digit: None,
+            new_site_name: None,
+            valid_from: None,
+            expires_at: None,
+            tags: None,
         };
-        assert!(form.validate().is_ok());
+        assert!(form.validate(None).is_ok());
+
+        for code in Code::all() {
+            let form = CreateCodeForm {
+                code_type: code.to_string(),
+                code_value: code.sample_value().to_string(),
+                site_id: "00000000-0000-0000-0000-000000000000".to_string(),
+                code_name: None,
+                add_check_digit: None,
+                new_site_name: None,
+                valid_from: None,
+                expires_at: None,
+                tags: None,
+            };
+            assert!(form.validate(None).is_ok(), "{} should be valid", code);
+        }
+
+        // the pre-symbology type name is no longer accepted
+        let form = CreateCodeForm {
+            code_type: "barcode".to_string(),
+            code_value: "123456".to_string(),
+            site_id: "00000000-0000-0000-0000-000000000000".to_string(),
+            code_name: None,
+            add_check_digit: None,
+            new_site_name: None,
+            valid_from: None,
+            expires_at: None,
+            tags: None,
+        };
+        assert!(form.validate(None).is_err());
 
         let form = CreateCodeForm {
             code_type: "invalid".to_string(),
             code_value: "123456".to_string(),
             site_id: "00000000-0000-0000-0000-000000000000".to_string(),
             code_name: None,
+            add_check_digit: None,
+            new_site_name: None,
+            valid_from: None,
+            expires_at: None,
+            tags: None,
         };
-        assert!(form.validate().is_err());
+        assert!(form.validate(None).is_err());
     }
 
     #[test]
     fn test_validate_code_value() {
         let form = CreateCodeForm {
-            code_type: "barcode".to_string(),
+            code_type: "code128".to_string(),
             code_value: "".to_string(),
             site_id: "00000000-0000-0000-0000-000000000000".to_string(),
             code_name: None,
+            add_check_digit: None,
+            new_site_name: None,
+            valid_from: None,
+            expires_at: None,
+            tags: None,
         };
-        assert!(form.validate().is_err());
+        assert!(form.validate(None).is_err());
 
         let form = CreateCodeForm {
-            code_type: "barcode".to_string(),
+            code_type: "code128".to_string(),
             code_value: "a".repeat(256),
             site_id: "00000000-0000-0000-0000-000000000000".to_string(),
             code_name: None,
+            add_check_digit: None,
+            new_site_name: None,
+            valid_from: None,
+            expires_at: None,
+            tags: None,
         };
-        assert!(form.validate().is_err());
+        assert!(form.validate(None).is_err());
     }
 
     #[test]
     fn test_validate_site_id() {
         let form = CreateCodeForm {
-            code_type: "barcode".to_string(),
+            code_type: "code128".to_string(),
             code_value: "123456".to_string(),
             site_id: "not-a-uuid".to_string(),
             code_name: None,
+            add_check_digit: None,
+            new_site_name: None,
+            valid_from: None,
+            expires_at: None,
+            tags: None,
+        };
+        assert!(form.validate(None).is_err());
+    }
+
+    #[test]
+    fn test_validate_check_digit() {
+        let mut form = CreateCodeForm {
+            code_type: Code::Ean13.to_string(),
+            code_value: "590123412345".to_string(),
+            site_id: "00000000-0000-0000-0000-000000000000".to_string(),
+            code_name: None,
+            add_check_digit: None,
+            new_site_name: None,
+            valid_from: None,
+            expires_at: None,
+            tags: None,
+        };
+        assert!(form.validate(None).is_err());
+        assert_eq!(form.completed_value(), "590123412345");
+
+        // asking for the check digit fills it in
+        form.add_check_digit = Some("on".to_string());
+        assert!(form.validate(None).is_ok());
+        assert_eq!(form.completed_value(), "5901234123457");
+
+        // but won't fix a wrong one
+        form.code_value = "5901234123458".to_string();
+        assert!(form.validate(None).is_err());
+
+        let form = CreateCodeForm {
+            code_type: Code::Code39.to_string(),
+            code_value: "lowercase".to_string(),
+            site_id: "00000000-0000-0000-0000-000000000000".to_string(),
+            code_name: None,
+            add_check_digit: None,
+            new_site_name: None,
+            valid_from: None,
+            expires_at: None,
+            tags: None,
+        };
+        assert!(form.validate(None).is_err());
+    }
+
+    #[test]
+    fn test_validate_value_pattern() {
+        let site = site::Model {
+            id: Uuid::now_v7(),
+            name: "City Library".to_string(),
+            url: String::new(),
+            created_at: time::macros::datetime!(2026-01-01 0:00),
+            brand_colour: None,
+            default_code_type: None,
+            instructions: None,
+            value_pattern: Some(r"LIB-\d{4}".to_string()),
+        };
+        let form = |code_value: &str| CreateCodeForm {
+            code_type: "code128".to_string(),
+            code_value: code_value.to_string(),
+            site_id: site.id.to_string(),
+            code_name: None,
+            add_check_digit: None,
+            new_site_name: None,
+            valid_from: None,
+            expires_at: None,
+            tags: None,
+        };
+        assert!(form("LIB-1234").validate(Some(&site)).is_ok());
+        assert!(matches!(
+            form("LIB-12345").validate(Some(&site)),
+            Err(HoofprintError::ValidationError(errors))
+                if errors == vec!["That doesn't look like a City Library code".to_string()]
+        ));
+        // other sites' codes don't have to match
+        assert!(form("LIB-12345").validate(None).is_ok());
+        assert!(
+            form("LIB-12345")
+                .validate(Some(&site::Model {
+                    value_pattern: None,
+                    ..site.clone()
+                }))
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_edit_form_render_options() {
+        let mut form = EditCodeForm {
+            code_type: Code::QR.to_string(),
+            code_value: "hello".to_string(),
+            site_id: "00000000-0000-0000-0000-000000000000".to_string(),
+            code_name: None,
+            add_check_digit: None,
+            error_correction: None,
+            module_size: Some("".to_string()),
+            quiet_zone: None,
+            show_text: None,
+            invert: None,
+            rotate: None,
+            valid_from: None,
+            expires_at: None,
+            tags: None,
+            notes: None,
+        };
+        assert_eq!(form.render_options(), Ok(RenderOptions::default()));
+
+        form.error_correction = Some("M".to_string());
+        form.module_size = Some(" 6 ".to_string());
+        form.quiet_zone = Some("0".to_string());
+        form.rotate = Some("on".to_string());
+        assert_eq!(
+            form.render_options(),
+            Ok(RenderOptions {
+                error_correction: ErrorCorrection::M,
+                module_size: Some(6),
+                quiet_zone: Some(0),
+                rotate: true,
+                ..Default::default()
+            })
+        );
+        assert!(form.validate(None).is_ok());
+
+        form.error_correction = Some("X".to_string());
+        form.module_size = Some("big".to_string());
+        form.quiet_zone = Some("51".to_string());
+        let errors = form
+            .render_options()
+            .expect_err("Options should be invalid");
+        assert_eq!(errors.len(), 3);
+        assert!(form.validate(None).is_err());
+    }
+
+    #[test]
+    fn test_parse_tags() {
+        assert_eq!(parse_tags(None), Ok(Vec::new()));
+        assert_eq!(
+            parse_tags(Some(" Gym, library,,gym , Travel ")),
+            Ok(vec![
+                "Gym".to_string(),
+                "library".to_string(),
+                "Travel".to_string()
+            ])
+        );
+        assert!(parse_tags(Some(&"x".repeat(MAX_TAG_LENGTH + 1))).is_err());
+        let too_many: Vec<String> = (0..=MAX_TAGS_PER_CODE).map(|n| n.to_string()).collect();
+        assert!(parse_tags(Some(&too_many.join(","))).is_err());
+    }
+
+    #[test]
+    fn test_coarse_location() {
+        let location = |location: &str| {
+            ViewCodeQuery {
+                location: Some(location.to_string()),
+            }
+            .coarse_location()
+        };
+        assert_eq!(
+            location("-33.8688, 151.2093"),
+            Some("-33.9,151.2".to_string())
+        );
+        assert_eq!(location("-0.01,0"), Some("0.0,0.0".to_string()));
+        assert_eq!(location("91,0"), None);
+        assert_eq!(location("0,-180.5"), None);
+        assert_eq!(location("NaN,0"), None);
+        assert_eq!(location("somewhere"), None);
+        assert_eq!(ViewCodeQuery::default().coarse_location(), None);
+    }
+
+    #[test]
+    fn test_validity_dates() {
+        assert_eq!(validity_dates(None, Some("")), Ok((None, None)));
+        assert_eq!(
+            validity_dates(Some("2026-01-01"), Some(" 2026-12-31 ")),
+            Ok((
+                Date::from_ymd_opt(2026, 1, 1),
+                Date::from_ymd_opt(2026, 12, 31)
+            ))
+        );
+        // a code can be valid for a single day
+        assert!(validity_dates(Some("2026-06-01"), Some("2026-06-01")).is_ok());
+        assert_eq!(
+            validity_dates(Some("2026-06-02"), Some("2026-06-01")),
+            Err(vec![
+                "Expiry date can't be before the valid from date".to_string()
+            ])
+        );
+        assert_eq!(
+            validity_dates(Some("tomorrow"), Some("2026-02-30"))
+                .expect_err("Dates should be invalid")
+                .len(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_validate_png_export_query() {
+        assert!(PngExportQuery::default().validate().is_ok());
+
+        let query = PngExportQuery {
+            width: Some(600),
+            module_size: Some(4),
+            quiet_zone: Some(0),
+            dpi: Some(300),
         };
-        assert!(form.validate().is_err());
+        assert!(query.validate().is_ok());
+
+        let query = PngExportQuery {
+            width: Some(MAX_PNG_DIMENSION + 1),
+            ..Default::default()
+        };
+        assert!(query.validate().is_err());
+
+        let query = PngExportQuery {
+            module_size: Some(0),
+            ..Default::default()
+        };
+        assert!(query.validate().is_err());
+
+        let query = PngExportQuery {
+            dpi: Some(0),
+            ..Default::default()
+        };
+        assert!(query.validate().is_err());
+    }
+
+    #[test]
+    fn test_parse_wallet_sheet_query() {
+        let query = WalletSheetQuery::parse(None).expect("Empty query should parse");
+        assert_eq!(query.paper, PaperSize::A4);
+        assert_eq!(query.codes, None);
+        assert!(query.includes(Uuid::nil()));
+
+        let query = WalletSheetQuery::parse(Some(
+            "paper=letter&code=00000000-0000-0000-0000-000000000000&code=00000000-0000-0000-0000-000000000001",
+        ))
+        .expect("Query should parse");
+        assert_eq!(query.paper, PaperSize::Letter);
+        assert_eq!(query.codes.as_ref().map(Vec::len), Some(2));
+        assert!(!query.includes(Uuid::max()));
+
+        // the form picked nothing, which isn't the same as not picking
+        let query =
+            WalletSheetQuery::parse(Some("paper=a4&selected=1")).expect("Query should parse");
+        assert_eq!(query.codes, Some(Vec::new()));
+        assert!(!query.includes(Uuid::nil()));
+
+        assert!(WalletSheetQuery::parse(Some("paper=a3")).is_err());
+        assert!(WalletSheetQuery::parse(Some("code=not-a-uuid")).is_err());
+    }
+
+    #[test]
+    fn test_payload_form() {
+        let form = PayloadForm {
+            payload_type: "wifi".to_string(),
+            wifi_ssid: "Home;Net".to_string(),
+            wifi_password: "hunter2hunter2".to_string(),
+            wifi_security: "WPA".to_string(),
+            ..Default::default()
+        };
+        let value = form.value().expect("Failed to build Wi-Fi payload");
+        assert_eq!(value, r"WIFI:T:WPA;S:Home\;Net;P:hunter2hunter2;;");
+        let payload = Payload::parse(&value).expect("Failed to parse Wi-Fi payload");
+        assert_eq!(PayloadForm::from(&payload).wifi_ssid, "Home;Net");
+
+        let form = PayloadForm {
+            payload_type: "event".to_string(),
+            event_summary: "Dentist".to_string(),
+            event_start: "2026-11-02T09:00".to_string(),
+            event_end: "2026-11-02T08:00".to_string(),
+            ..Default::default()
+        };
+        assert!(form.value().is_err());
+
+        let form = PayloadForm {
+            payload_type: "geo".to_string(),
+            geo_latitude: "north".to_string(),
+            geo_longitude: "".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(form.payload().err().map(|errors| errors.len()), Some(2));
+
+        let form = PayloadForm {
+            payload_type: "contact".to_string(),
+            contact_first_name: "Ada".to_string(),
+            contact_note: "n".repeat(300),
+            ..Default::default()
+        };
+        assert!(form.value().is_err());
+
+        assert!(PayloadForm::default().payload().is_err());
+    }
+
+    #[test]
+    fn test_secret_form() {
+        let form = SecretForm {
+            label: "PIN".to_string(),
+            value: "4321".to_string(),
+            require_password: None,
+        };
+        assert!(form.validate().is_ok());
+        assert!(!format!("{:?}", form).contains("4321"));
+        assert!(
+            !format!(
+                "{:?}",
+                RevealSecretForm {
+                    password: Some("hunter2".to_string())
+                }
+            )
+            .contains("hunter2")
+        );
+
+        let form = SecretForm {
+            label: " ".to_string(),
+            value: String::new(),
+            require_password: None,
+        };
+        assert!(matches!(
+            form.validate(),
+            Err(HoofprintError::ValidationError(errors)) if errors.len() == 2
+        ));
+    }
+
+    #[test]
+    fn test_share_link_form() {
+        let form = ShareLinkForm {
+            hours: "24".to_string(),
+            max_views: String::new(),
+        };
+        assert_eq!(form.validate().ok(), Some((24, None)));
+        let form = ShareLinkForm {
+            hours: " 1 ".to_string(),
+            max_views: "3".to_string(),
+        };
+        assert_eq!(form.validate().ok(), Some((1, Some(3))));
+
+        let form = ShareLinkForm {
+            hours: "0".to_string(),
+            max_views: "-1".to_string(),
+        };
+        assert!(matches!(
+            form.validate(),
+            Err(HoofprintError::ValidationError(errors)) if errors.len() == 2
+        ));
+        let form = ShareLinkForm {
+            hours: (MAX_LINK_HOURS + 1).to_string(),
+            max_views: "lots".to_string(),
+        };
+        assert!(matches!(
+            form.validate(),
+            Err(HoofprintError::ValidationError(errors)) if errors.len() == 2
+        ));
+    }
+
+    #[test]
+    fn test_site_form() {
+        let form = SiteForm {
+            name: " City Library ".to_string(),
+            url: String::new(),
+            ..Default::default()
+        };
+        assert!(form.validate().is_ok());
+        let form = SiteForm {
+            name: "Costco".to_string(),
+            url: "https://www.costco.com".to_string(),
+            ..Default::default()
+        };
+        assert!(form.validate().is_ok());
+
+        let form = SiteForm {
+            name: " ".to_string(),
+            url: "javascript:alert(1)".to_string(),
+            ..Default::default()
+        };
+        assert!(matches!(
+            form.validate(),
+            Err(HoofprintError::ValidationError(errors)) if errors.len() == 2
+        ));
+        let form = SiteForm {
+            name: "x".repeat(MAX_SITE_NAME_LENGTH + 1),
+            url: format!("https://{}", "x".repeat(MAX_SITE_URL_LENGTH)),
+            ..Default::default()
+        };
+        assert!(matches!(
+            form.validate(),
+            Err(HoofprintError::ValidationError(errors)) if errors.len() == 2
+        ));
+
+        let form = SiteForm {
+            name: "Costco".to_string(),
+            url: String::new(),
+            brand_colour: " #E31837 ".to_string(),
+            default_code_type: Code::Ean13.to_string(),
+            instructions: "Show this at the *front* desk".to_string(),
+            value_pattern: r"\d{12}".to_string(),
+            ..Default::default()
+        };
+        assert!(form.validate().is_ok());
+        assert_eq!(form.brand_colour().as_deref(), Some("#e31837"));
+        let form = SiteForm {
+            name: "Costco".to_string(),
+            url: String::new(),
+            brand_colour: "red".to_string(),
+            default_code_type: "barcode".to_string(),
+            instructions: "x".repeat(MAX_SITE_INSTRUCTIONS_LENGTH + 1),
+            value_pattern: "(".to_string(),
+            ..Default::default()
+        };
+        assert!(matches!(
+            form.validate(),
+            Err(HoofprintError::ValidationError(errors)) if errors.len() == 4
+        ));
+        assert_eq!(
+            SiteForm {
+                name: "Costco".to_string(),
+                value_pattern: "  ".to_string(),
+                ..Default::default()
+            }
+            .value_pattern(),
+            None
+        );
+
+        assert!(
+            SiteMergeForm {
+                site_id: Uuid::nil().to_string(),
+                csrf_token: String::new(),
+            }
+            .site_id()
+            .is_ok()
+        );
+        assert!(
+            SiteMergeForm {
+                site_id: "costco".to_string(),
+                csrf_token: String::new(),
+            }
+            .site_id()
+            .is_err()
+        );
     }
 }
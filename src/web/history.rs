@@ -0,0 +1,274 @@
+//! Revision history for codes, and restoring old revisions
+
+use std::{collections::HashMap, time::SystemTime};
+
+use axum::extract::Path;
+use sea_orm::{ActiveValue::Set, prelude::DateTimeUtc};
+
+use crate::{
+    Code,
+    db::entities::{code, code_revision, code_share::Permission, site, user},
+    prelude::*,
+    web::views::get_code,
+};
+
+/// How revision times are shown
+pub(crate) const TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S UTC";
+
+/// One field that a revision changed
+pub(crate) struct FieldChange {
+    pub(crate) field: &'static str,
+    /// None for the first revision
+    pub(crate) before: Option<String>,
+    pub(crate) after: String,
+}
+
+pub(crate) struct RevisionItem {
+    pub(crate) id: Uuid,
+    pub(crate) created_at: String,
+    pub(crate) changed_by: String,
+    pub(crate) changes: Vec<FieldChange>,
+    /// Whether how the code is drawn changed
+    pub(crate) options_changed: bool,
+    /// When the revision this one restored was made
+    pub(crate) restored_from: Option<String>,
+    /// Whether it's the code as it is now, so there's nothing to restore
+    pub(crate) current: bool,
+    /// Sealed with a data key that was retired by a password reset
+    pub(crate) locked: bool,
+}
+
+#[derive(Template, WebTemplate)]
+#[template(path = "code_history.html")]
+pub(crate) struct CodeHistoryPage {
+    pub(crate) code_id: Uuid,
+    pub(crate) code_name: Option<String>,
+    /// Newest first
+    pub(crate) revisions: Vec<RevisionItem>,
+    /// Whether the user can restore revisions
+    pub(crate) can_edit: bool,
+}
+
+fn type_label(type_: &str) -> String {
+    Code::try_from(type_)
+        .map(|code| code.label().to_string())
+        .unwrap_or_else(|_| type_.to_string())
+}
+
+/// The fields that changed between two revisions, or all of them for the first
+fn changes(
+    previous: Option<&code_revision::Model>,
+    revision: &code_revision::Model,
+    site_names: &HashMap<Uuid, String>,
+) -> Vec<FieldChange> {
+    let site_name = |site_id: &Uuid| {
+        site_names
+            .get(site_id)
+            .cloned()
+            .unwrap_or_else(|| "Unknown site".to_string())
+    };
+    let fields = |revision: &code_revision::Model| {
+        [
+            ("Type", type_label(&revision.type_)),
+            ("Value", revision.value.clone()),
+            ("Name", revision.name.clone().unwrap_or_default()),
+            ("Site", site_name(&revision.site_id)),
+            ("Notes", revision.notes.clone().unwrap_or_default()),
+            (
+                "Valid from",
+                revision
+                    .valid_from
+                    .map(|date| date.to_string())
+                    .unwrap_or_default(),
+            ),
+            (
+                "Expires",
+                revision
+                    .expires_at
+                    .map(|date| date.to_string())
+                    .unwrap_or_default(),
+            ),
+        ]
+    };
+    let after = fields(revision);
+    match previous {
+        None => after
+            .into_iter()
+            .filter(|(_, value)| !value.is_empty())
+            .map(|(field, after)| FieldChange {
+                field,
+                before: None,
+                after,
+            })
+            .collect(),
+        Some(previous) => fields(previous)
+            .into_iter()
+            .zip(after)
+            .filter(|((_, before), (_, after))| before != after)
+            .map(|((field, before), (_, after))| FieldChange {
+                field,
+                before: Some(before),
+                after,
+            })
+            .collect(),
+    }
+}
+
+#[instrument(level = "debug", skip(app_state, session))]
+pub(crate) async fn code_history_get(
+    State(app_state): State<AppState>,
+    Path(code_id_str): Path<String>,
+    session: Session,
+) -> Result<CodeHistoryPage, HoofprintError> {
+    let auth = app_state.get_authenticated_user(&session).await?;
+    let mut cipher = app_state.get_code_cipher(&session).await?;
+    let (code_model, _site_model, permission) = get_code(
+        &app_state,
+        &mut cipher,
+        auth.user_id,
+        &code_id_str,
+        Permission::View,
+    )
+    .await?;
+
+    let revisions = code_revision::Model::find_for_code(&app_state.db, code_model.id).await?;
+
+    let user_names: HashMap<Uuid, String> = user::Entity::find()
+        .filter(user::Column::Id.is_in(revisions.iter().map(|revision| revision.user_id)))
+        .all(&app_state.db)
+        .await?
+        .into_iter()
+        .map(|user| (user.id, user.display_name))
+        .collect();
+    let site_names: HashMap<Uuid, String> = site::Entity::find()
+        .filter(site::Column::Id.is_in(revisions.iter().map(|revision| revision.site_id)))
+        .all(&app_state.db)
+        .await?
+        .into_iter()
+        .map(|site| (site.id, site.name))
+        .collect();
+    let created_at: HashMap<Uuid, String> = revisions
+        .iter()
+        .map(|revision| {
+            (
+                revision.id,
+                revision.created_at.format(TIME_FORMAT).to_string(),
+            )
+        })
+        .collect();
+
+    let last = revisions.len().saturating_sub(1);
+    let mut previous: Option<code_revision::Model> = None;
+    let mut items = Vec::with_capacity(revisions.len());
+    for (index, revision) in revisions.into_iter().enumerate() {
+        let item = RevisionItem {
+            id: revision.id,
+            created_at: created_at.get(&revision.id).cloned().unwrap_or_default(),
+            changed_by: user_names
+                .get(&revision.user_id)
+                .cloned()
+                .unwrap_or_else(|| "Unknown user".to_string()),
+            changes: Vec::new(),
+            options_changed: false,
+            restored_from: revision
+                .restored_from
+                .and_then(|id| created_at.get(&id).cloned()),
+            current: index == last,
+            locked: false,
+        };
+        match revision.decrypt(&cipher) {
+            Ok(revision) => {
+                items.push(RevisionItem {
+                    changes: changes(previous.as_ref(), &revision, &site_names),
+                    options_changed: previous
+                        .as_ref()
+                        .is_some_and(|previous| previous.render_options != revision.render_options),
+                    ..item
+                });
+                previous = Some(revision);
+            }
+            Err(HoofprintError::CodeLocked(_)) => {
+                items.push(RevisionItem {
+                    locked: true,
+                    ..item
+                });
+                previous = None;
+            }
+            Err(err) => return Err(err),
+        }
+    }
+    items.reverse();
+
+    Ok(CodeHistoryPage {
+        code_id: code_model.id,
+        code_name: code_model.name,
+        revisions: items,
+        can_edit: permission >= Permission::Edit,
+    })
+}
+
+/// Put a code back the way it was at an earlier revision, saved as a new revision
+#[instrument(level = "debug", skip(app_state, session))]
+pub(crate) async fn code_restore_post(
+    State(app_state): State<AppState>,
+    Path((code_id_str, revision_id_str)): Path<(String, String)>,
+    session: Session,
+) -> Result<Redirect, HoofprintError> {
+    let auth = app_state.get_authenticated_user(&session).await?;
+    let mut cipher = app_state.get_code_cipher(&session).await?;
+    let (code_model, _site_model, _permission) = get_code(
+        &app_state,
+        &mut cipher,
+        auth.user_id,
+        &code_id_str,
+        Permission::Edit,
+    )
+    .await?;
+
+    let revision_id = Uuid::parse_str(&revision_id_str).map_err(|_| {
+        HoofprintError::NotFound(format!("Invalid revision ID: {}", revision_id_str))
+    })?;
+    let revision = code_revision::Entity::find_by_id(revision_id)
+        .filter(code_revision::Column::CodeId.eq(code_model.id))
+        .one(&app_state.db)
+        .await?
+        .ok_or_else(|| HoofprintError::NotFound(format!("Revision {}", revision_id)))?
+        .decrypt(&cipher)?;
+
+    site::Entity::find_by_id(revision.site_id)
+        .one(&app_state.db)
+        .await?
+        .ok_or_else(|| {
+            HoofprintError::ValidationError(vec![format!(
+                "Site {} no longer exists",
+                revision.site_id
+            )])
+        })?;
+
+    let code_id = code_model.id;
+    let mut code_active: code::ActiveModel = code_model.clone().into();
+    code_active.type_ = Set(revision.type_);
+    code_active.value = Set(cipher.seal_value(code_id, &revision.value)?);
+    code_active.name = Set(cipher.seal_name(code_id, revision.name)?);
+    code_active.site_id = Set(revision.site_id);
+    code_active.render_options = Set(revision.render_options);
+    code_active.notes = Set(revision
+        .notes
+        .map(|notes| cipher.seal_notes(code_id, &notes))
+        .transpose()?);
+    code_active.valid_from = Set(revision.valid_from);
+    code_active.expires_at = Set(revision.expires_at);
+    code_active.last_updated = Set(Some(DateTimeUtc::from(SystemTime::now())));
+    code::Model::update_with_revision(
+        &app_state.db,
+        &cipher,
+        &code_model,
+        code_active,
+        auth.user_id,
+        Some(revision_id),
+    )
+    .await?;
+    info!(code_id = %code_id, revision_id = %revision_id, "Restored code revision");
+
+    Ok(Redirect::to(&format!("/view/{}/history", code_id)))
+}
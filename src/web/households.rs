@@ -0,0 +1,378 @@
+//! Households, whose members own a set of codes together, and giving codes to them
+
+use axum::extract::Path;
+use sea_orm::{ActiveModelTrait, ActiveValue::Set, PaginatorTrait};
+
+use crate::{
+    db::entities::{
+        code,
+        code_share::Permission,
+        household,
+        household_member::{self, Role},
+    },
+    prelude::*,
+    web::{
+        attachments::attachment_dir,
+        forms::{CodeHouseholdForm, HouseholdForm, HouseholdMemberForm, HouseholdRoleForm},
+        views::get_code,
+    },
+};
+
+/// A household as it's listed to one of its members
+pub(crate) struct HouseholdItem {
+    pub(crate) id: Uuid,
+    pub(crate) name: String,
+    /// The member's role
+    pub(crate) role: Role,
+}
+
+/// The households a user is in, in the order they joined them
+pub(crate) async fn household_items(
+    app_state: &AppState,
+    user_id: Uuid,
+) -> Result<Vec<HouseholdItem>, HoofprintError> {
+    Ok(
+        household_member::Model::find_for_user(&app_state.db, user_id)
+            .await?
+            .into_iter()
+            .filter_map(|(member, household)| {
+                let household = household?;
+                Some(HouseholdItem {
+                    id: household.id,
+                    name: household.name,
+                    role: member.role(),
+                })
+            })
+            .collect(),
+    )
+}
+
+/// A household and the user's membership of it, as long as they're in it
+async fn get_membership(
+    app_state: &AppState,
+    user_id: Uuid,
+    household_id_str: &str,
+) -> Result<(household::Model, household_member::Model), HoofprintError> {
+    let household_id = Uuid::parse_str(household_id_str).map_err(|_| {
+        HoofprintError::NotFound(format!("Invalid household ID: {}", household_id_str))
+    })?;
+    let member =
+        household_member::Model::find_for_household_and_user(&app_state.db, household_id, user_id)
+            .await?
+            .ok_or_else(|| HoofprintError::NotFound(format!("Household {}", household_id)))?;
+    let household = household::Entity::find_by_id(household_id)
+        .one(&app_state.db)
+        .await?
+        .ok_or_else(|| HoofprintError::NotFound(format!("Household {}", household_id)))?;
+    Ok((household, member))
+}
+
+/// A household's member, by the ID of their membership
+async fn get_member(
+    app_state: &AppState,
+    household_id: Uuid,
+    member_id_str: &str,
+) -> Result<household_member::Model, HoofprintError> {
+    let member_id = Uuid::parse_str(member_id_str)
+        .map_err(|_| HoofprintError::NotFound(format!("Invalid member ID: {}", member_id_str)))?;
+    household_member::Entity::find_by_id(member_id)
+        .filter(household_member::Column::HouseholdId.eq(household_id))
+        .one(&app_state.db)
+        .await?
+        .ok_or_else(|| HoofprintError::NotFound(format!("Member {}", member_id)))
+}
+
+/// Refuse to leave a household without an admin, when `member` stops being one
+async fn keep_an_admin(
+    app_state: &AppState,
+    member: &household_member::Model,
+) -> Result<(), HoofprintError> {
+    if member.role() == Role::Admin
+        && household_member::Model::count_admins(&app_state.db, member.household_id).await? <= 1
+    {
+        return Err(HoofprintError::ValidationError(vec![
+            "A household needs at least one admin".to_string(),
+        ]));
+    }
+    Ok(())
+}
+
+#[derive(Template, WebTemplate)]
+#[template(path = "households.html")]
+pub(crate) struct HouseholdsPage {
+    pub(crate) households: Vec<HouseholdItem>,
+}
+
+#[instrument(level = "debug", skip_all)]
+pub(crate) async fn households_get(
+    State(app_state): State<AppState>,
+    session: Session,
+) -> Result<HouseholdsPage, HoofprintError> {
+    let auth = app_state.get_authenticated_user(&session).await?;
+    Ok(HouseholdsPage {
+        households: household_items(&app_state, auth.user_id).await?,
+    })
+}
+
+/// Make a household, with the user as its admin
+#[instrument(level = "debug", skip(app_state, session))]
+pub(crate) async fn households_post(
+    State(app_state): State<AppState>,
+    session: Session,
+    Form(form): Form<HouseholdForm>,
+) -> Result<Redirect, HoofprintError> {
+    let auth = app_state.get_authenticated_user(&session).await?;
+    let cipher = app_state.get_code_cipher(&session).await?;
+    form.validate()?;
+    let household =
+        household::Model::create_new(&app_state.db, &cipher, auth.user_id, form.name.trim())
+            .await?;
+    info!(household_id = %household.id, user_id = %auth.user_id, "Created household");
+    Ok(Redirect::to(&format!(
+        "{}/{}",
+        Urls::Households.as_ref(),
+        household.id
+    )))
+}
+
+/// Someone in a household, as they're listed to its members
+pub(crate) struct MemberItem {
+    pub(crate) id: Uuid,
+    pub(crate) name: String,
+    pub(crate) email: String,
+    pub(crate) role: Role,
+    /// Whether this is the user looking at the page
+    pub(crate) is_self: bool,
+}
+
+#[derive(Template, WebTemplate)]
+#[template(path = "household.html")]
+pub(crate) struct HouseholdPage {
+    pub(crate) household_id: Uuid,
+    pub(crate) name: String,
+    /// The user's role
+    pub(crate) role: Role,
+    pub(crate) members: Vec<MemberItem>,
+    /// How many codes the household owns
+    pub(crate) codes: u64,
+    pub(crate) roles: [Role; 3],
+}
+
+impl HouseholdPage {
+    pub fn is_admin(&self) -> bool {
+        self.role == Role::Admin
+    }
+}
+
+#[instrument(level = "debug", skip(app_state, session))]
+pub(crate) async fn household_get(
+    State(app_state): State<AppState>,
+    Path(household_id_str): Path<String>,
+    session: Session,
+) -> Result<HouseholdPage, HoofprintError> {
+    let auth = app_state.get_authenticated_user(&session).await?;
+    let (household, member) = get_membership(&app_state, auth.user_id, &household_id_str).await?;
+
+    let members = household_member::Model::find_for_household(&app_state.db, household.id)
+        .await?
+        .into_iter()
+        .filter_map(|(member, user)| {
+            let user = user?;
+            Some(MemberItem {
+                id: member.id,
+                name: user.display_name,
+                email: user.email,
+                role: member.role(),
+                is_self: user.id == auth.user_id,
+            })
+        })
+        .collect();
+    let codes = code::Entity::find()
+        .filter(code::Column::HouseholdId.eq(household.id))
+        .count(&app_state.db)
+        .await?;
+
+    Ok(HouseholdPage {
+        household_id: household.id,
+        name: household.name,
+        role: member.role(),
+        members,
+        codes,
+        roles: Role::ALL,
+    })
+}
+
+/// Add someone to a household by their email address, or change their role if they're in it
+#[instrument(level = "debug", skip(app_state, session))]
+pub(crate) async fn member_add_post(
+    State(app_state): State<AppState>,
+    Path(household_id_str): Path<String>,
+    session: Session,
+    Form(form): Form<HouseholdMemberForm>,
+) -> Result<Redirect, HoofprintError> {
+    let auth = app_state.get_authenticated_user(&session).await?;
+    let cipher = app_state.get_code_cipher(&session).await?;
+    let (household, member) = get_membership(&app_state, auth.user_id, &household_id_str).await?;
+    if member.role() != Role::Admin {
+        return Err(HoofprintError::Unauthorized);
+    }
+    let role = form.role()?;
+
+    let email = form.email.trim();
+    let recipient = user::Entity::find()
+        .filter(user::Column::Email.eq(email))
+        .one(&app_state.db)
+        .await?
+        .ok_or_else(|| {
+            HoofprintError::ValidationError(vec![format!("No user found with email {}", email)])
+        })?;
+    if recipient.id == auth.user_id {
+        return Err(HoofprintError::ValidationError(vec![
+            "You're already in this household".to_string(),
+        ]));
+    }
+
+    household_member::Model::add(
+        &app_state.db,
+        household.id,
+        &member.household_key(&app_state.db, &cipher).await?,
+        &recipient,
+        role,
+    )
+    .await?;
+    info!(household_id = %household.id, user_id = %recipient.id, role = role.as_str(), "Added household member");
+
+    Ok(Redirect::to(&format!(
+        "{}/{}",
+        Urls::Households.as_ref(),
+        household.id
+    )))
+}
+
+/// Change a household member's role
+#[instrument(level = "debug", skip(app_state, session))]
+pub(crate) async fn member_role_post(
+    State(app_state): State<AppState>,
+    Path((household_id_str, member_id_str)): Path<(String, String)>,
+    session: Session,
+    Form(form): Form<HouseholdRoleForm>,
+) -> Result<Redirect, HoofprintError> {
+    let auth = app_state.get_authenticated_user(&session).await?;
+    let (household, member) = get_membership(&app_state, auth.user_id, &household_id_str).await?;
+    if member.role() != Role::Admin {
+        return Err(HoofprintError::Unauthorized);
+    }
+    let role = form.role()?;
+    let target = get_member(&app_state, household.id, &member_id_str).await?;
+    if role != Role::Admin {
+        keep_an_admin(&app_state, &target).await?;
+    }
+
+    let mut target: household_member::ActiveModel = target.into();
+    target.role = Set(role.as_str().to_string());
+    target.update(&app_state.db).await?;
+
+    Ok(Redirect::to(&format!(
+        "{}/{}",
+        Urls::Households.as_ref(),
+        household.id
+    )))
+}
+
+/// Take someone out of a household, or leave it, so they lose access to its codes through
+/// hoofprint. The household gets a new key, see [household_member::Model::remove].
+#[instrument(level = "debug", skip(app_state, session))]
+pub(crate) async fn member_remove_post(
+    State(app_state): State<AppState>,
+    Path((household_id_str, member_id_str)): Path<(String, String)>,
+    session: Session,
+) -> Result<Redirect, HoofprintError> {
+    let auth = app_state.get_authenticated_user(&session).await?;
+    let cipher = app_state.get_code_cipher(&session).await?;
+    let (household, member) = get_membership(&app_state, auth.user_id, &household_id_str).await?;
+    let target = get_member(&app_state, household.id, &member_id_str).await?;
+    let leaving = target.user_id == auth.user_id;
+    if !leaving && member.role() != Role::Admin {
+        return Err(HoofprintError::Unauthorized);
+    }
+    keep_an_admin(&app_state, &target).await?;
+
+    let target_user_id = target.user_id;
+    household_member::Model::remove(
+        &app_state.db,
+        target,
+        &member.household_key(&app_state.db, &cipher).await?,
+        &cipher,
+        auth.user_id,
+    )
+    .await?;
+    info!(household_id = %household.id, user_id = %target_user_id, "Removed household member");
+
+    if leaving {
+        Ok(Redirect::to(Urls::Households.as_ref()))
+    } else {
+        Ok(Redirect::to(&format!(
+            "{}/{}",
+            Urls::Households.as_ref(),
+            household.id
+        )))
+    }
+}
+
+/// Give a code to one of the user's households, or take it back to be their own
+#[instrument(level = "debug", skip(app_state, session))]
+pub(crate) async fn code_household_post(
+    State(app_state): State<AppState>,
+    Path(code_id_str): Path<String>,
+    session: Session,
+    Form(form): Form<CodeHouseholdForm>,
+) -> Result<Redirect, HoofprintError> {
+    let auth = app_state.get_authenticated_user(&session).await?;
+    let mut cipher = app_state.get_code_cipher(&session).await?;
+    let (code_model, _site_model, _permission) = get_code(
+        &app_state,
+        &mut cipher,
+        auth.user_id,
+        &code_id_str,
+        Permission::Owner,
+    )
+    .await?;
+
+    let household_id = form.household_id()?;
+    if household_id == code_model.household_id {
+        return Ok(Redirect::to(&format!("/view/{}", code_model.id)));
+    }
+    let household_key = match household_id {
+        Some(household_id) => {
+            let member = household_member::Model::find_for_household_and_user(
+                &app_state.db,
+                household_id,
+                auth.user_id,
+            )
+            .await?
+            .ok_or_else(|| HoofprintError::NotFound(format!("Household {}", household_id)))?;
+            // viewers can't add codes they couldn't change afterwards
+            if member.role() < Role::Member {
+                return Err(HoofprintError::Unauthorized);
+            }
+            Some((
+                household_id,
+                member.household_key(&app_state.db, &cipher).await?,
+            ))
+        }
+        None => None,
+    };
+
+    code::Model::set_household(
+        &app_state.db,
+        attachment_dir(&app_state).await.as_deref(),
+        &cipher,
+        code_model.id,
+        auth.user_id,
+        household_key.as_ref().map(|(id, key)| (*id, key)),
+    )
+    .await?;
+    info!(code_id = %code_model.id, household_id = ?household_id, "Changed code's household");
+
+    Ok(Redirect::to(&format!("/view/{}", code_model.id)))
+}
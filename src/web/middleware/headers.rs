@@ -45,7 +45,6 @@ pub(crate) async fn apply_headers(
         HeaderValue::from_static("Access-Control-Allow-Headers: Content-Type"),
     );
 
-    // TODO: remove the style-src 'unsafe-inline' once <https://github.com/lindell/JsBarcode/pull/474> is merged and released
     headers.insert(
         CONTENT_SECURITY_POLICY,
         HeaderValue::from_static(
@@ -1,12 +1,22 @@
+pub(crate) mod account;
 pub(crate) mod admin;
+pub(crate) mod attachments;
 pub(crate) mod auth;
+pub(crate) mod export;
 pub(crate) mod forms;
+pub(crate) mod history;
+pub(crate) mod households;
 pub(crate) mod logging;
 pub(crate) mod manifest;
 pub(crate) mod middleware;
 pub(crate) mod registration;
 pub mod routes;
+pub(crate) mod search;
+pub(crate) mod secrets;
 pub mod sessions;
+pub(crate) mod share_links;
+pub(crate) mod shares;
+pub(crate) mod sites;
 pub mod state;
 pub(crate) mod views;
 
@@ -1,10 +1,14 @@
 use crate::prelude::*;
 
+use axum::extract::DefaultBodyLimit;
 use axum::middleware::from_fn_with_state;
 use axum::routing::{get, post};
 
+use super::attachments::MAX_ATTACHMENT_SIZE;
 use super::state::AppState;
 use super::views;
+use crate::db::entities::site_logo::MAX_LOGO_SIZE;
+use crate::decode::MAX_UPLOAD_SIZE;
 
 /// Creates the application router with all routes
 pub fn routes(state: &AppState) -> Router<AppState> {
@@ -18,6 +22,31 @@ pub fn routes(state: &AppState) -> Router<AppState> {
             Urls::AdminPasswordReset.as_ref(),
             get(super::admin::password_reset_get).post(super::admin::password_reset_post),
         )
+        .route(
+            Urls::AdminSites.as_ref(),
+            get(super::admin::sites_get).post(super::admin::sites_post),
+        )
+        .route(
+            "/admin/sites/{site}",
+            get(super::admin::site_get).post(super::admin::site_post),
+        )
+        .route(
+            "/admin/sites/{site}/delete",
+            post(super::admin::site_delete_post),
+        )
+        .route(
+            "/admin/sites/{site}/merge",
+            post(super::admin::site_merge_post),
+        )
+        .route(
+            "/admin/sites/{site}/logo",
+            post(super::admin::site_logo_post)
+                .layer(DefaultBodyLimit::max(MAX_LOGO_SIZE + 64 * 1024)),
+        )
+        .route(
+            "/admin/sites/{site}/logo/delete",
+            post(super::admin::site_logo_delete_post),
+        )
         .layer(from_fn_with_state(
             state.clone(),
             super::middleware::admin::ensure_admin,
@@ -26,20 +55,132 @@ pub fn routes(state: &AppState) -> Router<AppState> {
     let requires_auth = Router::new()
         .route(Urls::Home.as_ref(), get(views::homepage))
         .route("/view/{code}", get(views::view_code))
+        .route("/view/{code}/image.svg", get(views::view_code_svg))
+        .route("/view/{code}/image.png", get(views::view_code_png))
+        .route("/view/{code}/pass.pkpass", get(views::view_code_pkpass))
+        .route(
+            "/view/{code}/history",
+            get(super::history::code_history_get),
+        )
+        .route(
+            "/view/{code}/history/{revision}/restore",
+            post(super::history::code_restore_post),
+        )
         .route(
             "/edit/{code}",
             get(views::edit_code_get).post(views::edit_code_post),
         )
+        .route("/edit/{code}/payload", post(views::edit_payload_post))
+        .route(
+            "/view/{code}/attachments/{side}",
+            get(super::attachments::attachment_get),
+        )
+        .route(
+            "/edit/{code}/attachments",
+            post(super::attachments::attachment_upload_post)
+                .layer(DefaultBodyLimit::max(MAX_ATTACHMENT_SIZE + 64 * 1024)),
+        )
+        .route(
+            "/edit/{code}/attachments/{side}/delete",
+            post(super::attachments::attachment_delete_post),
+        )
+        .route(
+            "/view/{code}/secrets/{secret}",
+            post(super::secrets::secret_reveal_post),
+        )
+        .route(
+            "/edit/{code}/secrets",
+            post(super::secrets::secret_add_post),
+        )
+        .route(
+            "/edit/{code}/secrets/{secret}/delete",
+            post(super::secrets::secret_delete_post),
+        )
+        .route("/view/{code}/shares", post(super::shares::share_post))
+        .route(
+            "/view/{code}/links",
+            post(super::share_links::share_link_post),
+        )
+        .route(
+            "/view/{code}/links/{link}/revoke",
+            post(super::share_links::share_link_revoke_post),
+        )
+        .route(
+            "/view/{code}/household",
+            post(super::households::code_household_post),
+        )
+        .route(
+            Urls::Households.as_ref(),
+            get(super::households::households_get).post(super::households::households_post),
+        )
+        .route(
+            "/households/{household}",
+            get(super::households::household_get),
+        )
+        .route(
+            "/households/{household}/members",
+            post(super::households::member_add_post),
+        )
+        .route(
+            "/households/{household}/members/{member}",
+            post(super::households::member_role_post),
+        )
+        .route(
+            "/households/{household}/members/{member}/remove",
+            post(super::households::member_remove_post),
+        )
+        .route(
+            "/view/{code}/shares/{share}/revoke",
+            post(super::shares::share_revoke_post),
+        )
+        .route("/sites/{site}/logo", get(super::sites::site_logo_get))
+        .route(
+            Urls::SiteStyles.as_ref(),
+            get(super::sites::site_styles_get),
+        )
         .route(Urls::Manifest.as_ref(), get(super::manifest::manifest))
         .route(
             Urls::Create.as_ref(),
             get(views::create_code_get).post(views::create_code_post),
         )
+        .route(
+            Urls::CreatePayload.as_ref(),
+            post(views::create_payload_post),
+        )
         .route("/delete/{code}", post(views::code_delete))
+        .route("/favourite/{code}", post(views::favourite_post))
+        .route(Urls::Reorder.as_ref(), post(views::reorder_post))
         .route(
             Urls::Scan.as_ref(),
             get(views::scan_get).post(views::scan_post),
         )
+        .route(
+            Urls::ScanUpload.as_ref(),
+            post(views::scan_upload_post).layer(DefaultBodyLimit::max(MAX_UPLOAD_SIZE)),
+        )
+        .route(
+            Urls::WalletSheet.as_ref(),
+            get(super::export::wallet_sheet_get),
+        )
+        .route(
+            Urls::WalletSheetPdf.as_ref(),
+            get(super::export::wallet_sheet_pdf),
+        )
+        .route(Urls::Search.as_ref(), get(super::search::search_get))
+        .route(Urls::SearchJson.as_ref(), get(super::search::search_json))
+        .route(Urls::Account.as_ref(), get(super::account::account_get))
+        .route(
+            Urls::AccountPassword.as_ref(),
+            post(super::account::password_post),
+        )
+        .route(
+            Urls::AccountRecover.as_ref(),
+            post(super::account::recover_post),
+        )
+        .route(
+            Urls::AccountPreferences.as_ref(),
+            post(super::account::preferences_post),
+        )
         .route(
             Urls::Logout.as_ref(),
             post(super::auth::logout).get(super::auth::logout),
@@ -61,6 +202,8 @@ pub fn routes(state: &AppState) -> Router<AppState> {
             post(super::views::csp_report_only),
         )
         .route(Urls::HealthCheck.as_ref(), get(super::views::health_check))
+        // public links are opened without logging in
+        .route("/s/{token}", get(super::share_links::public_code_get))
 }
 
 #[tokio::test]
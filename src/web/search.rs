@@ -0,0 +1,51 @@
+//! Searching codes, as a page or as JSON for the PWA
+
+use crate::{
+    prelude::*,
+    search::{SearchResult, search_codes},
+    web::forms::SearchQuery,
+};
+
+#[derive(Template, WebTemplate)]
+#[template(path = "search.html")]
+pub(crate) struct SearchPage {
+    pub(crate) query: String,
+    pub(crate) results: Vec<SearchResult>,
+}
+
+#[derive(Serialize)]
+pub(crate) struct SearchResponse {
+    pub(crate) query: String,
+    pub(crate) results: Vec<SearchResult>,
+}
+
+async fn run_search(
+    app_state: &AppState,
+    session: &Session,
+    query: &SearchQuery,
+) -> Result<SearchResponse, HoofprintError> {
+    let auth = app_state.get_authenticated_user(session).await?;
+    let mut cipher = app_state.get_code_cipher(session).await?;
+    let query = query.q().unwrap_or_default().to_string();
+    let results = search_codes(&app_state.db, &mut cipher, auth.user_id, &query).await?;
+    Ok(SearchResponse { query, results })
+}
+
+#[instrument(level = "debug", skip_all)]
+pub(crate) async fn search_get(
+    State(app_state): State<AppState>,
+    Query(query): Query<SearchQuery>,
+    session: Session,
+) -> Result<SearchPage, HoofprintError> {
+    let SearchResponse { query, results } = run_search(&app_state, &session, &query).await?;
+    Ok(SearchPage { query, results })
+}
+
+#[instrument(level = "debug", skip_all)]
+pub(crate) async fn search_json(
+    State(app_state): State<AppState>,
+    Query(query): Query<SearchQuery>,
+    session: Session,
+) -> Result<Json<SearchResponse>, HoofprintError> {
+    Ok(Json(run_search(&app_state, &session, &query).await?))
+}
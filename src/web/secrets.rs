@@ -0,0 +1,206 @@
+//! Secrets that go with a code, like a gift card's PIN, which stay hidden until they're asked for.
+//!
+//! The forms these handlers take aren't logged, and neither are the secrets they open.
+
+use axum::{
+    extract::Path,
+    http::header::{CACHE_CONTROL, PRAGMA},
+};
+use tracing::warn;
+
+use crate::{
+    db::entities::{
+        code_secret::{self, MAX_SECRETS_PER_CODE},
+        code_share::Permission,
+    },
+    password::verify_password,
+    prelude::*,
+    web::{
+        forms::{RevealSecretForm, SecretForm},
+        views::get_code,
+    },
+};
+
+/// A secret as it's listed, without its value
+pub(crate) struct SecretItem {
+    pub(crate) id: Uuid,
+    pub(crate) label: String,
+    pub(crate) require_password: bool,
+}
+
+impl From<code_secret::Model> for SecretItem {
+    fn from(secret: code_secret::Model) -> Self {
+        Self {
+            id: secret.id,
+            label: secret.label,
+            require_password: secret.require_password,
+        }
+    }
+}
+
+/// A code's secrets, without their values
+pub(crate) async fn secret_items(
+    app_state: &AppState,
+    code_id: Uuid,
+) -> Result<Vec<SecretItem>, HoofprintError> {
+    Ok(code_secret::Model::find_for_code(&app_state.db, code_id)
+        .await?
+        .into_iter()
+        .map(SecretItem::from)
+        .collect())
+}
+
+fn parse_secret_id(secret_id_str: &str) -> Result<Uuid, HoofprintError> {
+    Uuid::parse_str(secret_id_str)
+        .map_err(|_| HoofprintError::NotFound(format!("Invalid secret ID: {}", secret_id_str)))
+}
+
+#[derive(Template, WebTemplate)]
+#[template(path = "reveal_secret.html")]
+pub(crate) struct RevealSecretPage {
+    pub(crate) code_id: Uuid,
+    pub(crate) code_name: Option<String>,
+    pub(crate) secret: SecretItem,
+    /// The secret, once the user's allowed to see it
+    pub(crate) value: Option<String>,
+    pub(crate) error: Option<String>,
+}
+
+/// Show one of a code's secrets, checking the user's password first if the secret needs it
+#[instrument(level = "debug", skip(app_state, session, form))]
+pub(crate) async fn secret_reveal_post(
+    State(app_state): State<AppState>,
+    Path((code_id_str, secret_id_str)): Path<(String, String)>,
+    session: Session,
+    Form(form): Form<RevealSecretForm>,
+) -> Result<impl IntoResponse, HoofprintError> {
+    let auth = app_state.get_authenticated_user(&session).await?;
+    let mut cipher = app_state.get_code_cipher(&session).await?;
+    let (code_model, _site_model, _permission) = get_code(
+        &app_state,
+        &mut cipher,
+        auth.user_id,
+        &code_id_str,
+        Permission::View,
+    )
+    .await?;
+    let secret = code_secret::Model::find_for_code_by_id(
+        &app_state.db,
+        code_model.id,
+        parse_secret_id(&secret_id_str)?,
+    )
+    .await?;
+
+    let mut error = None;
+    if secret.require_password {
+        let user = user::Entity::find_by_id(auth.user_id)
+            .one(&app_state.db)
+            .await?
+            .ok_or(HoofprintError::Unauthorized)?;
+        match form
+            .password
+            .as_deref()
+            .filter(|password| !password.is_empty())
+        {
+            None => error = Some("Enter your password to see this".to_string()),
+            Some(password) => {
+                if verify_password(password, &user.password).is_err() {
+                    warn!(user_id = %auth.user_id, secret_id = %secret.id, "Wrong password entered to reveal a secret");
+                    error = Some("That password is incorrect".to_string());
+                }
+            }
+        }
+    }
+    let value = match error {
+        Some(_) => None,
+        None => Some(secret.reveal(&cipher)?),
+    };
+
+    Ok((
+        [
+            // keep the secret out of the browser's cache
+            (CACHE_CONTROL, "no-store"),
+            (PRAGMA, "no-cache"),
+        ],
+        RevealSecretPage {
+            code_id: code_model.id,
+            code_name: code_model.name,
+            secret: SecretItem::from(secret),
+            value,
+            error,
+        },
+    ))
+}
+
+/// Add a secret to a code
+#[instrument(level = "debug", skip(app_state, session, form))]
+pub(crate) async fn secret_add_post(
+    State(app_state): State<AppState>,
+    Path(code_id_str): Path<String>,
+    session: Session,
+    Form(form): Form<SecretForm>,
+) -> Result<Redirect, HoofprintError> {
+    let auth = app_state.get_authenticated_user(&session).await?;
+    let mut cipher = app_state.get_code_cipher(&session).await?;
+    let (code_model, _site_model, _permission) = get_code(
+        &app_state,
+        &mut cipher,
+        auth.user_id,
+        &code_id_str,
+        Permission::Edit,
+    )
+    .await?;
+    form.validate()?;
+    if code_secret::Model::find_for_code(&app_state.db, code_model.id)
+        .await?
+        .len()
+        >= MAX_SECRETS_PER_CODE
+    {
+        return Err(HoofprintError::ValidationError(vec![format!(
+            "A code can have at most {} secrets",
+            MAX_SECRETS_PER_CODE
+        )]));
+    }
+
+    code_secret::Model::add(
+        &app_state.db,
+        &cipher,
+        code_model.id,
+        form.label.trim(),
+        &form.value,
+        form.require_password.is_some(),
+    )
+    .await?;
+
+    Ok(Redirect::to(&format!("/edit/{}", code_model.id)))
+}
+
+/// Remove a secret from a code
+#[instrument(level = "debug", skip(app_state, session))]
+pub(crate) async fn secret_delete_post(
+    State(app_state): State<AppState>,
+    Path((code_id_str, secret_id_str)): Path<(String, String)>,
+    session: Session,
+) -> Result<Redirect, HoofprintError> {
+    let auth = app_state.get_authenticated_user(&session).await?;
+    let mut cipher = app_state.get_code_cipher(&session).await?;
+    let (code_model, _site_model, _permission) = get_code(
+        &app_state,
+        &mut cipher,
+        auth.user_id,
+        &code_id_str,
+        Permission::Edit,
+    )
+    .await?;
+    let secret = code_secret::Model::find_for_code_by_id(
+        &app_state.db,
+        code_model.id,
+        parse_secret_id(&secret_id_str)?,
+    )
+    .await?;
+    code_secret::Entity::delete_by_id(secret.id)
+        .exec(&app_state.db)
+        .await?;
+
+    Ok(Redirect::to(&format!("/edit/{}", code_model.id)))
+}
@@ -0,0 +1,169 @@
+//! Public links to codes, for showing a code to someone who doesn't have an account
+
+use std::time::Duration;
+
+use axum::{
+    extract::Path,
+    http::{
+        HeaderName,
+        header::{CACHE_CONTROL, PRAGMA, REFERRER_POLICY},
+    },
+};
+use sea_orm::sqlx::types::chrono;
+
+use crate::{
+    Code,
+    crypto::{CodeCipher, LinkToken},
+    db::entities::{code, share_link},
+    prelude::*,
+    web::{
+        attachments::attachment_dir, forms::ShareLinkForm, history::TIME_FORMAT,
+        views::get_owned_code,
+    },
+};
+
+/// Keeps public links out of search engines
+const X_ROBOTS_TAG: HeaderName = HeaderName::from_static("x-robots-tag");
+
+/// A public link to a code, as it's listed to the code's owner
+pub(crate) struct ShareLinkItem {
+    pub(crate) id: Uuid,
+    /// The whole link, if the owner's cipher can open its token
+    pub(crate) url: Option<String>,
+    pub(crate) expires_at: String,
+    pub(crate) views: i32,
+    pub(crate) max_views: Option<i32>,
+    /// Whether the link can still be opened
+    pub(crate) active: bool,
+}
+
+/// A code's public links, newest first
+pub(crate) async fn share_link_items(
+    app_state: &AppState,
+    cipher: &CodeCipher,
+    code_id: Uuid,
+) -> Result<Vec<ShareLinkItem>, HoofprintError> {
+    let now = chrono::Utc::now();
+    Ok(share_link::Model::find_for_code(&app_state.db, code_id)
+        .await?
+        .into_iter()
+        .map(|link| ShareLinkItem {
+            id: link.id,
+            url: link
+                .token(cipher)
+                .map(|token| format!("{}/s/{}", app_state.base_url, token)),
+            expires_at: link.expires_at.format(TIME_FORMAT).to_string(),
+            views: link.views,
+            max_views: link.max_views,
+            active: link.is_active(now),
+        })
+        .collect())
+}
+
+/// Make a public link to a code
+#[instrument(level = "debug", skip(app_state, session))]
+pub(crate) async fn share_link_post(
+    State(app_state): State<AppState>,
+    Path(code_id_str): Path<String>,
+    session: Session,
+    Form(form): Form<ShareLinkForm>,
+) -> Result<Redirect, HoofprintError> {
+    let auth = app_state.get_authenticated_user(&session).await?;
+    let mut cipher = app_state.get_code_cipher(&session).await?;
+    let (code_model, _site_model) =
+        get_owned_code(&app_state, &mut cipher, auth.user_id, &code_id_str).await?;
+    let (hours, max_views) = form.validate()?;
+
+    let code_key = code::Model::own_key(
+        &app_state.db,
+        attachment_dir(&app_state).await.as_deref(),
+        &cipher,
+        code_model.id,
+    )
+    .await?;
+    let link = share_link::Model::create(
+        &app_state.db,
+        code_model.id,
+        &code_key,
+        auth.user_id,
+        chrono::Utc::now() + Duration::from_secs(u64::from(hours) * 60 * 60),
+        max_views,
+    )
+    .await?;
+    info!(code_id = %code_model.id, link_id = %link.id, hours, max_views, "Made public link");
+
+    Ok(Redirect::to(&format!("/view/{}", code_model.id)))
+}
+
+/// Revoke a public link, so it can't be opened any more
+#[instrument(level = "debug", skip(app_state, session))]
+pub(crate) async fn share_link_revoke_post(
+    State(app_state): State<AppState>,
+    Path((code_id_str, link_id_str)): Path<(String, String)>,
+    session: Session,
+) -> Result<Redirect, HoofprintError> {
+    let auth = app_state.get_authenticated_user(&session).await?;
+    let mut cipher = app_state.get_code_cipher(&session).await?;
+    let (code_model, _site_model) =
+        get_owned_code(&app_state, &mut cipher, auth.user_id, &code_id_str).await?;
+    let link_id = Uuid::parse_str(&link_id_str)
+        .map_err(|_| HoofprintError::NotFound(format!("Invalid link ID: {}", link_id_str)))?;
+
+    let result = share_link::Entity::delete_many()
+        .filter(share_link::Column::Id.eq(link_id))
+        .filter(share_link::Column::CodeId.eq(code_model.id))
+        .exec(&app_state.db)
+        .await?;
+    if result.rows_affected == 0 {
+        return Err(HoofprintError::NotFound(format!("Link {}", link_id)));
+    }
+    info!(code_id = %code_model.id, link_id = %link_id, "Revoked public link");
+
+    Ok(Redirect::to(&format!("/view/{}", code_model.id)))
+}
+
+#[derive(Template, WebTemplate)]
+#[template(path = "public_code.html")]
+pub(crate) struct PublicCodePage {
+    pub code_value: String,
+    pub code_name: Option<String>,
+    pub code_svg: Option<String>,
+}
+
+/// Show a code to anyone with a public link to it, without logging in
+#[instrument(level = "debug", skip_all)]
+pub(crate) async fn public_code_get(
+    State(app_state): State<AppState>,
+    Path(token): Path<String>,
+) -> Result<impl IntoResponse, HoofprintError> {
+    let token =
+        LinkToken::parse(&token).ok_or_else(|| HoofprintError::NotFound("Link".to_string()))?;
+    let (code_model, code_key) = share_link::Model::redeem(&app_state.db, &token).await?;
+    let code_model = code_model
+        .decrypt(&CodeCipher::new(code_key, false))
+        .map_err(|_| HoofprintError::NotFound("Link".to_string()))?;
+
+    let code = Code::try_from(&code_model)?;
+    let code_svg = match code.as_html(&code_model.value, &code_model.render_options) {
+        Ok(svg) => Some(svg),
+        Err(err) => {
+            error!(code_id = %code_model.id, error = %err, "Failed to render code");
+            None
+        }
+    };
+
+    Ok((
+        [
+            // the page is only for whoever has the link
+            (CACHE_CONTROL, "no-store"),
+            (PRAGMA, "no-cache"),
+            (REFERRER_POLICY, "no-referrer"),
+            (X_ROBOTS_TAG, "noindex, nofollow"),
+        ],
+        PublicCodePage {
+            code_value: code_model.value,
+            code_name: code_model.name,
+            code_svg,
+        },
+    ))
+}
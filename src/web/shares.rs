@@ -0,0 +1,116 @@
+//! Sharing codes with other users, and taking them back
+
+use axum::extract::Path;
+
+use crate::{
+    db::entities::{
+        code,
+        code_share::{self, Permission},
+    },
+    prelude::*,
+    web::{attachments::attachment_dir, forms::ShareForm, views::get_owned_code},
+};
+
+/// Someone a code's shared with, as it's listed to the code's owner
+pub(crate) struct ShareItem {
+    pub(crate) id: Uuid,
+    pub(crate) name: String,
+    pub(crate) email: String,
+    pub(crate) permission: Permission,
+}
+
+/// Who a code's shared with
+pub(crate) async fn share_items(
+    app_state: &AppState,
+    code_id: Uuid,
+) -> Result<Vec<ShareItem>, HoofprintError> {
+    Ok(code_share::Model::find_for_code(&app_state.db, code_id)
+        .await?
+        .into_iter()
+        .filter_map(|(share, recipient)| {
+            let recipient = recipient?;
+            Some(ShareItem {
+                id: share.id,
+                name: recipient.display_name,
+                email: recipient.email,
+                permission: share.permission(),
+            })
+        })
+        .collect())
+}
+
+/// Share a code with another user by their email address, or change what they can do with it
+#[instrument(level = "debug", skip(app_state, session))]
+pub(crate) async fn share_post(
+    State(app_state): State<AppState>,
+    Path(code_id_str): Path<String>,
+    session: Session,
+    Form(form): Form<ShareForm>,
+) -> Result<Redirect, HoofprintError> {
+    let auth = app_state.get_authenticated_user(&session).await?;
+    let mut cipher = app_state.get_code_cipher(&session).await?;
+    let (code_model, _site_model) =
+        get_owned_code(&app_state, &mut cipher, auth.user_id, &code_id_str).await?;
+    let can_edit = form.can_edit()?;
+
+    let email = form.email.trim();
+    let recipient = user::Entity::find()
+        .filter(user::Column::Email.eq(email))
+        .one(&app_state.db)
+        .await?
+        .ok_or_else(|| {
+            HoofprintError::ValidationError(vec![format!("No user found with email {}", email)])
+        })?;
+    if recipient.id == auth.user_id {
+        return Err(HoofprintError::ValidationError(vec![
+            "You can't share a code with yourself".to_string(),
+        ]));
+    }
+
+    let code_key = code::Model::own_key(
+        &app_state.db,
+        attachment_dir(&app_state).await.as_deref(),
+        &cipher,
+        code_model.id,
+    )
+    .await?;
+    code_share::Model::share(
+        &app_state.db,
+        code_model.id,
+        &code_key,
+        &recipient,
+        can_edit,
+    )
+    .await?;
+    info!(code_id = %code_model.id, recipient_id = %recipient.id, can_edit, "Shared code");
+
+    Ok(Redirect::to(&format!("/view/{}", code_model.id)))
+}
+
+/// Stop sharing a code with someone, so they lose access to it through hoofprint. Nothing can
+/// take back what they've already seen, so its value still works for them if they kept it.
+#[instrument(level = "debug", skip(app_state, session))]
+pub(crate) async fn share_revoke_post(
+    State(app_state): State<AppState>,
+    Path((code_id_str, share_id_str)): Path<(String, String)>,
+    session: Session,
+) -> Result<Redirect, HoofprintError> {
+    let auth = app_state.get_authenticated_user(&session).await?;
+    let mut cipher = app_state.get_code_cipher(&session).await?;
+    let (code_model, _site_model) =
+        get_owned_code(&app_state, &mut cipher, auth.user_id, &code_id_str).await?;
+    let share_id = Uuid::parse_str(&share_id_str)
+        .map_err(|_| HoofprintError::NotFound(format!("Invalid share ID: {}", share_id_str)))?;
+
+    let result = code_share::Entity::delete_many()
+        .filter(code_share::Column::Id.eq(share_id))
+        .filter(code_share::Column::CodeId.eq(code_model.id))
+        .exec(&app_state.db)
+        .await?;
+    if result.rows_affected == 0 {
+        return Err(HoofprintError::NotFound(format!("Share {}", share_id)));
+    }
+    info!(code_id = %code_model.id, share_id = %share_id, "Stopped sharing code");
+
+    Ok(Redirect::to(&format!("/view/{}", code_model.id)))
+}
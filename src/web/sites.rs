@@ -0,0 +1,147 @@
+//! Sites' logos and brand colours, as they're shown with their codes
+
+use axum::{
+    extract::Path,
+    http::header::{CACHE_CONTROL, CONTENT_TYPE, X_CONTENT_TYPE_OPTIONS},
+};
+use sea_orm::QueryOrder;
+
+use crate::{
+    db::entities::{site, site_logo},
+    notes,
+    prelude::*,
+};
+
+/// The class that tints a site's codes with its brand colour, see [site_styles_get]
+pub(crate) fn site_class(site: &site::Model) -> Option<String> {
+    site.brand_colour()
+        .map(|_| format!("site_{}", site.id.simple()))
+}
+
+/// A code's site, as it's shown with the code
+pub(crate) struct SiteSummary {
+    pub(crate) id: Uuid,
+    pub(crate) name: String,
+    /// See [site_class]
+    pub(crate) class: Option<String>,
+    pub(crate) has_logo: bool,
+    /// How to use the site's codes, rendered from Markdown and sanitised
+    pub(crate) instructions_html: Option<String>,
+}
+
+impl SiteSummary {
+    pub(crate) async fn load(
+        app_state: &AppState,
+        site: &site::Model,
+    ) -> Result<Self, HoofprintError> {
+        Ok(Self {
+            id: site.id,
+            name: site.name.clone(),
+            class: site_class(site),
+            has_logo: site_logo::Model::exists(&app_state.db, site.id).await?,
+            instructions_html: site.instructions.as_deref().map(notes::render_markdown),
+        })
+    }
+
+    /// The generic site is what codes without a site use, so its name isn't worth showing
+    pub fn is_generic(&self) -> bool {
+        self.id.is_nil()
+    }
+}
+
+/// The stylesheet for the sites with brand colours
+fn site_styles(sites: &[site::Model]) -> String {
+    sites
+        .iter()
+        .filter_map(|site| {
+            // only hex colours get through, so nothing else can end up in the stylesheet
+            let colour = site.brand_colour()?;
+            Some(format!(
+                ".{} {{ border-top: 0.35rem solid {colour}; background-color: {colour}18; }}\n",
+                site_class(site)?
+            ))
+        })
+        .collect()
+}
+
+/// Tints each site's codes with its brand colour. It's a stylesheet rather than inline styles
+/// because the Content Security Policy doesn't allow those.
+#[instrument(level = "debug", skip_all)]
+pub(crate) async fn site_styles_get(
+    State(app_state): State<AppState>,
+    session: Session,
+) -> Result<impl IntoResponse, HoofprintError> {
+    app_state.get_authenticated_user(&session).await?;
+    let sites = site::Entity::find()
+        .filter(site::Column::BrandColour.is_not_null())
+        .order_by_asc(site::Column::Id)
+        .all(&app_state.db)
+        .await?;
+
+    Ok((
+        [
+            (CONTENT_TYPE, "text/css; charset=utf-8"),
+            (CACHE_CONTROL, "private, no-cache"),
+        ],
+        site_styles(&sites),
+    ))
+}
+
+/// A site's logo
+#[instrument(level = "debug", skip(app_state, session))]
+pub(crate) async fn site_logo_get(
+    State(app_state): State<AppState>,
+    Path(site_id_str): Path<String>,
+    session: Session,
+) -> Result<impl IntoResponse, HoofprintError> {
+    app_state.get_authenticated_user(&session).await?;
+    let site_id = Uuid::parse_str(&site_id_str)
+        .map_err(|_| HoofprintError::NotFound(format!("Invalid site ID: {}", site_id_str)))?;
+    let logo = site_logo::Entity::find_by_id(site_id)
+        .one(&app_state.db)
+        .await?
+        .ok_or_else(|| HoofprintError::NotFound(format!("Logo for site {}", site_id)))?;
+
+    Ok((
+        [
+            (CONTENT_TYPE, logo.content_type),
+            (CACHE_CONTROL, "private, max-age=3600".to_string()),
+            (X_CONTENT_TYPE_OPTIONS, "nosniff".to_string()),
+        ],
+        logo.data,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_site_styles() {
+        let site = |brand_colour: Option<&str>| site::Model {
+            id: Uuid::now_v7(),
+            name: "Costco".to_string(),
+            url: String::new(),
+            created_at: time::macros::datetime!(2026-01-01 0:00),
+            brand_colour: brand_colour.map(str::to_string),
+            default_code_type: None,
+            instructions: None,
+            value_pattern: None,
+        };
+        let branded = site(Some("#e31837"));
+        let sites = [
+            branded.clone(),
+            site(None),
+            site(Some("red; } body { display: none")),
+        ];
+        assert_eq!(
+            site_styles(&sites),
+            format!(
+                ".site_{} {{ border-top: 0.35rem solid #e31837; background-color: #e3183718; }}\n",
+                branded.id.simple()
+            )
+        );
+        assert_eq!(site_class(&sites[1]), None);
+        assert_eq!(site_class(&sites[2]), None);
+    }
+}
@@ -1,8 +1,9 @@
 use std::collections::HashMap;
 
 use crate::{
+    crypto::{CodeCipher, DataKey},
     prelude::*,
-    web::auth::{AUTH_USER_ID, AuthenticatedUser},
+    web::auth::{AUTH_DATA_KEY, AUTH_USER_ID, AuthenticatedUser},
 };
 
 /// Application state shared across all web handlers
@@ -12,6 +13,9 @@ pub struct AppState {
     pub config: SendableConfig,
     pub etags: Arc<RwLock<HashMap<String, String>>>,
     pub base_url: String,
+    /// Wraps users' data keys while they're in the session. It's never stored, so sessions from
+    /// before a restart have to log in again to unlock their codes.
+    pub session_key: DataKey,
 }
 
 impl AppState {
@@ -33,6 +37,7 @@ impl AppState {
             config,
             etags: Arc::new(RwLock::new(HashMap::new())),
             base_url,
+            session_key: DataKey::generate(),
         }
     }
 
@@ -67,6 +72,27 @@ impl AppState {
         }
     }
 
+    /// The logged in user's unlocked data key, for reading and writing their codes. Codes with
+    /// keys of their own need those adding, see
+    /// [crate::db::entities::code_share::Model::load_code_keys].
+    pub(crate) async fn get_code_cipher(
+        &self,
+        session: &tower_sessions::Session,
+    ) -> Result<CodeCipher, HoofprintError> {
+        let wrapped = session
+            .get::<String>(AUTH_DATA_KEY)
+            .await?
+            .ok_or(HoofprintError::NeedToLogin)?;
+        let data_key = self.session_key.unwrap_key(&wrapped).ok_or_else(|| {
+            debug!("Session's data key was wrapped before a restart");
+            HoofprintError::NeedToLogin
+        })?;
+        Ok(CodeCipher::new(
+            data_key,
+            self.config.read().await.encrypt_code_names,
+        ))
+    }
+
     #[cfg(test)]
     pub(crate) async fn test() -> Self {
         let config = Arc::new(RwLock::new(crate::config::Configuration::test()));
@@ -75,10 +101,4 @@ impl AppState {
             .expect("Failed to connect to test database!");
         Self::new(db, config).await
     }
-
-    #[cfg(test)]
-    pub(crate) async fn test_with_db(db: DatabaseConnection) -> Self {
-        let config = Arc::new(RwLock::new(crate::config::Configuration::test()));
-        Self::new(db, config).await
-    }
 }
@@ -6,10 +6,17 @@ use askama::Template;
 use askama_web::WebTemplate;
 use axum::{
     body::Bytes,
-    extract::{Form, Path, State},
+    extract::{Form, Multipart, Path, State},
+    http::{
+        StatusCode,
+        header::{CONTENT_DISPOSITION, CONTENT_TYPE},
+    },
 };
 use sea_orm::{
-    ActiveModelTrait, ActiveValue::Set, ColumnTrait, EntityTrait, QueryFilter, prelude::DateTimeUtc,
+    ActiveModelTrait,
+    ActiveValue::Set,
+    ColumnTrait, EntityTrait, QueryFilter, TransactionTrait,
+    prelude::{Date, DateTimeUtc},
 };
 use tower_sessions::Session;
 use tracing::instrument;
@@ -17,10 +24,36 @@ use uuid::Uuid;
 
 use crate::{
     Code,
-    db::entities::{code, site},
+    crypto::CodeCipher,
+    db::entities::{
+        code::{self, Validity},
+        code_attachment::{self, Side},
+        code_revision, code_secret,
+        code_share::{self, Permission},
+        code_usage::{self, UsageStats},
+        household,
+        household_member::Role,
+        share_link, site, site_logo, tag,
+    },
+    decode::{DecodedCode, decode_image},
     error::HoofprintError,
+    gs1::{self, Gs1Element},
+    notes,
+    payload::Payload,
+    render::{CodeImage, MIME_TYPE_PNG, MIME_TYPE_SVG, RasterOptions, RenderOptions},
+    wallet::{MIME_TYPE_PKPASS, PassSigner, is_pkpass, read_pass},
     web::{
-        forms::{CreateCodeForm, EditCodeForm},
+        attachments::attachment_dir,
+        forms::{
+            CreateCodeForm, CreatePayloadForm, EditCodeForm, FavouriteForm, HomepageQuery,
+            NewCodeQuery, PayloadForm, PngExportQuery, ReorderRequest, SORT_MOST_USED,
+            ViewCodeQuery,
+        },
+        households::{HouseholdItem, household_items},
+        secrets::{SecretItem, secret_items},
+        share_links::{ShareLinkItem, share_link_items},
+        shares::{ShareItem, share_items},
+        sites::{self, SiteSummary},
         state::AppState,
     },
 };
@@ -29,6 +62,22 @@ use crate::{
 #[template(path = "index.html")]
 pub(crate) struct HomePage {
     codes: Vec<CodeListItem>,
+    /// Whether any codes are locked, and can be recovered from the account page
+    has_locked_codes: bool,
+    /// Every tag the user has, for the sidebar
+    tags: Vec<TagCount>,
+    /// How many codes the user has, before filtering by tag
+    total_codes: usize,
+    /// The tag the codes are filtered by
+    selected_tag: Option<String>,
+    /// Whether the most used codes are first, rather than the user's own order
+    most_used: bool,
+    /// The homepage in the user's own order, keeping the tag filter
+    own_order_href: String,
+    /// The homepage with the most used codes first, keeping the tag filter
+    most_used_href: String,
+    /// Every code, keeping the sort
+    all_codes_href: String,
     user_name: String,
     user_email: String,
     user_groups: Vec<String>,
@@ -40,103 +89,604 @@ struct CodeListItem {
     code_value: String,
     code_name: Option<String>,
     site_name: String,
+    /// Tints the code with its site's brand colour, see [sites::site_class]
+    site_class: Option<String>,
+    /// The site whose logo to show, if it has one
+    logo_site_id: Option<Uuid>,
+    /// Sealed with a data key that was retired by a password reset
+    locked: bool,
+    validity: Validity,
+    tags: Vec<String>,
+    favourite: bool,
+    times_used: i64,
+    /// How much the code has been used, if it has been
+    usage: Option<String>,
+    /// Who owns the code, when someone else shared it with the user
+    owner: Option<String>,
+}
+
+struct TagCount {
+    name: String,
+    count: usize,
+    /// The homepage filtered by this tag
+    href: String,
+    selected: bool,
+}
+
+/// A link to the homepage, filtered by a tag and sorted
+fn homepage_href(tag: Option<&str>, most_used: bool) -> String {
+    let mut query = url::form_urlencoded::Serializer::new(String::new());
+    if let Some(tag) = tag {
+        query.append_pair("tag", tag);
+    }
+    if most_used {
+        query.append_pair("sort", SORT_MOST_USED);
+    }
+    let query = query.finish();
+    if query.is_empty() {
+        Urls::Home.as_ref().to_string()
+    } else {
+        format!("{}?{}", Urls::Home.as_ref(), query)
+    }
+}
+
+/// Describe how much a code has been used
+fn usage_summary(stats: Option<&UsageStats>) -> Option<String> {
+    let stats = stats?;
+    let last_used = stats.last_used.format("%Y-%m-%d");
+    Some(match stats.times_used {
+        1 => format!("Used once, on {}", last_used),
+        times_used => format!("Used {} times, last on {}", times_used, last_used),
+    })
 }
 
 /// Homepage handler that returns a simple HTML response
 #[instrument(level = "debug", skip_all)]
 pub(crate) async fn homepage(
     State(app_state): State<AppState>,
+    Query(query): Query<HomepageQuery>,
     session: Session,
 ) -> Result<HomePage, HoofprintError> {
     // Query all codes for the authenticated user with related sites
     let auth = app_state.get_authenticated_user(&session).await?;
+    let mut cipher = app_state.get_code_cipher(&session).await?;
 
-    let codes_with_sites = code::Entity::find()
-        .filter(code::Column::UserId.eq(auth.user_id))
-        .find_also_related(site::Entity)
-        .all(&app_state.db)
-        .await?;
+    // the user's own codes, then their households' and then the ones other people have shared
+    // with them
+    let mut codes_with_sites: Vec<(code::Model, Option<site::Model>, Option<String>)> =
+        code::Model::find_for_user(&app_state.db, auth.user_id)
+            .await?
+            .into_iter()
+            .map(|(code_model, site_model)| (code_model, site_model, None))
+            .collect();
+    codes_with_sites.extend(
+        household::Model::codes_for_member(&app_state.db, auth.user_id)
+            .await?
+            .into_iter()
+            .map(|(code_model, site_model, household)| {
+                (code_model, site_model, Some(household.name))
+            }),
+    );
+    for shared in code_share::Model::codes_shared_with(&app_state.db, auth.user_id).await? {
+        if !codes_with_sites
+            .iter()
+            .any(|(code_model, _, _)| code_model.id == shared.code.id)
+        {
+            codes_with_sites.push((shared.code, shared.site, Some(shared.owner_name)));
+        }
+    }
+    code_share::Model::load_code_keys(
+        &app_state.db,
+        auth.user_id,
+        &mut cipher,
+        &codes_with_sites
+            .iter()
+            .map(|(code, _, _)| code)
+            .collect::<Vec<_>>(),
+    )
+    .await?;
+    let code_ids: Vec<Uuid> = codes_with_sites
+        .iter()
+        .map(|(code, _, _)| code.id)
+        .collect();
+    let mut code_tags = tag::Model::find_for_codes(&app_state.db, auth.user_id, &code_ids).await?;
+    let usage = code_usage::Model::stats_for_codes(&app_state.db, &code_ids).await?;
+
+    let today = code::today();
+    let logo_site_ids = site_logo::Model::site_ids(&app_state.db).await?;
 
     // Transform into template-friendly structure
-    let codes = codes_with_sites
+    let mut codes = codes_with_sites
         .into_iter()
-        .map(|(code_model, site_model)| {
+        .map(|(code_model, site_model, owner)| {
+            let site_class = site_model.as_ref().and_then(sites::site_class);
+            let logo_site_id = site_model
+                .as_ref()
+                .map(|s| s.id)
+                .filter(|site_id| logo_site_ids.contains(site_id));
             let site_name = site_model
                 .map(|s| s.name)
                 .unwrap_or_else(|| "Unknown Site".to_string());
 
-            CodeListItem {
-                id: code_model.id,
-                code_value: code_model.value,
-                code_name: code_model.name.clone(),
-                site_name,
+            let id = code_model.id;
+            let validity = code_model.validity(today);
+            // favourites are the owner's, or the household's
+            let favourite =
+                code_model.favourite && (owner.is_none() || code_model.household_id.is_some());
+            let times_used = usage.get(&id).map_or(0, |stats| stats.times_used);
+            let usage = usage_summary(usage.get(&id));
+            let tags = code_tags.remove(&id).unwrap_or_default();
+            match code_model.decrypt(&cipher) {
+                Ok(code_model) => Ok(CodeListItem {
+                    id: code_model.id,
+                    code_value: code_model.value,
+                    code_name: code_model.name.clone(),
+                    site_name,
+                    site_class,
+                    logo_site_id,
+                    locked: false,
+                    validity,
+                    tags,
+                    favourite,
+                    times_used,
+                    usage,
+                    owner,
+                }),
+                Err(HoofprintError::CodeLocked(_)) => Ok(CodeListItem {
+                    id,
+                    code_value: String::new(),
+                    code_name: None,
+                    site_name,
+                    site_class,
+                    logo_site_id,
+                    locked: true,
+                    validity,
+                    tags,
+                    favourite,
+                    times_used,
+                    usage,
+                    owner,
+                }),
+                Err(err) => Err(err),
             }
         })
-        .collect();
+        .collect::<Result<Vec<_>, HoofprintError>>()?;
+    let most_used = query.most_used();
+    if most_used {
+        codes.sort_by_key(|code| std::cmp::Reverse(code.times_used));
+    }
+    // expired codes go to the bottom, otherwise keeping their order
+    codes.sort_by_key(|code| code.validity.is_expired());
+
+    let selected_tag = query.tag().map(str::to_lowercase);
+    let mut tag_counts: Vec<TagCount> = Vec::new();
+    for name in codes.iter().flat_map(|code| &code.tags) {
+        match tag_counts
+            .iter_mut()
+            .find(|tag| tag.name.to_lowercase() == name.to_lowercase())
+        {
+            Some(tag) => tag.count += 1,
+            None => tag_counts.push(TagCount {
+                name: name.clone(),
+                count: 1,
+                href: homepage_href(Some(name), most_used),
+                selected: selected_tag.as_deref() == Some(name.to_lowercase().as_str()),
+            }),
+        }
+    }
+    tag_counts.sort_by_key(|tag| tag.name.to_lowercase());
+
+    let has_locked_codes = codes.iter().any(|code| code.locked);
+    let total_codes = codes.len();
+    if let Some(selected_tag) = &selected_tag {
+        codes.retain(|code| {
+            code.tags
+                .iter()
+                .any(|tag| &tag.to_lowercase() == selected_tag)
+        });
+    }
 
     Ok(HomePage {
         codes,
+        has_locked_codes,
+        tags: tag_counts,
+        total_codes,
+        selected_tag: query.tag().map(str::to_string),
+        most_used,
+        own_order_href: homepage_href(query.tag(), false),
+        most_used_href: homepage_href(query.tag(), true),
+        all_codes_href: homepage_href(None, most_used),
         user_name: auth.display_name,
         user_email: auth.email,
         user_groups: auth.groups,
     })
 }
 
+/// Fetch a code and its site, as long as the user has at least the `needed` permission for it,
+/// without decrypting it. Returns what the user can do with it too.
+///
+/// Codes the user can't see at all aren't found, so nobody can tell which codes exist.
+async fn find_code(
+    app_state: &AppState,
+    user_id: Uuid,
+    code_id_str: &str,
+    needed: Permission,
+) -> Result<(code::Model, Option<site::Model>, Permission), HoofprintError> {
+    // Parse code_id as UUID
+    let code_id = Uuid::parse_str(code_id_str)
+        .map_err(|_| HoofprintError::NotFound(format!("Invalid code ID: {}", code_id_str)))?;
+
+    // Fetch code from database with related site
+    let (code_model, site_model) = code::Entity::find_by_id(code_id)
+        .find_also_related(site::Entity)
+        .one(&app_state.db)
+        .await?
+        .ok_or_else(|| HoofprintError::NotFound(format!("Code {}", code_id)))?;
+
+    let permission = code_share::Model::permission_for(&app_state.db, &code_model, user_id)
+        .await?
+        .ok_or_else(|| HoofprintError::NotFound(format!("Code {}", code_id)))?;
+    if permission < needed {
+        return Err(HoofprintError::Unauthorized);
+    }
+    Ok((code_model, site_model, permission))
+}
+
+/// Fetch a code and its site, as long as the user owns it, it's their household's or it's been
+/// shared with them, with at least the `needed` permission, with its value decrypted. Returns
+/// what the user can do with it too.
+///
+/// The code's own key, if it has one, is added to the cipher, so its revisions, secrets and
+/// photos can be opened with it too.
+pub(crate) async fn get_code(
+    app_state: &AppState,
+    cipher: &mut CodeCipher,
+    user_id: Uuid,
+    code_id_str: &str,
+    needed: Permission,
+) -> Result<(code::Model, site::Model, Permission), HoofprintError> {
+    let (code_model, site_model, permission) =
+        find_code(app_state, user_id, code_id_str, needed).await?;
+    let site_model = site_model.ok_or_else(|| HoofprintError::InvalidSite)?;
+    code_share::Model::load_code_keys(&app_state.db, user_id, cipher, &[&code_model]).await?;
+    Ok((code_model.decrypt(cipher)?, site_model, permission))
+}
+
+/// Fetch a code and its site, as long as it belongs to the given user, with its value decrypted
+pub(crate) async fn get_owned_code(
+    app_state: &AppState,
+    cipher: &mut CodeCipher,
+    user_id: Uuid,
+    code_id_str: &str,
+) -> Result<(code::Model, site::Model), HoofprintError> {
+    let (code_model, site_model, _permission) =
+        get_code(app_state, cipher, user_id, code_id_str, Permission::Owner).await?;
+    Ok((code_model, site_model))
+}
+
 #[derive(Template, WebTemplate)]
 #[template(path = "view_code.html")]
-// #[allow(dead_code)]
 pub(crate) struct ViewCodePage {
-    pub code: Code,
     pub code_id: Uuid,
     pub code_value: String,
     pub code_name: Option<String>,
+    pub code_svg: Option<String>,
+    pub render_error: Option<String>,
+    /// Whether the code can be downloaded as a Wallet pass
+    pub wallet_pass: bool,
+    /// The Application Identifier fields of a GS1 code
+    pub gs1_fields: Vec<Gs1Element>,
+    /// The structured content of a QR code, like a Wi-Fi login
+    pub payload: Option<Payload>,
+    pub valid_from: Option<Date>,
+    pub expires_at: Option<Date>,
+    pub validity: Validity,
+    pub favourite: bool,
+    /// The notes, rendered from Markdown and sanitised
+    pub notes_html: Option<String>,
+    /// The sides of the card there are photos of
+    pub photos: Vec<Side>,
+    /// Secrets like PINs, without their values
+    pub secrets: Vec<SecretItem>,
+    /// What the user can do with the code
+    pub permission: Permission,
+    /// Who owns the code, when it's been shared with the user, or its household
+    pub owner_name: Option<String>,
+    /// The household that owns the code, if one does
+    pub household_id: Option<Uuid>,
+    /// The households the owner can give the code to
+    pub households: Vec<HouseholdItem>,
+    /// Who the code's shared with, which only its owner sees
+    pub shares: Vec<ShareItem>,
+    /// The code's public links, which only its owner sees
+    pub links: Vec<ShareLinkItem>,
+    pub site: SiteSummary,
 }
 
-#[instrument(level = "debug", skip(app_state, session))]
+impl ViewCodePage {
+    pub fn can_edit(&self) -> bool {
+        self.permission >= Permission::Edit
+    }
+
+    pub fn is_owner(&self) -> bool {
+        self.permission == Permission::Owner
+    }
+}
+
+// the query's location is only kept coarse, and only if the user's tracking usage
+#[instrument(level = "debug", skip(app_state, session, query))]
 pub(crate) async fn view_code(
     State(app_state): State<AppState>,
     Path(code_id_str): Path<String>,
+    Query(query): Query<ViewCodeQuery>,
     session: Session,
 ) -> Result<ViewCodePage, HoofprintError> {
     let auth = app_state.get_authenticated_user(&session).await?;
-    // Parse code_id as UUID
-    let code_id = Uuid::parse_str(&code_id_str)
-        .map_err(|_| HoofprintError::NotFound(format!("Invalid code ID: {}", code_id_str)))?;
+    let mut cipher = app_state.get_code_cipher(&session).await?;
+    let (code_model, site_model, permission) = get_code(
+        &app_state,
+        &mut cipher,
+        auth.user_id,
+        &code_id_str,
+        Permission::View,
+    )
+    .await?;
 
-    // Fetch code from database with related site
-    let code_with_site = code::Entity::find_by_id(code_id)
-        .filter(code::Column::UserId.eq(auth.user_id))
-        .find_also_related(site::Entity)
-        .one(&app_state.db)
-        .await?
-        .ok_or_else(|| HoofprintError::NotFound(format!("Code {}", code_id)))?;
+    // Convert database code to display Code enum
+    let code = Code::try_from(&code_model)?;
 
-    let (code_model, site_model) = code_with_site;
-    let _site_model = site_model.ok_or_else(|| HoofprintError::InvalidSite)?;
+    // a value that can't be encoded still gets a page, so it can be fixed
+    let (code_svg, render_error) = match code.as_html(&code_model.value, &code_model.render_options)
+    {
+        Ok(svg) => (Some(svg), None),
+        Err(err) => {
+            error!(code_id = %code_model.id, error = %err, "Failed to render code");
+            (None, Some(err.to_string()))
+        }
+    };
 
-    // Convert database code to display Code enum
+    if code_svg.is_some() {
+        record_usage(
+            &app_state,
+            &code_model,
+            auth.user_id,
+            query.coarse_location(),
+        )
+        .await;
+    }
 
-    let code = Code::try_from(&code_model)?;
+    let wallet_pass = app_state.config.read().await.pass_type_id.is_some()
+        && code.pass_barcode_format().is_some();
+
+    // an invalid GS1 value already shows up as a render error
+    let gs1_fields = match code {
+        Code::Gs1Code128 | Code::Gs1DataMatrix => gs1::parse(&code_model.value).unwrap_or_default(),
+        _ => Vec::new(),
+    };
+
+    let payload = match code {
+        Code::QR => Payload::parse(&code_model.value),
+        _ => None,
+    };
+
+    let notes_html = code_model.notes.as_deref().map(notes::render_markdown);
+    let site = SiteSummary::load(&app_state, &site_model).await?;
+    let photos = photo_sides(&app_state, code_model.id).await?;
+    let secrets = secret_items(&app_state, code_model.id).await?;
+    let owner_name = match code_model.household_id {
+        Some(household_id) => household::Entity::find_by_id(household_id)
+            .one(&app_state.db)
+            .await?
+            .map(|household| household.name),
+        None if permission != Permission::Owner => user::Entity::find_by_id(code_model.user_id)
+            .one(&app_state.db)
+            .await?
+            .map(|owner| owner.display_name),
+        None => None,
+    };
+    let (shares, links, households) = if permission == Permission::Owner {
+        (
+            share_items(&app_state, code_model.id).await?,
+            share_link_items(&app_state, &cipher, code_model.id).await?,
+            household_items(&app_state, auth.user_id)
+                .await?
+                .into_iter()
+                .filter(|household| household.role >= Role::Member)
+                .collect(),
+        )
+    } else {
+        (Vec::new(), Vec::new(), Vec::new())
+    };
 
     let code_page = ViewCodePage {
-        code,
         code_id: code_model.id,
         code_value: code_model.value.clone(),
         code_name: code_model.name.clone(),
-        // site_name: site_model.name,
-        // created_at: code_model.created_at.to_string(),
-        // last_updated: code_model.last_updated.map(|dt| dt.to_string()),
-        // is_owner: code_model.user_id == auth.user_id,
+        code_svg,
+        render_error,
+        wallet_pass,
+        gs1_fields,
+        payload,
+        valid_from: code_model.valid_from,
+        expires_at: code_model.expires_at,
+        validity: code_model.validity(code::today()),
+        favourite: code_model.favourite,
+        notes_html,
+        photos,
+        secrets,
+        permission,
+        owner_name,
+        household_id: code_model.household_id,
+        households,
+        shares,
+        links,
+        site,
     };
 
     Ok(code_page)
 }
 
+/// The sides of a code's card that there are photos of, front first
+async fn photo_sides(app_state: &AppState, code_id: Uuid) -> Result<Vec<Side>, HoofprintError> {
+    Ok(
+        code_attachment::Model::find_for_code(&app_state.db, code_id)
+            .await?
+            .iter()
+            .filter_map(|attachment| Side::try_from(attachment.side.as_str()).ok())
+            .collect(),
+    )
+}
+
+/// Record a code being shown to a user, unless they've turned that off. Failing to record it
+/// shouldn't stop the code being shown, so errors are only logged.
+async fn record_usage(
+    app_state: &AppState,
+    code_model: &code::Model,
+    user_id: Uuid,
+    location: Option<String>,
+) {
+    let result = match user::Entity::find_by_id(user_id).one(&app_state.db).await {
+        Ok(Some(user)) if user.track_usage => {
+            debug!(code_id = %code_model.id, location = ?location, "Recording code usage");
+            code_usage::Model::record(&app_state.db, code_model.id, user.id, location).await
+        }
+        Ok(_) => Ok(()),
+        Err(err) => Err(err.into()),
+    };
+    if let Err(err) = result {
+        error!(code_id = %code_model.id, error = %err, "Failed to record code usage");
+    }
+}
+
+/// Serve a code as a standalone SVG image
+#[instrument(level = "debug", skip(app_state, session))]
+pub(crate) async fn view_code_svg(
+    State(app_state): State<AppState>,
+    Path(code_id_str): Path<String>,
+    session: Session,
+) -> Result<impl IntoResponse, HoofprintError> {
+    let auth = app_state.get_authenticated_user(&session).await?;
+    let mut cipher = app_state.get_code_cipher(&session).await?;
+    let (code_model, _site_model, _permission) = get_code(
+        &app_state,
+        &mut cipher,
+        auth.user_id,
+        &code_id_str,
+        Permission::View,
+    )
+    .await?;
+
+    let code = Code::try_from(&code_model)?;
+    let svg = code.as_html(&code_model.value, &code_model.render_options)?;
+
+    Ok((
+        [
+            (CONTENT_TYPE, MIME_TYPE_SVG.to_string()),
+            (
+                CONTENT_DISPOSITION,
+                format!("inline; filename=\"{}.svg\"", code_model.id),
+            ),
+        ],
+        svg,
+    ))
+}
+
+/// Serve a code as a PNG image, sized by the query parameters
+#[instrument(level = "debug", skip(app_state, session))]
+pub(crate) async fn view_code_png(
+    State(app_state): State<AppState>,
+    Path(code_id_str): Path<String>,
+    Query(query): Query<PngExportQuery>,
+    session: Session,
+) -> Result<impl IntoResponse, HoofprintError> {
+    let auth = app_state.get_authenticated_user(&session).await?;
+    let mut cipher = app_state.get_code_cipher(&session).await?;
+    let (code_model, _site_model, _permission) = get_code(
+        &app_state,
+        &mut cipher,
+        auth.user_id,
+        &code_id_str,
+        Permission::View,
+    )
+    .await?;
+    query.validate()?;
+
+    let code = Code::try_from(&code_model)?;
+    let mut image = CodeImage::encode(&code, &code_model.value, &code_model.render_options)?;
+    if let Some(quiet_zone) = query.quiet_zone {
+        image = image.with_quiet_zone(quiet_zone);
+    }
+    // a width in the query picks its own module size
+    let module_size = match query.width {
+        Some(_) => query.module_size,
+        None => query.module_size.or(code_model.render_options.module_size),
+    };
+    let png_bytes = image.to_png(&RasterOptions {
+        module_size,
+        width: query.width,
+        dpi: query.dpi,
+    })?;
+
+    Ok((
+        [
+            (CONTENT_TYPE, MIME_TYPE_PNG.to_string()),
+            (
+                CONTENT_DISPOSITION,
+                format!("inline; filename=\"{}.png\"", code_model.id),
+            ),
+        ],
+        png_bytes,
+    ))
+}
+
+/// Serve a code as a signed Apple Wallet pass
+#[instrument(level = "debug", skip(app_state, session))]
+pub(crate) async fn view_code_pkpass(
+    State(app_state): State<AppState>,
+    Path(code_id_str): Path<String>,
+    session: Session,
+) -> Result<impl IntoResponse, HoofprintError> {
+    let auth = app_state.get_authenticated_user(&session).await?;
+    let mut cipher = app_state.get_code_cipher(&session).await?;
+    let (code_model, site_model, _permission) = get_code(
+        &app_state,
+        &mut cipher,
+        auth.user_id,
+        &code_id_str,
+        Permission::View,
+    )
+    .await?;
+
+    let config = app_state.config.read().await;
+    let signer = PassSigner::from_config(&config).await?.ok_or_else(|| {
+        HoofprintError::NotFound("Apple Wallet passes aren't set up on this server".to_string())
+    })?;
+    drop(config);
+    let pkpass = signer.build_pass(&code_model, &site_model)?;
+
+    Ok((
+        [
+            (CONTENT_TYPE, MIME_TYPE_PKPASS.to_string()),
+            (
+                CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}.pkpass\"", code_model.id),
+            ),
+        ],
+        pkpass,
+    ))
+}
+
 #[derive(Template, WebTemplate)]
 #[template(path = "create_code.html")]
 pub(crate) struct CreateCodePage {
     pub sites: Vec<SiteOption>,
     pub error: Option<String>,
+    /// The site to preselect, if the code's being added from one
+    pub selected_site_id: Option<String>,
+    /// The type of code to preselect, which is the site's usual one
+    pub selected_code_type: Option<Code>,
+    /// The payload builder, which starts out empty
+    pub payload_form: PayloadForm,
 }
 
 #[derive(Template, WebTemplate)]
@@ -145,55 +695,83 @@ pub(crate) struct ScanCodePage {
     pub sites: Vec<SiteOption>,
     pub error: Option<String>,
     pub uuid_nil: String,
+    /// A code read from an uploaded image or pass, waiting for the user to confirm it
+    pub detected: Option<DecodedCode>,
+    /// A name for the detected code, from an imported pass's description
+    pub code_name: Option<String>,
+    /// The site to preselect
+    pub selected_site_id: String,
+    /// A site to create for the detected code, from an imported pass's organisation
+    pub new_site_name: Option<String>,
 }
 
 pub(crate) struct SiteOption {
     pub id: String,
     pub name: String,
+    /// The type of code the site usually issues
+    pub default_code_type: Option<Code>,
+}
+
+/// Every site, for the dropdowns
+async fn site_options(app_state: &AppState) -> Result<Vec<SiteOption>, HoofprintError> {
+    Ok(site::Entity::find()
+        .all(&app_state.db)
+        .await?
+        .into_iter()
+        .map(|site| SiteOption {
+            id: site.id.to_string(),
+            default_code_type: site.default_code_type(),
+            name: site.name,
+        })
+        .collect())
+}
+
+/// The site a new code's being added from, if there is one
+fn selected_site<'a>(sites: &'a [SiteOption], query: &NewCodeQuery) -> Option<&'a SiteOption> {
+    let site_id = query.site_id()?.to_string();
+    sites.iter().find(|site| site.id == site_id)
 }
 
 #[instrument(level = "debug")]
 pub(crate) async fn create_code_get(
     State(app_state): State<AppState>,
+    Query(query): Query<NewCodeQuery>,
     session: Session,
 ) -> Result<CreateCodePage, HoofprintError> {
     app_state.get_authenticated_user(&session).await?;
 
-    // Fetch all sites for dropdown
-    let sites_models = site::Entity::find().all(&app_state.db).await?;
+    let sites = site_options(&app_state).await?;
+    let selected = selected_site(&sites, &query);
+    let selected_site_id = selected.map(|site| site.id.clone());
+    let selected_code_type = selected.and_then(|site| site.default_code_type.clone());
 
-    // Transform into template structure
-    let sites = sites_models
-        .into_iter()
-        .map(|site| SiteOption {
-            id: site.id.to_string(),
-            name: site.name,
-        })
-        .collect();
-
-    Ok(CreateCodePage { sites, error: None })
+    Ok(CreateCodePage {
+        sites,
+        error: None,
+        selected_site_id,
+        selected_code_type,
+        payload_form: PayloadForm::default(),
+    })
 }
 
-#[instrument(level = "debug")]
+#[instrument(level = "debug", skip(app_state, session, form), fields(site_id = %form.site_id, code_type = %form.code_type))]
 pub(crate) async fn create_code_post(
     State(app_state): State<AppState>,
     session: Session,
     Form(form): Form<CreateCodeForm>,
 ) -> Result<Redirect, HoofprintError> {
     let auth = app_state.get_authenticated_user(&session).await?;
+    let cipher = app_state.get_code_cipher(&session).await?;
     // Validate form data
-    form.validate()?;
+    let site_model = chosen_site(&app_state, &form.site_id, form.new_site_name()).await?;
+    form.validate(site_model.as_ref())?;
+    let code_value = form.completed_value();
+    let (valid_from, expires_at) = form
+        .validity_dates()
+        .map_err(HoofprintError::ValidationError)?;
+    let tags = form.tags().map_err(HoofprintError::ValidationError)?;
 
-    // Parse site_id
-    let site_id = form.parse_site_id()?;
-
-    // Verify site exists
-    site::Entity::find_by_id(site_id)
-        .one(&app_state.db)
-        .await?
-        .ok_or_else(|| {
-            HoofprintError::ValidationError(vec![format!("Site {} not found", site_id)])
-        })?;
+    let site_id = resolve_site(&app_state, &form.site_id, form.new_site_name()).await?;
 
     // Create new Code
     let new_code_id = Uuid::now_v7();
@@ -209,20 +787,100 @@ pub(crate) async fn create_code_post(
         id: Set(new_code_id),
         user_id: Set(auth.user_id),
         type_: Set(form.code_type),
-        value: Set(form.code_value),
-        name: Set(name),
+        value: Set(cipher.seal_value(new_code_id, &code_value)?),
+        name: Set(cipher.seal_name(new_code_id, name)?),
         site_id: Set(site_id),
         created_at: Set(DateTimeUtc::from(SystemTime::now())),
         last_updated: Set(None),
+        render_options: Set(RenderOptions::default()),
+        valid_from: Set(valid_from),
+        expires_at: Set(expires_at),
+        favourite: Set(false),
+        position: Set(code::Model::next_position(&app_state.db, auth.user_id).await?),
+        notes: Set(None),
+        wrapped_key: Set(None),
+        household_id: Set(None),
     };
 
     // Insert into database
     new_code.insert(&app_state.db).await?;
+    tag::Model::set_for_code(&app_state.db, auth.user_id, new_code_id, &tags).await?;
 
     // Redirect to view page
     Ok(Redirect::to(&format!("/view/{}", new_code_id)))
 }
 
+/// Create a QR code from the structured payload builder
+#[instrument(level = "debug", skip_all)]
+pub(crate) async fn create_payload_post(
+    State(app_state): State<AppState>,
+    session: Session,
+    Form(form): Form<CreatePayloadForm>,
+) -> Result<Redirect, HoofprintError> {
+    let auth = app_state.get_authenticated_user(&session).await?;
+    let cipher = app_state.get_code_cipher(&session).await?;
+    form.validate()?;
+    let code_value = form.payload.value()?;
+
+    let site_id = resolve_site(&app_state, &form.site_id, None).await?;
+
+    let new_code = code::Model::create_new(
+        app_state.db.clone(),
+        &cipher,
+        auth.user_id,
+        Code::QR,
+        &code_value,
+        site_id,
+        form.code_name.as_deref().filter(|name| !name.is_empty()),
+    )
+    .await?;
+
+    Ok(Redirect::to(&format!("/view/{}", new_code.id)))
+}
+
+/// Replace a QR code's value with one from the structured payload builder
+#[instrument(level = "debug", skip(app_state, session, form))]
+pub(crate) async fn edit_payload_post(
+    State(app_state): State<AppState>,
+    Path(code_id_str): Path<String>,
+    session: Session,
+    Form(form): Form<PayloadForm>,
+) -> Result<Redirect, HoofprintError> {
+    let auth = app_state.get_authenticated_user(&session).await?;
+    let mut cipher = app_state.get_code_cipher(&session).await?;
+    let (code_model, _site_model, _permission) = get_code(
+        &app_state,
+        &mut cipher,
+        auth.user_id,
+        &code_id_str,
+        Permission::Edit,
+    )
+    .await?;
+
+    if Code::try_from(&code_model)? != Code::QR {
+        return Err(HoofprintError::ValidationError(vec![
+            "Only QR codes can be built from structured fields".to_string(),
+        ]));
+    }
+    let code_value = form.value()?;
+
+    let code_id = code_model.id;
+    let mut code_active: code::ActiveModel = code_model.clone().into();
+    code_active.value = Set(cipher.seal_value(code_id, &code_value)?);
+    code_active.last_updated = Set(Some(DateTimeUtc::from(SystemTime::now())));
+    code::Model::update_with_revision(
+        &app_state.db,
+        &cipher,
+        &code_model,
+        code_active,
+        auth.user_id,
+        None,
+    )
+    .await?;
+
+    Ok(Redirect::to(&format!("/view/{}", code_id)))
+}
+
 #[derive(Template, WebTemplate)]
 #[template(path = "edit_code.html")]
 pub(crate) struct EditCodePage {
@@ -234,6 +892,21 @@ pub(crate) struct EditCodePage {
     pub sites: Vec<SiteOption>,
     pub created_at: String,
     pub last_updated: Option<String>,
+    pub render_options: RenderOptions,
+    pub valid_from: Option<Date>,
+    pub expires_at: Option<Date>,
+    /// Comma separated, for the tags field
+    pub tags: String,
+    /// The payload builder, filled in when the code is a QR code it can edit
+    pub payload_form: Option<PayloadForm>,
+    /// The notes as they were written, in Markdown
+    pub notes: String,
+    /// The sides of the card there are photos of
+    pub photos: Vec<Side>,
+    /// Secrets like PINs, without their values
+    pub secrets: Vec<SecretItem>,
+    /// Whether the user owns the code, so can delete it
+    pub is_owner: bool,
     pub error: Option<String>,
 }
 
@@ -244,36 +917,32 @@ pub(crate) async fn edit_code_get(
     session: Session,
 ) -> Result<EditCodePage, HoofprintError> {
     let auth = app_state.get_authenticated_user(&session).await?;
-    // Parse code_id as UUID
-    let code_id = Uuid::parse_str(&code_id_str)
-        .map_err(|_| HoofprintError::NotFound(format!("Invalid code ID: {}", code_id_str)))?;
+    let mut cipher = app_state.get_code_cipher(&session).await?;
+    let (code_model, _site_model, permission) = get_code(
+        &app_state,
+        &mut cipher,
+        auth.user_id,
+        &code_id_str,
+        Permission::Edit,
+    )
+    .await?;
+
+    let sites = site_options(&app_state).await?;
+
+    let payload_form = match Code::try_from(&code_model) {
+        Ok(Code::QR) => {
+            Payload::parse(&code_model.value).map(|payload| PayloadForm::from(&payload))
+        }
+        _ => None,
+    };
 
-    // Fetch code from database
-    let code_model = code::Entity::find_by_id(code_id)
-        .filter(code::Column::UserId.eq(auth.user_id))
-        .one(&app_state.db)
+    let tags = tag::Model::find_for_codes(&app_state.db, auth.user_id, &[code_model.id])
         .await?
-        .ok_or_else(|| HoofprintError::NotFound(format!("Code {}", code_id)))?;
-
-    // Verify ownership
-    if code_model.user_id != auth.user_id {
-        return Err(HoofprintError::Unauthorized);
-    }
-
-    // Fetch all sites for dropdown
-    let sites_models = site::Entity::find()
-        // .filter(site::Column::Id.ne(Uuid::nil().to_string()))
-        .all(&app_state.db)
-        .await?;
-
-    // Transform sites
-    let sites = sites_models
-        .into_iter()
-        .map(|site| SiteOption {
-            id: site.id.to_string(),
-            name: site.name,
-        })
-        .collect();
+        .remove(&code_model.id)
+        .unwrap_or_default()
+        .join(", ");
+    let photos = photo_sides(&app_state, code_model.id).await?;
+    let secrets = secret_items(&app_state, code_model.id).await?;
 
     // Create page data with pre-filled values
     let page = EditCodePage {
@@ -285,6 +954,15 @@ pub(crate) async fn edit_code_get(
         sites,
         created_at: code_model.created_at.to_string(),
         last_updated: code_model.last_updated.map(|dt| dt.to_string()),
+        render_options: code_model.render_options,
+        valid_from: code_model.valid_from,
+        expires_at: code_model.expires_at,
+        tags,
+        payload_form,
+        notes: code_model.notes.unwrap_or_default(),
+        photos,
+        secrets,
+        is_owner: permission == Permission::Owner,
         error: None,
     };
 
@@ -299,38 +977,42 @@ pub(crate) async fn edit_code_post(
     Form(form): Form<EditCodeForm>,
 ) -> Result<Redirect, HoofprintError> {
     let auth = app_state.get_authenticated_user(&session).await?;
-    // Parse code_id as UUID
-    let code_id = Uuid::parse_str(&code_id_str)
-        .map_err(|_| HoofprintError::NotFound(format!("Invalid code ID: {}", code_id_str)))?;
+    let mut cipher = app_state.get_code_cipher(&session).await?;
 
     // Validate form data
-    form.validate()?;
+    let site_model = chosen_site(&app_state, &form.site_id, None).await?;
+    form.validate(site_model.as_ref())?;
+    let code_value = form.completed_value();
+    let render_options = form
+        .render_options()
+        .map_err(HoofprintError::ValidationError)?;
+    let (valid_from, expires_at) = form
+        .validity_dates()
+        .map_err(HoofprintError::ValidationError)?;
+    let tags = form.tags().map_err(HoofprintError::ValidationError)?;
+    let notes = form.notes();
 
     // Parse site_id
     let site_id = form.parse_site_id()?;
 
-    // Fetch existing code from database
-    let code_model = code::Entity::find_by_id(code_id)
-        .filter(code::Column::UserId.eq(auth.user_id))
-        .one(&app_state.db)
-        .await?
-        .ok_or_else(|| HoofprintError::NotFound(format!("Code {}", code_id)))?;
-
-    // Verify user owns this code
-    if code_model.user_id != auth.user_id {
-        return Err(HoofprintError::Unauthorized);
-    }
+    // Fetch existing code from database, which also checks we own it
+    let (code_model, _site_model, _permission) = get_code(
+        &app_state,
+        &mut cipher,
+        auth.user_id,
+        &code_id_str,
+        Permission::Edit,
+    )
+    .await?;
+    let code_id = code_model.id;
 
     // Verify site exists
-    site::Entity::find_by_id(site_id)
-        .one(&app_state.db)
-        .await?
-        .ok_or_else(|| {
-            HoofprintError::ValidationError(vec![format!("Site {} not found", site_id)])
-        })?;
+    site_model.ok_or_else(|| {
+        HoofprintError::ValidationError(vec![format!("Site {} not found", site_id)])
+    })?;
 
     // Update code
-    let mut code_active: code::ActiveModel = code_model.into();
+    let mut code_active: code::ActiveModel = code_model.clone().into();
 
     // Convert empty string to None for name field
     let name = if form.code_name.as_ref().is_none_or(|s| s.is_empty()) {
@@ -340,12 +1022,27 @@ pub(crate) async fn edit_code_post(
     };
 
     code_active.type_ = Set(form.code_type);
-    code_active.value = Set(form.code_value);
-    code_active.name = Set(name);
+    code_active.value = Set(cipher.seal_value(code_id, &code_value)?);
+    code_active.name = Set(cipher.seal_name(code_id, name)?);
     code_active.site_id = Set(site_id);
+    code_active.render_options = Set(render_options);
+    code_active.valid_from = Set(valid_from);
+    code_active.expires_at = Set(expires_at);
+    code_active.notes = Set(notes
+        .map(|notes| cipher.seal_notes(code_id, &notes))
+        .transpose()?);
     code_active.last_updated = Set(Some(DateTimeUtc::from(SystemTime::now())));
 
-    code_active.update(&app_state.db).await?;
+    code::Model::update_with_revision(
+        &app_state.db,
+        &cipher,
+        &code_model,
+        code_active,
+        auth.user_id,
+        None,
+    )
+    .await?;
+    tag::Model::set_for_code(&app_state.db, auth.user_id, code_id, &tags).await?;
 
     // Redirect to view page
     Ok(Redirect::to(&format!("/view/{}", code_id)))
@@ -358,57 +1055,217 @@ pub(crate) async fn code_delete(
     session: Session,
 ) -> Result<Redirect, HoofprintError> {
     let auth = app_state.get_authenticated_user(&session).await?;
-    // Parse code_id as UUID
-    let code_id = Uuid::parse_str(&code_id_str)
-        .map_err(|_| HoofprintError::NotFound(format!("Invalid code ID: {}", code_id_str)))?;
-
-    // Fetch code from database
-    let code_model = code::Entity::find_by_id(code_id)
-        .filter(code::Column::UserId.eq(auth.user_id))
-        .one(&app_state.db)
-        .await?
-        .ok_or_else(|| HoofprintError::NotFound(format!("Code {}", code_id)))?;
+    // a locked code can still be deleted, so it isn't decrypted
+    let (code_model, _site_model, _permission) =
+        find_code(&app_state, auth.user_id, &code_id_str, Permission::Owner).await?;
+    let code_id = code_model.id;
+
+    // Delete code from database, along with its history, tags, usage, photos, secrets and shares
+    let txn = app_state.db.begin().await?;
+    code_share::Model::clear_for_code(&txn, code_id).await?;
+    share_link::Model::clear_for_code(&txn, code_id).await?;
+    tag::Model::clear_for_code(&txn, code_id).await?;
+    code_secret::Model::clear_for_code(&txn, code_id).await?;
+    code_usage::Model::clear_for_code(&txn, code_id).await?;
+    let files = code_attachment::Model::clear_for_code(
+        &txn,
+        attachment_dir(&app_state).await.as_deref(),
+        code_id,
+    )
+    .await?;
+    code_revision::Entity::delete_many()
+        .filter(code_revision::Column::CodeId.eq(code_id))
+        .exec(&txn)
+        .await?;
+    code::Entity::delete_by_id(code_id).exec(&txn).await?;
+    txn.commit().await?;
 
-    // Verify user owns this code
-    if code_model.user_id != auth.user_id {
-        return Err(HoofprintError::Unauthorized);
+    // the code's gone either way, so a photo file that can't be removed is only logged
+    if let Err(err) = code_attachment::Model::remove_files(files).await {
+        error!(code_id = %code_id, error = ?err, "Failed to remove photo files of deleted code");
     }
 
-    // Delete code from database
-    code::Entity::delete_by_id(code_id)
-        .exec(&app_state.db)
-        .await?;
-
     // Redirect to homepage (code no longer exists)
     Ok(Redirect::to("/"))
 }
 
+/// Mark a code as a favourite, or stop it being one
+#[instrument(level = "debug", skip(app_state, session))]
+pub(crate) async fn favourite_post(
+    State(app_state): State<AppState>,
+    Path(code_id_str): Path<String>,
+    session: Session,
+    Form(form): Form<FavouriteForm>,
+) -> Result<Redirect, HoofprintError> {
+    let auth = app_state.get_authenticated_user(&session).await?;
+    let (code_model, _site_model, _permission) =
+        find_code(&app_state, auth.user_id, &code_id_str, Permission::Owner).await?;
+    let code_id = code_model.id;
+
+    // not a change to the code itself, so there's no revision for it
+    let mut code_active: code::ActiveModel = code_model.into();
+    code_active.favourite = Set(form.favourite);
+    code_active.update(&app_state.db).await?;
+
+    Ok(Redirect::to(&format!("/view/{}", code_id)))
+}
+
+/// Save a new order for the user's codes, as sent by dragging them around the homepage
+#[instrument(level = "debug", skip_all)]
+pub(crate) async fn reorder_post(
+    State(app_state): State<AppState>,
+    session: Session,
+    Json(request): Json<ReorderRequest>,
+) -> Result<StatusCode, HoofprintError> {
+    let auth = app_state.get_authenticated_user(&session).await?;
+    code::Model::reorder(&app_state.db, auth.user_id, &request.code_ids).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
 #[instrument(level = "debug", skip_all)]
 pub(crate) async fn scan_get(
     State(app_state): State<AppState>,
+    Query(query): Query<NewCodeQuery>,
     session: Session,
 ) -> Result<ScanCodePage, HoofprintError> {
     app_state.get_authenticated_user(&session).await?;
 
-    // Fetch all sites for dropdown
-    let sites_models = site::Entity::find().all(&app_state.db).await?;
-
-    // Transform into template structure
-    let sites = sites_models
-        .into_iter()
-        .map(|site| SiteOption {
-            id: site.id.to_string(),
-            name: site.name,
-        })
-        .collect();
+    let sites = site_options(&app_state).await?;
+    let selected_site_id = selected_site(&sites, &query)
+        .map(|site| site.id.clone())
+        .unwrap_or_else(|| Uuid::nil().to_string());
 
     Ok(ScanCodePage {
         sites,
         error: None,
         uuid_nil: Uuid::nil().to_string(),
+        detected: None,
+        code_name: None,
+        selected_site_id,
+        new_site_name: None,
     })
 }
 
+/// Read a code from an uploaded photo, screenshot or Wallet pass, and show it for the user to confirm
+#[instrument(level = "debug", skip_all)]
+pub(crate) async fn scan_upload_post(
+    State(app_state): State<AppState>,
+    session: Session,
+    mut multipart: Multipart,
+) -> Result<ScanCodePage, HoofprintError> {
+    app_state.get_authenticated_user(&session).await?;
+
+    let mut upload = None;
+    while let Some(field) = multipart.next_field().await? {
+        if field.name() == Some("upload") {
+            upload = Some(field.bytes().await?);
+        }
+    }
+    let upload = upload.filter(|bytes| !bytes.is_empty()).ok_or_else(|| {
+        HoofprintError::ValidationError(vec!["Choose an image or pass to upload".to_string()])
+    })?;
+
+    // passes also tell us the organisation and a description
+    let result = tokio::task::spawn_blocking(move || {
+        if is_pkpass(&upload) {
+            read_pass(&upload).map(|pass| {
+                (
+                    DecodedCode {
+                        code: pass.code,
+                        value: pass.value,
+                    },
+                    Some(pass.organization_name),
+                    pass.description,
+                )
+            })
+        } else {
+            decode_image(&upload).map(|detected| (detected, None, None))
+        }
+    })
+    .await?;
+
+    let sites = site_options(&app_state).await?;
+
+    let mut page = ScanCodePage {
+        sites,
+        error: None,
+        uuid_nil: Uuid::nil().to_string(),
+        detected: None,
+        code_name: None,
+        selected_site_id: Uuid::nil().to_string(),
+        new_site_name: None,
+    };
+
+    match result {
+        Ok((detected, organization_name, description)) => {
+            match detected.code.validate_value(&detected.value) {
+                Ok(()) => {
+                    if let Some(organization_name) = organization_name {
+                        match site::Model::find_by_name(&app_state.db, &organization_name).await? {
+                            Some(site) => page.selected_site_id = site.id.to_string(),
+                            None => page.new_site_name = Some(organization_name),
+                        }
+                    }
+                    page.code_name = description;
+                    page.detected = Some(detected);
+                }
+                Err(errors) => page.error = Some(errors.join(", ")),
+            }
+        }
+        Err(HoofprintError::ValidationError(errors)) => page.error = Some(errors.join(", ")),
+        Err(err) => return Err(err),
+    }
+
+    Ok(page)
+}
+
+/// The existing site a code's being saved to, if it's one that exists rather than a new one
+async fn chosen_site(
+    app_state: &AppState,
+    site_id: &str,
+    new_site_name: Option<&str>,
+) -> Result<Option<site::Model>, HoofprintError> {
+    if new_site_name.is_some() {
+        return Ok(None);
+    }
+    let Ok(site_id) = Uuid::parse_str(site_id) else {
+        return Ok(None);
+    };
+    Ok(site::Entity::find_by_id(site_id).one(&app_state.db).await?)
+}
+
+/// Use the site the form names, creating it if needed, or check the site ID it picked exists
+async fn resolve_site(
+    app_state: &AppState,
+    site_id: &str,
+    new_site_name: Option<&str>,
+) -> Result<Uuid, HoofprintError> {
+    if let Some(new_site_name) = new_site_name {
+        return Ok(site::Model::find_or_create(&app_state.db, new_site_name)
+            .await?
+            .id);
+    }
+
+    // Parse site_id, default to Uuid::nil() if empty or already nil
+    if site_id.is_empty() {
+        return Ok(Uuid::nil());
+    }
+    let site_id = Uuid::parse_str(site_id)
+        .map_err(|_| HoofprintError::ValidationError(vec!["Invalid site ID".to_string()]))?;
+    if site_id == Uuid::nil() {
+        return Ok(Uuid::nil());
+    }
+
+    // Verify site exists if not using default
+    site::Entity::find_by_id(site_id)
+        .one(&app_state.db)
+        .await?
+        .ok_or_else(|| {
+            HoofprintError::ValidationError(vec![format!("Site {} not found", site_id)])
+        })?;
+    Ok(site_id)
+}
+
 #[instrument(level = "debug", skip_all, fields(site_id = %form.site_id, code_type = %form.code_type))]
 pub(crate) async fn scan_post(
     State(app_state): State<AppState>,
@@ -416,23 +1273,17 @@ pub(crate) async fn scan_post(
     Form(form): Form<CreateCodeForm>,
 ) -> Result<Redirect, HoofprintError> {
     let auth = app_state.get_authenticated_user(&session).await?;
+    let cipher = app_state.get_code_cipher(&session).await?;
     // Validate form data
-    form.validate()?;
+    let site_model = chosen_site(&app_state, &form.site_id, form.new_site_name()).await?;
+    form.validate(site_model.as_ref())?;
+    let code_value = form.completed_value();
+    let (valid_from, expires_at) = form
+        .validity_dates()
+        .map_err(HoofprintError::ValidationError)?;
+    let tags = form.tags().map_err(HoofprintError::ValidationError)?;
 
-    // Parse site_id, default to Uuid::nil() if empty or already nil
-    let site_id = form.parse_site_id()?;
-    let site_id = if site_id == Uuid::nil() || form.site_id.is_empty() {
-        Uuid::nil()
-    } else {
-        // Verify site exists if not using default
-        site::Entity::find_by_id(site_id)
-            .one(&app_state.db)
-            .await?
-            .ok_or_else(|| {
-                HoofprintError::ValidationError(vec![format!("Site {} not found", site_id)])
-            })?;
-        site_id
-    };
+    let site_id = resolve_site(&app_state, &form.site_id, form.new_site_name()).await?;
 
     // Create new Code
     let new_code_id = Uuid::now_v7();
@@ -448,15 +1299,24 @@ pub(crate) async fn scan_post(
         id: Set(new_code_id),
         user_id: Set(auth.user_id),
         type_: Set(form.code_type),
-        value: Set(form.code_value),
-        name: Set(name),
+        value: Set(cipher.seal_value(new_code_id, &code_value)?),
+        name: Set(cipher.seal_name(new_code_id, name)?),
         site_id: Set(site_id),
         created_at: Set(DateTimeUtc::from(SystemTime::now())),
         last_updated: Set(None),
+        render_options: Set(RenderOptions::default()),
+        valid_from: Set(valid_from),
+        expires_at: Set(expires_at),
+        favourite: Set(false),
+        position: Set(code::Model::next_position(&app_state.db, auth.user_id).await?),
+        notes: Set(None),
+        wrapped_key: Set(None),
+        household_id: Set(None),
     };
 
     // Insert into database
     new_code.insert(&app_state.db).await?;
+    tag::Model::set_for_code(&app_state.db, auth.user_id, new_code_id, &tags).await?;
 
     // Redirect to view page
     Ok(Redirect::to(&format!("/view/{}", new_code_id)))
@@ -0,0 +1,22 @@
+// payload_form.js - Show only the fields for the chosen kind of QR code content
+
+function showPayloadFields() {
+	const payloadType = document.getElementById("payload_type");
+	if (!payloadType) {
+		return;
+	}
+	for (const fieldset of document.querySelectorAll("fieldset[data-payload]")) {
+		const chosen = fieldset.dataset.payload === payloadType.value;
+		fieldset.hidden = !chosen;
+		// hidden fields shouldn't be submitted
+		fieldset.disabled = !chosen;
+	}
+}
+
+document.addEventListener("DOMContentLoaded", () => {
+	const payloadType = document.getElementById("payload_type");
+	if (payloadType) {
+		payloadType.addEventListener("change", showPayloadFields);
+		showPayloadFields();
+	}
+});
@@ -0,0 +1,60 @@
+// reorder.js - Drag codes around the homepage to change their order
+
+let dragged = null;
+let orderBefore = "";
+
+function currentOrder(container) {
+	return Array.from(
+		container.querySelectorAll("a[data-code-id]"),
+		(link) => link.dataset.codeId,
+	);
+}
+
+function saveOrder(container) {
+	const codeIds = currentOrder(container);
+	if (codeIds.join(",") === orderBefore) {
+		return;
+	}
+	fetch(container.dataset.reorderUrl, {
+		method: "POST",
+		headers: { "Content-Type": "application/json" },
+		body: JSON.stringify({ code_ids: codeIds }),
+	}).then((response) => {
+		if (!response.ok) {
+			console.error("Failed to save the new order", response.status);
+		}
+	});
+}
+
+document.addEventListener("DOMContentLoaded", () => {
+	const container = document.querySelector(".code_blocks[data-reorder-url]");
+	if (!container) {
+		return;
+	}
+	for (const link of container.querySelectorAll("a[data-code-id]")) {
+		link.addEventListener("dragstart", (event) => {
+			dragged = link;
+			orderBefore = currentOrder(container).join(",");
+			link.classList.add("dragging");
+			event.dataTransfer.effectAllowed = "move";
+		});
+		link.addEventListener("dragend", () => {
+			link.classList.remove("dragging");
+			dragged = null;
+			// saved when the drag ends, wherever it's dropped
+			saveOrder(container);
+		});
+		link.addEventListener("dragover", (event) => {
+			if (!dragged || dragged === link) {
+				return;
+			}
+			event.preventDefault();
+			const box = link.getBoundingClientRect();
+			const after = event.clientX > box.left + box.width / 2;
+			container.insertBefore(dragged, after ? link.nextSibling : link);
+		});
+		link.addEventListener("drop", (event) => {
+			event.preventDefault();
+		});
+	}
+});
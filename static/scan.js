@@ -264,13 +264,25 @@ async function handleCodeDetected(symbol) {
 }
 
 function determineCodeType(typeName) {
-	// typeName examples: "QR-Code", "EAN-13", "Code-128", etc.
-	if (typeName === "QR-Code" || typeName === "ZBAR_QRCODE") {
-		return "qrcode";
-	} else {
-		// All other formats are barcodes
-		return "barcode";
-	}
+	// typeName examples: "ZBAR_QRCODE", "ZBAR_EAN13", "ZBAR_CODE128", etc.
+	const codeTypes = {
+		"QR-Code": "qrcode",
+		ZBAR_QRCODE: "qrcode",
+		ZBAR_EAN13: "ean13",
+		ZBAR_ISBN13: "ean13",
+		ZBAR_EAN8: "ean8",
+		ZBAR_UPCA: "upca",
+		ZBAR_UPCE: "upce",
+		ZBAR_CODE39: "code39",
+		ZBAR_I25: "itf14",
+		ZBAR_CODABAR: "codabar",
+		ZBAR_PDF417: "pdf417",
+	};
+	// Everything else is stored as the chosen site's usual type, or Code 128, which can carry
+	// any value
+	const siteSelect = document.getElementById("site_id");
+	const siteCodeType = siteSelect?.selectedOptions[0]?.dataset.codeType;
+	return codeTypes[typeName] || siteCodeType || "code128";
 }
 
 function scanAgain() {